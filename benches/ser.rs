@@ -0,0 +1,54 @@
+#![allow(missing_docs, clippy::unwrap_used)]
+
+// Compares allocating a new `String` for each serialized document with reusing one buffer. Run
+// with `cargo bench --bench ser`
+
+use std::fmt::Write as _;
+
+use bencher::{benchmark_group, benchmark_main, black_box, Bencher};
+use soml::Value;
+
+// A document with a mix of values, tables and arrays of tables
+fn document() -> (Value, u64) {
+    let mut document = String::from("title = \"Benchmark\"\nversion = 3\n");
+    for index in 0..32 {
+        write!(
+            document,
+            "\n[[servers]]\nname = \"server-{index}\"\naddress = \"10.0.0.{index}\"\nport = {}\n\
+             weight = {index}.5\ntags = [\"a\", \"b\", \"c\"]\n",
+            8000 + index,
+        )
+        .unwrap();
+    }
+    let value = soml::from_str(&document).unwrap();
+    let len = soml::to_string(&value).unwrap().len();
+    (value, len as u64)
+}
+
+// Allocates a new String for each document, growing it as the output is written
+fn to_string(bench: &mut Bencher) {
+    let (value, len) = document();
+    bench.bytes = len;
+    bench.iter(|| soml::to_string(black_box(&value)).unwrap());
+}
+
+// Allocates a new String for each document, pre-sized using the estimated length
+fn to_toml_string(bench: &mut Bencher) {
+    let (value, len) = document();
+    bench.bytes = len;
+    bench.iter(|| black_box(&value).to_toml_string().unwrap());
+}
+
+// Reuses the same String for every document, so only the first one allocates
+fn to_string_in(bench: &mut Bencher) {
+    let (value, len) = document();
+    bench.bytes = len;
+    let mut buf = String::new();
+    bench.iter(|| {
+        soml::to_string_in(black_box(&value), &mut buf).unwrap();
+        buf.len()
+    });
+}
+
+benchmark_group!(benches, to_string, to_toml_string, to_string_in);
+benchmark_main!(benches);
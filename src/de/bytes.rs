@@ -0,0 +1,212 @@
+use std::fmt;
+
+use serde::de;
+
+/// Deserializes a hex encoded string into a `Vec<u8>`.
+///
+/// This is intended to be used with `#[serde(deserialize_with = "soml::de::hex_bytes")]`. Both
+/// upper and lower case hex digits are accepted, for example `"deadBEEF"`.
+///
+/// # Errors
+///
+/// Returns an error if the value is not a string or is not valid hex.
+#[inline]
+pub fn hex_bytes<'de, D>(deserializer: D) -> Result<Vec<u8>, D::Error>
+where
+    D: de::Deserializer<'de>,
+{
+    deserializer.deserialize_str(DecodeVisitor {
+        decode: decode_hex,
+        expected: "a hex encoded string",
+    })
+}
+
+/// Deserializes a base64 encoded string into a `Vec<u8>`.
+///
+/// This is intended to be used with `#[serde(deserialize_with = "soml::de::base64_bytes")]`. The
+/// standard base64 alphabet (using `+` and `/`) is used, with optional `=` padding.
+///
+/// # Errors
+///
+/// Returns an error if the value is not a string or is not valid base64.
+#[inline]
+pub fn base64_bytes<'de, D>(deserializer: D) -> Result<Vec<u8>, D::Error>
+where
+    D: de::Deserializer<'de>,
+{
+    deserializer.deserialize_str(DecodeVisitor {
+        decode: decode_base64,
+        expected: "a base64 encoded string",
+    })
+}
+
+struct DecodeVisitor {
+    decode: fn(&str) -> Option<Vec<u8>>,
+    expected: &'static str,
+}
+
+impl de::Visitor<'_> for DecodeVisitor {
+    type Value = Vec<u8>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.expected)
+    }
+
+    fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        (self.decode)(value).ok_or_else(|| E::invalid_value(de::Unexpected::Str(value), &self))
+    }
+}
+
+fn decode_hex(str: &str) -> Option<Vec<u8>> {
+    const fn digit(byte: u8) -> Option<u8> {
+        match byte {
+            b'0'..=b'9' => Some(byte - b'0'),
+            b'a'..=b'f' => Some(byte - b'a' + 10),
+            b'A'..=b'F' => Some(byte - b'A' + 10),
+            _ => None,
+        }
+    }
+
+    let bytes = str.as_bytes();
+    if bytes.len() % 2 != 0 {
+        return None;
+    }
+
+    bytes
+        .chunks_exact(2)
+        .map(|pair| Some((digit(pair[0])? << 4) | digit(pair[1])?))
+        .collect()
+}
+
+fn decode_base64(str: &str) -> Option<Vec<u8>> {
+    const fn sextet(byte: u8) -> Option<u8> {
+        match byte {
+            b'A'..=b'Z' => Some(byte - b'A'),
+            b'a'..=b'z' => Some(byte - b'a' + 26),
+            b'0'..=b'9' => Some(byte - b'0' + 52),
+            b'+' => Some(62),
+            b'/' => Some(63),
+            _ => None,
+        }
+    }
+
+    let bytes = str.as_bytes();
+
+    // Padding is optional, but if present the padded length must be a multiple of 4
+    let unpadded = bytes
+        .strip_suffix(b"==")
+        .or_else(|| bytes.strip_suffix(b"="))
+        .unwrap_or(bytes);
+    if unpadded.len() != bytes.len() && bytes.len() % 4 != 0 {
+        return None;
+    }
+    // A single trailing character can't encode a whole byte
+    if unpadded.len() % 4 == 1 {
+        return None;
+    }
+
+    let mut result = Vec::with_capacity(unpadded.len() * 3 / 4);
+    for chunk in unpadded.chunks(4) {
+        let mut bits = 0_u32;
+        for &byte in chunk {
+            bits = (bits << 6) | u32::from(sextet(byte)?);
+        }
+        // Align a partial chunk as if it was a whole one
+        bits <<= 6 * (4 - chunk.len());
+
+        // The top byte of bits is unused. Each character after the first gives one byte
+        result.extend_from_slice(&bits.to_be_bytes()[1..chunk.len()]);
+    }
+
+    Some(result)
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage, coverage(off))]
+mod tests {
+    use assert_matches::assert_matches;
+    use serde::Deserialize;
+
+    use super::*;
+    use crate::de::{Error, ErrorKind};
+
+    #[derive(Debug, Deserialize)]
+    struct Hex {
+        #[serde(deserialize_with = "hex_bytes")]
+        data: Vec<u8>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct Base64 {
+        #[serde(deserialize_with = "base64_bytes")]
+        data: Vec<u8>,
+    }
+
+    #[test]
+    fn test_hex_bytes() {
+        let result: Hex = crate::from_str(r#"data = "deadBEEF""#).unwrap();
+        assert_eq!(result.data, [0xde, 0xad, 0xbe, 0xef]);
+
+        assert_matches!(
+            crate::from_str::<Hex>(r#"data = "xyz""#),
+            Err(Error(ErrorKind::InvalidValue(..)))
+        );
+        assert_matches!(
+            crate::from_str::<Hex>("data = 42"),
+            Err(Error(ErrorKind::InvalidType(..)))
+        );
+    }
+
+    #[test]
+    fn test_base64_bytes() {
+        let result: Base64 = crate::from_str(r#"data = "3q2+7w==""#).unwrap();
+        assert_eq!(result.data, [0xde, 0xad, 0xbe, 0xef]);
+
+        assert_matches!(
+            crate::from_str::<Base64>(r#"data = "3q2+7w=""#),
+            Err(Error(ErrorKind::InvalidValue(..)))
+        );
+        assert_matches!(
+            crate::from_str::<Base64>("data = 42"),
+            Err(Error(ErrorKind::InvalidType(..)))
+        );
+    }
+
+    #[test]
+    fn test_decode_hex() {
+        assert_eq!(decode_hex(""), Some(vec![]));
+        assert_eq!(decode_hex("00ff"), Some(vec![0x00, 0xff]));
+        assert_eq!(
+            decode_hex("0123456789abcdef"),
+            Some(vec![0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef])
+        );
+        assert_eq!(decode_hex("ABCDEF"), Some(vec![0xab, 0xcd, 0xef]));
+
+        assert_eq!(decode_hex("abc"), None);
+        assert_eq!(decode_hex("0g"), None);
+        assert_eq!(decode_hex("0x00"), None);
+        assert_eq!(decode_hex("😎"), None);
+    }
+
+    #[test]
+    fn test_decode_base64() {
+        assert_eq!(decode_base64(""), Some(vec![]));
+        assert_eq!(decode_base64("Zg=="), Some(b"f".to_vec()));
+        assert_eq!(decode_base64("Zg"), Some(b"f".to_vec()));
+        assert_eq!(decode_base64("Zm8="), Some(b"fo".to_vec()));
+        assert_eq!(decode_base64("Zm8"), Some(b"fo".to_vec()));
+        assert_eq!(decode_base64("Zm9v"), Some(b"foo".to_vec()));
+        assert_eq!(decode_base64("Zm9vYmFy"), Some(b"foobar".to_vec()));
+        assert_eq!(decode_base64("+/+/"), Some(vec![0xfb, 0xff, 0xbf]));
+
+        assert_eq!(decode_base64("Z"), None);
+        assert_eq!(decode_base64("Zm9vY"), None);
+        assert_eq!(decode_base64("Zg="), None);
+        assert_eq!(decode_base64("Zg==="), None);
+        assert_eq!(decode_base64("Zm9v-_"), None);
+        assert_eq!(decode_base64("Zm 9v"), None);
+    }
+}
@@ -0,0 +1,133 @@
+use std::fmt;
+use std::ops::Range;
+
+use super::{Error, Warning};
+
+/// The severity of a [`Diagnostic`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Severity {
+    /// The input is not valid, so no value could be deserialized.
+    Error,
+    /// A non-fatal issue, see [`Warning`].
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::Error => f.write_str("error"),
+            Self::Warning => f.write_str("warning"),
+        }
+    }
+}
+
+/// An error or warning found while parsing a document, returned by
+/// [`parse_with_diagnostics`](super::parse_with_diagnostics).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    severity: Severity,
+    message: String,
+    span: Range<usize>,
+}
+
+impl Diagnostic {
+    /// Returns the severity of the diagnostic.
+    #[must_use]
+    #[inline]
+    pub fn severity(&self) -> Severity {
+        self.severity
+    }
+
+    /// Returns a description of the issue.
+    #[must_use]
+    #[inline]
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Returns the byte range in the input of the statement in which the issue was found.
+    ///
+    /// A statement is a table header or key/value pair, including any comment on the same line.
+    /// This spans several lines for multi-line strings and arrays.
+    #[must_use]
+    #[inline]
+    pub fn span(&self) -> Range<usize> {
+        self.span.clone()
+    }
+
+    pub(super) fn error(error: &Error, span: Range<usize>) -> Self {
+        Self {
+            severity: Severity::Error,
+            message: error.to_string(),
+            span,
+        }
+    }
+
+    pub(super) fn warning(warning: Warning, span: Range<usize>) -> Self {
+        Self {
+            severity: Severity::Warning,
+            message: warning.kind().to_string(),
+            span,
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.severity, self.message)
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage, coverage(off))]
+mod tests {
+    use super::*;
+    use crate::de::ErrorKind;
+
+    #[test]
+    fn diagnostic_error() {
+        let error = Error(ErrorKind::IllegalChar(b'\0', 4));
+        let diagnostic = Diagnostic::error(&error, 0..6);
+        assert_eq!(diagnostic.severity(), Severity::Error);
+        assert_eq!(diagnostic.message(), r"illegal character '\0' at offset 4");
+        assert_eq!(diagnostic.span(), 0..6);
+        assert_eq!(
+            diagnostic.to_string(),
+            r"error: illegal character '\0' at offset 4"
+        );
+
+        let error = Error(ErrorKind::UnterminatedString);
+        let diagnostic = Diagnostic::error(&error, 2..10);
+        assert_eq!(diagnostic.span(), 2..10);
+        assert_eq!(diagnostic.to_string(), "error: unterminated string");
+    }
+
+    #[cfg(feature = "datetime")]
+    #[test]
+    fn diagnostic_warning() {
+        use std::sync::{Arc, Mutex};
+
+        use serde::Deserialize as _;
+
+        let warnings = Arc::new(Mutex::new(Vec::new()));
+        let deserializer = crate::Deserializer::from_str("a = 12:00:00.0123456789").on_warning({
+            let warnings = Arc::clone(&warnings);
+            move |warning| warnings.lock().unwrap().push(warning)
+        });
+        crate::Value::deserialize(deserializer).unwrap();
+
+        let diagnostic = Diagnostic::warning(warnings.lock().unwrap()[0], 0..23);
+        assert_eq!(diagnostic.severity(), Severity::Warning);
+        assert_eq!(
+            diagnostic.message(),
+            "date-time has more than nanosecond precision"
+        );
+        assert_eq!(diagnostic.span(), 0..23);
+        assert_eq!(
+            diagnostic.to_string(),
+            "warning: date-time has more than nanosecond precision"
+        );
+    }
+}
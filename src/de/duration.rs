@@ -0,0 +1,187 @@
+use std::time::Duration;
+
+use serde::{de, ser, Deserialize as _};
+
+/// Serializes and deserializes a [`Duration`] as an integer number of seconds.
+///
+/// This is intended to be used with `#[serde(with = "soml::de::duration_seconds")]`. When
+/// serializing, any fractional seconds are truncated.
+///
+/// [`Duration`]: std::time::Duration
+pub mod duration_seconds {
+    use std::time::Duration;
+
+    use serde::{de, ser};
+
+    /// Serializes a [`Duration`] as a number of seconds.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the duration is too long to be represented by an `i64`, or if the
+    /// serializer fails to serialize the integer.
+    #[inline]
+    pub fn serialize<S>(value: &Duration, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        super::serialize(u128::from(value.as_secs()), serializer)
+    }
+
+    /// Deserializes a [`Duration`] from a number of seconds.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the value is not an integer or is negative.
+    #[inline]
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Duration, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        super::deserialize(
+            deserializer,
+            Duration::from_secs,
+            "a non-negative number of seconds",
+        )
+    }
+}
+
+/// Serializes and deserializes a [`Duration`] as an integer number of milliseconds.
+///
+/// This is intended to be used with `#[serde(with = "soml::de::duration_millis")]`. When
+/// serializing, any sub-millisecond precision is truncated.
+///
+/// [`Duration`]: std::time::Duration
+pub mod duration_millis {
+    use std::time::Duration;
+
+    use serde::{de, ser};
+
+    /// Serializes a [`Duration`] as a number of milliseconds.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the duration is too long to be represented by an `i64`, or if the
+    /// serializer fails to serialize the integer.
+    #[inline]
+    pub fn serialize<S>(value: &Duration, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        super::serialize(value.as_millis(), serializer)
+    }
+
+    /// Deserializes a [`Duration`] from a number of milliseconds.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the value is not an integer or is negative.
+    #[inline]
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Duration, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        super::deserialize(
+            deserializer,
+            Duration::from_millis,
+            "a non-negative number of milliseconds",
+        )
+    }
+}
+
+fn serialize<S>(units: u128, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: ser::Serializer,
+{
+    // TOML integers are signed 64-bit, so anything larger can't be represented
+    let units = i64::try_from(units)
+        .map_err(|_| ser::Error::custom("duration is too long to be represented by an i64"))?;
+    serializer.serialize_i64(units)
+}
+
+fn deserialize<'de, D>(
+    deserializer: D,
+    from_units: fn(u64) -> Duration,
+    expected: &'static str,
+) -> Result<Duration, D::Error>
+where
+    D: de::Deserializer<'de>,
+{
+    let value = i64::deserialize(deserializer)?;
+
+    u64::try_from(value)
+        .map(from_units)
+        .map_err(|_| de::Error::invalid_value(de::Unexpected::Signed(value), &expected))
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage, coverage(off))]
+mod tests {
+    use assert_matches::assert_matches;
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::de::{Error, ErrorKind};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Seconds {
+        #[serde(with = "duration_seconds")]
+        timeout: Duration,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Millis {
+        #[serde(with = "duration_millis")]
+        timeout: Duration,
+    }
+
+    #[test]
+    fn test_duration_seconds() {
+        let result: Seconds = crate::from_str("timeout = 30").unwrap();
+        assert_eq!(result.timeout, Duration::from_secs(30));
+
+        let result: Seconds = crate::from_str("timeout = 0").unwrap();
+        assert_eq!(result.timeout, Duration::ZERO);
+
+        let value = Seconds {
+            timeout: Duration::from_millis(30_999),
+        };
+        assert_eq!(crate::to_string(&value).unwrap(), "timeout = 30\n");
+
+        assert_matches!(
+            crate::from_str::<Seconds>("timeout = -1"),
+            Err(Error(ErrorKind::InvalidValue(unexp, exp)))
+                if &*unexp == "integer `-1`" && &*exp == "a non-negative number of seconds"
+        );
+        assert_matches!(
+            crate::from_str::<Seconds>("timeout = 1.5"),
+            Err(Error(ErrorKind::InvalidType(..)))
+        );
+        assert_matches!(
+            crate::to_string(&Seconds {
+                timeout: Duration::MAX
+            }),
+            Err(crate::ser::Error(crate::ser::ErrorKind::Custom(..)))
+        );
+    }
+
+    #[test]
+    fn test_duration_millis() {
+        let result: Millis = crate::from_str("timeout = 1500").unwrap();
+        assert_eq!(result.timeout, Duration::from_millis(1500));
+
+        let value = Millis {
+            timeout: Duration::from_micros(1_500_999),
+        };
+        assert_eq!(crate::to_string(&value).unwrap(), "timeout = 1500\n");
+
+        assert_matches!(
+            crate::from_str::<Millis>("timeout = -1500"),
+            Err(Error(ErrorKind::InvalidValue(unexp, exp)))
+                if &*unexp == "integer `-1500`" && &*exp == "a non-negative number of milliseconds"
+        );
+        assert_matches!(
+            crate::from_str::<Millis>(r#"timeout = "1500ms""#),
+            Err(Error(ErrorKind::InvalidType(..)))
+        );
+    }
+}
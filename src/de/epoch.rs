@@ -0,0 +1,307 @@
+use serde::{de, ser, Deserialize as _};
+
+use crate::value::{LocalDate, LocalTime, Offset, OffsetDatetime};
+
+const SECONDS_PER_DAY: i64 = 86_400;
+
+/// Serializes and deserializes an [`OffsetDatetime`] as an integer number of seconds since the
+/// Unix epoch.
+///
+/// This is intended to be used with `#[serde(with = "soml::de::epoch_seconds")]`. Deserialized
+/// values are always in UTC. When serializing, any fractional seconds are truncated.
+///
+/// [`OffsetDatetime`]: crate::value::OffsetDatetime
+pub mod epoch_seconds {
+    use serde::{de, ser};
+
+    use crate::value::OffsetDatetime;
+
+    /// Serializes an [`OffsetDatetime`] as seconds since the Unix epoch.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the serializer fails to serialize the integer.
+    #[inline]
+    pub fn serialize<S>(value: &OffsetDatetime, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        serializer.serialize_i64(super::to_seconds(value))
+    }
+
+    /// Deserializes an [`OffsetDatetime`] from seconds since the Unix epoch.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the value is not an integer or is outside the range of years
+    /// supported by TOML (0--9999).
+    #[inline]
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<OffsetDatetime, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        super::deserialize(deserializer, 1)
+    }
+}
+
+/// Serializes and deserializes an [`OffsetDatetime`] as an integer number of milliseconds since
+/// the Unix epoch.
+///
+/// This is intended to be used with `#[serde(with = "soml::de::epoch_millis")]`. Deserialized
+/// values are always in UTC. When serializing, any sub-millisecond precision is truncated.
+///
+/// [`OffsetDatetime`]: crate::value::OffsetDatetime
+pub mod epoch_millis {
+    use serde::{de, ser};
+
+    use crate::value::OffsetDatetime;
+
+    /// Serializes an [`OffsetDatetime`] as milliseconds since the Unix epoch.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the serializer fails to serialize the integer.
+    #[inline]
+    pub fn serialize<S>(value: &OffsetDatetime, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        super::serialize_scaled(value, serializer, 1000)
+    }
+
+    /// Deserializes an [`OffsetDatetime`] from milliseconds since the Unix epoch.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the value is not an integer or is outside the range of years
+    /// supported by TOML (0--9999).
+    #[inline]
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<OffsetDatetime, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        super::deserialize(deserializer, 1000)
+    }
+}
+
+fn serialize_scaled<S>(
+    value: &OffsetDatetime,
+    serializer: S,
+    per_second: i64,
+) -> Result<S::Ok, S::Error>
+where
+    S: ser::Serializer,
+{
+    let fraction = i64::from(value.time.nanosecond) / (1_000_000_000 / per_second);
+    serializer.serialize_i64(to_seconds(value) * per_second + fraction)
+}
+
+// Deserializes an integer number of `1 / per_second` second units since the epoch
+fn deserialize<'de, D>(deserializer: D, per_second: i64) -> Result<OffsetDatetime, D::Error>
+where
+    D: de::Deserializer<'de>,
+{
+    let value = i64::deserialize(deserializer)?;
+
+    let seconds = value.div_euclid(per_second);
+    let nanosecond = value.rem_euclid(per_second) * (1_000_000_000 / per_second);
+    u32::try_from(nanosecond)
+        .ok()
+        .and_then(|nanosecond| from_seconds(seconds, nanosecond))
+        .ok_or_else(|| {
+            de::Error::invalid_value(
+                de::Unexpected::Signed(value),
+                &"a timestamp between the years 0 and 9999",
+            )
+        })
+}
+
+fn to_seconds(value: &OffsetDatetime) -> i64 {
+    let LocalDate { year, month, day } = value.date;
+    let days = days_from_civil(i64::from(year), i64::from(month), i64::from(day));
+
+    let offset = match value.offset {
+        Offset::Z => 0,
+        Offset::Custom { minutes } => i64::from(minutes) * 60,
+    };
+
+    days * SECONDS_PER_DAY
+        + i64::from(value.time.hour) * 3600
+        + i64::from(value.time.minute) * 60
+        + i64::from(value.time.second)
+        - offset
+}
+
+fn from_seconds(seconds: i64, nanosecond: u32) -> Option<OffsetDatetime> {
+    let (year, month, day) = civil_from_days(seconds.div_euclid(SECONDS_PER_DAY));
+    let time = seconds.rem_euclid(SECONDS_PER_DAY);
+
+    let date = LocalDate {
+        year: u16::try_from(year).ok().filter(|&year| year <= 9999)?,
+        month: u8::try_from(month).ok()?,
+        day: u8::try_from(day).ok()?,
+    };
+    let time = LocalTime {
+        hour: u8::try_from(time / 3600).ok()?,
+        minute: u8::try_from(time / 60 % 60).ok()?,
+        second: u8::try_from(time % 60).ok()?,
+        nanosecond,
+    };
+
+    Some(OffsetDatetime {
+        date,
+        time,
+        offset: Offset::Z,
+    })
+}
+
+// Number of days since 1970-01-01 in the proleptic Gregorian calendar. See
+// <https://howardhinnant.github.io/date_algorithms.html#days_from_civil> for the algorithm
+const fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+// Inverse of days_from_civil. See
+// <https://howardhinnant.github.io/date_algorithms.html#civil_from_days> for the algorithm
+const fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153; // Starting from March
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = (shifted_month + 2) % 12 + 1;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage, coverage(off))]
+mod tests {
+    use assert_matches::assert_matches;
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::de::{Error, ErrorKind};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Seconds {
+        #[serde(with = "epoch_seconds")]
+        time: OffsetDatetime,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Millis {
+        #[serde(with = "epoch_millis")]
+        time: OffsetDatetime,
+    }
+
+    fn datetime(str: &str) -> OffsetDatetime {
+        OffsetDatetime::from_slice(str.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn test_epoch_seconds() {
+        let result: Seconds = crate::from_str("time = 1700000000").unwrap();
+        assert_eq!(result.time, datetime("2023-11-14T22:13:20Z"));
+
+        let result: Seconds = crate::from_str("time = -1").unwrap();
+        assert_eq!(result.time, datetime("1969-12-31T23:59:59Z"));
+
+        let value = Seconds {
+            time: datetime("2023-11-15T01:43:20.999+03:30"),
+        };
+        assert_eq!(crate::to_string(&value).unwrap(), "time = 1700000000\n");
+
+        assert_matches!(
+            crate::from_str::<Seconds>("time = 253402300800"),
+            Err(Error(ErrorKind::InvalidValue(..)))
+        );
+        assert_matches!(
+            crate::from_str::<Seconds>("time = 1979-05-27T07:32:00Z"),
+            Err(Error(ErrorKind::InvalidType(..)))
+        );
+    }
+
+    #[test]
+    fn test_epoch_millis() {
+        let result: Millis = crate::from_str("time = 1700000000123").unwrap();
+        assert_eq!(result.time, datetime("2023-11-14T22:13:20.123Z"));
+
+        let result: Millis = crate::from_str("time = -1").unwrap();
+        assert_eq!(result.time, datetime("1969-12-31T23:59:59.999Z"));
+
+        let value = Millis {
+            time: datetime("2023-11-14T22:13:20.123456Z"),
+        };
+        assert_eq!(crate::to_string(&value).unwrap(), "time = 1700000000123\n");
+
+        assert_matches!(
+            crate::from_str::<Millis>("time = -62167219200001"),
+            Err(Error(ErrorKind::InvalidValue(..)))
+        );
+    }
+
+    #[test]
+    fn test_to_seconds() {
+        assert_eq!(to_seconds(&datetime("1970-01-01T00:00:00Z")), 0);
+        assert_eq!(to_seconds(&datetime("1970-01-01T00:00:00+01:00")), -3600);
+        assert_eq!(to_seconds(&datetime("2000-03-01T00:00:00Z")), 951_868_800);
+        assert_eq!(
+            to_seconds(&datetime("0000-01-01T00:00:00Z")),
+            -62_167_219_200
+        );
+        assert_eq!(
+            to_seconds(&datetime("9999-12-31T23:59:59Z")),
+            253_402_300_799
+        );
+    }
+
+    #[test]
+    fn test_from_seconds() {
+        assert_eq!(from_seconds(0, 0), Some(datetime("1970-01-01T00:00:00Z")));
+        assert_eq!(
+            from_seconds(951_868_800, 5),
+            Some(datetime("2000-03-01T00:00:00.000000005Z"))
+        );
+        assert_eq!(
+            from_seconds(-62_167_219_200, 0),
+            Some(datetime("0000-01-01T00:00:00Z"))
+        );
+        assert_eq!(
+            from_seconds(253_402_300_799, 0),
+            Some(datetime("9999-12-31T23:59:59Z"))
+        );
+        assert_eq!(from_seconds(-62_167_219_201, 0), None);
+        assert_eq!(from_seconds(253_402_300_800, 0), None);
+        assert_eq!(from_seconds(i64::MIN, 0), None);
+        assert_eq!(from_seconds(i64::MAX, 0), None);
+    }
+
+    #[test]
+    fn test_civil_days() {
+        for (days, date) in [
+            (0, (1970, 1, 1)),
+            (-1, (1969, 12, 31)),
+            (59, (1970, 3, 1)),
+            (11_016, (2000, 2, 29)),
+            (-719_528, (0, 1, 1)),
+        ] {
+            assert_eq!(civil_from_days(days), date);
+            assert_eq!(days_from_civil(date.0, date.1, date.2), days);
+        }
+
+        // Every day round trips
+        for days in -800_000..800_000 {
+            let (year, month, day) = civil_from_days(days);
+            assert_eq!(days_from_civil(year, month, day), days);
+        }
+    }
+}
@@ -12,6 +12,115 @@ pub type Result<T> = std::result::Result<T, Error>;
 #[derive(Clone)]
 pub struct Error(pub(crate) ErrorKind);
 
+/// A broad category of deserialization error, returned by [`Error::category`].
+///
+/// Unlike the error message, the category is stable and can be used to handle different classes of
+/// error. More categories may be added in the future.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorCategory {
+    /// The input could not be read.
+    Io,
+    /// The input is not a syntactically valid TOML document.
+    Syntax,
+    /// The input contains an invalid date-time.
+    Datetime,
+    /// An integer is out of range of the type being deserialized.
+    Overflow,
+    /// The input is valid TOML, but does not match the type being deserialized.
+    Type,
+}
+
+impl Error {
+    /// Returns the category of the error.
+    ///
+    /// Date-time errors are also syntax errors and integer overflows are also type errors, so
+    /// [`is_syntax`](Self::is_syntax) and [`is_type`](Self::is_type) can be used to check for the
+    /// broader classes.
+    #[must_use]
+    #[inline]
+    pub fn category(&self) -> ErrorCategory {
+        match self.0 {
+            ErrorKind::Io(_) => ErrorCategory::Io,
+            ErrorKind::InvalidDatetime(_) | ErrorKind::ExcessPrecision => ErrorCategory::Datetime,
+            ErrorKind::InvalidInteger(ref lit, ref error) if int_overflow(lit, error).is_some() => {
+                ErrorCategory::Overflow
+            }
+            _ if self.is_syntax() => ErrorCategory::Syntax,
+            _ => ErrorCategory::Type,
+        }
+    }
+
+    /// Returns `true` if the error was caused by failing to read the input.
+    #[must_use]
+    #[inline]
+    pub fn is_io(&self) -> bool {
+        matches!(self.0, ErrorKind::Io(_))
+    }
+
+    /// Returns `true` if the input is not a syntactically valid TOML document.
+    #[must_use]
+    #[inline]
+    pub fn is_syntax(&self) -> bool {
+        matches!(
+            self.0,
+            ErrorKind::InvalidEncoding(_)
+                | ErrorKind::IllegalChar(..)
+                | ErrorKind::UnterminatedString
+                | ErrorKind::UnterminatedArray
+                | ErrorKind::InvalidEscape(_)
+                | ErrorKind::InvalidNumber(_)
+                | ErrorKind::InvalidDatetime(_)
+                | ErrorKind::ExcessPrecision
+                | ErrorKind::ExpectedToken(_)
+                | ErrorKind::TrailingComma(_)
+                | ErrorKind::TrailingData(_)
+                | ErrorKind::DuplicateKey(..)
+                | ErrorKind::InvalidTableHeader(_)
+                | ErrorKind::InvalidKeyPath(..)
+        )
+    }
+
+    /// Returns `true` if the input is valid TOML, but does not match the type being deserialized.
+    ///
+    /// This includes values which are out of range of the target type, as well as any custom
+    /// errors returned by a [`Deserialize`](serde::Deserialize) implementation.
+    #[must_use]
+    #[inline]
+    pub fn is_type(&self) -> bool {
+        matches!(
+            self.0,
+            ErrorKind::InvalidInteger(..)
+                | ErrorKind::InvalidFloat(..)
+                | ErrorKind::InvalidType(..)
+                | ErrorKind::InvalidValue(..)
+                | ErrorKind::InvalidLength(..)
+                | ErrorKind::UnknownVariant(..)
+                | ErrorKind::UnknownField(..)
+                | ErrorKind::MissingField(_)
+                | ErrorKind::EmptyDocument(_)
+                | ErrorKind::DuplicateField(_)
+                | ErrorKind::UnknownKey(_)
+                | ErrorKind::MissingSection(_)
+                | ErrorKind::InvalidSection(_)
+                | ErrorKind::Custom(_)
+        )
+    }
+
+    /// Returns `true` if the input ended before a string or array was closed.
+    ///
+    /// This usually indicates that the input was truncated. All such errors are also syntax
+    /// errors.
+    #[must_use]
+    #[inline]
+    pub fn is_eof(&self) -> bool {
+        matches!(
+            self.0,
+            ErrorKind::UnterminatedString | ErrorKind::UnterminatedArray
+        )
+    }
+}
+
 impl fmt::Display for Error {
     #[inline]
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -32,8 +141,8 @@ impl std::error::Error for Error {
     #[inline]
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self.0 {
-            ErrorKind::InvalidInteger(ref error) => Some(error),
-            ErrorKind::InvalidFloat(ref error) => Some(error),
+            ErrorKind::InvalidInteger(_, ref error) => Some(error),
+            ErrorKind::InvalidFloat(_, ref error) => Some(error),
             ErrorKind::Io(ref io_error) => Some(&**io_error),
             _ => None,
         }
@@ -108,27 +217,52 @@ impl From<io::Error> for Error {
     }
 }
 
+// Returns whether an integer which failed to parse is too large (`PosOverflow`) or too small
+// (`NegOverflow`) for the target type, or `None` if it failed for some other reason
+pub(super) fn int_overflow(literal: &str, error: &num::ParseIntError) -> Option<num::IntErrorKind> {
+    match *error.kind() {
+        kind @ (num::IntErrorKind::PosOverflow | num::IntErrorKind::NegOverflow) => Some(kind),
+        // Unsigned types report any negative number as an invalid digit
+        num::IntErrorKind::InvalidDigit
+            if literal
+                .strip_prefix('-')
+                .map_or(false, |digits| digits.bytes().all(|b| b.is_ascii_digit())) =>
+        {
+            Some(num::IntErrorKind::NegOverflow)
+        }
+        _ => None,
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum ErrorKind {
     // Parser errors
-    /// File is not UTF-8 encoded
-    InvalidEncoding,
-    /// Illegal control character
-    IllegalChar(u8),
+    /// File is not UTF-8 encoded (byte offset)
+    InvalidEncoding(usize),
+    /// Illegal control character (character, byte offset)
+    IllegalChar(u8, usize),
     /// Unterminated string
     UnterminatedString,
+    /// Unterminated array
+    UnterminatedArray,
     /// Invalid escape sequence
     InvalidEscape(Box<str>),
     /// Invalid number (rejected by parser)
     InvalidNumber(Box<str>),
-    /// Invalid integer (rejected by str->int conversion)
-    InvalidInteger(num::ParseIntError),
-    /// Invalid float (rejected by str->float conversion)
-    InvalidFloat(num::ParseFloatError),
-    /// Invalid date-time
-    InvalidDatetime,
+    /// Invalid integer (literal, error rejected by str->int conversion)
+    InvalidInteger(Box<str>, num::ParseIntError),
+    /// Invalid float (literal, error rejected by str->float conversion)
+    InvalidFloat(Box<str>, num::ParseFloatError),
+    /// Invalid date-time (literal)
+    InvalidDatetime(Box<str>),
+    /// Date-time with more than nanosecond precision
+    ExcessPrecision,
     /// Unexpected token
     ExpectedToken(Box<str>),
+    /// Trailing comma in an inline table (byte offset of the comma)
+    TrailingComma(usize),
+    /// Unexpected data after the end of the document (byte offset)
+    TrailingData(usize),
     /// Duplicate key
     DuplicateKey(Box<str>, Box<str>),
     /// Invalid table header
@@ -149,8 +283,16 @@ pub enum ErrorKind {
     UnknownField(Box<str>, OneOf),
     /// Missing field (field)
     MissingField(&'static str),
+    /// Missing field in a document with no keys (field)
+    EmptyDocument(&'static str),
     /// Duplicate field (field)
     DuplicateField(&'static str),
+    /// Unknown key at the root of the document (key)
+    UnknownKey(Box<str>),
+    /// Section not found in the document (path)
+    MissingSection(Box<str>),
+    /// Section is not a table (path)
+    InvalidSection(Box<str>),
 
     // Misc
     /// IO Error
@@ -165,15 +307,29 @@ impl fmt::Display for ErrorKind {
         use ErrorKind::*;
 
         match *self {
-            InvalidEncoding => write!(f, "file contains invalid UTF-8 bytes"),
-            IllegalChar(ch) => write!(f, "illegal character: {:?}", char::from(ch)),
+            InvalidEncoding(offset) => {
+                write!(f, "file contains invalid UTF-8 bytes at offset {offset}")
+            }
+            IllegalChar(ch, offset) => {
+                write!(
+                    f,
+                    "illegal character {:?} at offset {offset}",
+                    char::from(ch)
+                )
+            }
             UnterminatedString => write!(f, "unterminated string"),
+            UnterminatedArray => write!(f, "unterminated array"),
             InvalidEscape(ref seq) => write!(f, "invalid escape sequence: {seq}"),
             InvalidNumber(ref error) => write!(f, "invalid number: {error}"),
-            InvalidInteger(ref error) => write!(f, "invalid integer: {error}"),
-            InvalidFloat(ref error) => write!(f, "invalid float: {error}"),
-            InvalidDatetime => write!(f, "invalid date-time"),
+            InvalidInteger(ref lit, ref error) => write!(f, "invalid integer `{lit}`: {error}"),
+            InvalidFloat(ref lit, ref error) => write!(f, "invalid float `{lit}`: {error}"),
+            InvalidDatetime(ref lit) => write!(f, "invalid date-time `{lit}`"),
+            ExcessPrecision => write!(f, "date-time has more than nanosecond precision"),
             ExpectedToken(ref token) => write!(f, "expected {token}"),
+            TrailingComma(offset) => {
+                write!(f, "trailing comma in inline table at offset {offset}")
+            }
+            TrailingData(offset) => write!(f, "unexpected trailing data at offset {offset}"),
             DuplicateKey(ref key, ref table) => write!(f, "duplicate key: {key} in {table}"),
             InvalidTableHeader(ref key) => write!(f, "invalid table header: {key}"),
             InvalidKeyPath(ref key, ref table) => write!(f, "invalid key: {key} in {table}"),
@@ -183,7 +339,11 @@ impl fmt::Display for ErrorKind {
             UnknownVariant(ref var, ref exp) => write!(f, "unknown variant: {var}, expected {exp}"),
             UnknownField(ref fld, ref exp) => write!(f, "unknown field: {fld}, expected {exp}"),
             MissingField(fld) => write!(f, "missing field: {fld}"),
+            EmptyDocument(fld) => write!(f, "missing field: {fld}, the document is empty"),
             DuplicateField(fld) => write!(f, "duplicate field: {fld}"),
+            UnknownKey(ref key) => write!(f, "unknown key: {key}"),
+            MissingSection(ref path) => write!(f, "missing section: {path}"),
+            InvalidSection(ref path) => write!(f, "section is not a table: {path}"),
             Io(ref io_error) => write!(f, "IO error: {io_error}"),
             Custom(ref msg) => write!(f, "{msg}"),
         }
@@ -235,13 +395,19 @@ mod tests {
         let error = Error(ErrorKind::InvalidType("foo".into(), "bar".into()));
         assert!(error.source().is_none());
 
-        let error = Error(ErrorKind::InvalidInteger(i32::from_str("foo").unwrap_err()));
+        let error = Error(ErrorKind::InvalidInteger(
+            "foo".into(),
+            i32::from_str("foo").unwrap_err(),
+        ));
         let source = error.source().unwrap();
         let source = source.downcast_ref::<num::ParseIntError>().unwrap();
         assert_eq!(source.kind(), &num::IntErrorKind::InvalidDigit);
         assert_eq!(source.to_string(), "invalid digit found in string");
 
-        let error = Error(ErrorKind::InvalidFloat(f32::from_str("foo").unwrap_err()));
+        let error = Error(ErrorKind::InvalidFloat(
+            "foo".into(),
+            f32::from_str("foo").unwrap_err(),
+        ));
         let source = error.source().unwrap();
         let source = source.downcast_ref::<num::ParseFloatError>().unwrap();
         assert_eq!(source.to_string(), "invalid float literal");
@@ -256,6 +422,96 @@ mod tests {
         assert_eq!(source.to_string(), "foo");
     }
 
+    #[test]
+    fn error_classification() {
+        let error = Error(ErrorKind::Io(Arc::new(io::Error::new(
+            io::ErrorKind::NotFound,
+            "foo",
+        ))));
+        assert!(error.is_io());
+        assert!(!error.is_syntax());
+        assert!(!error.is_type());
+        assert!(!error.is_eof());
+
+        let error = crate::from_str::<crate::Value>("a = = 1").unwrap_err();
+        assert!(!error.is_io());
+        assert!(error.is_syntax());
+        assert!(!error.is_type());
+        assert!(!error.is_eof());
+
+        let error = crate::from_str::<crate::Value>("a = [1, 2").unwrap_err();
+        assert!(error.is_syntax());
+        assert!(error.is_eof());
+
+        let error = crate::from_str::<crate::Value>(r#"a = """abc"#).unwrap_err();
+        assert!(error.is_syntax());
+        assert!(error.is_eof());
+
+        let error =
+            crate::from_str::<std::collections::HashMap<String, u8>>("a = 256").unwrap_err();
+        assert!(!error.is_io());
+        assert!(!error.is_syntax());
+        assert!(error.is_type());
+        assert!(!error.is_eof());
+
+        let error =
+            crate::from_str::<std::collections::HashMap<String, u8>>(r#"a = "b""#).unwrap_err();
+        assert!(!error.is_syntax());
+        assert!(error.is_type());
+
+        let error = Error::custom("foo");
+        assert!(error.is_type());
+    }
+
+    #[test]
+    fn error_category() {
+        let error = Error(ErrorKind::Io(Arc::new(io::Error::new(
+            io::ErrorKind::NotFound,
+            "foo",
+        ))));
+        assert_eq!(error.category(), ErrorCategory::Io);
+
+        let error = crate::from_str::<crate::Value>("a = = 1").unwrap_err();
+        assert_eq!(error.category(), ErrorCategory::Syntax);
+
+        let error = crate::from_str::<crate::Value>("a = [1, 2").unwrap_err();
+        assert_eq!(error.category(), ErrorCategory::Syntax);
+
+        let error = Error(ErrorKind::InvalidDatetime("1979-02-30".into()));
+        assert_eq!(error.category(), ErrorCategory::Datetime);
+        assert!(error.is_syntax());
+
+        let error = Error(ErrorKind::ExcessPrecision);
+        assert_eq!(error.category(), ErrorCategory::Datetime);
+
+        let error =
+            crate::from_str::<std::collections::HashMap<String, u8>>("a = 256").unwrap_err();
+        assert_eq!(error.category(), ErrorCategory::Overflow);
+        assert!(error.is_type());
+
+        let error = crate::from_str::<std::collections::HashMap<String, u8>>("a = -1").unwrap_err();
+        assert_eq!(error.category(), ErrorCategory::Overflow);
+
+        let error = Error(ErrorKind::InvalidInteger(
+            "foo".into(),
+            i32::from_str("foo").unwrap_err(),
+        ));
+        assert_eq!(error.category(), ErrorCategory::Type);
+
+        let error = Error(ErrorKind::InvalidInteger(
+            "-1.5".into(),
+            i32::from_str("-1.5").unwrap_err(),
+        ));
+        assert_eq!(error.category(), ErrorCategory::Type);
+
+        let error =
+            crate::from_str::<std::collections::HashMap<String, u8>>(r#"a = "b""#).unwrap_err();
+        assert_eq!(error.category(), ErrorCategory::Type);
+
+        let error = Error::custom("foo");
+        assert_eq!(error.category(), ErrorCategory::Type);
+    }
+
     #[test]
     fn error_custom() {
         let error = Error::custom("foo");
@@ -368,36 +624,60 @@ mod tests {
 
     #[test]
     fn error_kind_display() {
-        let kind = ErrorKind::InvalidEncoding;
-        assert_eq!(kind.to_string(), "file contains invalid UTF-8 bytes");
+        let kind = ErrorKind::InvalidEncoding(42);
+        assert_eq!(
+            kind.to_string(),
+            "file contains invalid UTF-8 bytes at offset 42"
+        );
 
-        let kind = ErrorKind::IllegalChar(b'a');
-        assert_eq!(kind.to_string(), "illegal character: 'a'");
+        let kind = ErrorKind::IllegalChar(b'\x07', 42);
+        assert_eq!(kind.to_string(), "illegal character '\\u{7}' at offset 42");
 
         let kind = ErrorKind::UnterminatedString;
         assert_eq!(kind.to_string(), "unterminated string");
 
+        let kind = ErrorKind::UnterminatedArray;
+        assert_eq!(kind.to_string(), "unterminated array");
+
         let kind = ErrorKind::InvalidEscape("foo".into());
         assert_eq!(kind.to_string(), "invalid escape sequence: foo");
 
         let kind = ErrorKind::InvalidNumber("foo".into());
         assert_eq!(kind.to_string(), "invalid number: foo");
 
-        let kind = ErrorKind::InvalidInteger(i32::from_str("foo").unwrap_err());
+        let kind = ErrorKind::InvalidInteger("foo".into(), i32::from_str("foo").unwrap_err());
         assert_eq!(
             kind.to_string(),
-            "invalid integer: invalid digit found in string"
+            "invalid integer `foo`: invalid digit found in string"
         );
 
-        let kind = ErrorKind::InvalidFloat(f32::from_str("foo").unwrap_err());
-        assert_eq!(kind.to_string(), "invalid float: invalid float literal");
+        let kind = ErrorKind::InvalidFloat("foo".into(), f32::from_str("foo").unwrap_err());
+        assert_eq!(
+            kind.to_string(),
+            "invalid float `foo`: invalid float literal"
+        );
 
-        let kind = ErrorKind::InvalidDatetime;
-        assert_eq!(kind.to_string(), "invalid date-time");
+        let kind = ErrorKind::InvalidDatetime("foo".into());
+        assert_eq!(kind.to_string(), "invalid date-time `foo`");
+
+        let kind = ErrorKind::ExcessPrecision;
+        assert_eq!(
+            kind.to_string(),
+            "date-time has more than nanosecond precision"
+        );
 
         let kind = ErrorKind::ExpectedToken("foo".into());
         assert_eq!(kind.to_string(), "expected foo");
 
+        let kind = ErrorKind::TrailingComma(42);
+        assert_eq!(
+            kind.to_string(),
+            "trailing comma in inline table at offset 42"
+        );
+
+        let kind = ErrorKind::TrailingData(42);
+        assert_eq!(kind.to_string(), "unexpected trailing data at offset 42");
+
         let kind = ErrorKind::DuplicateKey("foo".into(), "bar".into());
         assert_eq!(kind.to_string(), "duplicate key: foo in bar");
 
@@ -425,6 +705,21 @@ mod tests {
         let kind = ErrorKind::MissingField("foo");
         assert_eq!(kind.to_string(), "missing field: foo");
 
+        let kind = ErrorKind::EmptyDocument("foo");
+        assert_eq!(
+            kind.to_string(),
+            "missing field: foo, the document is empty"
+        );
+
+        let kind = ErrorKind::UnknownKey("foo".into());
+        assert_eq!(kind.to_string(), "unknown key: foo");
+
+        let kind = ErrorKind::MissingSection("foo".into());
+        assert_eq!(kind.to_string(), "missing section: foo");
+
+        let kind = ErrorKind::InvalidSection("foo".into());
+        assert_eq!(kind.to_string(), "section is not a table: foo");
+
         let kind = ErrorKind::DuplicateField("foo");
         assert_eq!(kind.to_string(), "duplicate field: foo");
 
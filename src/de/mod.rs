@@ -1,17 +1,31 @@
 //! TOML deserialization functions and trait implementations.
 
+use core::num::IntErrorKind;
 use core::str;
-use std::io;
+use std::borrow::Cow;
+use std::marker::PhantomData;
 use std::result::Result as StdResult;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::{fmt, io, mem, slice, vec};
 
+#[cfg(any(unix, windows))]
+use serde::de::value::SeqDeserializer;
 use serde::de::value::StrDeserializer;
 use serde::de::{DeserializeOwned, Error as _, IntoDeserializer as _};
 use serde::{de, Deserialize};
 
+pub use self::bytes::{base64_bytes, hex_bytes};
+pub use self::diagnostic::{Diagnostic, Severity};
+pub use self::duration::{duration_millis, duration_seconds};
+#[cfg(feature = "datetime")]
+pub use self::epoch::{epoch_millis, epoch_seconds};
 pub(crate) use self::error::ErrorKind;
-pub use self::error::{Error, Result};
+pub use self::error::{Error, ErrorCategory, Result};
 use self::parser::{Parser, SpecialFloat, Table as ParsedTable, Value as ParsedValue};
 use self::reader::Reader;
+use self::warning::WarningSink;
+pub use self::warning::{Warning, WarningKind};
+use crate::ser::Formatter;
 #[cfg(feature = "datetime")]
 use crate::value::datetime::{
     LocalDateAccess, LocalDatetimeAccess, LocalTimeAccess, OffsetDatetimeAccess,
@@ -19,9 +33,17 @@ use crate::value::datetime::{
 #[cfg(feature = "datetime")]
 use crate::value::{AnyDatetime, LocalDate, LocalDatetime, LocalTime, OffsetDatetime};
 
+mod bytes;
+mod diagnostic;
+mod duration;
+#[cfg(feature = "datetime")]
+mod epoch;
 mod error;
 mod parser;
 mod reader;
+#[cfg(feature = "uuid")]
+pub mod uuid;
+mod warning;
 
 /// Deserialize a value of type `T` from a TOML string slice.
 ///
@@ -49,6 +71,63 @@ where
     T::deserialize(Deserializer::from_slice(bytes))
 }
 
+/// Parse a TOML string slice into a [`Value`], returning any errors and warnings as diagnostics.
+///
+/// This is intended for tools such as editors and validators which report problems with a
+/// document rather than just failing. Parsing recovers from an error by skipping the rest of the
+/// statement's line and continuing with the next one, so every error in the document can be
+/// reported. Statements with errors are left out of the returned value, as are keys following an
+/// invalid table header. Diagnostics are returned in the order they occur in the input, each with
+/// the [span](Diagnostic::span) of the statement in which it was found. Warnings are those
+/// described by [`Deserializer::on_warning`].
+///
+/// The value is only `None` if no value could be built at all.
+///
+/// [`Value`]: crate::Value
+#[must_use]
+#[inline]
+pub fn parse_with_diagnostics(s: &str) -> (Option<crate::Value>, Vec<Diagnostic>) {
+    let warnings = Arc::new(Mutex::new(Vec::new()));
+    let mut parser = Parser::from_str(s);
+    parser.on_warning = WarningSink::new({
+        let warnings = Arc::clone(&warnings);
+        move |warning| {
+            warnings
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .push(warning);
+        }
+    });
+
+    // Values are checked before they're inserted so any which can't be deserialized (e.g. an
+    // out of range integer) are reported like any other error rather than failing the whole
+    // document when it's deserialized below
+    let check_value = |value: &ParsedValue| {
+        crate::Value::deserialize(ValueDeserializer::new(value.clone())).map(drop)
+    };
+    let mut diagnostics = Vec::new();
+    let result = parser.parse_with(check_value, |span, result| {
+        let warnings = mem::take(&mut *warnings.lock().unwrap_or_else(PoisonError::into_inner));
+        diagnostics.extend(
+            warnings
+                .into_iter()
+                .map(|warning| Diagnostic::warning(warning, span.clone())),
+        );
+        if let Err(ref error) = result {
+            diagnostics.push(Diagnostic::error(error, span));
+        }
+        Ok(())
+    });
+
+    match result.and_then(|value| crate::Value::deserialize(ValueDeserializer::new(value))) {
+        Ok(value) => (Some(value), diagnostics),
+        Err(ref error) => {
+            diagnostics.push(Diagnostic::error(error, 0..s.len()));
+            (None, diagnostics)
+        }
+    }
+}
+
 /// Deserialize a value of type `T` from an [`io::Read`] source.
 ///
 /// # Errors
@@ -65,10 +144,126 @@ where
     T::deserialize(Deserializer::from_slice(&bytes))
 }
 
+/// Deserialize a value of type `T` from an [`io::BufRead`] source.
+///
+/// This is equivalent to [`from_reader`], since the whole input is read before parsing begins.
+///
+/// # Errors
+///
+/// This function will return an error if reading from the source fails, or if the source is not
+/// valid TOML.
+#[inline]
+pub fn from_bufread<R, T>(read: R) -> Result<T>
+where
+    R: io::BufRead,
+    T: DeserializeOwned,
+{
+    from_reader(read)
+}
+
+/// Deserialize a value of type `T` from a TOML string slice, also returning any keys in the input
+/// which were ignored during deserialization.
+///
+/// This is useful for warning about unknown or misspelled keys in a configuration file without
+/// rejecting it outright as `#[serde(deny_unknown_fields)]` would.
+///
+/// # Errors
+///
+/// This function will return an error if the input slice is not valid TOML.
+#[inline]
+pub fn from_str_tracking<'a, T>(s: &'a str) -> Result<(T, UnusedKeys)>
+where
+    T: Deserialize<'a>,
+{
+    let mut deserializer = Deserializer {
+        unused: Some(Vec::new()),
+        ..Deserializer::from_str(s)
+    };
+    let value = T::deserialize(&mut deserializer)?;
+    Ok((value, UnusedKeys(deserializer.unused.unwrap_or_default())))
+}
+
+/// The keys in a TOML document which were ignored during deserialization.
+///
+/// Each key is given as a dotted path from the root of the document, with array elements indexed
+/// by their position, for example `server.port` or `servers.0.name`. Keys are written as they
+/// would be in a TOML document, so any key which is not a valid bare key is quoted, for example
+/// `servers."eu.west".name`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UnusedKeys(Vec<String>);
+
+impl UnusedKeys {
+    /// Returns `true` if no keys were ignored.
+    #[must_use]
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns the number of ignored keys.
+    #[must_use]
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns an iterator over the ignored keys.
+    #[inline]
+    pub fn iter(&self) -> slice::Iter<'_, String> {
+        self.0.iter()
+    }
+}
+
+impl IntoIterator for UnusedKeys {
+    type Item = String;
+    type IntoIter = vec::IntoIter<String>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a UnusedKeys {
+    type Item = &'a String;
+    type IntoIter = slice::Iter<'a, String>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
 /// A deserializer for a TOML document.
+///
+/// Tables can be deserialized into enums using serde's internally tagged representation, for
+/// example with `#[serde(tag = "kind")]`. The `kind` key of the table then selects the variant,
+/// and the remaining keys are deserialized as the variant's fields.
+///
+/// Keys of a table deserialized into a map can be strings, integers, unit enum variants, or any
+/// other type which deserializes from a string, such as [`IpAddr`](std::net::IpAddr). Keys which
+/// contain a `.` or any other character not allowed in bare keys, like in an IP address, must be
+/// quoted so they're not parsed as dotted keys.
 #[derive(Debug)]
 pub struct Deserializer<'de> {
     parser: Parser<'de>,
+    lone_value: bool,
+    // Set to `Some` to track unused keys, which are then returned here after deserializing
+    unused: Option<Vec<String>>,
+    deny_unknown_top_level: bool,
+    section: Option<String>,
+    settings: Settings,
+}
+
+/// Options used to control how values are deserialized by the [`Deserializer`].
+#[derive(Debug, Clone, Default)]
+struct Settings {
+    /// Deserialize empty strings as `None` when an `Option` is expected
+    empty_string_as_none: bool,
+    /// Clamp decimal integers which overflow the target type instead of returning an error
+    saturate_integers: bool,
+    /// Callback for non-fatal issues found while deserializing
+    on_warning: WarningSink,
 }
 
 impl<'de> Deserializer<'de> {
@@ -79,6 +274,11 @@ impl<'de> Deserializer<'de> {
     pub fn from_str(str: &'de str) -> Self {
         Self {
             parser: Parser::from_str(str),
+            lone_value: false,
+            unused: None,
+            deny_unknown_top_level: false,
+            section: None,
+            settings: Settings::default(),
         }
     }
 
@@ -88,8 +288,227 @@ impl<'de> Deserializer<'de> {
     pub fn from_slice(bytes: &'de [u8]) -> Self {
         Self {
             parser: Parser::from_slice(bytes),
+            lone_value: false,
+            unused: None,
+            deny_unknown_top_level: false,
+            section: None,
+            settings: Settings::default(),
+        }
+    }
+
+    /// Create a new deserializer for a single table within a document from a string slice.
+    ///
+    /// The whole document is parsed, but only the table at the dotted `path` (for example
+    /// `"tool.soml"`) is deserialized. The path is written as a TOML dotted key, so a key
+    /// containing a `.` can be quoted, for example `r#"servers."eu.west""#`. An error is returned
+    /// if there is no value at `path` or if it is not a table.
+    #[must_use]
+    #[inline]
+    pub fn from_str_section(str: &'de str, path: &str) -> Self {
+        Self {
+            section: Some(path.to_owned()),
+            ..Self::from_str(str)
+        }
+    }
+
+    /// Create a new deserializer for a single TOML value from a string slice.
+    ///
+    /// Unlike [`Self::from_str`], the input is parsed as a lone value expression such as `42`,
+    /// `"foo"`, or `[1, 2, 3]`, rather than a document of `key = value` pairs.
+    #[must_use]
+    #[inline]
+    pub fn value_from_str(str: &'de str) -> Self {
+        Self {
+            parser: Parser::from_str(str),
+            lone_value: true,
+            unused: None,
+            deny_unknown_top_level: false,
+            section: None,
+            settings: Settings::default(),
+        }
+    }
+
+    /// Create a new deserializer for a single TOML value from a byte slice.
+    ///
+    /// Unlike [`Self::from_slice`], the input is parsed as a lone value expression such as `42`,
+    /// `"foo"`, or `[1, 2, 3]`, rather than a document of `key = value` pairs.
+    #[must_use]
+    #[inline]
+    pub fn value_from_slice(bytes: &'de [u8]) -> Self {
+        Self {
+            parser: Parser::from_slice(bytes),
+            lone_value: true,
+            unused: None,
+            deny_unknown_top_level: false,
+            section: None,
+            settings: Settings::default(),
         }
     }
+
+    /// Sets whether date-time values are checked to be in range when parsing.
+    ///
+    /// When enabled, values such as `2023-02-30` or `24:00:00` are rejected. These checks are
+    /// always performed when the `strict` feature is enabled, so this option is only useful to
+    /// re-enable them for individual documents when the `strict` feature is disabled.
+    #[cfg(feature = "datetime")]
+    #[must_use]
+    #[inline]
+    pub fn validate_datetimes(mut self, value: bool) -> Self {
+        self.parser.validate_datetimes = value;
+        self
+    }
+
+    /// Sets whether date-time values with more than nanosecond precision are rejected.
+    ///
+    /// By default any fractional seconds beyond nanosecond precision (9 digits) are truncated.
+    /// When enabled, such values return an error instead so that precision is never lost silently.
+    #[cfg(feature = "datetime")]
+    #[must_use]
+    #[inline]
+    pub fn reject_excess_precision(mut self, value: bool) -> Self {
+        self.parser.reject_excess_precision = value;
+        self
+    }
+
+    /// Sets the maximum length of an integer or float literal, excluding any underscores.
+    ///
+    /// Longer numbers are rejected as soon as the limit is reached, which guards against
+    /// pathological inputs. The default of 512 is far longer than any number that can be
+    /// represented by an `i64` or `f64`, except for floats written with many redundant digits.
+    #[must_use]
+    #[inline]
+    pub fn max_number_length(mut self, value: usize) -> Self {
+        self.parser.max_number_length = value;
+        self
+    }
+
+    /// Sets whether a trailing comma is accepted after the last key/value pair of an inline table.
+    ///
+    /// TOML does not allow inline tables such as `{ a = 1, }`, so these are rejected by default.
+    /// Enabling this is useful for leniently reading the output of tools which do not follow the
+    /// spec exactly. Trailing commas in arrays are always allowed.
+    #[must_use]
+    #[inline]
+    pub fn allow_inline_table_trailing_comma(mut self, value: bool) -> Self {
+        self.parser.allow_inline_table_trailing_comma = value;
+        self
+    }
+
+    /// Sets whether keys in the root table which are ignored by the target type are rejected.
+    ///
+    /// This is similar to `#[serde(deny_unknown_fields)]`, but applies only to the root of the
+    /// document and works regardless of how the target type is defined. Keys in nested tables
+    /// are not checked.
+    #[must_use]
+    #[inline]
+    pub fn deny_unknown_top_level(mut self, value: bool) -> Self {
+        self.deny_unknown_top_level = value;
+        self
+    }
+
+    /// Sets whether empty strings are deserialized as `None` for `Option` fields.
+    ///
+    /// This is useful for configurations where `key = ""` is used to mean the key is unset. By
+    /// default an empty string is deserialized as `Some("")`. Empty strings are not affected when
+    /// the target type is not an `Option`.
+    #[must_use]
+    #[inline]
+    pub fn empty_string_as_none(mut self, value: bool) -> Self {
+        self.settings.empty_string_as_none = value;
+        self
+    }
+
+    /// Sets whether decimal integers which are out of range for the target type are saturated.
+    ///
+    /// When enabled, a value such as `300` deserialized into a `u8` is clamped to `u8::MAX`
+    /// instead of returning an error, and likewise values below the minimum are clamped to the
+    /// type's minimum. Binary, octal, and hexadecimal integers are not affected.
+    #[must_use]
+    #[inline]
+    pub fn saturate_integers(mut self, value: bool) -> Self {
+        self.settings.saturate_integers = value;
+        self
+    }
+
+    /// Sets a callback which is called for any non-fatal issues found while deserializing.
+    ///
+    /// Each [`Warning`] has a [`WarningKind`], such as a date-time with fractional seconds beyond
+    /// nanosecond precision which were truncated, or an integer deserialized as a float which
+    /// cannot represent it exactly. This is useful for reporting these issues to the user without
+    /// rejecting the input. By default warnings are ignored.
+    ///
+    /// The callback is shared between the parser and every value deserializer behind an
+    /// `Arc<Mutex<_>>`, so it must be `Send` and `'static` and cannot borrow local variables. To
+    /// collect the warnings it can instead push them to a shared `Arc<Mutex<Vec<Warning>>>`.
+    #[must_use]
+    #[inline]
+    pub fn on_warning(mut self, callback: impl FnMut(Warning) + Send + 'static) -> Self {
+        let sink = WarningSink::new(callback);
+        self.parser.on_warning = sink.clone();
+        self.settings.on_warning = sink;
+        self
+    }
+
+    /// Checks that only whitespace and comments remain in the input.
+    ///
+    /// This is intended for use when driving the deserializer by reference, for example with
+    /// `T::deserialize(&mut deserializer)`, to confirm that nothing unexpected follows the
+    /// document. Deserializing always consumes the whole input, so this only fails if it is called
+    /// before deserializing. The one-shot functions such as [`from_str`] and [`from_slice`]
+    /// already reject any trailing data, so there is no need to call this when using them.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if anything other than whitespace or comments remains in the input.
+    #[inline]
+    pub fn end(&mut self) -> Result<()> {
+        self.parser.end()
+    }
+
+    /// Parses the whole document, then returns an iterator which deserializes the elements of the
+    /// array at the dotted `path` one at a time.
+    ///
+    /// This is a convenience for handling each element of an array separately, for example to
+    /// skip over elements which fail to deserialize. It does not stream the input: the whole
+    /// document is parsed up front and held in memory while iterating, and only the conversion of
+    /// each parsed element into a `T` is deferred until the iterator is advanced. Like the
+    /// deserializer, the iterator borrows from the input so elements can borrow strings from it.
+    /// An empty `path` selects the root value, which is useful with [`Self::value_from_str`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the input is not valid TOML, or if there is no array at `path`. Errors
+    /// deserializing individual elements are instead returned by the iterator.
+    #[inline]
+    pub fn parsed_array_iter<T>(mut self, path: &str) -> Result<ArrayIter<'de, T>>
+    where
+        T: Deserialize<'de>,
+    {
+        let value = if self.lone_value {
+            self.parser.parse_lone_value()?
+        } else {
+            self.parser.parse()?
+        };
+        let value = if path.is_empty() {
+            value
+        } else {
+            select_value(value, path)?
+        };
+
+        let seq = match value {
+            ParsedValue::Array(array) => {
+                ArraySeq::Array(SeqAccess::new(array).with_settings(self.settings))
+            }
+            ParsedValue::ArrayOfTables(array) => {
+                ArraySeq::ArrayOfTables(SeqAccess::new(array).with_settings(self.settings))
+            }
+            _ => return Err(Error::invalid_type(value.typ().into(), &"an array")),
+        };
+        Ok(ArrayIter {
+            seq,
+            marker: PhantomData,
+        })
+    }
 }
 
 impl<'de> de::Deserializer<'de> for Deserializer<'de> {
@@ -100,7 +519,84 @@ impl<'de> de::Deserializer<'de> for Deserializer<'de> {
     where
         V: de::Visitor<'de>,
     {
-        ValueDeserializer::new(self.parser.parse()?).deserialize_any(visitor)
+        (&mut self).deserialize_any(visitor)
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+impl<'de> de::Deserializer<'de> for &mut Deserializer<'de> {
+    type Error = Error;
+
+    #[inline]
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        let value = if self.lone_value {
+            self.parser.parse_lone_value()?
+        } else {
+            self.parser.parse()?
+        };
+        let value = match self.section {
+            Some(ref path) => select_section(value, path)?,
+            None => value,
+        };
+
+        // A document with no keys at all is more likely to be the wrong file than one which is
+        // only missing some fields, so this is reported differently
+        let empty = !self.lone_value
+            && self.section.is_none()
+            && matches!(value, ParsedValue::Table(ref table) if table.is_empty());
+
+        // Only keys in the root table are rejected, not those of a lone value or nested tables
+        let deny_unknown = self.deny_unknown_top_level && matches!(value, ParsedValue::Table(_));
+        let unused = (self.unused.is_some() || self.deny_unknown_top_level)
+            .then(|| Arc::new(Mutex::new(Vec::new())));
+
+        let tracker = unused.as_ref().map(|unused| KeyTracker {
+            path: Vec::new(),
+            unused: Arc::clone(unused),
+        });
+        let result = ValueDeserializer::new(value)
+            .tracked(tracker)
+            .with_settings(self.settings.clone())
+            .deserialize_any(visitor)
+            .map_err(|err| match err.0 {
+                ErrorKind::MissingField(field) if empty => ErrorKind::EmptyDocument(field).into(),
+                _ => err,
+            })?;
+
+        let unused = unused.map_or_else(Vec::new, |unused| {
+            mem::take(&mut *unused.lock().unwrap_or_else(PoisonError::into_inner))
+        });
+        if deny_unknown {
+            let root_keys = unused.iter().filter_map(|path| match *path.as_slice() {
+                [ref key] => Some(key),
+                _ => None,
+            });
+            if let Some(key) = root_keys.min() {
+                return Err(ErrorKind::UnknownKey(key.as_str().into()).into());
+            }
+        }
+        if self.unused.is_some() {
+            self.unused = Some(
+                unused
+                    .iter()
+                    .map(|path| {
+                        path.iter().fold(String::new(), |mut dotted, key| {
+                            Formatter::push_key(&mut dotted, key);
+                            dotted
+                        })
+                    })
+                    .collect(),
+            );
+        }
+        Ok(result)
     }
 
     serde::forward_to_deserialize_any! {
@@ -110,19 +606,157 @@ impl<'de> de::Deserializer<'de> for Deserializer<'de> {
     }
 }
 
+// Returns the table at the dotted `path` within `value`
+fn select_section<'de>(value: ParsedValue<'de>, path: &str) -> Result<ParsedValue<'de>> {
+    match select_value(value, path)? {
+        value @ (ParsedValue::Table(_)
+        | ParsedValue::UndefinedTable(_)
+        | ParsedValue::DottedKeyTable(_)
+        | ParsedValue::InlineTable(_)) => Ok(value),
+        _ => Err(ErrorKind::InvalidSection(path.into()).into()),
+    }
+}
+
+// Returns the value at the dotted `path` within `value`. The path is parsed as a TOML dotted key,
+// so keys containing a `.` can be quoted
+fn select_value<'de>(mut value: ParsedValue<'de>, path: &str) -> Result<ParsedValue<'de>> {
+    for key in Parser::from_str(path).parse_key_path()? {
+        let (ParsedValue::Table(mut table)
+        | ParsedValue::UndefinedTable(mut table)
+        | ParsedValue::DottedKeyTable(mut table)
+        | ParsedValue::InlineTable(mut table)) = value
+        else {
+            return Err(ErrorKind::InvalidSection(path.into()).into());
+        };
+        value = table
+            .remove(&key)
+            .ok_or_else(|| ErrorKind::MissingSection(path.into()))?;
+    }
+    Ok(value)
+}
+
+/// An iterator over the elements of an array, returned by [`Deserializer::parsed_array_iter`].
+///
+/// Each element is deserialized into a `T` as the iterator is advanced. If an element fails to
+/// deserialize the error is returned for that element and iteration can continue.
+pub struct ArrayIter<'de, T> {
+    seq: ArraySeq<'de>,
+    marker: PhantomData<fn() -> T>,
+}
+
+// The remaining elements of either kind of array
+enum ArraySeq<'de> {
+    Array(SeqAccess<ParsedValue<'de>>),
+    ArrayOfTables(SeqAccess<ParsedTable<'de>>),
+}
+
+impl ArraySeq<'_> {
+    fn len(&self) -> usize {
+        match *self {
+            Self::Array(ref seq) => seq.values.len(),
+            Self::ArrayOfTables(ref seq) => seq.values.len(),
+        }
+    }
+}
+
+impl<'de, T> Iterator for ArrayIter<'de, T>
+where
+    T: Deserialize<'de>,
+{
+    type Item = Result<T>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.seq {
+            ArraySeq::Array(ref mut seq) => de::SeqAccess::next_element(seq),
+            ArraySeq::ArrayOfTables(ref mut seq) => de::SeqAccess::next_element(seq),
+        }
+        .transpose()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.len();
+        (len, Some(len))
+    }
+}
+
+impl<'de, T> ExactSizeIterator for ArrayIter<'de, T>
+where
+    T: Deserialize<'de>,
+{
+    #[inline]
+    fn len(&self) -> usize {
+        self.seq.len()
+    }
+}
+
+impl<T> fmt::Debug for ArrayIter<'_, T> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ArrayIter")
+            .field("remaining", &self.seq.len())
+            .finish()
+    }
+}
+
+// Records the path of the current value so ignored keys can be reported by `from_str_tracking`
+#[derive(Debug, Clone)]
+struct KeyTracker {
+    path: Vec<String>,
+    unused: Arc<Mutex<Vec<Vec<String>>>>,
+}
+
+impl KeyTracker {
+    fn child(&self, key: &str) -> Self {
+        let mut path = self.path.clone();
+        path.push(key.to_owned());
+        Self {
+            path,
+            unused: Arc::clone(&self.unused),
+        }
+    }
+
+    fn ignore(self) {
+        // Ignoring the root of the document is not considered an unused key
+        if !self.path.is_empty() {
+            self.unused
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .push(self.path);
+        }
+    }
+}
+
 #[derive(Debug)]
-struct ValueDeserializer {
-    value: ParsedValue,
+struct ValueDeserializer<'de> {
+    value: ParsedValue<'de>,
+    tracker: Option<KeyTracker>,
+    settings: Settings,
 }
 
-impl ValueDeserializer {
+impl<'de> ValueDeserializer<'de> {
+    #[inline]
+    fn new(value: ParsedValue<'de>) -> Self {
+        Self {
+            value,
+            tracker: None,
+            settings: Settings::default(),
+        }
+    }
+
+    #[inline]
+    fn tracked(self, tracker: Option<KeyTracker>) -> Self {
+        Self { tracker, ..self }
+    }
+
     #[inline]
-    const fn new(value: ParsedValue) -> Self {
-        Self { value }
+    fn with_settings(self, settings: Settings) -> Self {
+        Self { settings, ..self }
     }
 }
 
-impl<'de> de::Deserializer<'de> for ValueDeserializer {
+impl<'de> de::Deserializer<'de> for ValueDeserializer<'de> {
     type Error = Error;
 
     fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
@@ -130,8 +764,10 @@ impl<'de> de::Deserializer<'de> for ValueDeserializer {
         V: de::Visitor<'de>,
     {
         match self.value {
-            ParsedValue::String(str) => str.into_deserializer().deserialize_any(visitor),
-            ParsedValue::Integer(bytes) => visitor.visit_i64(parse_integer(&bytes)?),
+            ParsedValue::String(str) => visit_str(str, visitor),
+            ParsedValue::Integer(bytes, _) => {
+                visitor.visit_i64(parse_integer(&bytes, self.settings.saturate_integers)?)
+            }
             ParsedValue::BinaryInt(bytes) => visitor.visit_i64(parse_binary(&bytes)?),
             ParsedValue::OctalInt(bytes) => visitor.visit_i64(parse_octal(&bytes)?),
             ParsedValue::HexInt(bytes) => visitor.visit_i64(parse_hexadecimal(&bytes)?),
@@ -150,12 +786,24 @@ impl<'de> de::Deserializer<'de> for ValueDeserializer {
             ParsedValue::LocalDate(date) => visitor.visit_map(LocalDateAccess::new(date)),
             #[cfg(feature = "datetime")]
             ParsedValue::LocalTime(time) => visitor.visit_map(LocalTimeAccess::new(time)),
-            ParsedValue::Array(array) => visitor.visit_seq(SeqAccess::new(array)),
-            ParsedValue::ArrayOfTables(array) => visitor.visit_seq(SeqAccess::new(array)),
+            ParsedValue::Array(array) => visitor.visit_seq(
+                SeqAccess::new(array)
+                    .tracked(self.tracker)
+                    .with_settings(self.settings),
+            ),
+            ParsedValue::ArrayOfTables(array) => visitor.visit_seq(
+                SeqAccess::new(array)
+                    .tracked(self.tracker)
+                    .with_settings(self.settings),
+            ),
             ParsedValue::Table(table)
             | ParsedValue::UndefinedTable(table)
             | ParsedValue::InlineTable(table)
-            | ParsedValue::DottedKeyTable(table) => visitor.visit_map(MapAccess::new(table)),
+            | ParsedValue::DottedKeyTable(table) => visitor.visit_map(
+                MapAccess::new(table)
+                    .tracked(self.tracker)
+                    .with_settings(self.settings),
+            ),
         }
     }
 
@@ -176,7 +824,9 @@ impl<'de> de::Deserializer<'de> for ValueDeserializer {
         V: de::Visitor<'de>,
     {
         match self.value {
-            ParsedValue::Integer(bytes) => visitor.visit_i8(parse_integer(&bytes)?),
+            ParsedValue::Integer(bytes, _) => {
+                visitor.visit_i8(parse_integer(&bytes, self.settings.saturate_integers)?)
+            }
             ParsedValue::BinaryInt(bytes) => visitor.visit_i8(parse_binary(&bytes)?),
             ParsedValue::OctalInt(bytes) => visitor.visit_i8(parse_octal(&bytes)?),
             ParsedValue::HexInt(bytes) => visitor.visit_i8(parse_hexadecimal(&bytes)?),
@@ -190,7 +840,9 @@ impl<'de> de::Deserializer<'de> for ValueDeserializer {
         V: de::Visitor<'de>,
     {
         match self.value {
-            ParsedValue::Integer(bytes) => visitor.visit_i16(parse_integer(&bytes)?),
+            ParsedValue::Integer(bytes, _) => {
+                visitor.visit_i16(parse_integer(&bytes, self.settings.saturate_integers)?)
+            }
             ParsedValue::BinaryInt(bytes) => visitor.visit_i16(parse_binary(&bytes)?),
             ParsedValue::OctalInt(bytes) => visitor.visit_i16(parse_octal(&bytes)?),
             ParsedValue::HexInt(bytes) => visitor.visit_i16(parse_hexadecimal(&bytes)?),
@@ -204,7 +856,9 @@ impl<'de> de::Deserializer<'de> for ValueDeserializer {
         V: de::Visitor<'de>,
     {
         match self.value {
-            ParsedValue::Integer(bytes) => visitor.visit_i32(parse_integer(&bytes)?),
+            ParsedValue::Integer(bytes, _) => {
+                visitor.visit_i32(parse_integer(&bytes, self.settings.saturate_integers)?)
+            }
             ParsedValue::BinaryInt(bytes) => visitor.visit_i32(parse_binary(&bytes)?),
             ParsedValue::OctalInt(bytes) => visitor.visit_i32(parse_octal(&bytes)?),
             ParsedValue::HexInt(bytes) => visitor.visit_i32(parse_hexadecimal(&bytes)?),
@@ -218,7 +872,9 @@ impl<'de> de::Deserializer<'de> for ValueDeserializer {
         V: de::Visitor<'de>,
     {
         match self.value {
-            ParsedValue::Integer(bytes) => visitor.visit_i64(parse_integer(&bytes)?),
+            ParsedValue::Integer(bytes, _) => {
+                visitor.visit_i64(parse_integer(&bytes, self.settings.saturate_integers)?)
+            }
             ParsedValue::BinaryInt(bytes) => visitor.visit_i64(parse_binary(&bytes)?),
             ParsedValue::OctalInt(bytes) => visitor.visit_i64(parse_octal(&bytes)?),
             ParsedValue::HexInt(bytes) => visitor.visit_i64(parse_hexadecimal(&bytes)?),
@@ -232,7 +888,9 @@ impl<'de> de::Deserializer<'de> for ValueDeserializer {
         V: de::Visitor<'de>,
     {
         match self.value {
-            ParsedValue::Integer(bytes) => visitor.visit_i128(parse_integer(&bytes)?),
+            ParsedValue::Integer(bytes, _) => {
+                visitor.visit_i128(parse_integer(&bytes, self.settings.saturate_integers)?)
+            }
             ParsedValue::BinaryInt(bytes) => visitor.visit_i128(parse_binary(&bytes)?),
             ParsedValue::OctalInt(bytes) => visitor.visit_i128(parse_octal(&bytes)?),
             ParsedValue::HexInt(bytes) => visitor.visit_i128(parse_hexadecimal(&bytes)?),
@@ -246,7 +904,9 @@ impl<'de> de::Deserializer<'de> for ValueDeserializer {
         V: de::Visitor<'de>,
     {
         match self.value {
-            ParsedValue::Integer(bytes) => visitor.visit_u8(parse_integer(&bytes)?),
+            ParsedValue::Integer(bytes, _) => {
+                visitor.visit_u8(parse_integer(&bytes, self.settings.saturate_integers)?)
+            }
             ParsedValue::BinaryInt(bytes) => visitor.visit_u8(parse_binary(&bytes)?),
             ParsedValue::OctalInt(bytes) => visitor.visit_u8(parse_octal(&bytes)?),
             ParsedValue::HexInt(bytes) => visitor.visit_u8(parse_hexadecimal(&bytes)?),
@@ -260,7 +920,9 @@ impl<'de> de::Deserializer<'de> for ValueDeserializer {
         V: de::Visitor<'de>,
     {
         match self.value {
-            ParsedValue::Integer(bytes) => visitor.visit_u16(parse_integer(&bytes)?),
+            ParsedValue::Integer(bytes, _) => {
+                visitor.visit_u16(parse_integer(&bytes, self.settings.saturate_integers)?)
+            }
             ParsedValue::BinaryInt(bytes) => visitor.visit_u16(parse_binary(&bytes)?),
             ParsedValue::OctalInt(bytes) => visitor.visit_u16(parse_octal(&bytes)?),
             ParsedValue::HexInt(bytes) => visitor.visit_u16(parse_hexadecimal(&bytes)?),
@@ -274,7 +936,9 @@ impl<'de> de::Deserializer<'de> for ValueDeserializer {
         V: de::Visitor<'de>,
     {
         match self.value {
-            ParsedValue::Integer(bytes) => visitor.visit_u32(parse_integer(&bytes)?),
+            ParsedValue::Integer(bytes, _) => {
+                visitor.visit_u32(parse_integer(&bytes, self.settings.saturate_integers)?)
+            }
             ParsedValue::BinaryInt(bytes) => visitor.visit_u32(parse_binary(&bytes)?),
             ParsedValue::OctalInt(bytes) => visitor.visit_u32(parse_octal(&bytes)?),
             ParsedValue::HexInt(bytes) => visitor.visit_u32(parse_hexadecimal(&bytes)?),
@@ -288,7 +952,9 @@ impl<'de> de::Deserializer<'de> for ValueDeserializer {
         V: de::Visitor<'de>,
     {
         match self.value {
-            ParsedValue::Integer(bytes) => visitor.visit_u64(parse_integer(&bytes)?),
+            ParsedValue::Integer(bytes, _) => {
+                visitor.visit_u64(parse_integer(&bytes, self.settings.saturate_integers)?)
+            }
             ParsedValue::BinaryInt(bytes) => visitor.visit_u64(parse_binary(&bytes)?),
             ParsedValue::OctalInt(bytes) => visitor.visit_u64(parse_octal(&bytes)?),
             ParsedValue::HexInt(bytes) => visitor.visit_u64(parse_hexadecimal(&bytes)?),
@@ -302,7 +968,9 @@ impl<'de> de::Deserializer<'de> for ValueDeserializer {
         V: de::Visitor<'de>,
     {
         match self.value {
-            ParsedValue::Integer(bytes) => visitor.visit_u128(parse_integer(&bytes)?),
+            ParsedValue::Integer(bytes, _) => {
+                visitor.visit_u128(parse_integer(&bytes, self.settings.saturate_integers)?)
+            }
             ParsedValue::BinaryInt(bytes) => visitor.visit_u128(parse_binary(&bytes)?),
             ParsedValue::OctalInt(bytes) => visitor.visit_u128(parse_octal(&bytes)?),
             ParsedValue::HexInt(bytes) => visitor.visit_u128(parse_hexadecimal(&bytes)?),
@@ -316,9 +984,12 @@ impl<'de> de::Deserializer<'de> for ValueDeserializer {
         V: de::Visitor<'de>,
     {
         match self.value {
-            ParsedValue::Float(bytes) | ParsedValue::Integer(bytes) => {
-                visitor.visit_f32(parse_float(&bytes)?)
-            }
+            ParsedValue::Float(bytes) => visitor.visit_f32(parse_float(&bytes)?),
+            ParsedValue::Integer(bytes, offset) => visitor.visit_f32(parse_integer_as_float(
+                &bytes,
+                offset,
+                &self.settings.on_warning,
+            )?),
             ParsedValue::SpecialFloat(special) => visitor.visit_f32(parse_special(special)),
             _ => Err(Error::invalid_type(self.value.typ().into(), &visitor)),
         }
@@ -330,9 +1001,12 @@ impl<'de> de::Deserializer<'de> for ValueDeserializer {
         V: de::Visitor<'de>,
     {
         match self.value {
-            ParsedValue::Float(bytes) | ParsedValue::Integer(bytes) => {
-                visitor.visit_f64(parse_float(&bytes)?)
-            }
+            ParsedValue::Float(bytes) => visitor.visit_f64(parse_float(&bytes)?),
+            ParsedValue::Integer(bytes, offset) => visitor.visit_f64(parse_integer_as_float(
+                &bytes,
+                offset,
+                &self.settings.on_warning,
+            )?),
             ParsedValue::SpecialFloat(special) => visitor.visit_f64(parse_special(special)),
             _ => Err(Error::invalid_type(self.value.typ().into(), &visitor)),
         }
@@ -352,7 +1026,7 @@ impl<'de> de::Deserializer<'de> for ValueDeserializer {
         V: de::Visitor<'de>,
     {
         match self.value {
-            ParsedValue::String(string) => visitor.visit_string(string),
+            ParsedValue::String(str) => visit_str(str, visitor),
             _ => Err(Error::invalid_type(self.value.typ().into(), &visitor)),
         }
     }
@@ -371,7 +1045,14 @@ impl<'de> de::Deserializer<'de> for ValueDeserializer {
         V: de::Visitor<'de>,
     {
         match self.value {
-            ParsedValue::String(string) => visitor.visit_byte_buf(string.into_bytes()),
+            ParsedValue::String(Cow::Borrowed(str)) => visitor.visit_borrowed_bytes(str.as_bytes()),
+            ParsedValue::String(Cow::Owned(string)) => visitor.visit_byte_buf(string.into_bytes()),
+            // Bytes are serialized as an array of integers
+            ParsedValue::Array(array) => visitor.visit_seq(
+                SeqAccess::new(array)
+                    .tracked(self.tracker)
+                    .with_settings(self.settings),
+            ),
             _ => Err(Error::invalid_type(self.value.typ().into(), &visitor)),
         }
     }
@@ -389,7 +1070,14 @@ impl<'de> de::Deserializer<'de> for ValueDeserializer {
     where
         V: de::Visitor<'de>,
     {
-        visitor.visit_some(self)
+        match self.value {
+            ParsedValue::String(ref str)
+                if str.is_empty() && self.settings.empty_string_as_none =>
+            {
+                visitor.visit_none()
+            }
+            _ => visitor.visit_some(self),
+        }
     }
 
     #[inline]
@@ -422,31 +1110,49 @@ impl<'de> de::Deserializer<'de> for ValueDeserializer {
         V: de::Visitor<'de>,
     {
         match self.value {
-            ParsedValue::Array(array) => visitor.visit_seq(SeqAccess::new(array)),
-            ParsedValue::ArrayOfTables(array) => visitor.visit_seq(SeqAccess::new(array)),
+            ParsedValue::Array(array) => visitor.visit_seq(
+                SeqAccess::new(array)
+                    .tracked(self.tracker)
+                    .with_settings(self.settings),
+            ),
+            ParsedValue::ArrayOfTables(array) => visitor.visit_seq(
+                SeqAccess::new(array)
+                    .tracked(self.tracker)
+                    .with_settings(self.settings),
+            ),
             _ => Err(Error::invalid_type(self.value.typ().into(), &visitor)),
         }
     }
 
     #[inline]
-    fn deserialize_tuple<V>(self, _len: usize, visitor: V) -> Result<V::Value>
+    fn deserialize_tuple<V>(self, len: usize, visitor: V) -> Result<V::Value>
     where
         V: de::Visitor<'de>,
     {
-        self.deserialize_seq(visitor)
+        match self.value {
+            ParsedValue::Array(array) => SeqAccess::new(array)
+                .tracked(self.tracker)
+                .with_settings(self.settings)
+                .visit_tuple(len, visitor),
+            ParsedValue::ArrayOfTables(array) => SeqAccess::new(array)
+                .tracked(self.tracker)
+                .with_settings(self.settings)
+                .visit_tuple(len, visitor),
+            _ => Err(Error::invalid_type(self.value.typ().into(), &visitor)),
+        }
     }
 
     #[inline]
     fn deserialize_tuple_struct<V>(
         self,
         _name: &'static str,
-        _len: usize,
+        len: usize,
         visitor: V,
     ) -> Result<V::Value>
     where
         V: de::Visitor<'de>,
     {
-        self.deserialize_seq(visitor)
+        self.deserialize_tuple(len, visitor)
     }
 
     #[inline]
@@ -458,7 +1164,11 @@ impl<'de> de::Deserializer<'de> for ValueDeserializer {
             ParsedValue::Table(table)
             | ParsedValue::UndefinedTable(table)
             | ParsedValue::DottedKeyTable(table)
-            | ParsedValue::InlineTable(table) => visitor.visit_map(MapAccess::new(table)),
+            | ParsedValue::InlineTable(table) => visitor.visit_map(
+                MapAccess::new(table)
+                    .tracked(self.tracker)
+                    .with_settings(self.settings),
+            ),
             _ => Err(Error::invalid_type(self.value.typ().into(), &visitor)),
         }
     }
@@ -522,28 +1232,51 @@ impl<'de> de::Deserializer<'de> for ValueDeserializer {
             ParsedValue::Table(table)
             | ParsedValue::UndefinedTable(table)
             | ParsedValue::DottedKeyTable(table)
-            | ParsedValue::InlineTable(table) => visitor.visit_map(MapAccess::new(table)),
+            | ParsedValue::InlineTable(table) => visitor.visit_map(
+                MapAccess::new(table)
+                    .tracked(self.tracker)
+                    .with_settings(self.settings),
+            ),
             value => Err(Error::invalid_type(value.typ().into(), &visitor)),
         }
     }
 
+    #[cfg_attr(not(any(unix, windows)), allow(unused_variables))]
     #[inline]
     fn deserialize_enum<V>(
         self,
-        _name: &'static str,
-        _variants: &'static [&'static str],
+        name: &'static str,
+        variants: &'static [&'static str],
         visitor: V,
     ) -> StdResult<V::Value, Self::Error>
     where
         V: de::Visitor<'de>,
     {
         match self.value {
-            ParsedValue::String(str) => visitor.visit_enum(str.into_deserializer()),
+            // Serde deserializes an OsString as an enum of its platform specific representation
+            #[cfg(any(unix, windows))]
+            ParsedValue::String(string)
+                if name == "OsString" && variants == ["Unix", "Windows"] =>
+            {
+                visitor.visit_enum(OsStringAccess {
+                    string: string.into_owned(),
+                })
+            }
+            ParsedValue::String(variant) => visitor.visit_enum(UnitVariantAccess {
+                variant: variant.into_owned(),
+            }),
             ParsedValue::Table(table)
             | ParsedValue::UndefinedTable(table)
             | ParsedValue::DottedKeyTable(table)
-            | ParsedValue::InlineTable(table) => visitor.visit_enum(EnumAccess::new(table)?),
-            _ => Err(Error::invalid_type(self.value.typ().into(), &visitor)),
+            | ParsedValue::InlineTable(table) => visitor.visit_enum(
+                EnumAccess::new(table)?
+                    .tracked(self.tracker)
+                    .with_settings(self.settings),
+            ),
+            _ => Err(Error::invalid_type(
+                self.value.typ().into(),
+                &"a string for a unit variant or a table with a single key for any other variant",
+            )),
         }
     }
 
@@ -560,12 +1293,18 @@ impl<'de> de::Deserializer<'de> for ValueDeserializer {
     where
         V: de::Visitor<'de>,
     {
+        if let Some(tracker) = self.tracker {
+            tracker.ignore();
+        }
         visitor.visit_unit()
     }
 }
 
 struct SeqAccess<T> {
     values: <Vec<T> as IntoIterator>::IntoIter,
+    index: usize,
+    tracker: Option<KeyTracker>,
+    settings: Settings,
 }
 
 impl<T> SeqAccess<T> {
@@ -573,12 +1312,53 @@ impl<T> SeqAccess<T> {
     fn new(array: Vec<T>) -> Self {
         Self {
             values: array.into_iter(),
+            index: 0,
+            tracker: None,
+            settings: Settings::default(),
+        }
+    }
+
+    #[inline]
+    fn tracked(self, tracker: Option<KeyTracker>) -> Self {
+        Self { tracker, ..self }
+    }
+
+    #[inline]
+    fn with_settings(self, settings: Settings) -> Self {
+        Self { settings, ..self }
+    }
+
+    fn next_tracker(&mut self) -> Option<KeyTracker> {
+        let tracker = self
+            .tracker
+            .as_ref()
+            .map(|tracker| tracker.child(&self.index.to_string()));
+        self.index += 1;
+        tracker
+    }
+
+    // Visits the sequence as a tuple, erroring if any elements are left over. Serde's visitors
+    // already error if there are too few elements, but silently ignore any extra ones
+    fn visit_tuple<'de, V>(mut self, len: usize, visitor: V) -> Result<V::Value>
+    where
+        Self: de::SeqAccess<'de, Error = Error>,
+        V: de::Visitor<'de>,
+    {
+        let total = self.values.len();
+        let result = visitor.visit_seq(&mut self)?;
+        if self.values.len() == 0 {
+            Ok(result)
+        } else {
+            Err(Error::invalid_length(
+                total,
+                &format!("a tuple of size {len}").as_str(),
+            ))
         }
     }
 }
 
 // For regular arrays
-impl<'de> de::SeqAccess<'de> for SeqAccess<ParsedValue> {
+impl<'de> de::SeqAccess<'de> for SeqAccess<ParsedValue<'de>> {
     type Error = Error;
 
     #[inline]
@@ -588,7 +1368,14 @@ impl<'de> de::SeqAccess<'de> for SeqAccess<ParsedValue> {
     {
         self.values
             .next()
-            .map(|value| seed.deserialize(ValueDeserializer::new(value)))
+            .map(|value| {
+                let tracker = self.next_tracker();
+                seed.deserialize(
+                    ValueDeserializer::new(value)
+                        .tracked(tracker)
+                        .with_settings(self.settings.clone()),
+                )
+            })
             .transpose()
     }
 
@@ -599,7 +1386,7 @@ impl<'de> de::SeqAccess<'de> for SeqAccess<ParsedValue> {
 }
 
 // Used for array of tables
-impl<'de> de::SeqAccess<'de> for SeqAccess<ParsedTable> {
+impl<'de> de::SeqAccess<'de> for SeqAccess<ParsedTable<'de>> {
     type Error = Error;
 
     #[inline]
@@ -610,7 +1397,12 @@ impl<'de> de::SeqAccess<'de> for SeqAccess<ParsedTable> {
         self.values
             .next()
             .map(|value| {
-                seed.deserialize(de::value::MapAccessDeserializer::new(MapAccess::new(value)))
+                let tracker = self.next_tracker();
+                seed.deserialize(de::value::MapAccessDeserializer::new(
+                    MapAccess::new(value)
+                        .tracked(tracker)
+                        .with_settings(self.settings.clone()),
+                ))
             })
             .transpose()
     }
@@ -621,33 +1413,51 @@ impl<'de> de::SeqAccess<'de> for SeqAccess<ParsedTable> {
     }
 }
 
-struct MapAccess {
-    kv_pairs: <ParsedTable as IntoIterator>::IntoIter,
-    next_value: Option<ParsedValue>,
+struct MapAccess<'de> {
+    kv_pairs: <ParsedTable<'de> as IntoIterator>::IntoIter,
+    next_value: Option<(ParsedValue<'de>, Option<KeyTracker>)>,
+    tracker: Option<KeyTracker>,
+    settings: Settings,
 }
 
-impl MapAccess {
+impl<'de> MapAccess<'de> {
     #[inline]
-    fn new(table: ParsedTable) -> Self {
+    fn new(table: ParsedTable<'de>) -> Self {
         Self {
             kv_pairs: table.into_iter(),
             next_value: None,
+            tracker: None,
+            settings: Settings::default(),
         }
     }
-}
 
-impl<'de> de::MapAccess<'de> for MapAccess {
-    type Error = Error;
+    #[inline]
+    fn tracked(self, tracker: Option<KeyTracker>) -> Self {
+        Self { tracker, ..self }
+    }
 
     #[inline]
-    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>>
-    where
-        K: de::DeserializeSeed<'de>,
+    fn with_settings(self, settings: Settings) -> Self {
+        Self { settings, ..self }
+    }
+
+    fn child_tracker(&self, key: &str) -> Option<KeyTracker> {
+        self.tracker.as_ref().map(|tracker| tracker.child(key))
+    }
+}
+
+impl<'de> de::MapAccess<'de> for MapAccess<'de> {
+    type Error = Error;
+
+    #[inline]
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>>
+    where
+        K: de::DeserializeSeed<'de>,
     {
         self.kv_pairs
             .next()
             .map(|(key, value)| {
-                self.next_value = Some(value);
+                self.next_value = Some((value, self.child_tracker(&key)));
                 seed.deserialize(KeyDeserializer { key })
             })
             .transpose()
@@ -659,10 +1469,14 @@ impl<'de> de::MapAccess<'de> for MapAccess {
         V: de::DeserializeSeed<'de>,
     {
         #[allow(clippy::panic)]
-        let Some(value) = self.next_value.take() else {
+        let Some((value, tracker)) = self.next_value.take() else {
             panic!("MapAccess::next_value called without calling MapAccess::next_key first")
         };
-        seed.deserialize(ValueDeserializer::new(value))
+        seed.deserialize(
+            ValueDeserializer::new(value)
+                .tracked(tracker)
+                .with_settings(self.settings.clone()),
+        )
     }
 
     #[inline]
@@ -674,9 +1488,14 @@ impl<'de> de::MapAccess<'de> for MapAccess {
         self.kv_pairs
             .next()
             .map(|(key, value)| {
+                let tracker = self.child_tracker(&key);
                 kseed.deserialize(KeyDeserializer { key }).and_then(|k| {
                     vseed
-                        .deserialize(ValueDeserializer::new(value))
+                        .deserialize(
+                            ValueDeserializer::new(value)
+                                .tracked(tracker)
+                                .with_settings(self.settings.clone()),
+                        )
                         .map(|v| (k, v))
                 })
             })
@@ -849,31 +1668,56 @@ impl<'de> de::Deserializer<'de> for KeyDeserializer {
 }
 
 #[derive(Debug)]
-struct EnumAccess {
+struct EnumAccess<'de> {
     variant: String,
-    value: ParsedValue,
+    value: ParsedValue<'de>,
+    tracker: Option<KeyTracker>,
+    settings: Settings,
 }
 
-impl EnumAccess {
-    fn new(table: ParsedTable) -> Result<Self> {
+impl<'de> EnumAccess<'de> {
+    fn new(table: ParsedTable<'de>) -> Result<Self> {
         let mut table = table.into_iter();
         let (variant, value) = table.next().ok_or_else(|| {
             Error::invalid_value(
                 de::Unexpected::Other("empty table"),
-                &"exactly one key/value pair",
+                &"a table with a single key for an enum variant",
             )
         })?;
         if table.next().is_some() {
             return Err(Error::invalid_value(
                 de::Unexpected::Other("multiple entries in table"),
-                &"exactly one key/value pair",
+                &"a table with a single key for an enum variant",
             ));
         }
-        Ok(Self { variant, value })
+        Ok(Self {
+            variant,
+            value,
+            tracker: None,
+            settings: Settings::default(),
+        })
+    }
+
+    #[inline]
+    fn tracked(self, tracker: Option<KeyTracker>) -> Self {
+        let tracker = tracker.map(|tracker| tracker.child(&self.variant));
+        Self { tracker, ..self }
+    }
+
+    #[inline]
+    fn with_settings(self, settings: Settings) -> Self {
+        Self { settings, ..self }
+    }
+
+    #[inline]
+    fn into_value_deserializer(self) -> ValueDeserializer<'de> {
+        ValueDeserializer::new(self.value)
+            .tracked(self.tracker)
+            .with_settings(self.settings)
     }
 }
 
-impl<'de> de::EnumAccess<'de> for EnumAccess {
+impl<'de> de::EnumAccess<'de> for EnumAccess<'de> {
     type Error = Error;
     type Variant = Self;
 
@@ -887,7 +1731,7 @@ impl<'de> de::EnumAccess<'de> for EnumAccess {
     }
 }
 
-impl<'de> de::VariantAccess<'de> for EnumAccess {
+impl<'de> de::VariantAccess<'de> for EnumAccess<'de> {
     type Error = Error;
 
     #[inline]
@@ -895,8 +1739,13 @@ impl<'de> de::VariantAccess<'de> for EnumAccess {
         // We allow unit variants to be represented by `x = { variant = {} }` in addition to the
         // normal `x = "variant"`. toml-rs seems to do the same.
         match self.value {
-            ParsedValue::Table(table) if table.is_empty() => Ok(()),
-            _ => Err(Error::invalid_type(self.value.typ().into(), &"empty table")),
+            ParsedValue::Table(table) | ParsedValue::InlineTable(table) if table.is_empty() => {
+                Ok(())
+            }
+            _ => Err(Error::invalid_type(
+                self.value.typ().into(),
+                &"a string or an empty table for a unit variant",
+            )),
         }
     }
 
@@ -905,7 +1754,7 @@ impl<'de> de::VariantAccess<'de> for EnumAccess {
     where
         T: de::DeserializeSeed<'de>,
     {
-        seed.deserialize(ValueDeserializer::new(self.value))
+        seed.deserialize(self.into_value_deserializer())
     }
 
     #[inline]
@@ -913,7 +1762,7 @@ impl<'de> de::VariantAccess<'de> for EnumAccess {
     where
         V: de::Visitor<'de>,
     {
-        de::Deserializer::deserialize_seq(ValueDeserializer::new(self.value), visitor)
+        de::Deserializer::deserialize_seq(self.into_value_deserializer(), visitor)
     }
 
     #[inline]
@@ -921,14 +1770,151 @@ impl<'de> de::VariantAccess<'de> for EnumAccess {
     where
         V: de::Visitor<'de>,
     {
-        de::Deserializer::deserialize_map(ValueDeserializer::new(self.value), visitor)
+        de::Deserializer::deserialize_map(self.into_value_deserializer(), visitor)
+    }
+}
+
+// Used for enum variants represented by a string, which must be unit variants
+#[derive(Debug)]
+struct UnitVariantAccess {
+    variant: String,
+}
+
+impl UnitVariantAccess {
+    fn invalid_type(&self, kind: &str) -> Error {
+        Error::invalid_type(
+            de::Unexpected::Str(&self.variant),
+            &format!("a table with a single key for a {kind} variant").as_str(),
+        )
+    }
+}
+
+impl<'de> de::EnumAccess<'de> for UnitVariantAccess {
+    type Error = Error;
+    type Variant = Self;
+
+    #[inline]
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self::Variant)>
+    where
+        V: de::DeserializeSeed<'de>,
+    {
+        let variant = seed.deserialize(StrDeserializer::<Error>::new(&self.variant))?;
+        Ok((variant, self))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for UnitVariantAccess {
+    type Error = Error;
+
+    #[inline]
+    fn unit_variant(self) -> Result<()> {
+        Ok(())
+    }
+
+    #[inline]
+    fn newtype_variant_seed<T>(self, _seed: T) -> Result<T::Value>
+    where
+        T: de::DeserializeSeed<'de>,
+    {
+        Err(self.invalid_type("newtype"))
+    }
+
+    #[inline]
+    fn tuple_variant<V>(self, _len: usize, _visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        Err(self.invalid_type("tuple"))
+    }
+
+    #[inline]
+    fn struct_variant<V>(self, _fields: &'static [&'static str], _visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        Err(self.invalid_type("struct"))
+    }
+}
+
+// Presents a string as the variant of serde's OsString representation for the current platform
+#[cfg(any(unix, windows))]
+struct OsStringAccess {
+    string: String,
+}
+
+#[cfg(any(unix, windows))]
+impl<'de> de::EnumAccess<'de> for OsStringAccess {
+    type Error = Error;
+    type Variant = Self;
+
+    #[inline]
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self::Variant)>
+    where
+        V: de::DeserializeSeed<'de>,
+    {
+        #[cfg(unix)]
+        let variant = "Unix";
+        #[cfg(windows)]
+        let variant = "Windows";
+
+        let variant = seed.deserialize(StrDeserializer::<Error>::new(variant))?;
+        Ok((variant, self))
+    }
+}
+
+#[cfg(any(unix, windows))]
+impl<'de> de::VariantAccess<'de> for OsStringAccess {
+    type Error = Error;
+
+    #[inline]
+    fn unit_variant(self) -> Result<()> {
+        Err(Error::invalid_type(
+            de::Unexpected::NewtypeVariant,
+            &"a unit variant",
+        ))
+    }
+
+    #[inline]
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value>
+    where
+        T: de::DeserializeSeed<'de>,
+    {
+        // Unix uses the raw bytes and Windows uses UTF-16 code units
+        #[cfg(unix)]
+        let units = self.string.into_bytes().into_iter();
+        #[cfg(windows)]
+        let units = self.string.encode_utf16();
+
+        seed.deserialize(SeqDeserializer::<_, Error>::new(units))
+    }
+
+    #[inline]
+    fn tuple_variant<V>(self, _len: usize, _visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        Err(Error::invalid_type(
+            de::Unexpected::NewtypeVariant,
+            &"a tuple variant",
+        ))
+    }
+
+    #[inline]
+    fn struct_variant<V>(self, _fields: &'static [&'static str], _visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        Err(Error::invalid_type(
+            de::Unexpected::NewtypeVariant,
+            &"a struct variant",
+        ))
     }
 }
 
 trait Integer: Sized {
     fn from_str_radix(src: &[u8], radix: u32) -> Result<Self>;
 
-    fn from_str(src: &[u8]) -> Result<Self>;
+    fn from_str(src: &[u8], saturate: bool) -> Result<Self>;
 }
 
 macro_rules! impl_integer {
@@ -937,15 +1923,28 @@ macro_rules! impl_integer {
             fn from_str_radix(bytes: &[u8], radix: u32) -> Result<Self> {
                 let str = str::from_utf8(bytes)
                     .unwrap_or_else(|_| unreachable!("we should only have ASCII digits at this point"));
-                <Self>::from_str_radix(str, radix)
-                    .map_err(|err| $crate::de::ErrorKind::InvalidInteger(err).into())
+                <Self>::from_str_radix(str, radix).map_err(|err| {
+                    let prefix = match radix {
+                        2 => "0b",
+                        8 => "0o",
+                        16 => "0x",
+                        _ => "",
+                    };
+                    $crate::de::ErrorKind::InvalidInteger(format!("{prefix}{str}").into(), err)
+                        .into()
+                })
             }
 
-            fn from_str(bytes: &[u8]) -> Result<Self> {
+            fn from_str(bytes: &[u8], saturate: bool) -> Result<Self> {
                 let str = str::from_utf8(bytes)
                     .unwrap_or_else(|_| unreachable!("we should only have ASCII digits at this point"));
-                <Self as std::str::FromStr>::from_str(str)
-                    .map_err(|err| $crate::de::ErrorKind::InvalidInteger(err).into())
+                <Self as std::str::FromStr>::from_str(str).or_else(|err| {
+                    match $crate::de::error::int_overflow(str, &err) {
+                        Some(IntErrorKind::PosOverflow) if saturate => Ok(Self::MAX),
+                        Some(_) if saturate => Ok(Self::MIN),
+                        _ => Err($crate::de::ErrorKind::InvalidInteger(str.into(), err).into()),
+                    }
+                })
             }
         }
     )*);
@@ -973,7 +1972,7 @@ macro_rules! impl_float {
             let str = str::from_utf8(bytes)
                 .unwrap_or_else(|_| unreachable!("we should only have ASCII digits at this point"));
             <Self as std::str::FromStr>::from_str(str)
-                .map_err(|err| $crate::de::ErrorKind::InvalidFloat(err).into())
+                .map_err(|err| $crate::de::ErrorKind::InvalidFloat(str.into(), err).into())
         }
     })*);
 }
@@ -981,8 +1980,19 @@ macro_rules! impl_float {
 impl_float!(f32 f64);
 
 #[inline]
-fn parse_integer<T: Integer>(bytes: &[u8]) -> Result<T> {
-    T::from_str(bytes)
+// Visits a string, borrowing from the input if the parser did
+fn visit_str<'de, V>(str: Cow<'de, str>, visitor: V) -> Result<V::Value>
+where
+    V: de::Visitor<'de>,
+{
+    match str {
+        Cow::Borrowed(str) => visitor.visit_borrowed_str(str),
+        Cow::Owned(string) => visitor.visit_string(string),
+    }
+}
+
+fn parse_integer<T: Integer>(bytes: &[u8], saturate: bool) -> Result<T> {
+    T::from_str(bytes, saturate)
 }
 
 #[inline]
@@ -1005,6 +2015,23 @@ fn parse_float<T: Float>(bytes: &[u8]) -> Result<T> {
     T::from_str(bytes)
 }
 
+// Parses a decimal integer as a float, warning if it can't be represented exactly
+fn parse_integer_as_float<T: Float + fmt::Display>(
+    bytes: &[u8],
+    offset: usize,
+    on_warning: &WarningSink,
+) -> Result<T> {
+    let float = parse_float::<T>(bytes)?;
+    if on_warning.is_enabled() {
+        // Floats are displayed without an exponent, so an exact float has the same digits
+        let digits = bytes.strip_prefix(b"+").unwrap_or(bytes);
+        if float.to_string().as_bytes() != digits {
+            on_warning.emit(WarningKind::LossyFloat, offset);
+        }
+    }
+    Ok(float)
+}
+
 #[inline]
 const fn parse_special<T: Float>(special: SpecialFloat) -> T {
     match special {
@@ -1019,11 +2046,11 @@ const fn parse_special<T: Float>(special: SpecialFloat) -> T {
 #[cfg_attr(coverage, coverage(off))]
 mod tests {
     use std::collections::HashMap;
-    use std::{fmt, iter};
+    use std::iter;
 
     use assert_matches::assert_matches;
     use indoc::indoc;
-    use maplit::{btreemap, hashmap};
+    use maplit::hashmap;
     use serde::de::{EnumAccess as _, MapAccess as _, SeqAccess as _, VariantAccess as _};
     use serde_bytes::ByteBuf;
 
@@ -1171,6 +2198,17 @@ mod tests {
                 },
             }
         );
+
+        let result: HashMap<String, Vec<Value>> = from_str(r#"x = [1, "two", 3.0, true]"#).unwrap();
+        assert_eq!(
+            result["x"],
+            [
+                Value::Integer(1),
+                Value::String("two".into()),
+                Value::Float(3.0),
+                Value::Boolean(true),
+            ]
+        );
     }
 
     #[test]
@@ -1270,6 +2308,9 @@ mod tests {
                 },
             }
         );
+
+        let result: Result<HashMap<String, String>> = from_slice(b"a = \"b\"\nc = \"d\xe2\x82\"\n");
+        assert_matches!(result, Err(Error(ErrorKind::InvalidEncoding(14))));
     }
 
     #[test]
@@ -1372,27 +2413,1396 @@ mod tests {
     }
 
     #[test]
-    fn deserializer_from_str() {
-        let mut deserializer = Deserializer::from_str("abc = 123");
+    fn test_from_bufread() {
+        let input = indoc! {r#"
+            title = "TOML Example"
+
+            [database]
+            server = "192.168.1.1"
+            ports = [ 8000, 8001, 8002 ]
+            connection_max = 5000
+            enabled = true
+        "#};
+
+        // A small buffer capacity means the input is read in many chunks
+        let reader = io::BufReader::with_capacity(4, io::Cursor::new(input));
+        let result: HashMap<String, Value> = from_bufread(reader).unwrap();
+        assert_eq!(result, from_str::<HashMap<String, Value>>(input).unwrap());
+        assert_eq!(result["database"]["ports"][2], Value::Integer(8002));
+
+        // Multi-byte characters split across chunks
+        let reader = io::BufReader::with_capacity(3, io::Cursor::new("a = \"😎😎\""));
+        let result: HashMap<String, String> = from_bufread(reader).unwrap();
+        assert_eq!(result["a"], "😎😎");
+
+        let reader = io::BufReader::new(&b"a = \"b\"\nc = \"d\xe2\x82\"\n"[..]);
+        let result: Result<HashMap<String, String>> = from_bufread(reader);
+        assert_matches!(result, Err(Error(ErrorKind::InvalidEncoding(14))));
+    }
+
+    #[test]
+    fn test_from_str_tracking() {
+        #[derive(Debug, PartialEq, Eq, Deserialize)]
+        struct Config {
+            name: String,
+            server: Server,
+            clients: Vec<Server>,
+            mode: Option<Mode>,
+        }
+
+        #[derive(Debug, PartialEq, Eq, Deserialize)]
+        struct Server {
+            ip: String,
+        }
+
+        #[derive(Debug, PartialEq, Eq, Deserialize)]
+        enum Mode {
+            Fast { level: u8 },
+        }
+
+        let (result, unused): (Config, _) = from_str_tracking(indoc! {r#"
+            name = "example"
+            colour = "blue"
+            mode = { Fast = { level = 3, turbo = true } }
+
+            [server]
+            ip = "10.0.0.1"
+            port = 8080
+            "eu.west" = true
+
+            [database]
+            enabled = true
+
+            [[clients]]
+            ip = "10.0.0.2"
+
+            [[clients]]
+            ip = "10.0.0.3"
+            dc = "eqdc10"
+        "#})
+        .unwrap();
+
+        assert_eq!(
+            result,
+            Config {
+                name: "example".into(),
+                server: Server {
+                    ip: "10.0.0.1".into()
+                },
+                clients: vec![
+                    Server {
+                        ip: "10.0.0.2".into()
+                    },
+                    Server {
+                        ip: "10.0.0.3".into()
+                    },
+                ],
+                mode: Some(Mode::Fast { level: 3 }),
+            }
+        );
+
+        assert!(!unused.is_empty());
+        assert_eq!(unused.len(), 6);
+
+        let mut unused: Vec<_> = unused.into_iter().collect();
+        unused.sort();
+        assert_eq!(
+            unused,
+            [
+                "clients.1.dc",
+                "colour",
+                "database",
+                "mode.Fast.turbo",
+                r#"server."eu.west""#,
+                "server.port"
+            ]
+        );
+
+        let (result, unused): (HashMap<String, Value>, _) =
+            from_str_tracking("a = 1\nb = { c = 2 }").unwrap();
+        assert_eq!(result.len(), 2);
+        assert!(unused.is_empty());
+        assert_eq!(unused.iter().count(), 0);
+
+        let result: Result<(Config, _)> = from_str_tracking("name = 1");
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn deserializer_send_sync() {
+        const fn assert_send_sync<T: Send + Sync>() {}
+
+        assert_send_sync::<Deserializer<'_>>();
+        assert_send_sync::<ArrayIter<Value>>();
+        assert_send_sync::<ValueDeserializer>();
+    }
+
+    #[test]
+    fn deserializer_from_str() {
+        let mut deserializer = Deserializer::from_str("abc = 123");
+        assert!(!deserializer.lone_value);
+
+        assert_matches!(
+            deserializer.parser.parse(),
+            Ok(ParsedValue::Table(t)) if t == hashmap! {
+                "abc".into() => ParsedValue::Integer(b"123".to_vec(), 6)
+            }
+        );
+    }
+
+    #[test]
+    fn deserializer_from_slice() {
+        let mut deserializer = Deserializer::from_slice(b"abc = 123");
+        assert!(!deserializer.lone_value);
+
+        assert_matches!(
+            deserializer.parser.parse(),
+            Ok(ParsedValue::Table(t)) if t == hashmap! {
+                "abc".into() => ParsedValue::Integer(b"123".to_vec(), 6),
+            }
+        );
+    }
+
+    #[test]
+    fn deserializer_from_str_section() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Tool {
+            name: String,
+            version: u32,
+        }
+
+        let input = indoc! {r#"
+            title = "example"
+            owner = { name = "Tom" }
+            servers = [{ ip = "10.0.0.1" }]
+
+            [tool.soml]
+            name = "soml"
+            version = 2
+
+            [other]
+            tool.dotted = { name = "dotted", version = 3 }
+            "tool.quoted" = { name = "quoted", version = 4 }
+        "#};
+
+        let deserializer = Deserializer::from_str_section(input, "tool.soml");
+        assert_eq!(deserializer.section.as_deref(), Some("tool.soml"));
+        assert_eq!(
+            Tool::deserialize(deserializer).unwrap(),
+            Tool {
+                name: "soml".into(),
+                version: 2,
+            }
+        );
+
+        let deserializer = Deserializer::from_str_section(input, "other.tool.dotted");
+        assert_eq!(
+            Tool::deserialize(deserializer).unwrap(),
+            Tool {
+                name: "dotted".into(),
+                version: 3,
+            }
+        );
+
+        let deserializer = Deserializer::from_str_section(input, r#"other . "tool.quoted""#);
+        assert_eq!(
+            Tool::deserialize(deserializer).unwrap(),
+            Tool {
+                name: "quoted".into(),
+                version: 4,
+            }
+        );
+
+        let deserializer = Deserializer::from_str_section(input, "other.tool.quoted");
+        assert_matches!(
+            Tool::deserialize(deserializer),
+            Err(Error(ErrorKind::MissingSection(p))) if &*p == "other.tool.quoted"
+        );
+
+        let deserializer = Deserializer::from_str_section(input, "tool..soml");
+        assert_matches!(
+            Tool::deserialize(deserializer),
+            Err(Error(ErrorKind::ExpectedToken(..)))
+        );
+
+        let deserializer = Deserializer::from_str_section(input, "owner");
+        let result: HashMap<String, String> = HashMap::deserialize(deserializer).unwrap();
+        assert_eq!(result, HashMap::from([("name".into(), "Tom".into())]));
+
+        let deserializer = Deserializer::from_str_section(input, "tool.other");
+        assert_matches!(
+            Tool::deserialize(deserializer),
+            Err(Error(ErrorKind::MissingSection(p))) if &*p == "tool.other"
+        );
+
+        let deserializer = Deserializer::from_str_section(input, "title");
+        assert_matches!(
+            Tool::deserialize(deserializer),
+            Err(Error(ErrorKind::InvalidSection(p))) if &*p == "title"
+        );
+
+        let deserializer = Deserializer::from_str_section(input, "title.name");
+        assert_matches!(
+            Tool::deserialize(deserializer),
+            Err(Error(ErrorKind::InvalidSection(p))) if &*p == "title.name"
+        );
+
+        let deserializer = Deserializer::from_str_section(input, "servers");
+        assert_matches!(
+            Tool::deserialize(deserializer),
+            Err(Error(ErrorKind::InvalidSection(..)))
+        );
+
+        let deserializer = Deserializer::from_str_section("a = ", "a");
+        assert_matches!(
+            Tool::deserialize(deserializer),
+            Err(Error(ErrorKind::ExpectedToken(..)))
+        );
+    }
+
+    #[test]
+    fn deserializer_end() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Config {
+            name: String,
+        }
+
+        let mut deserializer = Deserializer::from_str("name = \"app\"\n# comment\n");
+        assert_eq!(
+            Config::deserialize(&mut deserializer).unwrap(),
+            Config { name: "app".into() }
+        );
+        assert_matches!(deserializer.end(), Ok(()));
+
+        let mut deserializer = Deserializer::value_from_str("42 # comment\n\n");
+        assert_eq!(u32::deserialize(&mut deserializer).unwrap(), 42);
+        assert_matches!(deserializer.end(), Ok(()));
+
+        // Nothing has been consumed yet
+        let mut deserializer = Deserializer::from_str("\nname = \"app\"\n");
+        assert_matches!(deserializer.end(), Err(Error(ErrorKind::TrailingData(1))));
+    }
+
+    #[test]
+    fn deserializer_parsed_array_iter() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Server {
+            ip: String,
+        }
+
+        let values: Vec<_> = (0..10_000).map(|i| i.to_string()).collect();
+        let input = format!(
+            "data.values = [{}]\n\n[[servers]]\nip = \"10.0.0.1\"\n\n[[servers]]\nip = \"10.0.0.2\"\n",
+            values.join(", ")
+        );
+
+        let iter = Deserializer::from_str(&input)
+            .parsed_array_iter::<u32>("data.values")
+            .unwrap();
+        assert_eq!(iter.len(), 10_000);
+        assert_eq!(format!("{iter:?}"), "ArrayIter { remaining: 10000 }");
+        let mut expected = 0;
+        for value in iter {
+            assert_eq!(value.unwrap(), expected);
+            expected += 1;
+        }
+        assert_eq!(expected, 10_000);
+
+        let mut iter = Deserializer::from_str(&input)
+            .parsed_array_iter::<Server>("servers")
+            .unwrap();
+        assert_eq!(
+            iter.next().unwrap().unwrap(),
+            Server {
+                ip: "10.0.0.1".into()
+            }
+        );
+        assert_eq!(iter.len(), 1);
+        assert_eq!(
+            iter.next().unwrap().unwrap(),
+            Server {
+                ip: "10.0.0.2".into()
+            }
+        );
+        assert!(iter.next().is_none());
+
+        // Errors in individual elements don't stop the iteration
+        let mut iter = Deserializer::value_from_str("[1, -1, 2]")
+            .parsed_array_iter::<u8>("")
+            .unwrap();
+        assert_matches!(iter.next(), Some(Ok(1)));
+        assert_matches!(iter.next(), Some(Err(Error(ErrorKind::InvalidInteger(..)))));
+        assert_matches!(iter.next(), Some(Ok(2)));
+        assert_matches!(iter.next(), None);
+
+        assert_matches!(
+            Deserializer::from_str(&input).parsed_array_iter::<u32>("data.other"),
+            Err(Error(ErrorKind::MissingSection(p))) if &*p == "data.other"
+        );
+        assert_matches!(
+            Deserializer::from_str(&input).parsed_array_iter::<u32>("data"),
+            Err(Error(ErrorKind::InvalidType(..)))
+        );
+        assert_matches!(
+            Deserializer::from_str("a = ").parsed_array_iter::<u32>("a"),
+            Err(Error(ErrorKind::ExpectedToken(..)))
+        );
+    }
+
+    #[test]
+    fn deserializer_value_from_str() {
+        let mut deserializer = Deserializer::value_from_str("123");
+        assert!(deserializer.lone_value);
+
+        assert_matches!(
+            deserializer.parser.parse_lone_value(),
+            Ok(ParsedValue::Integer(i, 0)) if i == b"123"
+        );
+    }
+
+    #[test]
+    fn deserializer_value_from_slice() {
+        let mut deserializer = Deserializer::value_from_slice(b"123");
+        assert!(deserializer.lone_value);
+
+        assert_matches!(
+            deserializer.parser.parse_lone_value(),
+            Ok(ParsedValue::Integer(i, 0)) if i == b"123"
+        );
+    }
+
+    #[cfg(feature = "datetime")]
+    #[test]
+    fn deserializer_validate_datetimes() {
+        let deserializer = Deserializer::from_str("date = 2023-02-30");
+        assert!(!deserializer.parser.validate_datetimes);
+        let result = HashMap::<String, LocalDate>::deserialize(deserializer);
+        if cfg!(feature = "strict") {
+            assert_matches!(result, Err(Error(..)));
+        } else {
+            assert_matches!(result, Ok(t) if t["date"].day == 30);
+        }
+
+        let deserializer = Deserializer::from_str("date = 2023-02-30").validate_datetimes(true);
+        assert!(deserializer.parser.validate_datetimes);
+        assert_matches!(
+            HashMap::<String, LocalDate>::deserialize(deserializer),
+            Err(Error(ErrorKind::InvalidDatetime(..)))
+        );
+
+        let deserializer = Deserializer::from_str("time = 24:00:00").validate_datetimes(true);
+        assert_matches!(
+            HashMap::<String, LocalTime>::deserialize(deserializer),
+            Err(Error(ErrorKind::InvalidDatetime(..)))
+        );
+
+        let deserializer =
+            Deserializer::from_str("datetime = 2023-01-02T03:04:05+24:00").validate_datetimes(true);
+        assert_matches!(
+            HashMap::<String, OffsetDatetime>::deserialize(deserializer),
+            Err(Error(ErrorKind::InvalidDatetime(..)))
+        );
+
+        let toml = format!("datetime = {}", OffsetDatetime::EXAMPLE_STR);
+        let deserializer = Deserializer::from_str(&toml).validate_datetimes(true);
+        assert_matches!(
+            HashMap::<String, OffsetDatetime>::deserialize(deserializer),
+            Ok(t) if t["datetime"] == OffsetDatetime::EXAMPLE
+        );
+    }
+
+    #[cfg(feature = "datetime")]
+    #[test]
+    fn deserializer_reject_excess_precision() {
+        let toml = "time = 03:04:05.0060000009";
+
+        let deserializer = Deserializer::from_str(toml);
+        assert!(!deserializer.parser.reject_excess_precision);
+        assert_matches!(
+            HashMap::<String, LocalTime>::deserialize(deserializer),
+            Ok(t) if t["time"] == LocalTime::EXAMPLE
+        );
+
+        let deserializer = Deserializer::from_str(toml).reject_excess_precision(true);
+        assert!(deserializer.parser.reject_excess_precision);
+        assert_matches!(
+            HashMap::<String, LocalTime>::deserialize(deserializer),
+            Err(Error(ErrorKind::ExcessPrecision))
+        );
+
+        let toml = format!("time = {}", LocalTime::EXAMPLE_STR);
+        let deserializer = Deserializer::from_str(&toml).reject_excess_precision(true);
+        assert_matches!(
+            HashMap::<String, LocalTime>::deserialize(deserializer),
+            Ok(t) if t["time"] == LocalTime::EXAMPLE
+        );
+    }
+
+    #[test]
+    fn deserializer_deny_unknown_top_level() {
+        #[derive(Debug, PartialEq, Eq, Deserialize)]
+        struct Config {
+            name: String,
+            server: Server,
+        }
+
+        #[derive(Debug, PartialEq, Eq, Deserialize)]
+        struct Server {
+            ip: String,
+        }
+
+        #[derive(Debug, PartialEq, Eq, Deserialize)]
+        struct Quoted {
+            #[serde(rename = "server.port")]
+            port: u16,
+            server: Server,
+        }
+
+        let toml = indoc! {r#"
+            name = "example"
+
+            [server]
+            ip = "10.0.0.1"
+            port = 8080
+        "#};
+
+        let deserializer = Deserializer::from_str(toml);
+        assert!(!deserializer.deny_unknown_top_level);
+
+        // Unknown nested keys are still allowed
+        let deserializer = Deserializer::from_str(toml).deny_unknown_top_level(true);
+        assert!(deserializer.deny_unknown_top_level);
+        assert_matches!(
+            Config::deserialize(deserializer),
+            Ok(c) if c.name == "example" && c.server.ip == "10.0.0.1"
+        );
+
+        let toml = indoc! {r#"
+            name = "example"
+            colour = "blue"
+
+            [server]
+            ip = "10.0.0.1"
+
+            [database]
+            enabled = true
+        "#};
+
+        let deserializer = Deserializer::from_str(toml);
+        assert_matches!(Config::deserialize(deserializer), Ok(_));
+
+        let deserializer = Deserializer::from_str(toml).deny_unknown_top_level(true);
+        assert_matches!(
+            Config::deserialize(deserializer),
+            Err(Error(ErrorKind::UnknownKey(k))) if &*k == "colour"
+        );
+
+        // A catch-all map uses every key
+        let deserializer = Deserializer::from_str(toml).deny_unknown_top_level(true);
+        assert_matches!(HashMap::<String, Value>::deserialize(deserializer), Ok(m) if m.len() == 4);
+
+        // Quoted keys containing a `.` are not confused with nested keys
+        let toml = indoc! {r#"
+            "server.port" = 8080
+
+            [server]
+            ip = "10.0.0.1"
+            port = 80
+        "#};
+        let deserializer = Deserializer::from_str(toml).deny_unknown_top_level(true);
+        assert_matches!(Quoted::deserialize(deserializer), Ok(q) if q.port == 8080);
+
+        let toml = indoc! {r#"
+            name = "example"
+            "server.port" = 8080
+
+            [server]
+            ip = "10.0.0.1"
+        "#};
+        let deserializer = Deserializer::from_str(toml).deny_unknown_top_level(true);
+        assert_matches!(
+            Config::deserialize(deserializer),
+            Err(Error(ErrorKind::UnknownKey(k))) if &*k == "server.port"
+        );
+    }
+
+    #[test]
+    fn deserializer_tuple_length() {
+        #[derive(Debug, Deserialize)]
+        struct Point {
+            point: (i32, i32),
+        }
+
+        let result: Point = from_str("point = [1, 2]").unwrap();
+        assert_eq!(result.point, (1, 2));
+
+        let error = from_str::<Point>("point = [1]").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid length: 1, expected a tuple of size 2"
+        );
+
+        let error = from_str::<Point>("point = [1, 2, 3]").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid length: 3, expected a tuple of size 2"
+        );
+    }
+
+    #[test]
+    fn deserializer_smart_pointers() {
+        use std::rc::Rc;
+        use std::sync::Arc;
+
+        #[derive(Debug, PartialEq, Eq, Deserialize)]
+        struct Database {
+            url: String,
+            pool: Box<u32>,
+        }
+
+        #[derive(Debug, PartialEq, Eq, Deserialize)]
+        struct Config {
+            name: Rc<str>,
+            database: Arc<Database>,
+            replica: Option<Box<Database>>,
+        }
+
+        let result: Config = from_str(indoc! {r#"
+            name = "app"
+
+            [database]
+            url = "postgres://localhost"
+            pool = 8
+
+            [replica]
+            url = "postgres://replica"
+            pool = 4
+        "#})
+        .unwrap();
+        assert_eq!(
+            result,
+            Config {
+                name: "app".into(),
+                database: Arc::new(Database {
+                    url: "postgres://localhost".into(),
+                    pool: Box::new(8),
+                }),
+                replica: Some(Box::new(Database {
+                    url: "postgres://replica".into(),
+                    pool: Box::new(4),
+                })),
+            }
+        );
+
+        assert_matches!(
+            from_str::<Config>(indoc! {r#"
+                name = "app"
+                database = 42
+            "#}),
+            Err(Error(ErrorKind::InvalidType(..)))
+        );
+    }
+
+    #[test]
+    fn deserializer_cow_str() {
+        use std::borrow::Cow;
+
+        #[derive(Debug, Deserialize)]
+        struct Struct<'a> {
+            #[serde(borrow)]
+            plain: Cow<'a, str>,
+            #[serde(borrow)]
+            literal: Cow<'a, str>,
+            #[serde(borrow)]
+            escaped: Cow<'a, str>,
+            #[serde(borrow)]
+            multiline: Cow<'a, str>,
+            array: Vec<&'a str>,
+        }
+
+        #[derive(Debug, Deserialize)]
+        struct Borrowed<'a> {
+            a: &'a str,
+        }
+
+        // Strings without escape sequences are borrowed from the input
+        let input = String::from(indoc! {r#"
+            plain = "hello"
+            literal = 'C:\path'
+            escaped = "hello\tworld"
+            multiline = """hello"""
+            array = ["a", 'b']
+        "#});
+        let result: Struct<'_> = from_str(&input).unwrap();
+        assert_matches!(result.plain, Cow::Borrowed("hello"));
+        assert_matches!(result.literal, Cow::Borrowed(r"C:\path"));
+        assert_matches!(result.escaped, Cow::Owned(ref str) if str == "hello\tworld");
+        assert_matches!(result.multiline, Cow::Owned(ref str) if str == "hello");
+        assert_eq!(result.array, ["a", "b"]);
+
+        // &str can only be borrowed if there are no escapes
+        let result: Borrowed<'_> = from_str(r#"a = "hello""#).unwrap();
+        assert_eq!(result.a, "hello");
+        assert_matches!(
+            from_str::<Borrowed<'_>>(r#"a = "hello\tworld""#),
+            Err(Error(ErrorKind::InvalidType(..)))
+        );
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)] // strict cmp is fine for literals
+    fn deserializer_max_number_length() {
+        let input = format!("a = {}", "1".repeat(10_000));
+        assert_matches!(
+            from_str::<HashMap<String, f64>>(&input),
+            Err(Error(ErrorKind::InvalidNumber(r))) if &*r == "longer than 512 characters"
+        );
+
+        let input = format!("a = 0.{}1e600", "0".repeat(599));
+        assert_matches!(
+            from_str::<HashMap<String, f64>>(&input),
+            Err(Error(ErrorKind::InvalidNumber(..)))
+        );
+        let deserializer = Deserializer::from_str(&input).max_number_length(1024);
+        assert_eq!(deserializer.parser.max_number_length, 1024);
+        let result = HashMap::<String, f64>::deserialize(deserializer).unwrap();
+        assert_eq!(result["a"], 1.0);
+
+        let deserializer = Deserializer::from_str("a = 0x1234_5678").max_number_length(4);
+        assert_matches!(
+            HashMap::<String, i64>::deserialize(deserializer),
+            Err(Error(ErrorKind::InvalidNumber(r))) if &*r == "longer than 4 characters"
+        );
+    }
+
+    #[test]
+    fn deserializer_allow_inline_table_trailing_comma() {
+        let toml = "point = { x = 1, y = 2, }";
+
+        let deserializer = Deserializer::from_str(toml);
+        assert!(!deserializer.parser.allow_inline_table_trailing_comma);
+        assert_matches!(
+            Value::deserialize(deserializer),
+            Err(Error(ErrorKind::TrailingComma(22)))
+        );
+
+        let deserializer = Deserializer::from_str(toml).allow_inline_table_trailing_comma(true);
+        assert!(deserializer.parser.allow_inline_table_trailing_comma);
+        assert_eq!(
+            Value::deserialize(deserializer).unwrap(),
+            Value::Table(table! {
+                "point".into() => Value::Table(table! {
+                    "x".into() => Value::Integer(1),
+                    "y".into() => Value::Integer(2),
+                }),
+            })
+        );
+    }
+
+    #[test]
+    fn deserializer_saturate_integers() {
+        #[derive(Debug, PartialEq, Eq, Deserialize)]
+        struct Telemetry {
+            level: u8,
+            offset: i16,
+            samples: Vec<u32>,
+        }
+
+        #[derive(Debug, PartialEq, Eq, Deserialize)]
+        struct Level {
+            level: u8,
+        }
+
+        let deserializer = Deserializer::from_str("level = 300");
+        assert!(!deserializer.settings.saturate_integers);
+        assert_matches!(
+            Level::deserialize(deserializer),
+            Err(Error(ErrorKind::InvalidInteger(lit, _))) if &*lit == "300"
+        );
+
+        let deserializer = Deserializer::from_str("level = 300").saturate_integers(true);
+        assert!(deserializer.settings.saturate_integers);
+        assert_eq!(
+            Level::deserialize(deserializer).unwrap(),
+            Level { level: u8::MAX }
+        );
+
+        let toml = indoc! {r"
+            level = 300
+            offset = -40000
+            samples = [1, -1, 5000000000]
+        "};
+        let deserializer = Deserializer::from_str(toml).saturate_integers(true);
+        assert_eq!(
+            Telemetry::deserialize(deserializer).unwrap(),
+            Telemetry {
+                level: u8::MAX,
+                offset: i16::MIN,
+                samples: vec![1, 0, u32::MAX],
+            }
+        );
+
+        // Only decimal integers are saturated
+        let deserializer = Deserializer::from_str("level = 0x1ff").saturate_integers(true);
+        assert_matches!(
+            Level::deserialize(deserializer),
+            Err(Error(ErrorKind::InvalidInteger(..)))
+        );
+    }
+
+    #[test]
+    fn deserializer_internally_tagged_enum() {
+        #[derive(Debug, PartialEq, Eq, Deserialize)]
+        #[serde(tag = "kind", rename_all = "lowercase")]
+        enum Source {
+            File {
+                path: String,
+                watch: Option<bool>,
+            },
+            Http {
+                url: String,
+                timeout: u32,
+                headers: Vec<String>,
+            },
+        }
+
+        #[derive(Debug, PartialEq, Eq, Deserialize)]
+        struct Config {
+            main: Source,
+            sources: Vec<Source>,
+        }
+
+        let result: Config = from_str(indoc! {r#"
+            main = { kind = "file", path = "config.toml" }
+
+            [[sources]]
+            kind = "http"
+            url = "https://example.com"
+            timeout = 30
+            headers = ["Accept: */*"]
+
+            [[sources]]
+            watch = true
+            kind = "file"
+            path = "/etc/config.toml"
+        "#})
+        .unwrap();
+        assert_eq!(
+            result,
+            Config {
+                main: Source::File {
+                    path: "config.toml".into(),
+                    watch: None
+                },
+                sources: vec![
+                    Source::Http {
+                        url: "https://example.com".into(),
+                        timeout: 30,
+                        headers: vec!["Accept: */*".into()],
+                    },
+                    Source::File {
+                        path: "/etc/config.toml".into(),
+                        watch: Some(true),
+                    },
+                ],
+            }
+        );
+
+        assert_matches!(
+            from_str::<Source>(r#"kind = "ftp""#),
+            Err(Error(ErrorKind::UnknownVariant(..)))
+        );
+        assert_matches!(
+            from_str::<Source>(r#"path = "config.toml""#),
+            Err(Error(ErrorKind::MissingField(..)))
+        );
+
+        // Date-times are buffered along with the other fields
+        #[cfg(feature = "datetime")]
+        {
+            #[derive(Debug, PartialEq, Eq, Deserialize)]
+            #[serde(tag = "kind", rename_all = "lowercase")]
+            enum Event {
+                Once { at: OffsetDatetime },
+                Daily { at: LocalTime },
+            }
+
+            #[derive(Debug, PartialEq, Eq, Deserialize)]
+            struct Schedule {
+                events: Vec<Event>,
+            }
+
+            let result: Schedule = from_str(indoc! {r#"
+                [[events]]
+                kind = "once"
+                at = 2023-01-02T03:04:05.006+07:08
+
+                [[events]]
+                kind = "daily"
+                at = 03:04:05.006
+            "#})
+            .unwrap();
+            assert_eq!(
+                result.events,
+                [
+                    Event::Once {
+                        at: OffsetDatetime::EXAMPLE
+                    },
+                    Event::Daily {
+                        at: LocalTime::EXAMPLE
+                    },
+                ]
+            );
+        };
+    }
+
+    #[test]
+    fn deserializer_flatten_map() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Plugin {
+            enabled: bool,
+            #[serde(default)]
+            priority: u32,
+        }
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Config {
+            name: String,
+            version: u32,
+            #[serde(flatten)]
+            plugins: HashMap<String, Plugin>,
+        }
+
+        let input = indoc! {r#"
+            name = "app"
+            version = 2
+
+            [lint]
+            enabled = true
+            priority = 3
+
+            [format]
+            enabled = false
+
+            [docs]
+            enabled = true
+            priority = 1
+        "#};
+        let config: Config = from_str(input).unwrap();
+        assert_eq!(config.name, "app");
+        assert_eq!(config.version, 2);
+        assert_eq!(
+            config.plugins,
+            hashmap! {
+                "lint".into() => Plugin { enabled: true, priority: 3 },
+                "format".into() => Plugin { enabled: false, priority: 0 },
+                "docs".into() => Plugin { enabled: true, priority: 1 },
+            }
+        );
+
+        // Any other key is routed to the flattened map too
+        let input = indoc! {r#"
+            name = "app"
+            version = 2
+            other = 2
+        "#};
+        assert_matches!(
+            from_str::<Config>(input),
+            Err(Error(ErrorKind::InvalidType(..)))
+        );
+    }
+
+    #[test]
+    fn deserializer_empty_document() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Config {
+            name: String,
+            port: Option<u16>,
+        }
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Optional {
+            port: Option<u16>,
+        }
+
+        for input in ["", "  \n\n", "# just a comment\n\n# and another\n"] {
+            let error = from_str::<Config>(input).unwrap_err();
+            assert_matches!(error, Error(ErrorKind::EmptyDocument("name")));
+            assert!(error.is_type());
+        }
+
+        // A document with some keys is only missing a field
+        assert_matches!(
+            from_str::<Config>("port = 80"),
+            Err(Error(ErrorKind::MissingField("name")))
+        );
+        assert_matches!(
+            from_str::<Config>("[other]"),
+            Err(Error(ErrorKind::MissingField("name")))
+        );
+
+        // An empty document is fine if there are no required fields
+        assert_eq!(from_str::<Optional>("").unwrap(), Optional { port: None });
+    }
+
+    #[test]
+    fn deserializer_root_array_of_tables() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Root {
+            items: Vec<Item>,
+        }
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Item {
+            name: String,
+            count: u32,
+        }
+
+        let input = indoc! {r#"
+            [[items]]
+            name = "a"
+            count = 1
+
+            [[items]]
+            name = "b"
+            count = 2
+
+            [[items]]
+            name = "c"
+            count = 3
+        "#};
+        assert_eq!(
+            from_str::<Root>(input).unwrap(),
+            Root {
+                items: vec![
+                    Item {
+                        name: "a".into(),
+                        count: 1,
+                    },
+                    Item {
+                        name: "b".into(),
+                        count: 2,
+                    },
+                    Item {
+                        name: "c".into(),
+                        count: 3,
+                    },
+                ],
+            }
+        );
+
+        // Also with leading comments before the first header
+        let input = format!("# items\n\n{input}");
+        assert_eq!(from_str::<Root>(&input).unwrap().items.len(), 3);
+    }
+
+    #[test]
+    fn deserializer_default_fn() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Config {
+            #[serde(default = "default_port")]
+            port: u16,
+            #[serde(default = "default_server")]
+            server: Server,
+        }
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Server {
+            host: String,
+            #[serde(default = "default_port")]
+            port: u16,
+        }
+
+        fn default_port() -> u16 {
+            8080
+        }
+
+        fn default_server() -> Server {
+            Server {
+                host: "localhost".into(),
+                port: 80,
+            }
+        }
+
+        assert_eq!(
+            from_str::<Config>("").unwrap(),
+            Config {
+                port: 8080,
+                server: default_server(),
+            }
+        );
+
+        let input = indoc! {r#"
+            port = 1234
+
+            [server]
+            host = "example.com"
+        "#};
+        assert_eq!(
+            from_str::<Config>(input).unwrap(),
+            Config {
+                port: 1234,
+                server: Server {
+                    host: "example.com".into(),
+                    port: 8080,
+                },
+            }
+        );
+
+        let input = indoc! {r#"
+            [server]
+            host = "example.com"
+            port = 443
+        "#};
+        assert_eq!(
+            from_str::<Config>(input).unwrap(),
+            Config {
+                port: 8080,
+                server: Server {
+                    host: "example.com".into(),
+                    port: 443,
+                },
+            }
+        );
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)] // strict cmp is fine for literals
+    fn deserializer_on_warning() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Config {
+            exact: f64,
+            lossy: f64,
+            small: f32,
+            float: f64,
+        }
+
+        let warnings = Arc::new(Mutex::new(Vec::new()));
+        let input = indoc! {r"
+            exact = -9_007_199_254_740_992
+            lossy = 9_007_199_254_740_993
+            small = 16_777_217
+            float = 0.1
+        "};
+        let deserializer = Deserializer::from_str(input).on_warning({
+            let warnings = Arc::clone(&warnings);
+            move |warning| warnings.lock().unwrap().push(warning)
+        });
+        let result = Config::deserialize(deserializer).unwrap();
+        assert_eq!(result.lossy, 9_007_199_254_740_992.0);
+        // Tables are unordered, so sort the warnings by offset
+        let mut warnings = warnings.lock().unwrap().clone();
+        warnings.sort_by_key(Warning::offset);
+        assert_eq!(
+            warnings
+                .iter()
+                .map(|warning| (warning.kind(), warning.offset()))
+                .collect::<Vec<_>>(),
+            [(WarningKind::LossyFloat, 39), (WarningKind::LossyFloat, 69)]
+        );
+
+        // Without a callback the values are converted silently
+        assert_eq!(from_str::<Config>(input).unwrap(), result);
+    }
+
+    #[cfg(feature = "datetime")]
+    #[test]
+    fn deserializer_on_warning_datetime() {
+        let warnings = Arc::new(Mutex::new(Vec::new()));
+        let input = "time = 12:00:00.0123456789";
+        let deserializer = Deserializer::from_str(input).on_warning({
+            let warnings = Arc::clone(&warnings);
+            move |warning| warnings.lock().unwrap().push(warning)
+        });
+        let result = HashMap::<String, LocalTime>::deserialize(deserializer).unwrap();
+        assert_eq!(result["time"].nanosecond, 12_345_678);
+        assert_eq!(
+            warnings
+                .lock()
+                .unwrap()
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            ["date-time has more than nanosecond precision at offset 7"]
+        );
+    }
+
+    #[test]
+    fn deserializer_ip_addr_keys() {
+        use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+        let input = indoc! {r#"
+            "127.0.0.1" = 8080
+            '10.0.0.1' = 80
+            "::1" = 443
+        "#};
+        let result: HashMap<IpAddr, u16> = from_str(input).unwrap();
+        assert_eq!(
+            result,
+            hashmap! {
+                IpAddr::V4(Ipv4Addr::LOCALHOST) => 8080,
+                IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1)) => 80,
+                IpAddr::V6(Ipv6Addr::LOCALHOST) => 443,
+            }
+        );
+
+        let input = indoc! {r#"
+            [ports]
+            "192.168.0.1" = 22
+        "#};
+        let result: HashMap<String, HashMap<Ipv4Addr, u16>> = from_str(input).unwrap();
+        assert_eq!(result["ports"][&Ipv4Addr::new(192, 168, 0, 1)], 22);
+
+        assert_matches!(
+            from_str::<HashMap<IpAddr, u16>>("localhost = 8080"),
+            Err(Error(ErrorKind::Custom(msg))) if &*msg == "invalid IP address syntax"
+        );
+
+        // Without quotes the key is a dotted key, which is a nested table
+        assert_matches!(
+            from_str::<HashMap<IpAddr, u16>>("127.0.0.1 = 8080"),
+            Err(Error(ErrorKind::Custom(msg))) if &*msg == "invalid IP address syntax"
+        );
+    }
+
+    #[test]
+    fn deserializer_parse_with_diagnostics() {
+        let (value, diagnostics) = parse_with_diagnostics("a = 1\nb = [2, 3]");
+        assert_eq!(
+            value,
+            Some(Value::Table(table! {
+                "a".into() => Value::Integer(1),
+                "b".into() => Value::Array(vec![
+                    Value::Integer(2),
+                    Value::Integer(3),
+                ]),
+            }))
+        );
+        assert!(diagnostics.is_empty());
+
+        // Every error is reported, and the statements containing them are skipped
+        let input = indoc! {"
+            a = 1
+            b = \0
+            c = = 2
+            a = 3 # comment
+            d = 99999999999999999999
+            [a]
+            x = 1
+            [e]
+            g = 'ok'
+            f = [1, 2
+        "};
+        let (value, diagnostics) = parse_with_diagnostics(input);
+        assert_eq!(
+            value,
+            Some(Value::Table(table! {
+                "a".into() => Value::Integer(1),
+                "e".into() => Value::Table(table! {
+                    "g".into() => Value::String("ok".into()),
+                }),
+            }))
+        );
+        assert!(diagnostics
+            .iter()
+            .all(|diagnostic| diagnostic.severity() == Severity::Error));
+        assert_eq!(
+            diagnostics
+                .iter()
+                .map(|diagnostic| (diagnostic.to_string(), diagnostic.span()))
+                .collect::<Vec<_>>(),
+            [
+                (r"error: illegal character '\0' at offset 10".into(), 6..11),
+                ("error: expected a value".into(), 12..19),
+                ("error: duplicate key: a in root table".into(), 20..35),
+                (
+                    "error: invalid integer `99999999999999999999`: number too large to fit in \
+                        target type"
+                        .into(),
+                    36..60,
+                ),
+                ("error: invalid table header: a".into(), 61..64),
+                ("error: unterminated array".into(), 84..93),
+            ]
+        );
+        assert_eq!(&input[diagnostics[2].span()], "a = 3 # comment");
+        assert_eq!(&input[diagnostics[5].span()], "f = [1, 2");
+
+        // A multi-line statement is spanned as a whole
+        let input = "a = [\n  1,\n  2 3,\n]\nb = 1";
+        let (value, diagnostics) = parse_with_diagnostics(input);
+        assert!(value.is_some());
+        assert_eq!(&input[diagnostics[0].span()], "a = [\n  1,\n  2 3,");
+    }
+
+    #[cfg(feature = "datetime")]
+    #[test]
+    fn deserializer_parse_with_diagnostics_warnings() {
+        let input = indoc! {"
+            a = 12:00:00.0123456789
+            b = 12:00:00.5
+            c = 1979-05-27T00:32:00.9999999999Z
+        "};
+        let (value, diagnostics) = parse_with_diagnostics(input);
+        assert!(value.is_some());
+        assert_eq!(
+            diagnostics
+                .iter()
+                .map(|diagnostic| (diagnostic.severity(), diagnostic.span()))
+                .collect::<Vec<_>>(),
+            [(Severity::Warning, 0..23), (Severity::Warning, 39..74)]
+        );
+
+        // Warnings and errors are interleaved in the order they're found
+        let input = "a = 12:00:00.0123456789\nb = \nc = 12:00:00.0123456789";
+        let (value, diagnostics) = parse_with_diagnostics(input);
+        assert!(value.is_some());
+        assert_eq!(
+            diagnostics
+                .iter()
+                .map(|diagnostic| (diagnostic.severity(), diagnostic.span()))
+                .collect::<Vec<_>>(),
+            [
+                (Severity::Warning, 0..23),
+                (Severity::Error, 24..28),
+                (Severity::Warning, 29..52)
+            ]
+        );
+    }
+
+    #[test]
+    fn deserializer_empty_string_as_none() {
+        #[derive(Debug, PartialEq, Eq, Deserialize)]
+        struct Config {
+            name: Option<String>,
+            token: Option<String>,
+            path: String,
+            server: Server,
+            tags: Vec<Option<String>>,
+        }
+
+        #[derive(Debug, PartialEq, Eq, Deserialize)]
+        struct Server {
+            ip: Option<String>,
+        }
+
+        let toml = indoc! {r#"
+            name = ""
+            token = "secret"
+            path = ""
+            tags = ["", "a"]
+
+            [server]
+            ip = ""
+        "#};
+
+        let deserializer = Deserializer::from_str(toml);
+        assert!(!deserializer.settings.empty_string_as_none);
+        assert_eq!(
+            Config::deserialize(deserializer).unwrap(),
+            Config {
+                name: Some(String::new()),
+                token: Some("secret".into()),
+                path: String::new(),
+                server: Server {
+                    ip: Some(String::new())
+                },
+                tags: vec![Some(String::new()), Some("a".into())],
+            }
+        );
+
+        let deserializer = Deserializer::from_str(toml).empty_string_as_none(true);
+        assert!(deserializer.settings.empty_string_as_none);
+        assert_eq!(
+            Config::deserialize(deserializer).unwrap(),
+            Config {
+                name: None,
+                token: Some("secret".into()),
+                path: String::new(),
+                server: Server { ip: None },
+                tags: vec![None, Some("a".into())],
+            }
+        );
+    }
+
+    #[test]
+    fn deserializer_invalid_literal_errors() {
+        let error = from_str::<HashMap<String, u8>>("a = 300").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid integer `300`: number too large to fit in target type"
+        );
 
-        assert_matches!(
-            deserializer.parser.parse(),
-            Ok(ParsedValue::Table(t)) if t == hashmap! {
-                "abc".into() => ParsedValue::Integer(b"123".to_vec())
-            }
+        let error = from_str::<HashMap<String, i8>>("a = 0xff").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid integer `0xff`: number too large to fit in target type"
         );
+
+        // Date-times are checked by the parser when validate_datetimes is enabled
+        #[cfg(feature = "datetime")]
+        {
+            let deserializer = Deserializer::from_str("a = 2023-02-30").validate_datetimes(true);
+            let error = HashMap::<String, Value>::deserialize(deserializer).unwrap_err();
+            assert_eq!(error.to_string(), "invalid date-time `2023-02-30`");
+
+            let deserializer =
+                Deserializer::from_str("a = 1979-05-27T07:32:00+25:00").validate_datetimes(true);
+            let error = HashMap::<String, Value>::deserialize(deserializer).unwrap_err();
+            assert_eq!(
+                error.to_string(),
+                "invalid date-time `1979-05-27T07:32:00+25:00`"
+            );
+        }
     }
 
     #[test]
-    fn deserializer_from_slice() {
-        let mut deserializer = Deserializer::from_slice(b"abc = 123");
+    fn deserializer_signed_floats() {
+        let result: HashMap<String, f64> = from_str(indoc! {"
+            a = +1.5
+            b = -1.5
+            c = +0.0
+            d = -0.0
+        "})
+        .unwrap();
+        assert_eq!(result["a"].to_bits(), 1.5_f64.to_bits());
+        assert_eq!(result["b"].to_bits(), (-1.5_f64).to_bits());
+        assert_eq!(result["c"].to_bits(), 0.0_f64.to_bits());
+        assert_eq!(result["d"].to_bits(), (-0.0_f64).to_bits());
 
-        assert_matches!(
-            deserializer.parser.parse(),
-            Ok(ParsedValue::Table(t)) if t == hashmap! {
-                "abc".into() => ParsedValue::Integer(b"123".to_vec()),
-            }
+        let value = HashMap::from([("d", -0.0_f64)]);
+        let result: HashMap<String, f64> = from_str(&crate::to_string(&value).unwrap()).unwrap();
+        assert_eq!(result["d"].to_bits(), (-0.0_f64).to_bits());
+    }
+
+    #[cfg(feature = "datetime")]
+    #[test]
+    fn deserializer_any_datetime_field() {
+        #[derive(Debug, Deserialize)]
+        struct Struct {
+            offset: AnyDatetime,
+            local: AnyDatetime,
+            date: AnyDatetime,
+            time: AnyDatetime,
+        }
+
+        let result: Struct = from_str(indoc! {"
+            offset = 2023-01-02T03:04:05.006+07:08
+            local = 2023-01-02T03:04:05.006
+            date = 2023-01-02
+            time = 03:04:05.006
+        "})
+        .unwrap();
+        assert_eq!(
+            result.offset,
+            AnyDatetime::OffsetDatetime(OffsetDatetime::EXAMPLE)
+        );
+        assert_eq!(
+            result.local,
+            AnyDatetime::LocalDatetime(LocalDatetime::EXAMPLE)
         );
+        assert_eq!(result.date, AnyDatetime::LocalDate(LocalDate::EXAMPLE));
+        assert_eq!(result.time, AnyDatetime::LocalTime(LocalTime::EXAMPLE));
     }
 
     #[test]
@@ -1405,6 +3815,26 @@ mod tests {
                 "abc".to_owned() => 123_i32,
             }
         );
+
+        let deserializer = Deserializer::value_from_str("[1, 2, 3]");
+        assert_matches!(Vec::<i32>::deserialize(deserializer), Ok(v) if v == [1, 2, 3]);
+
+        let deserializer = Deserializer::value_from_str(r#""foo""#);
+        assert_matches!(String::deserialize(deserializer), Ok(s) if s == "foo");
+
+        let deserializer = Deserializer::value_from_str("{ abc = 123 }");
+        assert_matches!(
+            HashMap::deserialize(deserializer),
+            Ok(t) if t == hashmap! {
+                "abc".to_owned() => 123_i32,
+            }
+        );
+
+        let deserializer = Deserializer::value_from_str("abc = 123");
+        assert_matches!(
+            HashMap::<String, i32>::deserialize(deserializer),
+            Err(Error(ErrorKind::ExpectedToken(..)))
+        );
     }
 
     #[test]
@@ -1420,7 +3850,7 @@ mod tests {
         let deserializer = ValueDeserializer::new(ParsedValue::String("hello".into()));
         assert_matches!(Value::deserialize(deserializer), Ok(Value::String(s)) if &*s == "hello");
 
-        let deserializer = ValueDeserializer::new(ParsedValue::Integer(b"123".to_vec()));
+        let deserializer = ValueDeserializer::new(ParsedValue::Integer(b"123".to_vec(), 0));
         assert_matches!(Value::deserialize(deserializer), Ok(Value::Integer(123)));
 
         let deserializer = ValueDeserializer::new(ParsedValue::BinaryInt(b"1010".to_vec()));
@@ -1476,9 +3906,9 @@ mod tests {
         };
 
         let deserializer = ValueDeserializer::new(ParsedValue::Array(vec![
-            ParsedValue::Integer(b"123".to_vec()),
-            ParsedValue::Integer(b"456".to_vec()),
-            ParsedValue::Integer(b"789".to_vec()),
+            ParsedValue::Integer(b"123".to_vec(), 0),
+            ParsedValue::Integer(b"456".to_vec(), 0),
+            ParsedValue::Integer(b"789".to_vec(), 0),
         ]));
         assert_matches!(
             Value::deserialize(deserializer),
@@ -1491,66 +3921,66 @@ mod tests {
 
         let deserializer = ValueDeserializer::new(ParsedValue::ArrayOfTables(vec![
             hashmap! {
-                "abc".into() => ParsedValue::Integer(b"123".to_vec()),
+                "abc".into() => ParsedValue::Integer(b"123".to_vec(), 0),
             },
             hashmap! {
-                "def".into() => ParsedValue::Integer(b"456".to_vec()),
+                "def".into() => ParsedValue::Integer(b"456".to_vec(), 0),
             },
             hashmap! {
-                "ghi".into() => ParsedValue::Integer(b"789".to_vec()),
+                "ghi".into() => ParsedValue::Integer(b"789".to_vec(), 0),
             },
         ]));
         assert_matches!(
             Value::deserialize(deserializer),
             Ok(Value::Array(a)) if a == [
-                Value::Table(btreemap! {
+                Value::Table(table! {
                     "abc".into() => Value::Integer(123),
                 }),
-                Value::Table(btreemap! {
+                Value::Table(table! {
                     "def".into() => Value::Integer(456),
                 }),
-                Value::Table(btreemap! {
+                Value::Table(table! {
                     "ghi".into() => Value::Integer(789),
                 }),
             ]
         );
 
         let deserializer = ValueDeserializer::new(ParsedValue::Table(hashmap! {
-            "abc".into() => ParsedValue::Integer(b"123".to_vec()),
+            "abc".into() => ParsedValue::Integer(b"123".to_vec(), 0),
         }));
         assert_matches!(
             Value::deserialize(deserializer),
-            Ok(Value::Table(t)) if t == btreemap! {
+            Ok(Value::Table(t)) if t == table! {
                 "abc".into() => Value::Integer(123),
             }
         );
 
         let deserializer = ValueDeserializer::new(ParsedValue::UndefinedTable(hashmap! {
-            "abc".into() => ParsedValue::Integer(b"123".to_vec()),
+            "abc".into() => ParsedValue::Integer(b"123".to_vec(), 0),
         }));
         assert_matches!(
             Value::deserialize(deserializer),
-            Ok(Value::Table(t)) if t == btreemap! {
+            Ok(Value::Table(t)) if t == table! {
                 "abc".into() => Value::Integer(123),
             }
         );
 
         let deserializer = ValueDeserializer::new(ParsedValue::DottedKeyTable(hashmap! {
-            "abc".into() => ParsedValue::Integer(b"123".to_vec()),
+            "abc".into() => ParsedValue::Integer(b"123".to_vec(), 0),
         }));
         assert_matches!(
             Value::deserialize(deserializer),
-            Ok(Value::Table(t)) if t == btreemap! {
+            Ok(Value::Table(t)) if t == table! {
                 "abc".into() => Value::Integer(123),
             }
         );
 
         let deserializer = ValueDeserializer::new(ParsedValue::InlineTable(hashmap! {
-            "abc".into() => ParsedValue::Integer(b"123".to_vec()),
+            "abc".into() => ParsedValue::Integer(b"123".to_vec(), 0),
         }));
         assert_matches!(
             Value::deserialize(deserializer),
-            Ok(Value::Table(t)) if t == btreemap! {
+            Ok(Value::Table(t)) if t == table! {
                 "abc".into() => Value::Integer(123),
             }
         );
@@ -1571,7 +4001,7 @@ mod tests {
 
     #[test]
     fn value_deserializer_deserialize_i8() {
-        let deserializer = ValueDeserializer::new(ParsedValue::Integer(b"123".to_vec()));
+        let deserializer = ValueDeserializer::new(ParsedValue::Integer(b"123".to_vec(), 0));
         assert_matches!(i8::deserialize(deserializer), Ok(123));
 
         let deserializer = ValueDeserializer::new(ParsedValue::BinaryInt(b"1010".to_vec()));
@@ -1592,7 +4022,7 @@ mod tests {
 
     #[test]
     fn value_deserializer_deserialize_i16() {
-        let deserializer = ValueDeserializer::new(ParsedValue::Integer(b"123".to_vec()));
+        let deserializer = ValueDeserializer::new(ParsedValue::Integer(b"123".to_vec(), 0));
         assert_matches!(i16::deserialize(deserializer), Ok(123));
 
         let deserializer = ValueDeserializer::new(ParsedValue::BinaryInt(b"1010".to_vec()));
@@ -1613,7 +4043,7 @@ mod tests {
 
     #[test]
     fn value_deserializer_deserialize_i32() {
-        let deserializer = ValueDeserializer::new(ParsedValue::Integer(b"123".to_vec()));
+        let deserializer = ValueDeserializer::new(ParsedValue::Integer(b"123".to_vec(), 0));
         assert_matches!(i32::deserialize(deserializer), Ok(123));
 
         let deserializer = ValueDeserializer::new(ParsedValue::BinaryInt(b"1010".to_vec()));
@@ -1634,7 +4064,7 @@ mod tests {
 
     #[test]
     fn value_deserializer_deserialize_i64() {
-        let deserializer = ValueDeserializer::new(ParsedValue::Integer(b"123".to_vec()));
+        let deserializer = ValueDeserializer::new(ParsedValue::Integer(b"123".to_vec(), 0));
         assert_matches!(i64::deserialize(deserializer), Ok(123));
 
         let deserializer = ValueDeserializer::new(ParsedValue::BinaryInt(b"1010".to_vec()));
@@ -1655,7 +4085,7 @@ mod tests {
 
     #[test]
     fn value_deserializer_deserialize_i128() {
-        let deserializer = ValueDeserializer::new(ParsedValue::Integer(b"123".to_vec()));
+        let deserializer = ValueDeserializer::new(ParsedValue::Integer(b"123".to_vec(), 0));
         assert_matches!(i128::deserialize(deserializer), Ok(123));
 
         let deserializer = ValueDeserializer::new(ParsedValue::BinaryInt(b"1010".to_vec()));
@@ -1676,7 +4106,7 @@ mod tests {
 
     #[test]
     fn value_deserializer_deserialize_u8() {
-        let deserializer = ValueDeserializer::new(ParsedValue::Integer(b"123".to_vec()));
+        let deserializer = ValueDeserializer::new(ParsedValue::Integer(b"123".to_vec(), 0));
         assert_matches!(u8::deserialize(deserializer), Ok(123));
 
         let deserializer = ValueDeserializer::new(ParsedValue::BinaryInt(b"1010".to_vec()));
@@ -1697,7 +4127,7 @@ mod tests {
 
     #[test]
     fn value_deserializer_deserialize_u16() {
-        let deserializer = ValueDeserializer::new(ParsedValue::Integer(b"123".to_vec()));
+        let deserializer = ValueDeserializer::new(ParsedValue::Integer(b"123".to_vec(), 0));
         assert_matches!(u16::deserialize(deserializer), Ok(123));
 
         let deserializer = ValueDeserializer::new(ParsedValue::BinaryInt(b"1010".to_vec()));
@@ -1718,7 +4148,7 @@ mod tests {
 
     #[test]
     fn value_deserializer_deserialize_u32() {
-        let deserializer = ValueDeserializer::new(ParsedValue::Integer(b"123".to_vec()));
+        let deserializer = ValueDeserializer::new(ParsedValue::Integer(b"123".to_vec(), 0));
         assert_matches!(u32::deserialize(deserializer), Ok(123));
 
         let deserializer = ValueDeserializer::new(ParsedValue::BinaryInt(b"1010".to_vec()));
@@ -1739,7 +4169,7 @@ mod tests {
 
     #[test]
     fn value_deserializer_deserialize_u64() {
-        let deserializer = ValueDeserializer::new(ParsedValue::Integer(b"123".to_vec()));
+        let deserializer = ValueDeserializer::new(ParsedValue::Integer(b"123".to_vec(), 0));
         assert_matches!(u64::deserialize(deserializer), Ok(123));
 
         let deserializer = ValueDeserializer::new(ParsedValue::BinaryInt(b"1010".to_vec()));
@@ -1760,7 +4190,7 @@ mod tests {
 
     #[test]
     fn value_deserializer_deserialize_u128() {
-        let deserializer = ValueDeserializer::new(ParsedValue::Integer(b"123".to_vec()));
+        let deserializer = ValueDeserializer::new(ParsedValue::Integer(b"123".to_vec(), 0));
         assert_matches!(u128::deserialize(deserializer), Ok(123));
 
         let deserializer = ValueDeserializer::new(ParsedValue::BinaryInt(b"1010".to_vec()));
@@ -1784,7 +4214,7 @@ mod tests {
         let deserializer = ValueDeserializer::new(ParsedValue::Float(b"123.0".to_vec()));
         assert_matches!(f32::deserialize(deserializer), Ok(123.0));
 
-        let deserializer = ValueDeserializer::new(ParsedValue::Integer(b"123".to_vec()));
+        let deserializer = ValueDeserializer::new(ParsedValue::Integer(b"123".to_vec(), 0));
         assert_matches!(f32::deserialize(deserializer), Ok(123.0));
 
         let deserializer =
@@ -1803,7 +4233,7 @@ mod tests {
         let deserializer = ValueDeserializer::new(ParsedValue::Float(b"123.0".to_vec()));
         assert_matches!(f64::deserialize(deserializer), Ok(123.0));
 
-        let deserializer = ValueDeserializer::new(ParsedValue::Integer(b"123".to_vec()));
+        let deserializer = ValueDeserializer::new(ParsedValue::Integer(b"123".to_vec(), 0));
         assert_matches!(f64::deserialize(deserializer), Ok(123.0));
 
         let deserializer =
@@ -1819,10 +4249,10 @@ mod tests {
 
     #[test]
     fn value_deserializer_deserialize_char() {
-        let deserializer = ValueDeserializer::new(ParsedValue::String("A".to_string()));
+        let deserializer = ValueDeserializer::new(ParsedValue::String("A".into()));
         assert_matches!(char::deserialize(deserializer), Ok('A'));
 
-        let deserializer = ValueDeserializer::new(ParsedValue::String("A".to_string()));
+        let deserializer = ValueDeserializer::new(ParsedValue::String("A".into()));
         assert_matches!(char::deserialize(deserializer), Ok('A'));
 
         let deserializer = ValueDeserializer::new(ParsedValue::String("hello".into()));
@@ -1831,7 +4261,7 @@ mod tests {
             Err(Error(ErrorKind::InvalidValue(..)))
         );
 
-        let deserializer = ValueDeserializer::new(ParsedValue::Integer(b"123".to_vec()));
+        let deserializer = ValueDeserializer::new(ParsedValue::Integer(b"123".to_vec(), 0));
         assert_matches!(
             char::deserialize(deserializer),
             Err(Error(ErrorKind::InvalidType(..)))
@@ -1840,14 +4270,17 @@ mod tests {
 
     #[test]
     fn value_deserializer_deserialize_str() {
-        let deserializer = ValueDeserializer::new(ParsedValue::String("hello".to_string()));
-        // Can't actually deserialize to a borrowed &str
+        let deserializer = ValueDeserializer::new(ParsedValue::String(Cow::Borrowed("hello")));
+        assert_eq!(<&str>::deserialize(deserializer).unwrap(), "hello");
+
+        // Can't borrow a string which was unescaped by the parser
+        let deserializer = ValueDeserializer::new(ParsedValue::String(Cow::Owned("hello".into())));
         assert_matches!(
             <&str>::deserialize(deserializer),
             Err(Error(ErrorKind::InvalidType(..)))
         );
 
-        let deserializer = ValueDeserializer::new(ParsedValue::Integer(b"123".to_vec()));
+        let deserializer = ValueDeserializer::new(ParsedValue::Integer(b"123".to_vec(), 0));
         assert_matches!(
             <&str>::deserialize(deserializer),
             Err(Error(ErrorKind::InvalidType(..)))
@@ -1856,26 +4289,116 @@ mod tests {
 
     #[test]
     fn value_deserializer_deserialize_string() {
-        let deserializer = ValueDeserializer::new(ParsedValue::String("hello".to_string()));
+        let deserializer = ValueDeserializer::new(ParsedValue::String("hello".into()));
         assert_matches!(String::deserialize(deserializer), Ok(s) if s == "hello");
 
-        let deserializer = ValueDeserializer::new(ParsedValue::Integer(b"123".to_vec()));
+        let deserializer = ValueDeserializer::new(ParsedValue::Integer(b"123".to_vec(), 0));
+        assert_matches!(
+            String::deserialize(deserializer),
+            Err(Error(ErrorKind::InvalidType(..)))
+        );
+    }
+
+    #[test]
+    fn value_deserializer_invalid_type_table() {
+        // Internal table variants should all be reported as a table
+        for value in [
+            ParsedValue::Table(HashMap::new()),
+            ParsedValue::InlineTable(HashMap::new()),
+            ParsedValue::UndefinedTable(HashMap::new()),
+            ParsedValue::DottedKeyTable(HashMap::new()),
+        ] {
+            let deserializer = ValueDeserializer::new(value);
+            assert_matches!(
+                String::deserialize(deserializer),
+                Err(Error(ErrorKind::InvalidType(t, _))) if &*t == "table"
+            );
+        }
+
+        let deserializer = ValueDeserializer::new(ParsedValue::ArrayOfTables(Vec::new()));
         assert_matches!(
             String::deserialize(deserializer),
+            Err(Error(ErrorKind::InvalidType(t, _))) if &*t == "array"
+        );
+
+        let error = from_str::<HashMap<String, String>>("a.b = 1").unwrap_err();
+        assert_eq!(error.to_string(), "invalid type: table, expected a string");
+
+        let error = from_str::<HashMap<String, String>>("[a.b]").unwrap_err();
+        assert_eq!(error.to_string(), "invalid type: table, expected a string");
+    }
+
+    #[test]
+    fn value_deserializer_deserialize_path_buf() {
+        use std::path::{Path, PathBuf};
+
+        let deserializer = ValueDeserializer::new(ParsedValue::String("/path/to/file.toml".into()));
+        assert_matches!(
+            PathBuf::deserialize(deserializer),
+            Ok(p) if p == Path::new("/path/to/file.toml")
+        );
+
+        let deserializer = ValueDeserializer::new(ParsedValue::String("".into()));
+        assert_matches!(PathBuf::deserialize(deserializer), Ok(p) if p == PathBuf::new());
+
+        let deserializer = ValueDeserializer::new(ParsedValue::Integer(b"123".to_vec(), 0));
+        assert_matches!(
+            PathBuf::deserialize(deserializer),
+            Err(Error(ErrorKind::InvalidType(..)))
+        );
+    }
+
+    #[cfg(any(unix, windows))]
+    #[test]
+    fn value_deserializer_deserialize_os_string() {
+        use std::ffi::OsString;
+
+        let deserializer = ValueDeserializer::new(ParsedValue::String("foo".into()));
+        assert_matches!(OsString::deserialize(deserializer), Ok(s) if s == "foo");
+
+        let deserializer = ValueDeserializer::new(ParsedValue::String("f\u{f6}\u{1f600}".into()));
+        assert_matches!(OsString::deserialize(deserializer), Ok(s) if s == "f\u{f6}\u{1f600}");
+
+        let deserializer = ValueDeserializer::new(ParsedValue::String(String::new().into()));
+        assert_matches!(OsString::deserialize(deserializer), Ok(s) if s.is_empty());
+
+        let deserializer = ValueDeserializer::new(ParsedValue::Integer(b"123".to_vec(), 0));
+        assert_matches!(
+            OsString::deserialize(deserializer),
             Err(Error(ErrorKind::InvalidType(..)))
         );
+
+        // Serde's platform specific enum representation is also accepted
+        #[cfg(unix)]
+        {
+            let deserializer = ValueDeserializer::new(ParsedValue::InlineTable(HashMap::from([(
+                "Unix".into(),
+                ParsedValue::Array(vec![
+                    ParsedValue::Integer(b"102".to_vec(), 0),
+                    ParsedValue::Integer(b"111".to_vec(), 0),
+                    ParsedValue::Integer(b"111".to_vec(), 0),
+                ]),
+            )])));
+            assert_matches!(OsString::deserialize(deserializer), Ok(s) if s == "foo");
+        }
     }
 
     #[test]
     fn value_deserializer_deserialize_bytes() {
-        let deserializer = ValueDeserializer::new(ParsedValue::String("hello".to_string()));
-        // Can't actually deserialize to a borrowed &[u8]
+        let deserializer = ValueDeserializer::new(ParsedValue::String(Cow::Borrowed("hello")));
+        assert_eq!(
+            <&[u8]>::deserialize(deserializer).unwrap(),
+            b"hello".as_slice()
+        );
+
+        // Can't borrow a string which was unescaped by the parser
+        let deserializer = ValueDeserializer::new(ParsedValue::String(Cow::Owned("hello".into())));
         assert_matches!(
             <&[u8]>::deserialize(deserializer),
             Err(Error(ErrorKind::InvalidType(..)))
         );
 
-        let deserializer = ValueDeserializer::new(ParsedValue::Integer(b"123".to_vec()));
+        let deserializer = ValueDeserializer::new(ParsedValue::Integer(b"123".to_vec(), 0));
         assert_matches!(
             <&[u8]>::deserialize(deserializer),
             Err(Error(ErrorKind::InvalidType(..)))
@@ -1884,10 +4407,23 @@ mod tests {
 
     #[test]
     fn value_deserializer_deserialize_byte_buf() {
-        let deserializer = ValueDeserializer::new(ParsedValue::String("hello".to_string()));
+        let deserializer = ValueDeserializer::new(ParsedValue::String("hello".into()));
         assert_matches!(ByteBuf::deserialize(deserializer), Ok(b) if &*b == b"hello");
 
-        let deserializer = ValueDeserializer::new(ParsedValue::Integer(b"123".to_vec()));
+        let deserializer = ValueDeserializer::new(ParsedValue::Array(vec![
+            ParsedValue::Integer(b"1".to_vec(), 0),
+            ParsedValue::Integer(b"2".to_vec(), 0),
+            ParsedValue::Integer(b"255".to_vec(), 0),
+        ]));
+        assert_matches!(ByteBuf::deserialize(deserializer), Ok(b) if *b == [1, 2, 255]);
+
+        let deserializer = ValueDeserializer::new(ParsedValue::Array(vec![ParsedValue::Integer(
+            b"256".to_vec(),
+            0,
+        )]));
+        assert_matches!(ByteBuf::deserialize(deserializer), Err(Error(..)));
+
+        let deserializer = ValueDeserializer::new(ParsedValue::Integer(b"123".to_vec(), 0));
         assert_matches!(
             ByteBuf::deserialize(deserializer),
             Err(Error(ErrorKind::InvalidType(..)))
@@ -1896,13 +4432,28 @@ mod tests {
 
     #[test]
     fn value_deserializer_deserialize_option() {
-        let deserializer = ValueDeserializer::new(ParsedValue::String("hello".to_string()));
+        let deserializer = ValueDeserializer::new(ParsedValue::String("hello".into()));
+        assert_matches!(Option::<String>::deserialize(deserializer), Ok(Some(s)) if s == "hello");
+
+        let deserializer = ValueDeserializer::new(ParsedValue::String("".into()));
+        assert_matches!(Option::<String>::deserialize(deserializer), Ok(Some(s)) if s.is_empty());
+
+        let settings = Settings {
+            empty_string_as_none: true,
+            ..Settings::default()
+        };
+        let deserializer =
+            ValueDeserializer::new(ParsedValue::String("".into())).with_settings(settings.clone());
+        assert_matches!(Option::<String>::deserialize(deserializer), Ok(None));
+
+        let deserializer =
+            ValueDeserializer::new(ParsedValue::String("hello".into())).with_settings(settings);
         assert_matches!(Option::<String>::deserialize(deserializer), Ok(Some(s)) if s == "hello");
     }
 
     #[test]
     fn value_deserializer_deserialize_unit() {
-        let deserializer = ValueDeserializer::new(ParsedValue::String("hello".to_string()));
+        let deserializer = ValueDeserializer::new(ParsedValue::String("hello".into()));
         assert_matches!(
             <()>::deserialize(deserializer),
             Err(Error(ErrorKind::InvalidType(..)))
@@ -1926,7 +4477,7 @@ mod tests {
         #[derive(Debug, PartialEq, Eq, Deserialize)]
         struct Newtype(i32);
 
-        let deserializer = ValueDeserializer::new(ParsedValue::Integer(b"123".to_vec()));
+        let deserializer = ValueDeserializer::new(ParsedValue::Integer(b"123".to_vec(), 0));
         assert_matches!(Newtype::deserialize(deserializer), Ok(Newtype(123)));
     }
 
@@ -1938,9 +4489,9 @@ mod tests {
         }
 
         let deserializer = ValueDeserializer::new(ParsedValue::Array(vec![
-            ParsedValue::Integer(b"123".to_vec()),
-            ParsedValue::Integer(b"456".to_vec()),
-            ParsedValue::Integer(b"789".to_vec()),
+            ParsedValue::Integer(b"123".to_vec(), 0),
+            ParsedValue::Integer(b"456".to_vec(), 0),
+            ParsedValue::Integer(b"789".to_vec(), 0),
         ]));
         assert_matches!(
             <Vec<i32>>::deserialize(deserializer),
@@ -1948,9 +4499,9 @@ mod tests {
         );
 
         let deserializer = ValueDeserializer::new(ParsedValue::ArrayOfTables(vec![
-            hashmap! { "val".into() => ParsedValue::Integer(b"123".to_vec()) },
-            hashmap! { "val".into() => ParsedValue::Integer(b"456".to_vec()) },
-            hashmap! { "val".into() => ParsedValue::Integer(b"789".to_vec()) },
+            hashmap! { "val".into() => ParsedValue::Integer(b"123".to_vec(), 0) },
+            hashmap! { "val".into() => ParsedValue::Integer(b"456".to_vec(), 0) },
+            hashmap! { "val".into() => ParsedValue::Integer(b"789".to_vec(), 0) },
         ]));
         assert_matches!(
             <Vec<Struct>>::deserialize(deserializer),
@@ -1961,6 +4512,23 @@ mod tests {
             ]
         );
 
+        // TOML 1.0 allows arrays with mixed types
+        let deserializer = ValueDeserializer::new(ParsedValue::Array(vec![
+            ParsedValue::Integer(b"1".to_vec(), 0),
+            ParsedValue::String("two".into()),
+            ParsedValue::Float(b"3.0".to_vec()),
+            ParsedValue::Boolean(true),
+        ]));
+        assert_matches!(
+            <Vec<Value>>::deserialize(deserializer),
+            Ok(a) if a == [
+                Value::Integer(1),
+                Value::String("two".into()),
+                Value::Float(3.0),
+                Value::Boolean(true),
+            ]
+        );
+
         let deserializer = ValueDeserializer::new(ParsedValue::String("hello".into()));
         assert_matches!(
             <Vec<i32>>::deserialize(deserializer),
@@ -1968,6 +4536,83 @@ mod tests {
         );
     }
 
+    #[test]
+    fn value_deserializer_deserialize_set() {
+        use std::collections::{BTreeSet, HashSet};
+
+        // Records the size hint passed to the visitor, as used by serde's set impls to pre-size
+        #[derive(Debug)]
+        struct SizeHint(Option<usize>);
+
+        impl<'de> Deserialize<'de> for SizeHint {
+            fn deserialize<D>(deserializer: D) -> StdResult<Self, D::Error>
+            where
+                D: de::Deserializer<'de>,
+            {
+                struct Visitor;
+
+                impl<'de> de::Visitor<'de> for Visitor {
+                    type Value = SizeHint;
+
+                    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                        f.write_str("a sequence")
+                    }
+
+                    fn visit_seq<A>(self, mut seq: A) -> StdResult<Self::Value, A::Error>
+                    where
+                        A: de::SeqAccess<'de>,
+                    {
+                        let hint = seq.size_hint();
+                        while seq.next_element::<de::IgnoredAny>()?.is_some() {}
+                        Ok(SizeHint(hint))
+                    }
+                }
+
+                deserializer.deserialize_seq(Visitor)
+            }
+        }
+
+        let array = || {
+            ParsedValue::Array(vec![
+                ParsedValue::String("b".into()),
+                ParsedValue::String("a".into()),
+                ParsedValue::String("b".into()),
+            ])
+        };
+
+        // Duplicates are silently merged
+        let deserializer = ValueDeserializer::new(array());
+        assert_matches!(
+            <BTreeSet<String>>::deserialize(deserializer),
+            Ok(s) if s == BTreeSet::from(["a".into(), "b".into()])
+        );
+
+        let deserializer = ValueDeserializer::new(array());
+        assert_matches!(
+            <HashSet<String>>::deserialize(deserializer),
+            Ok(s) if s == HashSet::from(["a".into(), "b".into()]) && s.capacity() >= 3
+        );
+
+        let deserializer = ValueDeserializer::new(array());
+        assert_matches!(SizeHint::deserialize(deserializer), Ok(SizeHint(Some(3))));
+
+        let deserializer =
+            ValueDeserializer::new(ParsedValue::ArrayOfTables(vec![hashmap! {}, hashmap! {}]));
+        assert_matches!(SizeHint::deserialize(deserializer), Ok(SizeHint(Some(2))));
+
+        let result: HashMap<String, BTreeSet<i32>> = from_str("a = [3, 1, 2, 1]").unwrap();
+        assert_eq!(result["a"], BTreeSet::from([1, 2, 3]));
+
+        let deserializer = ValueDeserializer::new(ParsedValue::Array(vec![
+            ParsedValue::Integer(b"1".to_vec(), 0),
+            ParsedValue::String("two".into()),
+        ]));
+        assert_matches!(
+            <HashSet<i32>>::deserialize(deserializer),
+            Err(Error(ErrorKind::InvalidType(..)))
+        );
+    }
+
     #[test]
     fn value_deserializer_deserialize_tuple() {
         #[derive(Debug, PartialEq, Eq, Deserialize)]
@@ -1976,7 +4621,7 @@ mod tests {
         }
 
         let deserializer = ValueDeserializer::new(ParsedValue::Array(vec![
-            ParsedValue::Integer(b"123".to_vec()),
+            ParsedValue::Integer(b"123".to_vec(), 0),
             ParsedValue::String("hello".into()),
             ParsedValue::Array(vec![]),
         ]));
@@ -1986,7 +4631,7 @@ mod tests {
         );
 
         let deserializer = ValueDeserializer::new(ParsedValue::ArrayOfTables(vec![
-            hashmap! { "val".into() => ParsedValue::Integer(b"123".to_vec()) },
+            hashmap! { "val".into() => ParsedValue::Integer(b"123".to_vec(), 0) },
             hashmap! { "val".into() => ParsedValue::String("hello".into()) },
             hashmap! { "val".into() => ParsedValue::Array(vec![]) },
         ]));
@@ -1999,6 +4644,25 @@ mod tests {
                 }
         );
 
+        let deserializer = ValueDeserializer::new(ParsedValue::Array(vec![ParsedValue::Integer(
+            b"1".to_vec(),
+            0,
+        )]));
+        assert_matches!(
+            <(i32, i32)>::deserialize(deserializer),
+            Err(Error(ErrorKind::InvalidLength(1, exp))) if &*exp == "a tuple of size 2"
+        );
+
+        let deserializer = ValueDeserializer::new(ParsedValue::Array(vec![
+            ParsedValue::Integer(b"1".to_vec(), 0),
+            ParsedValue::Integer(b"2".to_vec(), 0),
+            ParsedValue::Integer(b"3".to_vec(), 0),
+        ]));
+        assert_matches!(
+            <(i32, i32)>::deserialize(deserializer),
+            Err(Error(ErrorKind::InvalidLength(3, exp))) if &*exp == "a tuple of size 2"
+        );
+
         let deserializer = ValueDeserializer::new(ParsedValue::String("hello".into()));
         assert_matches!(
             <(i32, i32, i32)>::deserialize(deserializer),
@@ -2012,7 +4676,7 @@ mod tests {
         struct TupleStruct(i32, String, Vec<i64>);
 
         let deserializer = ValueDeserializer::new(ParsedValue::Array(vec![
-            ParsedValue::Integer(b"123".to_vec()),
+            ParsedValue::Integer(b"123".to_vec(), 0),
             ParsedValue::String("hello".into()),
             ParsedValue::Array(vec![]),
         ]));
@@ -2023,6 +4687,17 @@ mod tests {
                 && d.is_empty()
         );
 
+        let deserializer = ValueDeserializer::new(ParsedValue::Array(vec![
+            ParsedValue::Integer(b"123".to_vec(), 0),
+            ParsedValue::String("hello".into()),
+            ParsedValue::Array(vec![]),
+            ParsedValue::Boolean(true),
+        ]));
+        assert_matches!(
+            TupleStruct::deserialize(deserializer),
+            Err(Error(ErrorKind::InvalidLength(4, exp))) if &*exp == "a tuple of size 3"
+        );
+
         let deserializer = ValueDeserializer::new(ParsedValue::String("hello".into()));
         assert_matches!(
             TupleStruct::deserialize(deserializer),
@@ -2033,7 +4708,7 @@ mod tests {
     #[test]
     fn value_deserializer_deserialize_map() {
         let deserializer = ValueDeserializer::new(ParsedValue::Table(hashmap! {
-            "abc".into() => ParsedValue::Integer(b"123".to_vec()),
+            "abc".into() => ParsedValue::Integer(b"123".to_vec(), 0),
         }));
         assert_matches!(
             HashMap::<String, i32>::deserialize(deserializer),
@@ -2043,7 +4718,7 @@ mod tests {
         );
 
         let deserializer = ValueDeserializer::new(ParsedValue::UndefinedTable(hashmap! {
-            "abc".into() => ParsedValue::Integer(b"123".to_vec()),
+            "abc".into() => ParsedValue::Integer(b"123".to_vec(), 0),
         }));
         assert_matches!(
             HashMap::<String, i32>::deserialize(deserializer),
@@ -2053,7 +4728,7 @@ mod tests {
         );
 
         let deserializer = ValueDeserializer::new(ParsedValue::DottedKeyTable(hashmap! {
-            "abc".into() => ParsedValue::Integer(b"123".to_vec()),
+            "abc".into() => ParsedValue::Integer(b"123".to_vec(), 0),
         }));
         assert_matches!(
             HashMap::<String, i32>::deserialize(deserializer),
@@ -2063,7 +4738,7 @@ mod tests {
         );
 
         let deserializer = ValueDeserializer::new(ParsedValue::InlineTable(hashmap! {
-            "abc".into() => ParsedValue::Integer(b"123".to_vec()),
+            "abc".into() => ParsedValue::Integer(b"123".to_vec(), 0),
         }));
         assert_matches!(
             HashMap::<String, i32>::deserialize(deserializer),
@@ -2087,22 +4762,22 @@ mod tests {
         }
 
         let deserializer = ValueDeserializer::new(ParsedValue::Table(hashmap! {
-            "abc".into() => ParsedValue::Integer(b"123".to_vec()),
+            "abc".into() => ParsedValue::Integer(b"123".to_vec(), 0),
         }));
         assert_matches!(Struct::deserialize(deserializer), Ok(Struct { abc: 123 }));
 
         let deserializer = ValueDeserializer::new(ParsedValue::UndefinedTable(hashmap! {
-            "abc".into() => ParsedValue::Integer(b"123".to_vec()),
+            "abc".into() => ParsedValue::Integer(b"123".to_vec(), 0),
         }));
         assert_matches!(Struct::deserialize(deserializer), Ok(Struct { abc: 123 }));
 
         let deserializer = ValueDeserializer::new(ParsedValue::DottedKeyTable(hashmap! {
-            "abc".into() => ParsedValue::Integer(b"123".to_vec()),
+            "abc".into() => ParsedValue::Integer(b"123".to_vec(), 0),
         }));
         assert_matches!(Struct::deserialize(deserializer), Ok(Struct { abc: 123 }));
 
         let deserializer = ValueDeserializer::new(ParsedValue::InlineTable(hashmap! {
-            "abc".into() => ParsedValue::Integer(b"123".to_vec()),
+            "abc".into() => ParsedValue::Integer(b"123".to_vec(), 0),
         }));
         assert_matches!(Struct::deserialize(deserializer), Ok(Struct { abc: 123 }));
 
@@ -2170,6 +4845,42 @@ mod tests {
             Datetime::deserialize(deserializer),
             Ok(Datetime::EXAMPLE_LOCAL_TIME)
         );
+
+        let deserializer = ValueDeserializer::new(ParsedValue::OffsetDatetime(
+            OffsetDatetime::EXAMPLE_BYTES.to_vec(),
+        ));
+        assert_matches!(
+            AnyDatetime::deserialize(deserializer),
+            Ok(AnyDatetime::OffsetDatetime(OffsetDatetime::EXAMPLE))
+        );
+
+        let deserializer = ValueDeserializer::new(ParsedValue::LocalDatetime(
+            LocalDatetime::EXAMPLE_BYTES.to_vec(),
+        ));
+        assert_matches!(
+            AnyDatetime::deserialize(deserializer),
+            Ok(AnyDatetime::LocalDatetime(LocalDatetime::EXAMPLE))
+        );
+
+        let deserializer =
+            ValueDeserializer::new(ParsedValue::LocalDate(LocalDate::EXAMPLE_BYTES.to_vec()));
+        assert_matches!(
+            AnyDatetime::deserialize(deserializer),
+            Ok(AnyDatetime::LocalDate(LocalDate::EXAMPLE))
+        );
+
+        let deserializer =
+            ValueDeserializer::new(ParsedValue::LocalTime(LocalTime::EXAMPLE_BYTES.to_vec()));
+        assert_matches!(
+            AnyDatetime::deserialize(deserializer),
+            Ok(AnyDatetime::LocalTime(LocalTime::EXAMPLE))
+        );
+
+        let deserializer = ValueDeserializer::new(ParsedValue::String("hello".into()));
+        assert_matches!(
+            AnyDatetime::deserialize(deserializer),
+            Err(Error(ErrorKind::InvalidType(..)))
+        );
     }
 
     #[test]
@@ -2185,29 +4896,29 @@ mod tests {
         assert_matches!(Enum::deserialize(deserializer), Ok(Enum::VariantA));
 
         let deserializer = ValueDeserializer::new(ParsedValue::Table(hashmap! {
-            "VariantB".into() => ParsedValue::Integer(b"123".to_vec()),
+            "VariantB".into() => ParsedValue::Integer(b"123".to_vec(), 0),
         }));
         assert_matches!(Enum::deserialize(deserializer), Ok(Enum::VariantB(123)));
 
         let deserializer = ValueDeserializer::new(ParsedValue::UndefinedTable(hashmap! {
-            "VariantB".into() => ParsedValue::Integer(b"123".to_vec()),
+            "VariantB".into() => ParsedValue::Integer(b"123".to_vec(), 0),
         }));
         assert_matches!(Enum::deserialize(deserializer), Ok(Enum::VariantB(123)));
 
         let deserializer = ValueDeserializer::new(ParsedValue::DottedKeyTable(hashmap! {
-            "VariantB".into() => ParsedValue::Integer(b"123".to_vec()),
+            "VariantB".into() => ParsedValue::Integer(b"123".to_vec(), 0),
         }));
         assert_matches!(Enum::deserialize(deserializer), Ok(Enum::VariantB(123)));
 
         let deserializer = ValueDeserializer::new(ParsedValue::InlineTable(hashmap! {
-            "VariantB".into() => ParsedValue::Integer(b"123".to_vec()),
+            "VariantB".into() => ParsedValue::Integer(b"123".to_vec(), 0),
         }));
         assert_matches!(Enum::deserialize(deserializer), Ok(Enum::VariantB(123)));
 
         let deserializer = ValueDeserializer::new(ParsedValue::Table(hashmap! {
             "VariantC".into() => ParsedValue::InlineTable(hashmap! {
-                "a".into() => ParsedValue::Integer(b"123".to_vec()),
-                "b".into() => ParsedValue::Integer(b"456".to_vec()),
+                "a".into() => ParsedValue::Integer(b"123".to_vec(), 0),
+                "b".into() => ParsedValue::Integer(b"456".to_vec(), 0),
             }),
         }));
         assert_matches!(
@@ -2217,8 +4928,8 @@ mod tests {
 
         let deserializer = ValueDeserializer::new(ParsedValue::UndefinedTable(hashmap! {
             "VariantC".into() => ParsedValue::InlineTable(hashmap! {
-                "a".into() => ParsedValue::Integer(b"123".to_vec()),
-                "b".into() => ParsedValue::Integer(b"456".to_vec()),
+                "a".into() => ParsedValue::Integer(b"123".to_vec(), 0),
+                "b".into() => ParsedValue::Integer(b"456".to_vec(), 0),
             }),
         }));
         assert_matches!(
@@ -2228,8 +4939,8 @@ mod tests {
 
         let deserializer = ValueDeserializer::new(ParsedValue::DottedKeyTable(hashmap! {
             "VariantC".into() => ParsedValue::InlineTable(hashmap! {
-                "a".into() => ParsedValue::Integer(b"123".to_vec()),
-                "b".into() => ParsedValue::Integer(b"456".to_vec()),
+                "a".into() => ParsedValue::Integer(b"123".to_vec(), 0),
+                "b".into() => ParsedValue::Integer(b"456".to_vec(), 0),
             }),
         }));
         assert_matches!(
@@ -2239,8 +4950,8 @@ mod tests {
 
         let deserializer = ValueDeserializer::new(ParsedValue::InlineTable(hashmap! {
             "VariantC".into() => ParsedValue::InlineTable(hashmap! {
-                "a".into() => ParsedValue::Integer(b"123".to_vec()),
-                "b".into() => ParsedValue::Integer(b"456".to_vec()),
+                "a".into() => ParsedValue::Integer(b"123".to_vec(), 0),
+                "b".into() => ParsedValue::Integer(b"456".to_vec(), 0),
             }),
         }));
         assert_matches!(
@@ -2249,7 +4960,7 @@ mod tests {
         );
 
         let deserializer = ValueDeserializer::new(ParsedValue::Table(hashmap! {
-            "VariantA".into() => ParsedValue::Integer(b"123".to_vec()),
+            "VariantA".into() => ParsedValue::Integer(b"123".to_vec(), 0),
         }));
         assert_matches!(
             Enum::deserialize(deserializer),
@@ -2258,8 +4969,8 @@ mod tests {
 
         let deserializer = ValueDeserializer::new(ParsedValue::Table(hashmap! {
             "VariantB".into() => ParsedValue::InlineTable(hashmap! {
-                "a".into() => ParsedValue::Integer(b"123".to_vec()),
-                "b".into() => ParsedValue::Integer(b"456".to_vec()),
+                "a".into() => ParsedValue::Integer(b"123".to_vec(), 0),
+                "b".into() => ParsedValue::Integer(b"456".to_vec(), 0),
             }),
         }));
         assert_matches!(
@@ -2270,14 +4981,23 @@ mod tests {
         let deserializer = ValueDeserializer::new(ParsedValue::String("VariantC".into()));
         assert_matches!(
             Enum::deserialize(deserializer),
-            Err(Error(ErrorKind::InvalidType(..)))
+            Err(Error(ErrorKind::InvalidType(t, e)))
+                if &*t == r#"string "VariantC""#
+                    && &*e == "a table with a single key for a struct variant"
         );
 
-        let deserializer = ValueDeserializer::new(ParsedValue::Integer(b"123".to_vec()));
+        let deserializer = ValueDeserializer::new(ParsedValue::Integer(b"123".to_vec(), 0));
         assert_matches!(
             Enum::deserialize(deserializer),
-            Err(Error(ErrorKind::InvalidType(..)))
+            Err(Error(ErrorKind::InvalidType(t, e)))
+                if &*t == "integer"
+                    && &*e == "a string for a unit variant or a table with a single key for any other variant"
         );
+
+        let deserializer = ValueDeserializer::new(ParsedValue::InlineTable(hashmap! {
+            "VariantA".into() => ParsedValue::InlineTable(hashmap! {}),
+        }));
+        assert_matches!(Enum::deserialize(deserializer), Ok(Enum::VariantA));
     }
 
     #[test]
@@ -2313,9 +5033,9 @@ mod tests {
     #[test]
     fn seq_access_next_element() {
         let array = vec![
-            ParsedValue::Integer(b"123".to_vec()),
-            ParsedValue::Integer(b"456".to_vec()),
-            ParsedValue::Integer(b"789".to_vec()),
+            ParsedValue::Integer(b"123".to_vec(), 0),
+            ParsedValue::Integer(b"456".to_vec(), 0),
+            ParsedValue::Integer(b"789".to_vec(), 0),
         ];
         let mut seq = SeqAccess::new(array);
 
@@ -2325,9 +5045,9 @@ mod tests {
         assert_matches!(seq.next_element(), Ok(None::<i32>));
 
         let array = vec![
-            hashmap! { "abc".into() => ParsedValue::Integer(b"123".to_vec()) },
-            hashmap! { "def".into() => ParsedValue::Integer(b"456".to_vec()) },
-            hashmap! { "ghi".into() => ParsedValue::Integer(b"789".to_vec()) },
+            hashmap! { "abc".into() => ParsedValue::Integer(b"123".to_vec(), 0) },
+            hashmap! { "def".into() => ParsedValue::Integer(b"456".to_vec(), 0) },
+            hashmap! { "ghi".into() => ParsedValue::Integer(b"789".to_vec(), 0) },
         ];
         let mut seq = SeqAccess::new(array);
 
@@ -2349,18 +5069,18 @@ mod tests {
     #[test]
     fn seq_access_size_hint() {
         let array = vec![
-            ParsedValue::Integer(b"123".to_vec()),
-            ParsedValue::Integer(b"456".to_vec()),
-            ParsedValue::Integer(b"789".to_vec()),
+            ParsedValue::Integer(b"123".to_vec(), 0),
+            ParsedValue::Integer(b"456".to_vec(), 0),
+            ParsedValue::Integer(b"789".to_vec(), 0),
         ];
         let seq = SeqAccess::new(array);
 
         assert_eq!(seq.size_hint(), Some(3));
 
         let array = vec![
-            hashmap! { "abc".into() => ParsedValue::Integer(b"123".to_vec()) },
-            hashmap! { "def".into() => ParsedValue::Integer(b"456".to_vec()) },
-            hashmap! { "ghi".into() => ParsedValue::Integer(b"789".to_vec()) },
+            hashmap! { "abc".into() => ParsedValue::Integer(b"123".to_vec(), 0) },
+            hashmap! { "def".into() => ParsedValue::Integer(b"456".to_vec(), 0) },
+            hashmap! { "ghi".into() => ParsedValue::Integer(b"789".to_vec(), 0) },
         ];
         let seq = SeqAccess::new(array);
 
@@ -2370,9 +5090,9 @@ mod tests {
     #[test]
     fn map_access_new() {
         let table = hashmap! {
-            "abc".into() => ParsedValue::Integer(b"123".to_vec()),
-            "def".into() => ParsedValue::Integer(b"456".to_vec()),
-            "ghi".into() => ParsedValue::Integer(b"789".to_vec()),
+            "abc".into() => ParsedValue::Integer(b"123".to_vec(), 0),
+            "def".into() => ParsedValue::Integer(b"456".to_vec(), 0),
+            "ghi".into() => ParsedValue::Integer(b"789".to_vec(), 0),
         };
         let map = MapAccess::new(table.clone());
 
@@ -2385,9 +5105,9 @@ mod tests {
     #[test]
     fn map_access_next_key_value() {
         let table = hashmap! {
-            "abc".into() => ParsedValue::Integer(b"123".to_vec()),
-            "def".into() => ParsedValue::Integer(b"456".to_vec()),
-            "ghi".into() => ParsedValue::Integer(b"789".to_vec()),
+            "abc".into() => ParsedValue::Integer(b"123".to_vec(), 0),
+            "def".into() => ParsedValue::Integer(b"456".to_vec(), 0),
+            "ghi".into() => ParsedValue::Integer(b"789".to_vec(), 0),
         };
         let mut map = MapAccess::new(table);
 
@@ -2415,9 +5135,9 @@ mod tests {
     #[test]
     fn map_access_next_entry() {
         let table = hashmap! {
-            "abc".into() => ParsedValue::Integer(b"123".to_vec()),
-            "def".into() => ParsedValue::Integer(b"456".to_vec()),
-            "ghi".into() => ParsedValue::Integer(b"789".to_vec()),
+            "abc".into() => ParsedValue::Integer(b"123".to_vec(), 0),
+            "def".into() => ParsedValue::Integer(b"456".to_vec(), 0),
+            "ghi".into() => ParsedValue::Integer(b"789".to_vec(), 0),
         };
         let mut map = MapAccess::new(table);
 
@@ -2439,9 +5159,9 @@ mod tests {
     #[test]
     fn map_access_size_hint() {
         let table = hashmap! {
-            "abc".into() => ParsedValue::Integer(b"123".to_vec()),
-            "def".into() => ParsedValue::Integer(b"456".to_vec()),
-            "ghi".into() => ParsedValue::Integer(b"789".to_vec()),
+            "abc".into() => ParsedValue::Integer(b"123".to_vec(), 0),
+            "def".into() => ParsedValue::Integer(b"456".to_vec(), 0),
+            "ghi".into() => ParsedValue::Integer(b"789".to_vec(), 0),
         };
         let map = MapAccess::new(table);
         assert_eq!(map.size_hint(), Some(3));
@@ -2605,16 +5325,16 @@ mod tests {
     #[test]
     fn enum_access_new() {
         let table = hashmap! {
-            "Variant".into() => ParsedValue::Integer(b"123".to_vec()),
+            "Variant".into() => ParsedValue::Integer(b"123".to_vec(), 0),
         };
         let enum_ = EnumAccess::new(table).unwrap();
         assert_eq!(enum_.variant, "Variant");
-        assert_eq!(enum_.value, ParsedValue::Integer(b"123".to_vec()));
+        assert_eq!(enum_.value, ParsedValue::Integer(b"123".to_vec(), 0));
 
         let table = hashmap! {
-            "abc".into() => ParsedValue::Integer(b"123".to_vec()),
-            "def".into() => ParsedValue::Integer(b"456".to_vec()),
-            "ghi".into() => ParsedValue::Integer(b"789".to_vec()),
+            "abc".into() => ParsedValue::Integer(b"123".to_vec(), 0),
+            "def".into() => ParsedValue::Integer(b"456".to_vec(), 0),
+            "ghi".into() => ParsedValue::Integer(b"789".to_vec(), 0),
         };
         assert_matches!(
             EnumAccess::new(table),
@@ -2624,7 +5344,8 @@ mod tests {
         let table = hashmap! {};
         assert_matches!(
             EnumAccess::new(table),
-            Err(Error(ErrorKind::InvalidValue(..)))
+            Err(Error(ErrorKind::InvalidValue(v, e)))
+                if &*v == "empty table" && &*e == "a table with a single key for an enum variant"
         );
     }
 
@@ -2637,15 +5358,15 @@ mod tests {
         assert_matches!(access.variant::<String>(), Ok((v, _)) if v == "VariantA");
 
         let table = hashmap! {
-            "VariantB".into() => ParsedValue::Integer(b"123".to_vec()),
+            "VariantB".into() => ParsedValue::Integer(b"123".to_vec(), 0),
         };
         let access = EnumAccess::new(table).unwrap();
         assert_matches!(access.variant::<String>(), Ok((v, _)) if v == "VariantB");
 
         let table = hashmap! {
             "VariantC".into() => ParsedValue::Table(hashmap! {
-                "a".into() => ParsedValue::Integer(b"123".to_vec()),
-                "b".into() => ParsedValue::Integer(b"456".to_vec()),
+                "a".into() => ParsedValue::Integer(b"123".to_vec(), 0),
+                "b".into() => ParsedValue::Integer(b"456".to_vec(), 0),
             }),
         };
         let access = EnumAccess::new(table).unwrap();
@@ -2661,18 +5382,19 @@ mod tests {
         access.unit_variant().unwrap();
 
         let table = hashmap! {
-            "VariantB".into() => ParsedValue::Integer(b"123".to_vec()),
+            "VariantB".into() => ParsedValue::Integer(b"123".to_vec(), 0),
         };
         let access = EnumAccess::new(table).unwrap();
         assert_matches!(
             access.unit_variant(),
-            Err(Error(ErrorKind::InvalidType(..)))
+            Err(Error(ErrorKind::InvalidType(t, e)))
+                if &*t == "integer" && &*e == "a string or an empty table for a unit variant"
         );
 
         let table = hashmap! {
             "VariantC".into() => ParsedValue::Array(vec![
-                ParsedValue::Integer(b"123".to_vec()),
-                ParsedValue::Integer(b"456".to_vec()),
+                ParsedValue::Integer(b"123".to_vec(), 0),
+                ParsedValue::Integer(b"456".to_vec(), 0),
             ]),
         };
         let access = EnumAccess::new(table).unwrap();
@@ -2683,8 +5405,8 @@ mod tests {
 
         let table = hashmap! {
             "VariantD".into() => ParsedValue::Table(hashmap! {
-                "a".into() => ParsedValue::Integer(b"123".to_vec()),
-                "b".into() => ParsedValue::Integer(b"456".to_vec()),
+                "a".into() => ParsedValue::Integer(b"123".to_vec(), 0),
+                "b".into() => ParsedValue::Integer(b"456".to_vec(), 0),
             }),
         };
         let access = EnumAccess::new(table).unwrap();
@@ -2706,15 +5428,15 @@ mod tests {
         );
 
         let table = hashmap! {
-            "VariantB".into() => ParsedValue::Integer(b"123".to_vec()),
+            "VariantB".into() => ParsedValue::Integer(b"123".to_vec(), 0),
         };
         let access = EnumAccess::new(table).unwrap();
         assert_matches!(access.newtype_variant::<i32>(), Ok(123));
 
         let table = hashmap! {
             "VariantC".into() => ParsedValue::Array(vec![
-                ParsedValue::Integer(b"123".to_vec()),
-                ParsedValue::Integer(b"456".to_vec()),
+                ParsedValue::Integer(b"123".to_vec(), 0),
+                ParsedValue::Integer(b"456".to_vec(), 0),
             ]),
         };
         let access = EnumAccess::new(table).unwrap();
@@ -2725,8 +5447,8 @@ mod tests {
 
         let table = hashmap! {
             "VariantD".into() => ParsedValue::Table(hashmap! {
-                "a".into() => ParsedValue::Integer(b"123".to_vec()),
-                "b".into() => ParsedValue::Integer(b"456".to_vec()),
+                "a".into() => ParsedValue::Integer(b"123".to_vec(), 0),
+                "b".into() => ParsedValue::Integer(b"456".to_vec(), 0),
             }),
         };
         let access = EnumAccess::new(table).unwrap();
@@ -2769,7 +5491,7 @@ mod tests {
         );
 
         let table = hashmap! {
-            "VariantB".into() => ParsedValue::Integer(b"123".to_vec()),
+            "VariantB".into() => ParsedValue::Integer(b"123".to_vec(), 0),
         };
         let access = EnumAccess::new(table).unwrap();
         assert_matches!(
@@ -2779,8 +5501,8 @@ mod tests {
 
         let table = hashmap! {
             "VariantC".into() => ParsedValue::Array(vec![
-                ParsedValue::Integer(b"123".to_vec()),
-                ParsedValue::Integer(b"456".to_vec()),
+                ParsedValue::Integer(b"123".to_vec(), 0),
+                ParsedValue::Integer(b"456".to_vec(), 0),
             ]),
         };
         let access = EnumAccess::new(table).unwrap();
@@ -2788,8 +5510,8 @@ mod tests {
 
         let table = hashmap! {
             "VariantD".into() => ParsedValue::Table(hashmap! {
-                "a".into() => ParsedValue::Integer(b"123".to_vec()),
-                "b".into() => ParsedValue::Integer(b"456".to_vec()),
+                "a".into() => ParsedValue::Integer(b"123".to_vec(), 0),
+                "b".into() => ParsedValue::Integer(b"456".to_vec(), 0),
             }),
         };
         let access = EnumAccess::new(table).unwrap();
@@ -2829,7 +5551,7 @@ mod tests {
         assert_matches!(access.struct_variant(&[], Visitor), Ok(m) if m.is_empty());
 
         let table = hashmap! {
-            "VariantB".into() => ParsedValue::Integer(b"123".to_vec()),
+            "VariantB".into() => ParsedValue::Integer(b"123".to_vec(), 0),
         };
         let access = EnumAccess::new(table).unwrap();
         assert_matches!(
@@ -2839,8 +5561,8 @@ mod tests {
 
         let table = hashmap! {
             "VariantC".into() => ParsedValue::Array(vec![
-                ParsedValue::Integer(b"123".to_vec()),
-                ParsedValue::Integer(b"456".to_vec()),
+                ParsedValue::Integer(b"123".to_vec(), 0),
+                ParsedValue::Integer(b"456".to_vec(), 0),
             ]),
         };
         let access = EnumAccess::new(table).unwrap();
@@ -2851,8 +5573,8 @@ mod tests {
 
         let table = hashmap! {
             "VariantD".into() => ParsedValue::Table(hashmap! {
-                "a".into() => ParsedValue::Integer(b"123".to_vec()),
-                "b".into() => ParsedValue::Integer(b"456".to_vec()),
+                "a".into() => ParsedValue::Integer(b"123".to_vec(), 0),
+                "b".into() => ParsedValue::Integer(b"456".to_vec(), 0),
             }),
         };
         let access = EnumAccess::new(table).unwrap();
@@ -2865,23 +5587,80 @@ mod tests {
         );
     }
 
+    #[test]
+    fn unit_variant_access() {
+        let access = UnitVariantAccess {
+            variant: "Variant".into(),
+        };
+        let (variant, access) = access.variant::<String>().unwrap();
+        assert_eq!(variant, "Variant");
+        access.unit_variant().unwrap();
+
+        let access = UnitVariantAccess {
+            variant: "Variant".into(),
+        };
+        assert_matches!(
+            access.newtype_variant::<i32>(),
+            Err(Error(ErrorKind::InvalidType(t, e)))
+                if &*t == r#"string "Variant""#
+                    && &*e == "a table with a single key for a newtype variant"
+        );
+
+        let access = UnitVariantAccess {
+            variant: "Variant".into(),
+        };
+        assert_matches!(
+            access.tuple_variant(2, de::IgnoredAny),
+            Err(Error(ErrorKind::InvalidType(t, e)))
+                if &*t == r#"string "Variant""#
+                    && &*e == "a table with a single key for a tuple variant"
+        );
+
+        let access = UnitVariantAccess {
+            variant: "Variant".into(),
+        };
+        assert_matches!(
+            access.struct_variant(&["a"], de::IgnoredAny),
+            Err(Error(ErrorKind::InvalidType(t, e)))
+                if &*t == r#"string "Variant""#
+                    && &*e == "a table with a single key for a struct variant"
+        );
+    }
+
     #[test]
     fn test_parse_integer() {
         let bytes = b"123";
-        assert_matches!(parse_integer::<i32>(bytes), Ok(123));
+        assert_matches!(parse_integer::<i32>(bytes, false), Ok(123));
 
         let bytes = b"0123"; // Leading zeros are handled in the parser
-        assert_matches!(parse_integer::<i32>(bytes), Ok(123));
+        assert_matches!(parse_integer::<i32>(bytes, false), Ok(123));
 
         let bytes = b"1_2_3"; // Underscores are stripped in the parser
         assert_matches!(
-            parse_integer::<i32>(bytes),
+            parse_integer::<i32>(bytes, false),
+            Err(Error(ErrorKind::InvalidInteger(..)))
+        );
+
+        let bytes = b"123.0";
+        assert_matches!(
+            parse_integer::<i32>(bytes, false),
+            Err(Error(ErrorKind::InvalidInteger(lit, _))) if &*lit == "123.0"
+        );
+
+        let bytes = b"300";
+        assert_matches!(
+            parse_integer::<u8>(bytes, false),
             Err(Error(ErrorKind::InvalidInteger(..)))
         );
+        assert_matches!(parse_integer::<u8>(bytes, true), Ok(u8::MAX));
+
+        let bytes = b"-300";
+        assert_matches!(parse_integer::<i8>(bytes, true), Ok(i8::MIN));
+        assert_matches!(parse_integer::<u8>(bytes, true), Ok(u8::MIN));
 
         let bytes = b"123.0";
         assert_matches!(
-            parse_integer::<i32>(bytes),
+            parse_integer::<i32>(bytes, true),
             Err(Error(ErrorKind::InvalidInteger(..)))
         );
     }
@@ -2905,6 +5684,12 @@ mod tests {
             parse_binary::<i32>(bytes),
             Err(Error(ErrorKind::InvalidInteger(..)))
         );
+
+        let bytes = b"102";
+        assert_matches!(
+            parse_binary::<i32>(bytes),
+            Err(Error(ErrorKind::InvalidInteger(..)))
+        );
     }
 
     #[test]
@@ -2926,6 +5711,12 @@ mod tests {
             parse_octal::<i32>(bytes),
             Err(Error(ErrorKind::InvalidInteger(..)))
         );
+
+        let bytes = b"178";
+        assert_matches!(
+            parse_octal::<i32>(bytes),
+            Err(Error(ErrorKind::InvalidInteger(..)))
+        );
     }
 
     #[test]
@@ -2936,6 +5727,9 @@ mod tests {
         let bytes = b"0123"; // Leading zeros are ok because we already have a leading 0x
         assert_matches!(parse_hexadecimal::<i32>(bytes), Ok(291));
 
+        let bytes = b"DEADbeef"; // Mixed case is ok
+        assert_matches!(parse_hexadecimal::<i64>(bytes), Ok(0xdead_beef));
+
         let bytes = b"1_2_3"; // Underscores are stripped in the parser
         assert_matches!(
             parse_hexadecimal::<i32>(bytes),
@@ -2945,7 +5739,7 @@ mod tests {
         let bytes = b"123.0";
         assert_matches!(
             parse_hexadecimal::<i32>(bytes),
-            Err(Error(ErrorKind::InvalidInteger(..)))
+            Err(Error(ErrorKind::InvalidInteger(lit, _))) if &*lit == "0x123.0"
         );
     }
 
@@ -2954,6 +5748,21 @@ mod tests {
         let bytes = b"123.456";
         assert_matches!(parse_float::<f64>(bytes), Ok(123.456));
 
+        let bytes = b"+1.5";
+        assert_matches!(parse_float::<f64>(bytes), Ok(1.5));
+
+        let bytes = b"-1.5";
+        assert_matches!(parse_float::<f64>(bytes), Ok(-1.5));
+
+        let bytes = b"+0.0";
+        assert_matches!(parse_float::<f64>(bytes), Ok(f) if f == 0.0 && f.is_sign_positive());
+
+        let bytes = b"-0.0";
+        assert_matches!(parse_float::<f64>(bytes), Ok(f) if f == 0.0 && f.is_sign_negative());
+
+        let bytes = b"-0.0";
+        assert_matches!(parse_float::<f32>(bytes), Ok(f) if f == 0.0 && f.is_sign_negative());
+
         let bytes = b"123e0";
         assert_matches!(parse_float::<f64>(bytes), Ok(123.0));
 
@@ -3011,7 +5820,7 @@ mod tests {
         let bytes = b"123.0.0";
         assert_matches!(
             parse_float::<f32>(bytes),
-            Err(Error(ErrorKind::InvalidFloat(..)))
+            Err(Error(ErrorKind::InvalidFloat(lit, _))) if &*lit == "123.0.0"
         );
     }
 
@@ -1,10 +1,17 @@
+use std::borrow::Cow;
 use std::collections::hash_map::Entry;
+use std::ops::Range;
 use std::{fmt, str};
 
 use serde::de;
 
 use super::error::{ErrorKind, Result};
+#[cfg(feature = "datetime")]
+use super::warning::WarningKind;
+use super::warning::WarningSink;
 use super::{reader, Reader};
+#[cfg(feature = "datetime")]
+use crate::value::{Datetime, LocalDate, LocalTime, Offset};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub(super) enum SpecialFloat {
@@ -56,11 +63,11 @@ impl From<Type> for de::Unexpected<'_> {
 }
 
 #[derive(Debug, Clone, PartialEq)]
-pub(super) enum Value {
-    // String; any escape sequences are already parsed
-    String(String),
-    // Decimal integer
-    Integer(Vec<u8>),
+pub(super) enum Value<'de> {
+    // String; any escape sequences are already parsed. Borrowed from the input if possible
+    String(Cow<'de, str>),
+    // Decimal integer (digits, byte offset in the input)
+    Integer(Vec<u8>, usize),
     // Binary integer (without the 0b prefix)
     BinaryInt(Vec<u8>),
     // Octal integer (without the 0o prefix)
@@ -88,24 +95,24 @@ pub(super) enum Value {
     // Just a regular inline array
     Array(Vec<Self>),
     // Table defined by a table header. This is immutable aside from being able to add subtables
-    Table(Table),
+    Table(Table<'de>),
     // Super table created when parsing a subtable header. This can still be explicitly defined
     // later turning it into a `Table`
-    UndefinedTable(Table),
+    UndefinedTable(Table<'de>),
     // A table defined by dotted keys. This can be freely added to by other dotted keys
-    DottedKeyTable(Table),
+    DottedKeyTable(Table<'de>),
     // Inline table
-    InlineTable(Table),
+    InlineTable(Table<'de>),
     // Array of tables
-    ArrayOfTables(Vec<Table>),
+    ArrayOfTables(Vec<Table<'de>>),
 }
 
-impl Value {
+impl Value<'_> {
     #[inline]
     pub const fn typ(&self) -> Type {
         match *self {
             Self::String(_) => Type::String,
-            Self::Integer(_) | Self::BinaryInt(_) | Self::OctalInt(_) | Self::HexInt(_) => {
+            Self::Integer(..) | Self::BinaryInt(_) | Self::OctalInt(_) | Self::HexInt(_) => {
                 Type::Integer
             }
             Self::Float(_) | Self::SpecialFloat(_) => Type::Float,
@@ -124,7 +131,7 @@ impl Value {
     }
 }
 
-pub(super) type Table = std::collections::HashMap<String, Value>;
+pub(super) type Table<'de> = std::collections::HashMap<String, Value<'de>>;
 
 #[derive(Debug)]
 struct Key {
@@ -149,16 +156,26 @@ enum HeaderKind {
 }
 
 #[derive(Debug)]
-enum Line {
+enum Line<'de> {
     TableHeader { key: Key, kind: HeaderKind },
-    KeyValuePair { key: Key, value: Value },
+    KeyValuePair { key: Key, value: Value<'de> },
     Empty, // Blank line or comment
 }
 
+/// The default maximum length of an integer or float literal.
+pub(super) const DEFAULT_MAX_NUMBER_LENGTH: usize = 512;
+
 #[derive(Debug)]
 pub(super) struct Parser<'de> {
     reader: Reader<'de>,
     line: &'de [u8],
+    pub max_number_length: usize,
+    pub allow_inline_table_trailing_comma: bool,
+    #[cfg(feature = "datetime")]
+    pub validate_datetimes: bool,
+    #[cfg(feature = "datetime")]
+    pub reject_excess_precision: bool,
+    pub on_warning: WarningSink,
 }
 
 impl<'de> Parser<'de> {
@@ -168,6 +185,13 @@ impl<'de> Parser<'de> {
         Self {
             reader: Reader::from_str(str),
             line: b"",
+            max_number_length: DEFAULT_MAX_NUMBER_LENGTH,
+            allow_inline_table_trailing_comma: false,
+            #[cfg(feature = "datetime")]
+            validate_datetimes: false,
+            #[cfg(feature = "datetime")]
+            reject_excess_precision: false,
+            on_warning: WarningSink::default(),
         }
     }
 
@@ -177,13 +201,35 @@ impl<'de> Parser<'de> {
         Self {
             reader: Reader::from_slice(bytes),
             line: b"",
+            max_number_length: DEFAULT_MAX_NUMBER_LENGTH,
+            allow_inline_table_trailing_comma: false,
+            #[cfg(feature = "datetime")]
+            validate_datetimes: false,
+            #[cfg(feature = "datetime")]
+            reject_excess_precision: false,
+            on_warning: WarningSink::default(),
         }
     }
 }
 
-impl Parser<'_> {
-    pub fn parse(&mut self) -> Result<Value> {
+impl<'de> Parser<'de> {
+    pub fn parse(&mut self) -> Result<Value<'de>> {
+        self.parse_with(|_| Ok(()), |_, result| result)
+    }
+
+    // Parses the document one statement (a table header or key/value pair, including any
+    // following comment) at a time. Each value is passed to `check_value` before it is inserted,
+    // and the result of each statement is then passed to `on_statement` along with its span in
+    // the input. If `on_statement` returns the error parsing stops, otherwise the rest of the
+    // statement's line is skipped and parsing continues with the next line. Keys following an
+    // invalid table header are still parsed, but are discarded.
+    pub fn parse_with(
+        &mut self,
+        check_value: impl Fn(&Value<'de>) -> Result<()>,
+        mut on_statement: impl FnMut(Range<usize>, Result<()>) -> Result<()>,
+    ) -> Result<Value<'de>> {
         let mut root = Table::with_capacity(10);
+        let mut discard = Table::new();
 
         // The currently opened table
         let mut table = &mut root;
@@ -193,47 +239,109 @@ impl Parser<'_> {
             name: "root table".to_string(),
         };
 
-        while let Some(line) = self.parse_line()? {
-            match line {
-                Line::TableHeader { key, kind } => {
-                    let parent = root
-                        .get_subtable(&key.path)
-                        .ok_or_else(|| ErrorKind::InvalidTableHeader(key.to_string().into()))?;
-
-                    table = match kind {
+        loop {
+            let start = self.reader.offset();
+            let result = match self.parse_line() {
+                Ok(None) => break,
+                Ok(Some(Line::TableHeader { key, kind })) => {
+                    let subtable = root.get_subtable(&key.path).and_then(|parent| match kind {
                         HeaderKind::Table => parent.insert_table(key.name.clone()),
                         HeaderKind::Array => parent.append_array_of_tables(key.name.clone()),
+                    });
+
+                    if let Some(subtable) = subtable {
+                        table = subtable;
+                        table_path = key;
+                        Ok(())
+                    } else {
+                        discard.clear();
+                        table = &mut discard;
+                        Err(ErrorKind::InvalidTableHeader(key.to_string().into()).into())
                     }
-                    .ok_or_else(|| ErrorKind::InvalidTableHeader(key.to_string().into()))?;
-
-                    table_path = key;
                 }
-                Line::KeyValuePair { key, value } => {
-                    let subtable = table.get_dotted_subtable(&key.path, true).ok_or_else(|| {
-                        ErrorKind::InvalidKeyPath(
-                            key.to_string().into(),
-                            table_path.to_string().into(),
-                        )
-                    })?;
-
-                    // Check if the key is already present
-                    if subtable.contains_key(&key.name) {
-                        return Err(ErrorKind::DuplicateKey(
+                Ok(Some(Line::KeyValuePair { key, value })) => check_value(&value).and_then(|()| {
+                    match table.get_dotted_subtable(&key.path, true) {
+                        None => Err(ErrorKind::InvalidKeyPath(
                             key.to_string().into(),
                             table_path.to_string().into(),
                         )
-                        .into());
+                        .into()),
+                        // Check if the key is already present
+                        Some(subtable) if subtable.contains_key(&key.name) => {
+                            Err(ErrorKind::DuplicateKey(
+                                key.to_string().into(),
+                                table_path.to_string().into(),
+                            )
+                            .into())
+                        }
+                        Some(subtable) => {
+                            subtable.insert(key.name, value);
+                            Ok(())
+                        }
                     }
-                    subtable.insert(key.name.clone(), value);
-                }
-                Line::Empty => {}
-            }
+                }),
+                Ok(Some(Line::Empty)) => Ok(()),
+                Err(err) => Err(err),
+            };
+
+            on_statement(start..self.reader.line_end(), result)?;
         }
 
         Ok(Value::Table(root))
     }
 
-    fn parse_line(&mut self) -> Result<Option<Line>> {
+    pub fn parse_lone_value(&mut self) -> Result<Value<'de>> {
+        // Skip any leading empty lines or comments
+        loop {
+            self.next_line()
+                .ok_or_else(|| ErrorKind::ExpectedToken("a value".into()))?;
+            self.skip_whitespace();
+            self.skip_comment()?;
+            if !self.line.is_empty() {
+                break;
+            }
+        }
+
+        let value = self.parse_value()?;
+        self.end_line()?;
+
+        // Anything after the value should be empty lines or comments
+        while self.next_line().is_some() {
+            self.end_line()?;
+        }
+
+        Ok(value)
+    }
+
+    // Parses the whole input as a single dotted key, returning each of its keys
+    pub fn parse_key_path(&mut self) -> Result<Vec<String>> {
+        self.next_line()
+            .ok_or_else(|| ErrorKind::ExpectedToken("key".into()))?;
+        self.skip_whitespace();
+        let Key { mut path, name } = self.parse_dotted_key()?;
+
+        if !self.line.is_empty() || self.next_line().is_some() {
+            return Err(self.expected_token("end of key").into());
+        }
+
+        path.push(name);
+        Ok(path)
+    }
+
+    pub fn end(&mut self) -> Result<()> {
+        loop {
+            self.skip_whitespace();
+            self.skip_comment()?;
+            if !self.line.is_empty() {
+                return Err(ErrorKind::TrailingData(self.reader.offset_of(self.line)).into());
+            }
+            if self.next_line().is_none() {
+                return Ok(());
+            }
+        }
+    }
+
+    fn parse_line(&mut self) -> Result<Option<Line<'de>>> {
         if self.next_line().is_none() {
             return Ok(None);
         }
@@ -268,20 +376,22 @@ impl Parser<'_> {
             _ => Line::Empty,
         };
 
+        self.end_line()?;
+
+        Ok(Some(result))
+    }
+
+    fn end_line(&mut self) -> Result<()> {
         // Expect whitespace/comments after a line
         self.skip_whitespace();
         self.skip_comment()?;
 
         // Anything left unparsed at this point is unexpected/illegal
-        if let Some(&b) = self.line.first() {
-            return Err(if b.is_toml_legal() {
-                ErrorKind::ExpectedToken("end of line".into()).into()
-            } else {
-                ErrorKind::IllegalChar(b).into()
-            });
+        if !self.line.is_empty() {
+            return Err(self.expected_token("end of line").into());
         }
 
-        Ok(Some(result))
+        Ok(())
     }
 
     fn parse_array_header(&mut self) -> Result<Key> {
@@ -293,7 +403,7 @@ impl Parser<'_> {
             self.line = rest;
             Ok(key)
         } else {
-            Err(ErrorKind::ExpectedToken("]] after dotted key".into()).into())
+            Err(self.expected_token("]] after dotted key").into())
         }
     }
 
@@ -306,18 +416,18 @@ impl Parser<'_> {
             self.line = rest;
             Ok(key)
         } else {
-            Err(ErrorKind::ExpectedToken("] after dotted key".into()).into())
+            Err(self.expected_token("] after dotted key").into())
         }
     }
 
-    fn parse_key_value_pair(&mut self) -> Result<(Key, Value)> {
+    fn parse_key_value_pair(&mut self) -> Result<(Key, Value<'de>)> {
         let path = self.parse_dotted_key()?;
 
         // Whitespace should already have been consumed by parse_dotted_key looking for another '.'
         if let Some(rest) = self.line.strip_prefix(b"=") {
             self.line = rest;
         } else {
-            return Err(ErrorKind::ExpectedToken("= after key".into()).into());
+            return Err(self.expected_token("= after key").into());
         }
         self.skip_whitespace();
 
@@ -351,11 +461,11 @@ impl Parser<'_> {
             }
             [b'"', ref rest @ ..] => {
                 self.line = rest;
-                self.parse_basic_str()
+                self.parse_basic_str().map(Cow::into_owned)
             }
             [b'\'', ref rest @ ..] => {
                 self.line = rest;
-                self.parse_literal_str()
+                self.parse_literal_str().map(Cow::into_owned)
             }
             _ => self.parse_bare_key(),
         }
@@ -370,24 +480,31 @@ impl Parser<'_> {
         let (key, rest) = self.line.split_at(idx);
 
         if key.is_empty() {
-            Err(ErrorKind::ExpectedToken("key".into()).into())
+            Err(self.expected_token("key").into())
         } else {
             let result = str::from_utf8(key)
-                .map_err(|_| ErrorKind::InvalidEncoding)?
+                .map_err(|err| self.invalid_encoding(self.line, &err))?
                 .to_string();
             self.line = rest;
             Ok(result)
         }
     }
 
-    fn parse_value(&mut self) -> Result<Value> {
+    fn parse_value(&mut self) -> Result<Value<'de>> {
         match *self.line {
             // String
             [b'"' | b'\'', ..] => self.parse_string().map(Value::String),
             // Boolean
             [b't' | b'f', ..] => self.parse_bool().map(Value::Boolean),
             // Digit could mean either number or date-time
-            [b'0'..=b'9', ..] => self.parse_number_or_datetime(),
+            [b'0'..=b'9', ..] => {
+                #[cfg(feature = "datetime")]
+                let offset = self.reader.offset_of(self.line);
+                let value = self.parse_number_or_datetime()?;
+                #[cfg(feature = "datetime")]
+                self.check_datetime(&value, offset)?;
+                Ok(value)
+            }
             // Number
             [b'+' | b'-', ch, ..] if ch.is_ascii_digit() => self.parse_number_decimal(),
             // Special float
@@ -395,6 +512,9 @@ impl Parser<'_> {
                 self.parse_number_special().map(Value::SpecialFloat)
             }
             // Invalid
+            [b'+' | b'-', b'.', ..] | [b'.', b'0'..=b'9', ..] => {
+                Err(ErrorKind::InvalidNumber("missing digits before decimal point".into()).into())
+            }
             [b'+' | b'-', ..] => Err(ErrorKind::InvalidNumber("missing digits".into()).into()),
             // Array
             [b'[', ref rest @ ..] => {
@@ -408,16 +528,16 @@ impl Parser<'_> {
                 self.line = rest;
                 self.parse_inline_table().map(Value::InlineTable)
             }
-            [ch, ..] if !ch.is_toml_legal() => Err(ErrorKind::IllegalChar(ch).into()),
+            [ch, ..] if !ch.is_toml_legal() => Err(self.illegal_char(self.line).into()),
             _ => Err(ErrorKind::ExpectedToken("a value".into()).into()),
         }
     }
 
-    fn parse_string(&mut self) -> Result<String> {
+    fn parse_string(&mut self) -> Result<Cow<'de, str>> {
         match *self.line {
             [b'"', b'"', b'"', ref rest @ ..] => {
                 self.line = rest;
-                self.parse_multiline_basic_str()
+                self.parse_multiline_basic_str().map(Cow::Owned)
             }
             [b'"', ref rest @ ..] => {
                 self.line = rest;
@@ -425,7 +545,7 @@ impl Parser<'_> {
             }
             [b'\'', b'\'', b'\'', ref rest @ ..] => {
                 self.line = rest;
-                self.parse_multiline_literal_str()
+                self.parse_multiline_literal_str().map(Cow::Owned)
             }
             [b'\'', ref rest @ ..] => {
                 self.line = rest;
@@ -435,7 +555,8 @@ impl Parser<'_> {
         }
     }
 
-    fn parse_basic_str(&mut self) -> Result<String> {
+    // Borrows from the input if the string has no escape sequences
+    fn parse_basic_str(&mut self) -> Result<Cow<'de, str>> {
         let mut str = String::new();
 
         loop {
@@ -446,11 +567,20 @@ impl Parser<'_> {
                 .ok_or(ErrorKind::UnterminatedString)?;
             self.line = &orig[idx + 1..];
 
-            str.push_str(str::from_utf8(&orig[..idx]).map_err(|_| ErrorKind::InvalidEncoding)?);
+            let part =
+                str::from_utf8(&orig[..idx]).map_err(|err| self.invalid_encoding(orig, &err))?;
             match orig[idx] {
-                b'\\' => str.push(self.parse_escape_seq()?),
-                b'"' => break Ok(str),
-                char => break Err(ErrorKind::IllegalChar(char).into()),
+                b'\\' => {
+                    str.push_str(part);
+                    str.push(self.parse_escape_seq()?);
+                }
+                // Escape sequences always push a char, so this is only empty if there were none
+                b'"' if str.is_empty() => break Ok(Cow::Borrowed(part)),
+                b'"' => {
+                    str.push_str(part);
+                    break Ok(Cow::Owned(str));
+                }
+                _ => break Err(self.illegal_char(&orig[idx..]).into()),
             }
         }
     }
@@ -473,7 +603,8 @@ impl Parser<'_> {
 
             // Copy everything until idx
             str.push_str(
-                str::from_utf8(&self.line[..idx]).map_err(|_| ErrorKind::InvalidEncoding)?,
+                str::from_utf8(&self.line[..idx])
+                    .map_err(|err| self.invalid_encoding(self.line, &err))?,
             );
             self.line = &self.line[idx..];
 
@@ -514,7 +645,7 @@ impl Parser<'_> {
                     str.push('"');
                 }
                 // Any other char is illegal
-                [char, ..] => break Err(ErrorKind::IllegalChar(char).into()),
+                [_, ..] => break Err(self.illegal_char(self.line).into()),
                 // End of line
                 [] => {
                     str.push('\n');
@@ -524,7 +655,7 @@ impl Parser<'_> {
         }
     }
 
-    fn parse_literal_str(&mut self) -> Result<String> {
+    fn parse_literal_str(&mut self) -> Result<Cow<'de, str>> {
         let orig = self.line;
         let idx = orig
             .iter()
@@ -532,10 +663,11 @@ impl Parser<'_> {
             .ok_or(ErrorKind::UnterminatedString)?;
         self.line = &orig[idx + 1..];
 
-        let result = str::from_utf8(&orig[..idx]).map_err(|_| ErrorKind::InvalidEncoding)?;
+        let result =
+            str::from_utf8(&orig[..idx]).map_err(|err| self.invalid_encoding(orig, &err))?;
         match orig[idx] {
-            b'\'' => Ok(result.to_string()),
-            char => Err(ErrorKind::IllegalChar(char).into()),
+            b'\'' => Ok(Cow::Borrowed(result)),
+            _ => Err(self.illegal_char(&orig[idx..]).into()),
         }
     }
 
@@ -557,7 +689,8 @@ impl Parser<'_> {
 
             // Copy everything until idx
             str.push_str(
-                str::from_utf8(&self.line[..idx]).map_err(|_| ErrorKind::InvalidEncoding)?,
+                str::from_utf8(&self.line[..idx])
+                    .map_err(|err| self.invalid_encoding(self.line, &err))?,
             );
             self.line = &self.line[idx..];
 
@@ -585,7 +718,7 @@ impl Parser<'_> {
                     str.push('\'');
                 }
                 // Any other char is illegal
-                [char, ..] => break Err(ErrorKind::IllegalChar(char).into()),
+                [_, ..] => break Err(self.illegal_char(self.line).into()),
                 // End of line
                 [] => {
                     str.push('\n');
@@ -610,40 +743,34 @@ impl Parser<'_> {
             b'r' => Ok('\r'),
             b'"' => Ok('"'),
             b'\\' => Ok('\\'),
-            b'u' => {
-                let (bytes, rest) = if rest.len() >= 4 {
-                    rest.split_at(4)
+            b'u' | b'U' => {
+                let len = if esc == b'u' { 4 } else { 8 };
+                let (bytes, rest) = if rest.len() >= len {
+                    rest.split_at(len)
                 } else {
                     return Err(ErrorKind::UnterminatedString.into());
                 };
-                let str = str::from_utf8(bytes).map_err(|_| ErrorKind::InvalidEncoding)?;
-                let result = u32::from_str_radix(str, 16)
-                    .ok()
+                let str =
+                    str::from_utf8(bytes).map_err(|err| self.invalid_encoding(self.line, &err))?;
+                // from_str_radix also accepts a leading +, and char::from_u32 rejects surrogates
+                // and values above U+10FFFF
+                let result = Some(str)
+                    .filter(|str| str.bytes().all(|b| b.is_ascii_hexdigit()))
+                    .and_then(|str| u32::from_str_radix(str, 16).ok())
                     .and_then(char::from_u32)
-                    .ok_or_else(|| ErrorKind::InvalidEscape(format!("\\u{str}").into()))?;
-                self.line = rest;
-                Ok(result)
-            }
-            b'U' => {
-                let (bytes, rest) = if rest.len() >= 8 {
-                    rest.split_at(8)
-                } else {
-                    return Err(ErrorKind::UnterminatedString.into());
-                };
-                let str = str::from_utf8(bytes).map_err(|_| ErrorKind::InvalidEncoding)?;
-                let result = u32::from_str_radix(str, 16)
-                    .ok()
-                    .and_then(char::from_u32)
-                    .ok_or_else(|| ErrorKind::InvalidEscape(format!("\\U{str}").into()))?;
+                    .ok_or_else(|| {
+                        ErrorKind::InvalidEscape(format!("\\{}{str}", char::from(esc)).into())
+                    })?;
                 self.line = rest;
                 Ok(result)
             }
             _ => {
                 // We want a char here, not just a byte
                 let char = reader::utf8_len(esc)
-                    .and_then(|len| str::from_utf8(&orig[..len]).ok())
+                    .and_then(|len| orig.get(..len))
+                    .and_then(|bytes| str::from_utf8(bytes).ok())
                     .and_then(|l| l.chars().next())
-                    .ok_or(ErrorKind::InvalidEncoding)?;
+                    .ok_or_else(|| ErrorKind::InvalidEncoding(self.reader.offset_of(orig)))?;
 
                 Err(ErrorKind::InvalidEscape(format!("\\{char}").into()).into())
             }
@@ -662,25 +789,68 @@ impl Parser<'_> {
         }
     }
 
+    // Checks date-times are in range if requested. With feature = "strict" the same checks are
+    // also done when the value is deserialized, so this is only needed without it. Also checks
+    // for fractional seconds which would be truncated when parsed, either rejecting them or
+    // warning about them at the given offset
+    #[cfg(feature = "datetime")]
+    fn check_datetime(&self, value: &Value<'_>, offset: usize) -> Result<()> {
+        let (Value::OffsetDatetime(ref bytes)
+        | Value::LocalDatetime(ref bytes)
+        | Value::LocalDate(ref bytes)
+        | Value::LocalTime(ref bytes)) = *value
+        else {
+            return Ok(());
+        };
+
+        if self.validate_datetimes {
+            let datetime = Datetime::from_slice(bytes)?;
+            let is_valid = datetime.date.as_ref().map_or(true, LocalDate::is_valid)
+                && datetime.time.as_ref().map_or(true, LocalTime::is_valid)
+                && datetime.offset.as_ref().map_or(true, Offset::is_valid);
+            if !is_valid {
+                return Err(
+                    ErrorKind::InvalidDatetime(String::from_utf8_lossy(bytes).into()).into(),
+                );
+            }
+        }
+
+        if self.reject_excess_precision || self.on_warning.is_enabled() {
+            let digits = bytes.iter().position(|&b| b == b'.').map_or(0, |pos| {
+                bytes[pos + 1..]
+                    .iter()
+                    .take_while(|b| b.is_ascii_digit())
+                    .count()
+            });
+            if digits > 9 {
+                if self.reject_excess_precision {
+                    return Err(ErrorKind::ExcessPrecision.into());
+                }
+                self.on_warning.emit(WarningKind::ExcessPrecision, offset);
+            }
+        }
+
+        Ok(())
+    }
+
     // Parses anything that starts with a digit. Does not parse special floats or +/- values
-    fn parse_number_or_datetime(&mut self) -> Result<Value> {
+    fn parse_number_or_datetime(&mut self) -> Result<Value<'de>> {
         match *self.line {
             // Hex literal starts with "0x"
             [b'0', b'x', ref rest @ ..] => {
                 self.line = rest;
                 self.parse_digits(u8::is_ascii_hexdigit).map(Value::HexInt)
             }
-            // Octal literal starts with "0o"
+            // Octal literal starts with "0o". Out of range digits (8 and 9) are consumed here so
+            // they're reported as an invalid integer when converted rather than an unexpected token
             [b'0', b'o', ref rest @ ..] => {
                 self.line = rest;
-                self.parse_digits(|&b| matches!(b, b'0'..=b'7'))
-                    .map(Value::OctalInt)
+                self.parse_digits(u8::is_ascii_digit).map(Value::OctalInt)
             }
-            // Binary literal starts with "0b"
+            // Binary literal starts with "0b". Same as above for digits 2-9
             [b'0', b'b', ref rest @ ..] => {
                 self.line = rest;
-                self.parse_digits(|&b| matches!(b, b'0' | b'1'))
-                    .map(Value::BinaryInt)
+                self.parse_digits(u8::is_ascii_digit).map(Value::BinaryInt)
             }
             // LocalTime has a ':' at index 2
             #[cfg(feature = "datetime")]
@@ -712,8 +882,9 @@ impl Parser<'_> {
                     (end, t + 1)
                 }
                 // If we don't have a 'T' we might have a space-delimited date-time of which
-                // `value` is the first half, so check for space followed by a digit
-                else if matches!(self.line[end..], [b' ', b'0'..=b'9', ..]) {
+                // `value` is the first half, so check for space followed by the start of a time.
+                // Anything else after the space is left for the caller, so the date is a LocalDate
+                else if matches!(self.line[end..], [b' ', b'0'..=b'9', b'0'..=b'9', b':', ..]) {
                     // Discard the space
                     let idx = end + 1;
                     let time = idx;
@@ -753,7 +924,8 @@ impl Parser<'_> {
         }
     }
 
-    fn parse_number_decimal(&mut self) -> Result<Value> {
+    fn parse_number_decimal(&mut self) -> Result<Value<'de>> {
+        let offset = self.reader.offset_of(self.line);
         let mut float = false;
         let mut buf = Vec::new();
 
@@ -776,6 +948,13 @@ impl Parser<'_> {
         if let Some(rest) = self.line.strip_prefix(b".") {
             float = true;
 
+            // Unlike Rust's float syntax, TOML requires digits on both sides of the decimal point
+            if !matches!(*rest, [b'0'..=b'9' | b'_', ..]) {
+                return Err(
+                    ErrorKind::InvalidNumber("missing digits after decimal point".into()).into(),
+                );
+            }
+
             buf.push(b'.');
             self.line = rest;
 
@@ -802,7 +981,7 @@ impl Parser<'_> {
         Ok(if float {
             Value::Float(buf)
         } else {
-            Value::Integer(buf)
+            Value::Integer(buf, offset)
         })
     }
 
@@ -831,6 +1010,7 @@ impl Parser<'_> {
             .into());
         }
 
+        self.check_number_length(buf.len() + idx)?;
         buf.extend_from_slice(&self.line[..idx]);
         self.line = &self.line[idx..];
 
@@ -854,6 +1034,7 @@ impl Parser<'_> {
                 .into());
             }
 
+            self.check_number_length(buf.len() + idx)?;
             buf.extend_from_slice(&self.line[..idx]);
             self.line = &self.line[idx..];
         }
@@ -861,6 +1042,18 @@ impl Parser<'_> {
         Ok(())
     }
 
+    // Bail out early on absurdly long numbers rather than copying them only to fail parsing later
+    fn check_number_length(&self, len: usize) -> Result<()> {
+        if len > self.max_number_length {
+            Err(ErrorKind::InvalidNumber(
+                format!("longer than {} characters", self.max_number_length).into(),
+            )
+            .into())
+        } else {
+            Ok(())
+        }
+    }
+
     fn parse_number_special(&mut self) -> Result<SpecialFloat> {
         match *self.line {
             [b'-', ref rest @ ..] => match *rest {
@@ -880,12 +1073,12 @@ impl Parser<'_> {
         })
     }
 
-    fn parse_array(&mut self) -> Result<Vec<Value>> {
+    fn parse_array(&mut self) -> Result<Vec<Value<'de>>> {
         fn skip_comments_and_whitespace(slf: &mut Parser<'_>) -> Result<()> {
             slf.skip_whitespace();
             slf.skip_comment()?;
             while slf.line.is_empty() {
-                slf.next_line().ok_or(ErrorKind::UnterminatedString)?;
+                slf.next_line().ok_or(ErrorKind::UnterminatedArray)?;
                 slf.skip_whitespace();
                 slf.skip_comment()?;
             }
@@ -914,14 +1107,14 @@ impl Parser<'_> {
             if let Some(rest) = self.line.strip_prefix(b",") {
                 self.line = rest;
             } else {
-                return Err(ErrorKind::ExpectedToken(", or ] after value in array".into()).into());
+                return Err(self.expected_token(", or ] after value in array").into());
             }
         }
 
         Ok(result)
     }
 
-    fn parse_inline_table(&mut self) -> Result<Table> {
+    fn parse_inline_table(&mut self) -> Result<Table<'de>> {
         let mut result = Table::with_capacity(10);
 
         self.skip_whitespace();
@@ -957,13 +1150,22 @@ impl Parser<'_> {
             }
 
             if let Some(rest) = self.line.strip_prefix(b",") {
+                let comma = self.line;
                 self.line = rest;
                 self.skip_whitespace();
+
+                // Trailing commas are not allowed by the spec, but can be accepted if requested
+                if let Some(rest) = self.line.strip_prefix(b"}") {
+                    if !self.allow_inline_table_trailing_comma {
+                        return Err(ErrorKind::TrailingComma(self.reader.offset_of(comma)).into());
+                    }
+                    self.line = rest;
+                    break; // End of table
+                }
             } else {
-                return Err(ErrorKind::ExpectedToken(
-                    ", or } after key/value pair in inline table".into(),
-                )
-                .into());
+                return Err(self
+                    .expected_token(", or } after key/value pair in inline table")
+                    .into());
             }
         }
 
@@ -984,10 +1186,10 @@ impl Parser<'_> {
             // Only validate comments with feature = "strict"
             if cfg!(feature = "strict") {
                 // validate UTF-8
-                _ = str::from_utf8(rest).map_err(|_| ErrorKind::InvalidEncoding)?;
+                _ = str::from_utf8(rest).map_err(|err| self.invalid_encoding(rest, &err))?;
                 // Check for any invalid characters in the comment
-                if let Some(ch) = rest.iter().copied().find(|ch| !ch.is_toml_comment()) {
-                    return Err(ErrorKind::IllegalChar(ch).into());
+                if let Some(idx) = rest.iter().position(|ch| !ch.is_toml_comment()) {
+                    return Err(self.illegal_char(&rest[idx..]).into());
                 }
             }
             self.line = &self.line[self.line.len()..];
@@ -1000,6 +1202,24 @@ impl Parser<'_> {
         self.line = self.reader.next_line()?;
         Some(())
     }
+
+    fn invalid_encoding(&self, rest: &[u8], err: &str::Utf8Error) -> ErrorKind {
+        ErrorKind::InvalidEncoding(self.reader.offset_of(rest) + err.valid_up_to())
+    }
+
+    // Expects rest to start with the illegal char
+    fn illegal_char(&self, rest: &[u8]) -> ErrorKind {
+        ErrorKind::IllegalChar(rest[0], self.reader.offset_of(rest))
+    }
+
+    // Reports an illegal char (e.g. a NUL byte) at the start of the line in preference to the
+    // token that was expected, since no token could ever be valid there
+    fn expected_token(&self, token: &str) -> ErrorKind {
+        match *self.line {
+            [ch, ..] if !ch.is_toml_legal() => self.illegal_char(self.line),
+            _ => ErrorKind::ExpectedToken(token.into()),
+        }
+    }
 }
 
 trait TomlTable {
@@ -1009,7 +1229,7 @@ trait TomlTable {
     fn append_array_of_tables(&mut self, name: String) -> Option<&mut Self>;
 }
 
-impl TomlTable for Table {
+impl TomlTable for Table<'_> {
     fn get_subtable(&mut self, path: &[String]) -> Option<&mut Self> {
         // Navigate to the parent table, either a subtable with the given name or the last element
         // in an array of tables
@@ -1189,7 +1409,17 @@ mod tests {
         let mut reader = Reader::from_slice(bytes);
         let line = reader.next_line().unwrap_or(b"");
 
-        Parser { reader, line }
+        Parser {
+            reader,
+            line,
+            max_number_length: DEFAULT_MAX_NUMBER_LENGTH,
+            allow_inline_table_trailing_comma: false,
+            #[cfg(feature = "datetime")]
+            validate_datetimes: false,
+            #[cfg(feature = "datetime")]
+            reject_excess_precision: false,
+            on_warning: WarningSink::default(),
+        }
     }
 
     #[test]
@@ -1252,7 +1482,7 @@ mod tests {
     #[test]
     fn value_type() {
         assert_eq!(Value::String("foo".into()).typ(), Type::String);
-        assert_eq!(Value::Integer(b"123".to_vec()).typ(), Type::Integer);
+        assert_eq!(Value::Integer(b"123".to_vec(), 0).typ(), Type::Integer);
         assert_eq!(Value::BinaryInt(b"123".to_vec()).typ(), Type::Integer);
         assert_eq!(Value::OctalInt(b"123".to_vec()).typ(), Type::Integer);
         assert_eq!(Value::HexInt(b"123".to_vec()).typ(), Type::Integer);
@@ -1323,8 +1553,8 @@ mod tests {
         assert_matches!(
             parser.parse(),
             Ok(Value::Table(t)) if t == hashmap! {
-                "a".into() => Value::Integer(b"1".to_vec()),
-                "b".into() => Value::Integer(b"2".to_vec()),
+                "a".into() => Value::Integer(b"1".to_vec(), 4),
+                "b".into() => Value::Integer(b"2".to_vec(), 10),
             }
         );
 
@@ -1332,11 +1562,58 @@ mod tests {
         assert_matches!(
             parser.parse(),
             Ok(Value::Table(t)) if t == hashmap! {
-                "a".into() => Value::Integer(b"1".to_vec()),
-                "b".into() => Value::Integer(b"2".to_vec()),
+                "a".into() => Value::Integer(b"1".to_vec(), 4),
+                "b".into() => Value::Integer(b"2".to_vec(), 11),
+            }
+        );
+
+        // Quoted keys containing dots are a single key, not a path
+        let mut parser = Parser::from_slice(br#""a.b" = 1"#);
+        assert_matches!(
+            parser.parse(),
+            Ok(Value::Table(t)) if t == hashmap! {
+                "a.b".into() => Value::Integer(b"1".to_vec(), 8),
+            }
+        );
+
+        let mut parser = Parser::from_slice(b"a.b = 1");
+        assert_matches!(
+            parser.parse(),
+            Ok(Value::Table(t)) if t == hashmap! {
+                "a".into() => Value::DottedKeyTable(hashmap! {
+                    "b".into() => Value::Integer(b"1".to_vec(), 6),
+                }),
+            }
+        );
+
+        let mut parser = Parser::from_slice(indoc! {br#"
+            a."b.c" = 1
+            'a.b'.c = 2
+
+            ["d.e"]
+            f = 3
+        "#});
+        assert_matches!(
+            parser.parse(),
+            Ok(Value::Table(t)) if t == hashmap! {
+                "a".into() => Value::DottedKeyTable(hashmap! {
+                    "b.c".into() => Value::Integer(b"1".to_vec(), 10),
+                }),
+                "a.b".into() => Value::DottedKeyTable(hashmap! {
+                    "c".into() => Value::Integer(b"2".to_vec(), 22),
+                }),
+                "d.e".into() => Value::Table(hashmap! {
+                    "f".into() => Value::Integer(b"3".to_vec(), 37),
+                }),
             }
         );
 
+        // The offsets of the integers below depend on whether this line is included
+        let dob = if cfg!(feature = "datetime") {
+            "dob = 1979-05-27T07:32:00-08:00 # First class dates\n"
+        } else {
+            ""
+        };
         let toml = [
             indoc! {r#"
                 # This is a TOML document.
@@ -1346,11 +1623,7 @@ mod tests {
                 [owner]
                 name = "Tom Preston-Werner"
             "#},
-            if cfg!(feature = "datetime") {
-                "dob = 1979-05-27T07:32:00-08:00 # First class dates\n"
-            } else {
-                ""
-            },
+            dob,
             indoc! {r#"
                 [database]
                 server = "192.168.1.1"
@@ -1405,11 +1678,11 @@ mod tests {
                 "database".into() => Value::Table(hashmap! {
                     "server".into() => Value::String("192.168.1.1".into()),
                     "ports".into() => Value::Array(vec![
-                        Value::Integer(b"8000".to_vec()),
-                        Value::Integer(b"8001".to_vec()),
-                        Value::Integer(b"8002".to_vec()),
+                        Value::Integer(b"8000".to_vec(), 132 + dob.len()),
+                        Value::Integer(b"8001".to_vec(), 138 + dob.len()),
+                        Value::Integer(b"8002".to_vec(), 144 + dob.len()),
                     ]),
-                    "connection_max".into() => Value::Integer(b"5000".to_vec()),
+                    "connection_max".into() => Value::Integer(b"5000".to_vec(), 168 + dob.len()),
                     "enabled".into() => Value::Boolean(true),
                 }),
                 "servers".into() => Value::Table(hashmap! {
@@ -1436,14 +1709,42 @@ mod tests {
                         },
                         hashmap! {
                             "value".into() => Value::Array(vec![
-                                Value::Integer(b"1".to_vec()),
-                                Value::Integer(b"2".to_vec()),
+                                Value::Integer(b"1".to_vec(), 552 + dob.len()),
+                                Value::Integer(b"2".to_vec(), 555 + dob.len()),
                             ]),
                         }
                     ]),
                 }),
             }
         );
+
+        // A key with the same name as the array of tables is a key within its element
+        let mut parser = Parser::from_slice(indoc! {br"
+            [[a]]
+            a = 1
+        "});
+        assert_matches!(
+            parser.parse(),
+            Ok(Value::Table(t)) if t == hashmap! {
+                "a".into() => Value::ArrayOfTables(vec![hashmap! {
+                    "a".into() => Value::Integer(b"1".to_vec(), 10),
+                }]),
+            }
+        );
+
+        // A document with no root keys before the first array of tables
+        let mut parser =
+            Parser::from_slice(b"[[items]]\nname = 1\n\n[[items]]\n# comment\n[[items]]");
+        assert_matches!(
+            parser.parse(),
+            Ok(Value::Table(t)) if t == hashmap! {
+                "items".into() => Value::ArrayOfTables(vec![
+                    hashmap! { "name".into() => Value::Integer(b"1".to_vec(), 17) },
+                    hashmap! {},
+                    hashmap! {},
+                ]),
+            }
+        );
     }
 
     #[test]
@@ -1476,6 +1777,38 @@ mod tests {
             Err(Error(ErrorKind::InvalidTableHeader(..)))
         );
 
+        let mut parser = Parser::from_slice(indoc! {br"
+            a = [1]
+
+            [[a]]
+            b = 456
+        "});
+        assert_matches!(
+            parser.parse(),
+            Err(Error(ErrorKind::InvalidTableHeader(k))) if &*k == "a"
+        );
+
+        let mut parser = Parser::from_slice(indoc! {br"
+            [[a]]
+            b = 123
+
+            [a]
+            b = 456
+        "});
+        assert_matches!(
+            parser.parse(),
+            Err(Error(ErrorKind::InvalidTableHeader(k))) if &*k == "a"
+        );
+
+        let mut parser = Parser::from_slice(indoc! {br"
+            [[a.b]]
+            c = 123
+
+            [a]
+            b = 456
+        "});
+        assert_matches!(parser.parse(), Err(Error(ErrorKind::DuplicateKey(..))));
+
         let mut parser = Parser::from_slice(indoc! {br"
             a = 123
             a.b = 456
@@ -1512,6 +1845,271 @@ mod tests {
 
         let mut parser = Parser::from_slice(b"a = 1\rb = 2");
         assert_matches!(parser.parse(), Err(Error(ErrorKind::ExpectedToken(..))));
+
+        #[cfg(feature = "datetime")]
+        {
+            let mut parser = Parser::from_slice(b"a = 2023-01-02 garbage");
+            assert_matches!(parser.parse(), Err(Error(ErrorKind::ExpectedToken(..))));
+
+            let mut parser = Parser::from_slice(b"a = 2023-01-02 12");
+            assert_matches!(parser.parse(), Err(Error(ErrorKind::ExpectedToken(..))));
+
+            let mut parser = Parser::from_slice(b"a = 2023-01-02 # comment");
+            assert_matches!(
+                parser.parse(),
+                Ok(Value::Table(t)) if t == hashmap! {
+                    "a".into() => Value::LocalDate(b"2023-01-02".to_vec()),
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn parser_parse_trailing_comments() {
+        let mut parser = Parser::from_slice(indoc! {br##"
+            a = 1 # comment
+            b = "x#y" # comment
+            c = 'x#y'#comment
+            d = """x#y"""# comment
+            e = [1, 2] # comment
+            f = [ # comment
+                "#", # comment
+                '#' # comment
+            ] # comment
+            g = { h = "#", i = 1.5 } # comment
+            [j] # comment
+            [[k]] # comment
+        "##});
+        assert_matches!(
+            parser.parse(),
+            Ok(Value::Table(t)) if t == hashmap! {
+                "a".into() => Value::Integer(b"1".to_vec(), 4),
+                "b".into() => Value::String("x#y".into()),
+                "c".into() => Value::String("x#y".into()),
+                "d".into() => Value::String("x#y".into()),
+                "e".into() => Value::Array(vec![
+                    Value::Integer(b"1".to_vec(), 82),
+                    Value::Integer(b"2".to_vec(), 85),
+                ]),
+                "f".into() => Value::Array(vec![
+                    Value::String("#".into()),
+                    Value::String("#".into()),
+                ]),
+                "g".into() => Value::InlineTable(hashmap! {
+                    "h".into() => Value::String("#".into()),
+                    "i".into() => Value::Float(b"1.5".to_vec()),
+                }),
+                "j".into() => Value::Table(hashmap! {}),
+                "k".into() => Value::ArrayOfTables(vec![hashmap! {}]),
+            }
+        );
+
+        // A `#` in a multiline string spanning several lines is not a comment either
+        let mut parser = Parser::from_slice(b"a = \"\"\"\n# not a comment\n\"\"\" # comment");
+        assert_matches!(
+            parser.parse(),
+            Ok(Value::Table(t)) if t == hashmap! {
+                "a".into() => Value::String("# not a comment\n".into()),
+            }
+        );
+
+        // A comment can't come between a key and its value
+        let mut parser = Parser::from_slice(b"a = # comment\n1");
+        assert_matches!(parser.parse(), Err(Error(ErrorKind::ExpectedToken(..))));
+
+        #[cfg(feature = "datetime")]
+        {
+            let mut parser =
+                Parser::from_slice(b"a = 1979-05-27T07:32:00Z # comment\nb = 07:32:00#comment");
+            assert_matches!(
+                parser.parse(),
+                Ok(Value::Table(t)) if t == hashmap! {
+                    "a".into() => Value::OffsetDatetime(b"1979-05-27T07:32:00Z".to_vec()),
+                    "b".into() => Value::LocalTime(b"07:32:00".to_vec()),
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn parser_parse_illegal_char() {
+        // NUL bytes are always reported as such, wherever they appear between tokens
+        for (input, offset) in [
+            (&b"a\0= 1"[..], 1),
+            (b"a =\0 1", 3),
+            (b"a = 1\0", 5),
+            (b"[a\0]", 2),
+            (b"[[a\0]]", 3),
+            (b"a.\0 = 1", 2),
+            (b"a = [1\0]", 6),
+            (b"a = { b = 1\0}", 11),
+            (b"b = 1\n\0", 6),
+        ] {
+            let mut parser = Parser::from_slice(input);
+            assert_matches!(
+                parser.parse(),
+                Err(Error(ErrorKind::IllegalChar(0, o))) if o == offset,
+                "{input:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn parser_parse_with() {
+        let input = indoc! {b"
+            a = 1
+            b = 2 3
+            [a.b]
+            c = 4
+            [d]
+            e = 5
+        "};
+
+        let mut spans = Vec::new();
+        let mut parser = Parser::from_slice(input);
+        let result = parser.parse_with(
+            |value| match *value {
+                Value::Integer(ref int, _) if int == b"5" => {
+                    Err(ErrorKind::Custom("five".into()).into())
+                }
+                _ => Ok(()),
+            },
+            |span, result| {
+                spans.push((span, result.is_ok()));
+                Ok(())
+            },
+        );
+        assert_matches!(
+            result,
+            Ok(Value::Table(table)) if table == hashmap! {
+                "a".into() => Value::Integer(b"1".into(), 4),
+                "d".into() => Value::Table(hashmap! {}),
+            }
+        );
+        assert_eq!(
+            spans,
+            [
+                (0..5, true),
+                (6..13, false),
+                (14..19, false),
+                (20..25, true),
+                (26..29, true),
+                (30..35, false),
+            ]
+        );
+
+        // Stops at the first error if it's returned
+        let mut parser = Parser::from_slice(input);
+        let mut count = 0;
+        let result = parser.parse_with(
+            |_| Ok(()),
+            |_, result| {
+                count += 1;
+                result
+            },
+        );
+        assert_matches!(result, Err(Error(ErrorKind::ExpectedToken(_))));
+        assert_eq!(count, 2);
+    }
+
+    #[test]
+    fn parser_parse_lone_value() {
+        let mut parser = Parser::from_slice(b"42");
+        assert_matches!(parser.parse_lone_value(), Ok(Value::Integer(i, 0)) if i == b"42");
+
+        let mut parser = Parser::from_slice(b"\n# comment\n  \"foo\" # comment\n\n");
+        assert_matches!(parser.parse_lone_value(), Ok(Value::String(s)) if s == "foo");
+
+        let mut parser = Parser::from_slice(b"[\n  1,\n  2,\n]\n");
+        assert_matches!(
+            parser.parse_lone_value(),
+            Ok(Value::Array(a)) if a == [Value::Integer(b"1".to_vec(), 4), Value::Integer(b"2".to_vec(), 9)]
+        );
+
+        let mut parser = Parser::from_slice(b"{ a = 1 }");
+        assert_matches!(
+            parser.parse_lone_value(),
+            Ok(Value::InlineTable(t)) if t == hashmap! { "a".into() => Value::Integer(b"1".to_vec(), 6) }
+        );
+
+        let mut parser = Parser::from_slice(b"# comment\n");
+        assert_matches!(
+            parser.parse_lone_value(),
+            Err(Error(ErrorKind::ExpectedToken(t))) if &*t == "a value"
+        );
+
+        let mut parser = Parser::from_slice(b"a = 1");
+        assert_matches!(
+            parser.parse_lone_value(),
+            Err(Error(ErrorKind::ExpectedToken(t))) if &*t == "a value"
+        );
+
+        let mut parser = Parser::from_slice(b"1 2");
+        assert_matches!(
+            parser.parse_lone_value(),
+            Err(Error(ErrorKind::ExpectedToken(t))) if &*t == "end of line"
+        );
+
+        let mut parser = Parser::from_slice(b"1\n2");
+        assert_matches!(
+            parser.parse_lone_value(),
+            Err(Error(ErrorKind::ExpectedToken(t))) if &*t == "end of line"
+        );
+    }
+
+    #[test]
+    fn parser_end() {
+        let mut parser = Parser::from_slice(b"a = 1\nb = 2\n");
+        assert_matches!(parser.parse(), Ok(_));
+        assert_matches!(parser.end(), Ok(()));
+
+        let mut parser = Parser::from_slice(b"  \n# comment\n\n");
+        assert_matches!(parser.end(), Ok(()));
+
+        let mut parser = Parser::from_slice(b"# comment\n  a = 1\n");
+        assert_matches!(parser.end(), Err(Error(ErrorKind::TrailingData(12))));
+
+        // Comments are only validated with feature = "strict"
+        if cfg!(feature = "strict") {
+            let mut parser = Parser::from_slice(b"# comment\0\n");
+            assert_matches!(parser.end(), Err(Error(ErrorKind::IllegalChar(0, 9))));
+        }
+    }
+
+    #[test]
+    fn parser_parse_key_path() {
+        let mut parser = Parser::from_str("a.b");
+        assert_matches!(parser.parse_key_path(), Ok(p) if p == ["a", "b"]);
+
+        let mut parser = Parser::from_str(r#" a . "b.c" . 'd' "#);
+        assert_matches!(parser.parse_key_path(), Ok(p) if p == ["a", "b.c", "d"]);
+
+        let mut parser = Parser::from_str("a");
+        assert_matches!(parser.parse_key_path(), Ok(p) if p == ["a"]);
+
+        let mut parser = Parser::from_str("");
+        assert_matches!(
+            parser.parse_key_path(),
+            Err(Error(ErrorKind::ExpectedToken(t))) if &*t == "key"
+        );
+
+        let mut parser = Parser::from_str("a.");
+        assert_matches!(
+            parser.parse_key_path(),
+            Err(Error(ErrorKind::ExpectedToken(t))) if &*t == "key"
+        );
+
+        let mut parser = Parser::from_str("a b");
+        assert_matches!(
+            parser.parse_key_path(),
+            Err(Error(ErrorKind::ExpectedToken(t))) if &*t == "end of key"
+        );
+
+        let mut parser = Parser::from_str("a\nb");
+        assert_matches!(
+            parser.parse_key_path(),
+            Err(Error(ErrorKind::ExpectedToken(t))) if &*t == "end of key"
+        );
     }
 
     #[test]
@@ -1531,19 +2129,19 @@ mod tests {
         let mut parser = Parser::from_slice(b"a = 1");
         assert_matches!(
             parser.parse_line(),
-            Ok(Some(Line::KeyValuePair { key, value })) if key.to_string() == "a" && value == Value::Integer(b"1".to_vec())
+            Ok(Some(Line::KeyValuePair { key, value })) if key.to_string() == "a" && value == Value::Integer(b"1".to_vec(), 4)
         );
 
         let mut parser = Parser::from_slice(b"'a' = 1");
         assert_matches!(
             parser.parse_line(),
-            Ok(Some(Line::KeyValuePair { key, value })) if key.to_string() == "a" && value == Value::Integer(b"1".to_vec())
+            Ok(Some(Line::KeyValuePair { key, value })) if key.to_string() == "a" && value == Value::Integer(b"1".to_vec(), 6)
         );
 
         let mut parser = Parser::from_slice(br#""a" = 1"#);
         assert_matches!(
             parser.parse_line(),
-            Ok(Some(Line::KeyValuePair { key, value })) if key.to_string() == "a" && value == Value::Integer(b"1".to_vec())
+            Ok(Some(Line::KeyValuePair { key, value })) if key.to_string() == "a" && value == Value::Integer(b"1".to_vec(), 6)
         );
 
         let mut parser = Parser::from_slice(b"\n");
@@ -1594,7 +2192,25 @@ mod tests {
         let mut parser = start_parser(br"a = 123");
         assert_matches!(
             parser.parse_key_value_pair(),
-            Ok((k, Value::Integer(v))) if k.path.is_empty() && k.name == "a" && &*v == b"123"
+            Ok((k, Value::Integer(v, 4))) if k.path.is_empty() && k.name == "a" && &*v == b"123"
+        );
+
+        let mut parser = start_parser(b"1234 = true");
+        assert_matches!(
+            parser.parse_key_value_pair(),
+            Ok((k, Value::Boolean(true))) if k.path.is_empty() && k.name == "1234"
+        );
+
+        let mut parser = start_parser(b"key-2 = 1");
+        assert_matches!(
+            parser.parse_key_value_pair(),
+            Ok((k, Value::Integer(v, 8))) if k.path.is_empty() && k.name == "key-2" && &*v == b"1"
+        );
+
+        let mut parser = start_parser(b"3.14 = 1");
+        assert_matches!(
+            parser.parse_key_value_pair(),
+            Ok((k, Value::Integer(v, 7))) if k.path == ["3"] && k.name == "14" && &*v == b"1"
         );
 
         let mut parser = start_parser(br#""a = 123""#);
@@ -1609,6 +2225,12 @@ mod tests {
         let mut parser = start_parser(br#"a .b. "..c""#);
         assert_matches!(parser.parse_dotted_key(), Ok(s) if s.path == ["a", "b"] && s.name == "..c");
 
+        let mut parser = start_parser(br#""a.b""#);
+        assert_matches!(parser.parse_dotted_key(), Ok(s) if s.path.is_empty() && s.name == "a.b");
+
+        let mut parser = start_parser(b"'a.b'.c");
+        assert_matches!(parser.parse_dotted_key(), Ok(s) if s.path == ["a.b"] && s.name == "c");
+
         let mut parser = start_parser(b".");
         assert_matches!(
             parser.parse_dotted_key(),
@@ -1648,6 +2270,9 @@ mod tests {
         let mut parser = start_parser(b"123");
         assert_matches!(parser.parse_bare_key(), Ok(k) if k == "123");
 
+        let mut parser = start_parser(b"3-14");
+        assert_matches!(parser.parse_bare_key(), Ok(k) if k == "3-14");
+
         let mut parser = start_parser(b"-");
         assert_matches!(parser.parse_bare_key(), Ok(k) if k == "-");
 
@@ -1673,6 +2298,21 @@ mod tests {
         let mut parser = start_parser(b"0.2");
         assert_matches!(parser.parse_value(), Ok(Value::Float(b)) if &*b == b"0.2");
 
+        // TOML requires digits on both sides of the decimal point
+        for (input, reason) in [
+            (&b".5"[..], "missing digits before decimal point"),
+            (b"+.5", "missing digits before decimal point"),
+            (b"5.", "missing digits after decimal point"),
+            (b"-5.", "missing digits after decimal point"),
+            (b"5.e3", "missing digits after decimal point"),
+        ] {
+            let mut parser = start_parser(input);
+            assert_matches!(
+                parser.parse_value(),
+                Err(Error(ErrorKind::InvalidNumber(r))) if &*r == reason
+            );
+        }
+
         let mut parser = start_parser(b"0x123abc");
         assert_matches!(parser.parse_value(), Ok(Value::HexInt(v)) if &*v == b"123abc");
 
@@ -1692,13 +2332,13 @@ mod tests {
         };
 
         let mut parser = start_parser(b"0");
-        assert_matches!(parser.parse_value(), Ok(Value::Integer(b)) if &*b == b"0");
+        assert_matches!(parser.parse_value(), Ok(Value::Integer(b, 0)) if &*b == b"0");
 
         let mut parser = start_parser(b"12");
-        assert_matches!(parser.parse_value(), Ok(Value::Integer(b)) if &*b == b"12");
+        assert_matches!(parser.parse_value(), Ok(Value::Integer(b, 0)) if &*b == b"12");
 
         let mut parser = start_parser(b"1234");
-        assert_matches!(parser.parse_value(), Ok(Value::Integer(b)) if &*b == b"1234");
+        assert_matches!(parser.parse_value(), Ok(Value::Integer(b, 0)) if &*b == b"1234");
 
         #[cfg(feature = "datetime")]
         {
@@ -1716,10 +2356,10 @@ mod tests {
         };
 
         let mut parser = start_parser(b"-123");
-        assert_matches!(parser.parse_value(), Ok(Value::Integer(v)) if &*v == b"-123");
+        assert_matches!(parser.parse_value(), Ok(Value::Integer(v, 0)) if &*v == b"-123");
 
         let mut parser = start_parser(b"+123");
-        assert_matches!(parser.parse_value(), Ok(Value::Integer(v)) if &*v == b"+123");
+        assert_matches!(parser.parse_value(), Ok(Value::Integer(v, 0)) if &*v == b"+123");
 
         let mut parser = start_parser(b"+inf");
         assert_matches!(
@@ -1727,6 +2367,18 @@ mod tests {
             Ok(Value::SpecialFloat(SpecialFloat::Infinity))
         );
 
+        let mut parser = start_parser(b"+nan");
+        assert_matches!(
+            parser.parse_value(),
+            Ok(Value::SpecialFloat(SpecialFloat::Nan))
+        );
+
+        let mut parser = start_parser(b"-inf");
+        assert_matches!(
+            parser.parse_value(),
+            Ok(Value::SpecialFloat(SpecialFloat::NegInfinity))
+        );
+
         let mut parser = start_parser(b"-nan");
         assert_matches!(
             parser.parse_value(),
@@ -1749,9 +2401,9 @@ mod tests {
         assert_matches!(
             parser.parse_value(),
             Ok(Value::Array(a)) if a == [
-                Value::Integer(b"123".to_vec()),
-                Value::Integer(b"456".to_vec()),
-                Value::Integer(b"789".to_vec()),
+                Value::Integer(b"123".to_vec(), 1),
+                Value::Integer(b"456".to_vec(), 6),
+                Value::Integer(b"789".to_vec(), 11),
             ]
         );
 
@@ -1759,9 +2411,9 @@ mod tests {
         assert_matches!(
             parser.parse_value(),
             Ok(Value::InlineTable(t)) if t == hashmap! {
-                "a".into() => Value::Integer(b"123".to_vec()),
-                "b".into() => Value::Integer(b"456".to_vec()),
-                "c".into() => Value::Integer(b"789".to_vec()),
+                "a".into() => Value::Integer(b"123".to_vec(), 6),
+                "b".into() => Value::Integer(b"456".to_vec(), 15),
+                "c".into() => Value::Integer(b"789".to_vec(), 24),
             }
         );
     }
@@ -1896,7 +2548,10 @@ mod tests {
         let mut parser = start_parser(indoc! {br#"
             hello\n"
         "#});
-        assert_matches!(parser.parse_basic_str(), Ok(s) if s =="hello\n");
+        assert_matches!(parser.parse_basic_str(), Ok(Cow::Owned(s)) if s == "hello\n");
+
+        let mut parser = start_parser(b"hello\"");
+        assert_matches!(parser.parse_basic_str(), Ok(Cow::Borrowed("hello")));
 
         let mut parser = start_parser(indoc! {br#"
             hello\"
@@ -1919,6 +2574,12 @@ mod tests {
             parser.parse_basic_str(),
             Err(Error(ErrorKind::IllegalChar(..)))
         );
+
+        let mut parser = start_parser(b"hel\xfflo\"");
+        assert_matches!(
+            parser.parse_basic_str(),
+            Err(Error(ErrorKind::InvalidEncoding(3)))
+        );
     }
 
     #[test]
@@ -1977,6 +2638,29 @@ mod tests {
         let mut parser = start_parser(b"\nhello\n\"\"\"");
         assert_matches!(parser.parse_multiline_basic_str(), Ok(s) if s == "hello\n");
 
+        let mut parser = start_parser(b"\nfoo\"\"\"");
+        assert_matches!(parser.parse_multiline_basic_str(), Ok(s) if s == "foo");
+
+        let mut parser = start_parser(b"\r\nfoo\"\"\"");
+        assert_matches!(parser.parse_multiline_basic_str(), Ok(s) if s == "foo");
+
+        // But only the first newline is trimmed
+        let mut parser = start_parser(b"\n\nfoo\"\"\"");
+        assert_matches!(parser.parse_multiline_basic_str(), Ok(s) if s == "\nfoo");
+
+        let mut parser = start_parser(b"\r\n\r\nfoo\"\"\"");
+        assert_matches!(parser.parse_multiline_basic_str(), Ok(s) if s == "\nfoo");
+
+        // Line ending backslash trims all whitespace up to the next non-whitespace character
+        let mut parser = start_parser(b"foo\\\n   bar\"\"\"");
+        assert_matches!(parser.parse_multiline_basic_str(), Ok(s) if s == "foobar");
+
+        let mut parser = start_parser(b"foo\\\r\n   bar\"\"\"");
+        assert_matches!(parser.parse_multiline_basic_str(), Ok(s) if s == "foobar");
+
+        let mut parser = start_parser(b"\\\n\r\n\t\"\"\"");
+        assert_matches!(parser.parse_multiline_basic_str(), Ok(s) if s.is_empty());
+
         let mut parser = start_parser(indoc! {br#"
             hello
             ""
@@ -2006,12 +2690,18 @@ mod tests {
             parser.parse_multiline_basic_str(),
             Err(Error(ErrorKind::IllegalChar(..)))
         );
+
+        let mut parser = start_parser(b"hello\nw\xc3\x28\"\"\"");
+        assert_matches!(
+            parser.parse_multiline_basic_str(),
+            Err(Error(ErrorKind::InvalidEncoding(7)))
+        );
     }
 
     #[test]
     fn parser_parse_literal_str() {
         let mut parser = start_parser(b"hello\\n'");
-        assert_matches!(parser.parse_literal_str(), Ok(s) if s == "hello\\n");
+        assert_matches!(parser.parse_literal_str(), Ok(Cow::Borrowed("hello\\n")));
 
         let mut parser = start_parser(b"hello\n'");
         assert_matches!(
@@ -2024,6 +2714,12 @@ mod tests {
             parser.parse_literal_str(),
             Err(Error(ErrorKind::IllegalChar(..)))
         );
+
+        let mut parser = start_parser(b"hel\xfflo'");
+        assert_matches!(
+            parser.parse_literal_str(),
+            Err(Error(ErrorKind::InvalidEncoding(3)))
+        );
     }
 
     #[test]
@@ -2066,6 +2762,17 @@ mod tests {
         let mut parser = start_parser(b"\nhello\n'''");
         assert_matches!(parser.parse_multiline_literal_str(), Ok(s) if s == "hello\n");
 
+        let mut parser = start_parser(b"\r\nfoo'''");
+        assert_matches!(parser.parse_multiline_literal_str(), Ok(s) if s == "foo");
+
+        // But only the first newline is trimmed
+        let mut parser = start_parser(b"\n\nfoo'''");
+        assert_matches!(parser.parse_multiline_literal_str(), Ok(s) if s == "\nfoo");
+
+        // Line ending backslash is not special in literal strings
+        let mut parser = start_parser(b"foo\\\n   bar'''");
+        assert_matches!(parser.parse_multiline_literal_str(), Ok(s) if s == "foo\\\n   bar");
+
         let mut parser = start_parser(indoc! {br"
             hello
             ''
@@ -2086,6 +2793,12 @@ mod tests {
             parser.parse_multiline_literal_str(),
             Err(Error(ErrorKind::IllegalChar(..)))
         );
+
+        let mut parser = start_parser(b"hello\nw\xff'''");
+        assert_matches!(
+            parser.parse_multiline_literal_str(),
+            Err(Error(ErrorKind::InvalidEncoding(7)))
+        );
     }
 
     #[test]
@@ -2124,10 +2837,22 @@ mod tests {
         let mut parser = start_parser(b"ulmao");
         assert_matches!(parser.parse_escape_seq(), Err(Error(ErrorKind::InvalidEscape(esc))) if &*esc == "\\ulmao");
 
+        let mut parser = start_parser(b"u+041");
+        assert_matches!(parser.parse_escape_seq(), Err(Error(ErrorKind::InvalidEscape(esc))) if &*esc == "\\u+041");
+
+        let mut parser = start_parser(b"uD800");
+        assert_matches!(parser.parse_escape_seq(), Err(Error(ErrorKind::InvalidEscape(esc))) if &*esc == "\\uD800");
+
+        let mut parser = start_parser(b"uDFFF");
+        assert_matches!(parser.parse_escape_seq(), Err(Error(ErrorKind::InvalidEscape(esc))) if &*esc == "\\uDFFF");
+
+        let mut parser = start_parser(b"uFFFF");
+        assert_matches!(parser.parse_escape_seq(), Ok('\u{ffff}'));
+
         let mut parser = start_parser(b"u\xff\xff\xff\xff");
         assert_matches!(
             parser.parse_escape_seq(),
-            Err(Error(ErrorKind::InvalidEncoding))
+            Err(Error(ErrorKind::InvalidEncoding(1)))
         );
 
         let mut parser = start_parser(b"U0001f60e");
@@ -2142,10 +2867,19 @@ mod tests {
         let mut parser = start_parser(b"UROFLCOPTER");
         assert_matches!(parser.parse_escape_seq(), Err(Error(ErrorKind::InvalidEscape(esc))) if &*esc == "\\UROFLCOPT");
 
+        let mut parser = start_parser(b"U0000D800");
+        assert_matches!(parser.parse_escape_seq(), Err(Error(ErrorKind::InvalidEscape(esc))) if &*esc == "\\U0000D800");
+
+        let mut parser = start_parser(b"U00110000");
+        assert_matches!(parser.parse_escape_seq(), Err(Error(ErrorKind::InvalidEscape(esc))) if &*esc == "\\U00110000");
+
+        let mut parser = start_parser(b"U0010FFFF");
+        assert_matches!(parser.parse_escape_seq(), Ok('\u{10ffff}'));
+
         let mut parser = start_parser(b"U\xff\xff\xff\xff\xff\xff\xff\xff");
         assert_matches!(
             parser.parse_escape_seq(),
-            Err(Error(ErrorKind::InvalidEncoding))
+            Err(Error(ErrorKind::InvalidEncoding(1)))
         );
 
         let mut parser = start_parser(b"");
@@ -2156,6 +2890,12 @@ mod tests {
 
         let mut parser = start_parser(b"p");
         assert_matches!(parser.parse_escape_seq(), Err(Error(ErrorKind::InvalidEscape(esc))) if &*esc == "\\p");
+
+        let mut parser = start_parser(b"\xc3");
+        assert_matches!(
+            parser.parse_escape_seq(),
+            Err(Error(ErrorKind::InvalidEncoding(0)))
+        );
     }
 
     #[test]
@@ -2194,9 +2934,19 @@ mod tests {
         let mut parser = start_parser(b"0o123");
         assert_matches!(parser.parse_number_or_datetime(), Ok(Value::OctalInt(_)));
 
+        let mut parser = start_parser(b"0xDEADbeef");
+        assert_matches!(parser.parse_number_or_datetime(), Ok(Value::HexInt(v)) if v == b"DEADbeef");
+
         let mut parser = start_parser(b"0b101");
         assert_matches!(parser.parse_number_or_datetime(), Ok(Value::BinaryInt(_)));
 
+        // Out of range digits are rejected later when parsing the integer
+        let mut parser = start_parser(b"0o78");
+        assert_matches!(parser.parse_number_or_datetime(), Ok(Value::OctalInt(v)) if v == b"78");
+
+        let mut parser = start_parser(b"0b102");
+        assert_matches!(parser.parse_number_or_datetime(), Ok(Value::BinaryInt(v)) if v == b"102");
+
         #[cfg(feature = "datetime")]
         {
             let mut parser = start_parser(b"1980-01-01T12:00:00.000+02:30");
@@ -2226,42 +2976,164 @@ mod tests {
             let mut parser = start_parser(b"1980-01-01");
             assert_matches!(parser.parse_number_or_datetime(), Ok(Value::LocalDate(_)));
 
+            // A space is only a separator if it's followed by a time
+            let mut parser = start_parser(b"1980-01-01 # comment");
+            assert_matches!(parser.parse_number_or_datetime(), Ok(Value::LocalDate(v)) if v == b"1980-01-01");
+            assert_eq!(parser.line, b" # comment");
+
+            let mut parser = start_parser(b"1980-01-01 1");
+            assert_matches!(parser.parse_number_or_datetime(), Ok(Value::LocalDate(v)) if v == b"1980-01-01");
+            assert_eq!(parser.line, b" 1");
+
+            let mut parser = start_parser(b"1980-01-01 garbage");
+            assert_matches!(parser.parse_number_or_datetime(), Ok(Value::LocalDate(v)) if v == b"1980-01-01");
+            assert_eq!(parser.line, b" garbage");
+
             let mut parser = start_parser(b"12:00:00.000000000");
             assert_matches!(parser.parse_number_or_datetime(), Ok(Value::LocalTime(_)));
         };
 
         let mut parser = start_parser(b"123");
-        assert_matches!(parser.parse_number_or_datetime(), Ok(Value::Integer(_)));
+        assert_matches!(parser.parse_number_or_datetime(), Ok(Value::Integer(..)));
 
         let mut parser = start_parser(b"4.5");
         assert_matches!(parser.parse_number_or_datetime(), Ok(Value::Float(_)));
     }
 
+    #[cfg(feature = "datetime")]
     #[test]
-    #[allow(clippy::cognitive_complexity)]
+    fn parser_check_datetime() {
+        let mut parser = start_parser(b"");
+        assert!(!parser.validate_datetimes);
+        assert_matches!(
+            parser.check_datetime(&Value::LocalDate(b"2023-02-30".to_vec()), 0),
+            Ok(())
+        );
+
+        parser.validate_datetimes = true;
+        assert_matches!(
+            parser.check_datetime(&Value::LocalDate(b"2024-02-29".to_vec()), 0),
+            Ok(())
+        );
+        assert_matches!(
+            parser.check_datetime(&Value::Integer(b"123".to_vec(), 0), 0),
+            Ok(())
+        );
+        assert_matches!(
+            parser.check_datetime(&Value::LocalDate(b"2023-02-30".to_vec()), 0),
+            Err(Error(ErrorKind::InvalidDatetime(..)))
+        );
+        assert_matches!(
+            parser.check_datetime(&Value::LocalTime(b"12:60:00".to_vec()), 0),
+            Err(Error(ErrorKind::InvalidDatetime(..)))
+        );
+
+        assert_matches!(
+            parser.check_datetime(&Value::LocalDatetime(b"2023-01-02T25:00:00".to_vec()), 0),
+            Err(Error(ErrorKind::InvalidDatetime(lit))) if &*lit == "2023-01-02T25:00:00"
+        );
+        assert_matches!(
+            parser.check_datetime(&Value::OffsetDatetime(
+                b"2023-01-02T03:04:05-24:00".to_vec()
+            ), 0),
+            Err(Error(ErrorKind::InvalidDatetime(lit))) if &*lit == "2023-01-02T03:04:05-24:00"
+        );
+        assert_matches!(
+            parser.check_datetime(&Value::LocalDate(b"not a date".to_vec()), 0),
+            Err(Error(ErrorKind::InvalidDatetime(..)))
+        );
+
+        let mut parser = start_parser(b"");
+        assert!(!parser.reject_excess_precision);
+        assert_matches!(
+            parser.check_datetime(&Value::LocalTime(b"12:00:00.0123456789".to_vec()), 0),
+            Ok(())
+        );
+
+        parser.reject_excess_precision = true;
+        assert_matches!(
+            parser.check_datetime(&Value::LocalTime(b"12:00:00.012345678".to_vec()), 0),
+            Ok(())
+        );
+        assert_matches!(
+            parser.check_datetime(&Value::LocalDate(b"2023-01-02".to_vec()), 0),
+            Ok(())
+        );
+        assert_matches!(
+            parser.check_datetime(&Value::LocalTime(b"12:00:00.0123456789".to_vec()), 0),
+            Err(Error(ErrorKind::ExcessPrecision))
+        );
+        assert_matches!(
+            parser.check_datetime(
+                &Value::OffsetDatetime(b"2023-01-02T12:00:00.0123456789+01:00".to_vec()),
+                0
+            ),
+            Err(Error(ErrorKind::ExcessPrecision))
+        );
+    }
+
+    #[cfg(feature = "datetime")]
+    #[test]
+    fn parser_excess_precision_warning() {
+        use std::sync::{Arc, Mutex};
+
+        let warnings = Arc::new(Mutex::new(Vec::new()));
+        let mut parser = Parser::from_slice(b"a = 12:00:00.012345678\nb = 12:00:00.0123456789");
+        parser.on_warning = WarningSink::new({
+            let warnings = Arc::clone(&warnings);
+            move |warning| warnings.lock().unwrap().push(warning)
+        });
+        assert_matches!(parser.parse(), Ok(_));
+
+        let warnings = warnings.lock().unwrap().clone();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].kind(), WarningKind::ExcessPrecision);
+        assert_eq!(warnings[0].offset(), 27);
+
+        // Rejecting excess precision takes priority over warning about it
+        let mut parser = Parser::from_slice(b"a = 12:00:00.0123456789");
+        parser.on_warning = WarningSink::new(|_| unreachable!());
+        parser.reject_excess_precision = true;
+        assert_matches!(parser.parse(), Err(Error(ErrorKind::ExcessPrecision)));
+    }
+
+    #[test]
+    #[allow(clippy::too_many_lines, clippy::cognitive_complexity)]
     fn parser_parse_number_decimal() {
         let mut parser = start_parser(b"123_456");
         assert_matches!(
             parser.parse_number_decimal(),
-            Ok(Value::Integer(v)) if &*v == b"123456"
+            Ok(Value::Integer(v, 0)) if &*v == b"123456"
         );
 
         let mut parser = start_parser(b"+123_456");
         assert_matches!(
             parser.parse_number_decimal(),
-            Ok(Value::Integer(v)) if &*v == b"+123456"
+            Ok(Value::Integer(v, 0)) if &*v == b"+123456"
         );
 
         let mut parser = start_parser(b"-123_456");
         assert_matches!(
             parser.parse_number_decimal(),
-            Ok(Value::Integer(v)) if &*v == b"-123456"
+            Ok(Value::Integer(v, 0)) if &*v == b"-123456"
         );
 
         let mut parser = start_parser(b"0");
         assert_matches!(
             parser.parse_number_decimal(),
-            Ok(Value::Integer(v)) if &*v == b"0"
+            Ok(Value::Integer(v, 0)) if &*v == b"0"
+        );
+
+        let mut parser = start_parser(b"+1.5");
+        assert_matches!(
+            parser.parse_number_decimal(),
+            Ok(Value::Float(v)) if &*v == b"+1.5"
+        );
+
+        let mut parser = start_parser(b"-0.0");
+        assert_matches!(
+            parser.parse_number_decimal(),
+            Ok(Value::Float(v)) if &*v == b"-0.0"
         );
 
         let mut parser = start_parser(b"0123");
@@ -2327,7 +3199,7 @@ mod tests {
         let mut parser = start_parser(b"123.");
         assert_matches!(
             parser.parse_number_decimal(),
-            Err(Error(ErrorKind::InvalidNumber(..)))
+            Err(Error(ErrorKind::InvalidNumber(r))) if &*r == "missing digits after decimal point"
         );
 
         let mut parser = start_parser(b"123e");
@@ -2345,7 +3217,7 @@ mod tests {
         let mut parser = start_parser(b"123.e456");
         assert_matches!(
             parser.parse_number_decimal(),
-            Err(Error(ErrorKind::InvalidNumber(..)))
+            Err(Error(ErrorKind::InvalidNumber(r))) if &*r == "missing digits after decimal point"
         );
 
         let mut parser = start_parser(b"123.abc");
@@ -2361,6 +3233,58 @@ mod tests {
         );
     }
 
+    #[test]
+    fn parser_number_underscores() {
+        for (input, expected) in [
+            (&b"1_0_0"[..], &b"100"[..]),
+            (b"+1_0", b"+10"),
+            (b"0x1_f", b"1f"),
+            (b"0o1_7", b"17"),
+            (b"0b1_0", b"10"),
+            (b"1_0.0_1", b"10.01"),
+            (b"1e1_0", b"1e10"),
+        ] {
+            let mut parser = start_parser(input);
+            assert_matches!(
+                parser.parse_number_or_datetime(),
+                Ok(Value::Integer(v, 0) | Value::HexInt(v) | Value::OctalInt(v) | Value::BinaryInt(v) | Value::Float(v))
+                    if v == expected,
+                "{}",
+                String::from_utf8_lossy(input)
+            );
+        }
+
+        for (input, reason) in [
+            (&b"1__2"[..], "double underscore"),
+            (b"1_", "trailing underscore"),
+            (b"-_1", "leading underscore"),
+            (b"0x_1", "leading underscore"),
+            (b"0x1_", "trailing underscore"),
+            (b"0b1__0", "double underscore"),
+            (b"1_.0", "trailing underscore"),
+            (b"1._0", "leading underscore"),
+            (b"1.0_e1", "trailing underscore"),
+            (b"1e_1", "leading underscore"),
+            (b"1e1_", "trailing underscore"),
+            (b"0_1", "leading zero"),
+        ] {
+            let mut parser = start_parser(input);
+            assert_matches!(
+                parser.parse_number_or_datetime(),
+                Err(Error(ErrorKind::InvalidNumber(r))) if &*r == reason,
+                "{}",
+                String::from_utf8_lossy(input)
+            );
+        }
+
+        // A leading underscore isn't a number at all
+        let mut parser = start_parser(b"_1");
+        assert_matches!(
+            parser.parse_value(),
+            Err(Error(ErrorKind::ExpectedToken(..)))
+        );
+    }
+
     #[test]
     fn parser_parse_digits() {
         let mut parser = start_parser(b"123_456");
@@ -2410,6 +3334,20 @@ mod tests {
             parser.parse_digits(u8::is_ascii_digit),
             Err(Error(ErrorKind::InvalidNumber(..)))
         );
+
+        let mut parser = start_parser(b"1234_5678");
+        parser.max_number_length = 8;
+        assert_matches!(
+            parser.parse_digits(u8::is_ascii_digit),
+            Ok(v) if &*v == b"12345678"
+        );
+
+        let mut parser = start_parser(b"1234_56789");
+        parser.max_number_length = 8;
+        assert_matches!(
+            parser.parse_digits(u8::is_ascii_digit),
+            Err(Error(ErrorKind::InvalidNumber(r))) if &*r == "longer than 8 characters"
+        );
     }
 
     #[test]
@@ -2486,13 +3424,13 @@ mod tests {
         let mut parser = start_parser(b"123]");
         assert_matches!(
             parser.parse_array(),
-            Ok(s) if s == [Value::Integer(b"123".to_vec())]
+            Ok(s) if s == [Value::Integer(b"123".to_vec(), 0)]
         );
 
         let mut parser = start_parser(b"123,]");
         assert_matches!(
             parser.parse_array(),
-            Ok(s) if s == [Value::Integer(b"123".to_vec())]
+            Ok(s) if s == [Value::Integer(b"123".to_vec(), 0)]
         );
 
         let mut parser = start_parser(indoc! {br"
@@ -2501,16 +3439,16 @@ mod tests {
         "});
         assert_matches!(
             parser.parse_array(),
-            Ok(s) if s == [Value::Integer(b"123".to_vec())]
+            Ok(s) if s == [Value::Integer(b"123".to_vec(), 4)]
         );
 
         let mut parser = start_parser(br"123, 456, 789]");
         assert_matches!(
             parser.parse_array(),
             Ok(a) if a == [
-                Value::Integer(b"123".to_vec()),
-                Value::Integer(b"456".to_vec()),
-                Value::Integer(b"789".to_vec())
+                Value::Integer(b"123".to_vec(), 0),
+                Value::Integer(b"456".to_vec(), 5),
+                Value::Integer(b"789".to_vec(), 10)
             ]
         );
 
@@ -2518,9 +3456,9 @@ mod tests {
         assert_matches!(
             parser.parse_array(),
             Ok(a) if a == [
-                Value::Integer(b"123".to_vec()),
-                Value::Integer(b"456".to_vec()),
-                Value::Integer(b"789".to_vec())
+                Value::Integer(b"123".to_vec(), 0),
+                Value::Integer(b"456".to_vec(), 5),
+                Value::Integer(b"789".to_vec(), 10)
             ]
         );
 
@@ -2533,9 +3471,9 @@ mod tests {
         assert_matches!(
             parser.parse_array(),
             Ok(a) if a == [
-                Value::Integer(b"123".to_vec()),
-                Value::Integer(b"456".to_vec()),
-                Value::Integer(b"789".to_vec())
+                Value::Integer(b"123".to_vec(), 4),
+                Value::Integer(b"456".to_vec(), 13),
+                Value::Integer(b"789".to_vec(), 22)
             ]
         );
 
@@ -2548,9 +3486,9 @@ mod tests {
         assert_matches!(
             parser.parse_array(),
             Ok(a) if a == [
-                Value::Integer(b"123".to_vec()),
-                Value::Integer(b"456".to_vec()),
-                Value::Integer(b"789".to_vec())
+                Value::Integer(b"123".to_vec(), 4),
+                Value::Integer(b"456".to_vec(), 13),
+                Value::Integer(b"789".to_vec(), 32)
             ]
         );
 
@@ -2559,6 +3497,12 @@ mod tests {
             parser.parse_array(),
             Err(Error(ErrorKind::ExpectedToken(..)))
         );
+
+        let mut parser = start_parser(b"123, 456");
+        assert_matches!(
+            parser.parse_array(),
+            Err(Error(ErrorKind::UnterminatedArray))
+        );
     }
 
     #[test]
@@ -2572,16 +3516,16 @@ mod tests {
         let mut parser = start_parser(b"abc = 123 }");
         assert_matches!(
             parser.parse_inline_table(),
-            Ok(t) if t == hashmap! { "abc".into() => Value::Integer(b"123".to_vec()) }
+            Ok(t) if t == hashmap! { "abc".into() => Value::Integer(b"123".to_vec(), 6) }
         );
 
         let mut parser = start_parser(br"abc = 123, def = 456, ghi = 789 }");
         assert_matches!(
             parser.parse_inline_table(),
             Ok(t) if t == hashmap! {
-                "abc".into() => Value::Integer(b"123".to_vec()),
-                "def".into() => Value::Integer(b"456".to_vec()),
-                "ghi".into() => Value::Integer(b"789".to_vec()),
+                "abc".into() => Value::Integer(b"123".to_vec(), 6),
+                "def".into() => Value::Integer(b"456".to_vec(), 17),
+                "ghi".into() => Value::Integer(b"789".to_vec(), 28),
             }
         );
 
@@ -2590,8 +3534,8 @@ mod tests {
             parser.parse_inline_table(),
             Ok(t) if t == hashmap! {
                 "abc".into() => Value::InlineTable(hashmap! {
-                    "def".into() => Value::Integer(b"123".to_vec()),
-                    "ghi".into() => Value::Integer(b"456".to_vec()),
+                    "def".into() => Value::Integer(b"123".to_vec(), 14),
+                    "ghi".into() => Value::Integer(b"456".to_vec(), 25),
                 }),
             }
         );
@@ -2601,8 +3545,8 @@ mod tests {
             parser.parse_inline_table(),
             Ok(t) if t == hashmap! {
                 "abc".into() => Value::DottedKeyTable(hashmap! {
-                    "def".into() => Value::Integer(b"123".to_vec()),
-                    "ghi".into() => Value::Integer(b"456".to_vec()),
+                    "def".into() => Value::Integer(b"123".to_vec(), 10),
+                    "ghi".into() => Value::Integer(b"456".to_vec(), 25),
                 }),
             }
         );
@@ -2614,6 +3558,20 @@ mod tests {
         );
 
         let mut parser = start_parser(b"abc = 123, }");
+        assert_matches!(
+            parser.parse_inline_table(),
+            Err(Error(ErrorKind::TrailingComma(9)))
+        );
+
+        let mut parser = start_parser(b"abc = 123 , }");
+        parser.allow_inline_table_trailing_comma = true;
+        assert_matches!(
+            parser.parse_inline_table(),
+            Ok(t) if t == hashmap! { "abc".into() => Value::Integer(b"123".to_vec(), 6) }
+        );
+
+        let mut parser = start_parser(b"abc = 123,, }");
+        parser.allow_inline_table_trailing_comma = true;
         assert_matches!(
             parser.parse_inline_table(),
             Err(Error(ErrorKind::ExpectedToken(..)))
@@ -2705,14 +3663,23 @@ mod tests {
         let mut parser = start_parser(b"# comment\xff");
         assert_matches!(
             parser.skip_comment(),
-            Err(Error(ErrorKind::InvalidEncoding))
+            Err(Error(ErrorKind::InvalidEncoding(9)))
         );
 
         let mut parser = start_parser(b"# comment\0");
         assert_matches!(
             parser.skip_comment(),
-            Err(Error(ErrorKind::IllegalChar(..)))
+            Err(Error(ErrorKind::IllegalChar(b'\0', 9)))
         );
+
+        let mut parser = start_parser(b"# bell \x07 comment");
+        assert_matches!(
+            parser.skip_comment(),
+            Err(Error(ErrorKind::IllegalChar(0x07, 7)))
+        );
+
+        let mut parser = Parser::from_slice(b"a = 1\nb = 2 # bell \x07\n");
+        assert_matches!(parser.parse(), Err(Error(ErrorKind::IllegalChar(0x07, 19))));
     }
 
     #[test]
@@ -2723,6 +3690,13 @@ mod tests {
                 b = c
             "}),
             line: b"",
+            max_number_length: DEFAULT_MAX_NUMBER_LENGTH,
+            allow_inline_table_trailing_comma: false,
+            #[cfg(feature = "datetime")]
+            validate_datetimes: false,
+            #[cfg(feature = "datetime")]
+            reject_excess_precision: false,
+            on_warning: WarningSink::default(),
         };
         assert!(parser.next_line().is_some());
         assert_eq!(parser.line, b"[a]");
@@ -2741,7 +3715,7 @@ mod tests {
                 "e".into() => Value::Table(hashmap! {}),
                 "f".into() => Value::ArrayOfTables(vec![hashmap! {}]),
                 "g".into() => Value::InlineTable(hashmap! {}),
-                "h".into() => Value::Integer(b"123".to_vec()),
+                "h".into() => Value::Integer(b"123".to_vec(), 0),
             }),
         };
         assert_eq!(table.clone().get_subtable(&[]), Some(&mut table));
@@ -4,6 +4,8 @@ use std::str;
 #[derive(Debug, Clone)]
 pub struct Reader<'a> {
     bytes: &'a [u8],
+    offset: usize,
+    line_end: usize,
     line_no: usize,
 }
 
@@ -17,7 +19,12 @@ impl<'a> Reader<'a> {
     /// Create a TOML reader from a byte slice.
     #[inline]
     pub const fn from_slice(bytes: &'a [u8]) -> Self {
-        Self { bytes, line_no: 0 }
+        Self {
+            bytes,
+            offset: 0,
+            line_end: 0,
+            line_no: 0,
+        }
     }
 
     /// Gets the next line from the source. Returns `Ok(None)` if the end of the source is reached.
@@ -28,6 +35,8 @@ impl<'a> Reader<'a> {
                 let line = line.strip_suffix(b"\r").unwrap_or(line); // Windows newline
 
                 self.bytes = &self.bytes[offset + 1..]; // Exclude the \n here too
+                self.line_end = self.offset + line.len();
+                self.offset += offset + 1;
                 self.line_no += 1;
 
                 Some(line)
@@ -39,6 +48,8 @@ impl<'a> Reader<'a> {
                     let line = self.bytes;
 
                     self.bytes = &self.bytes[self.bytes.len()..];
+                    self.line_end = self.offset + line.len();
+                    self.offset = self.line_end;
                     self.line_no += 1;
 
                     Some(line)
@@ -46,6 +57,23 @@ impl<'a> Reader<'a> {
             }
         }
     }
+
+    /// Gets the byte offset in the source of the start of the next line.
+    pub const fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Gets the byte offset in the source of the end of the line most recently returned by
+    /// [`Self::next_line`], excluding the newline.
+    pub const fn line_end(&self) -> usize {
+        self.line_end
+    }
+
+    /// Gets the byte offset in the source of `rest`, which must be a suffix of the line most
+    /// recently returned by [`Self::next_line`].
+    pub const fn offset_of(&self, rest: &[u8]) -> usize {
+        self.line_end - rest.len()
+    }
 }
 
 #[inline]
@@ -78,6 +106,7 @@ mod tests {
         let r = Reader::from_str(s);
 
         assert_eq!(r.bytes, s.as_bytes());
+        assert_eq!(r.offset, 0);
         assert_eq!(r.line_no, 0);
     }
 
@@ -92,6 +121,7 @@ mod tests {
         let r = Reader::from_slice(s);
 
         assert_eq!(r.bytes, s);
+        assert_eq!(r.offset, 0);
         assert_eq!(r.line_no, 0);
     }
 
@@ -104,25 +134,68 @@ mod tests {
                 c = 2
                 d = 3
             "},
+            offset: 0,
+            line_end: 0,
             line_no: 0,
         };
 
         assert_matches!(reader.next_line(), Some(b"[a]"));
+        assert_eq!(reader.offset, 4);
         assert_eq!(reader.line_no, 1);
 
         assert_matches!(reader.next_line(), Some(b"b = 1"));
+        assert_eq!(reader.offset, 10);
         assert_eq!(reader.line_no, 2);
 
         assert_matches!(reader.next_line(), Some(b"c = 2"));
+        assert_eq!(reader.offset, 16);
         assert_eq!(reader.line_no, 3);
 
         assert_matches!(reader.next_line(), Some(b"d = 3"));
+        assert_eq!(reader.offset, 22);
         assert_eq!(reader.line_no, 4);
 
         assert_matches!(reader.next_line(), None);
+        assert_eq!(reader.offset, 22);
         assert_eq!(reader.line_no, 4);
     }
 
+    #[test]
+    fn slice_reader_offset_of() {
+        let mut reader = Reader::from_slice(b"a = 1\r\nb = 2\nc = 3");
+
+        let line = reader.next_line().unwrap();
+        assert_eq!(reader.offset_of(line), 0);
+        assert_eq!(reader.offset_of(&line[4..]), 4);
+        assert_eq!(reader.offset_of(b""), 5);
+
+        let line = reader.next_line().unwrap();
+        assert_eq!(reader.offset_of(line), 7);
+        assert_eq!(reader.offset_of(&line[2..]), 9);
+
+        let line = reader.next_line().unwrap();
+        assert_eq!(reader.offset_of(line), 13);
+        assert_eq!(reader.offset_of(b""), 18);
+    }
+
+    #[test]
+    fn slice_reader_offset() {
+        let mut reader = Reader::from_slice(b"a = 1\r\nb = 2\nc = 3");
+        assert_eq!(reader.offset(), 0);
+
+        reader.next_line().unwrap();
+        assert_eq!(reader.offset(), 7);
+        assert_eq!(reader.line_end(), 5);
+
+        reader.next_line().unwrap();
+        assert_eq!(reader.offset(), 13);
+        assert_eq!(reader.line_end(), 12);
+
+        reader.next_line().unwrap();
+        assert_eq!(reader.offset(), 18);
+        assert_eq!(reader.line_end(), 18);
+    }
+
     #[test]
     fn test_utf8_len() {
         let mut buf = [0; 4];
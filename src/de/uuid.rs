@@ -0,0 +1,109 @@
+//! Serializes and deserializes a [`Uuid`] as a string.
+//!
+//! This is intended to be used with `#[serde(with = "soml::de::uuid")]`. UUIDs are serialized in
+//! the lower case hyphenated form, for example `"550e8400-e29b-41d4-a716-446655440000"`. Any form
+//! accepted by [`Uuid::parse_str`] can be deserialized, including upper case and unhyphenated
+//! UUIDs.
+//!
+//! [`Uuid`]: ::uuid::Uuid
+//! [`Uuid::parse_str`]: ::uuid::Uuid::parse_str
+
+use std::fmt;
+
+use ::uuid::Uuid;
+use serde::{de, ser};
+
+/// Serializes a [`Uuid`] as a lower case hyphenated string.
+///
+/// # Errors
+///
+/// Returns an error if the serializer fails to serialize the string.
+#[inline]
+pub fn serialize<S>(value: &Uuid, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: ser::Serializer,
+{
+    serializer.serialize_str(value.hyphenated().encode_lower(&mut Uuid::encode_buffer()))
+}
+
+/// Deserializes a [`Uuid`] from a string.
+///
+/// # Errors
+///
+/// Returns an error if the value is not a string or is not a valid UUID.
+#[inline]
+pub fn deserialize<'de, D>(deserializer: D) -> Result<Uuid, D::Error>
+where
+    D: de::Deserializer<'de>,
+{
+    deserializer.deserialize_str(UuidVisitor)
+}
+
+struct UuidVisitor;
+
+impl de::Visitor<'_> for UuidVisitor {
+    type Value = Uuid;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a UUID string")
+    }
+
+    fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Uuid::parse_str(value).map_err(|_| E::invalid_value(de::Unexpected::Str(value), &self))
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage, coverage(off))]
+mod tests {
+    use assert_matches::assert_matches;
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::de::{Error, ErrorKind};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Config {
+        #[serde(with = "super")]
+        id: Uuid,
+    }
+
+    const ID: Uuid = Uuid::from_u128(0x550e_8400_e29b_41d4_a716_4466_5544_0000);
+
+    #[test]
+    fn test_uuid() {
+        let result: Config =
+            crate::from_str(r#"id = "550e8400-e29b-41d4-a716-446655440000""#).unwrap();
+        assert_eq!(result.id, ID);
+
+        let result: Config = crate::from_str(r#"id = "550E8400E29B41D4A716446655440000""#).unwrap();
+        assert_eq!(result.id, ID);
+
+        assert_eq!(
+            crate::to_string(&Config { id: ID }).unwrap(),
+            "id = \"550e8400-e29b-41d4-a716-446655440000\"\n"
+        );
+
+        assert_matches!(
+            crate::from_str::<Config>(r#"id = "550e8400-e29b-41d4-a716""#),
+            Err(Error(ErrorKind::InvalidValue(unexp, exp)))
+                if &*unexp == r#"string "550e8400-e29b-41d4-a716""# && &*exp == "a UUID string"
+        );
+        assert_matches!(
+            crate::from_str::<Config>("id = 42"),
+            Err(Error(ErrorKind::InvalidType(..)))
+        );
+    }
+
+    #[test]
+    fn test_uuid_round_trip() {
+        for id in [Uuid::nil(), Uuid::from_u128(u128::MAX), ID] {
+            let config = Config { id };
+            let toml = crate::to_string(&config).unwrap();
+            assert_eq!(crate::from_str::<Config>(&toml).unwrap(), config);
+        }
+    }
+}
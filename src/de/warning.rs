@@ -0,0 +1,152 @@
+use std::fmt;
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// A non-fatal issue found while deserializing, passed to [`Deserializer::on_warning`].
+///
+/// [`Deserializer::on_warning`]: super::Deserializer::on_warning
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Warning {
+    kind: WarningKind,
+    offset: usize,
+}
+
+impl Warning {
+    /// Returns the kind of warning.
+    #[must_use]
+    #[inline]
+    pub fn kind(&self) -> WarningKind {
+        self.kind
+    }
+
+    /// Returns the byte offset in the input of the value which caused the warning.
+    #[must_use]
+    #[inline]
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+}
+
+impl fmt::Display for Warning {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at offset {}", self.kind, self.offset)
+    }
+}
+
+/// The kind of a [`Warning`].
+///
+/// More kinds may be added in the future.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum WarningKind {
+    /// A date-time had more than nanosecond precision, and the excess digits were truncated.
+    ExcessPrecision,
+    /// An integer was deserialized as a float which cannot represent it exactly.
+    LossyFloat,
+}
+
+impl fmt::Display for WarningKind {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::ExcessPrecision => f.write_str("date-time has more than nanosecond precision"),
+            Self::LossyFloat => f.write_str("integer cannot be represented exactly as a float"),
+        }
+    }
+}
+
+type Callback = dyn FnMut(Warning) + Send;
+
+// The callback set with `Deserializer::on_warning`. This is shared between the parser and all of
+// the value deserializers, hence the `Arc<Mutex<_>>` and the `Send + 'static` bound on the
+// callback, and does nothing by default
+#[derive(Clone, Default)]
+pub(super) struct WarningSink(Option<Arc<Mutex<Callback>>>);
+
+impl WarningSink {
+    pub fn new(callback: impl FnMut(Warning) + Send + 'static) -> Self {
+        Self(Some(Arc::new(Mutex::new(callback))))
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.0.is_some()
+    }
+
+    pub fn emit(&self, kind: WarningKind, offset: usize) {
+        if let Some(ref callback) = self.0 {
+            let mut callback = callback.lock().unwrap_or_else(PoisonError::into_inner);
+            (callback)(Warning { kind, offset });
+        }
+    }
+}
+
+impl fmt::Debug for WarningSink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("WarningSink")
+            .field(&self.is_enabled())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage, coverage(off))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn warning_display() {
+        let warning = Warning {
+            kind: WarningKind::ExcessPrecision,
+            offset: 12,
+        };
+        assert_eq!(warning.kind(), WarningKind::ExcessPrecision);
+        assert_eq!(warning.offset(), 12);
+        assert_eq!(
+            warning.to_string(),
+            "date-time has more than nanosecond precision at offset 12"
+        );
+
+        let warning = Warning {
+            kind: WarningKind::LossyFloat,
+            offset: 4,
+        };
+        assert_eq!(warning.offset(), 4);
+        assert_eq!(
+            warning.to_string(),
+            "integer cannot be represented exactly as a float at offset 4"
+        );
+    }
+
+    #[test]
+    fn warning_sink() {
+        let sink = WarningSink::default();
+        assert!(!sink.is_enabled());
+        assert_eq!(format!("{sink:?}"), "WarningSink(false)");
+        sink.emit(WarningKind::LossyFloat, 0);
+
+        let warnings = Arc::new(Mutex::new(Vec::new()));
+        let sink = WarningSink::new({
+            let warnings = Arc::clone(&warnings);
+            move |warning| warnings.lock().unwrap().push(warning)
+        });
+        assert!(sink.is_enabled());
+        assert_eq!(format!("{sink:?}"), "WarningSink(true)");
+
+        let shared = sink.clone();
+        shared.emit(WarningKind::ExcessPrecision, 3);
+        sink.emit(WarningKind::LossyFloat, 7);
+        assert_eq!(
+            *warnings.lock().unwrap(),
+            [
+                Warning {
+                    kind: WarningKind::ExcessPrecision,
+                    offset: 3,
+                },
+                Warning {
+                    kind: WarningKind::LossyFloat,
+                    offset: 7,
+                },
+            ]
+        );
+    }
+}
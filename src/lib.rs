@@ -16,11 +16,14 @@
 //!
 //! # Features
 //!
-//! Feature    | Default | Description
-//! :----------|:-------:|:----------------------------------------------
-//! `std`      |    ✅    | Enables `std` support
-//! `strict`   |    ✅    | Enables extra checks for strict compliance with the TOML spec (see below)
-//! `datetime` |    ✅    | Enables support for TOML date-time values
+//! Feature          | Default | Description
+//! :----------------|:-------:|:----------------------------------------------
+//! `std`            |    ✅    | Enables `std` support
+//! `strict`         |    ✅    | Enables extra checks for strict compliance with the TOML spec (see below)
+//! `datetime`       |    ✅    | Enables support for TOML date-time values
+//! `toml-compat`    |         | Enables conversions to and from the [toml][toml-rs] crate's `Value` type
+//! `preserve_order` |         | Keeps the keys of a `Table` in insertion order rather than sorted order (see below)
+//! `uuid`           |         | Enables `soml::de::uuid` for (de)serializing a [`Uuid`][uuid] as a string
 //!
 //! ## The `strict` feature
 //!
@@ -39,6 +42,18 @@
 //! The exact list of checks controlled by the `strict` feature is considered an implementation detail and is subject to change.
 //! Changes to which not-strictly-valid TOML is accepted when `strict` is disabled is not considered a breaking change.
 //!
+//! ## The `preserve_order` feature
+//!
+//! By default a `Table` is backed by a `BTreeMap`, so its keys are always sorted and are serialized in alphabetical order.
+//! Enabling the `preserve_order` feature backs `Table` with an [`IndexMap`][indexmap] instead, so keys are kept in the order they are inserted.
+//! `Table` has the same API either way, so enabling the feature only changes the order of keys.
+//! This affects `Value`s built using `Table::insert` or `FromIterator`, as well as those deserialized from other formats which preserve order.
+//!
+//! The order of keys in a TOML document is not currently tracked when parsing, so tables deserialized from TOML do not retain their original order.
+//!
+//! [indexmap]: https://crates.io/crates/indexmap
+//! [uuid]: https://crates.io/crates/uuid
+//!
 //! <!-- binsize start -->
 //!
 //! # Comparison of TOML crates
@@ -88,14 +103,30 @@
 #![cfg_attr(not(any(feature = "std", test)), no_std)]
 
 #[doc(inline)]
-pub use self::de::{from_reader, from_slice, from_str, Deserializer};
+pub use self::de::{
+    from_bufread, from_reader, from_slice, from_str, from_str_tracking, Deserializer, UnusedKeys,
+};
 #[doc(inline)]
 pub use self::map::Table;
 #[doc(inline)]
-pub use self::ser::{to_string, Serializer};
+pub use self::ser::{append_to_string, to_fmt, to_string, to_string_in, Serializer};
 #[doc(inline)]
 pub use self::value::Value;
 
+// Like maplit's btreemap! but creates a Table, which is not a BTreeMap
+#[cfg(test)]
+#[allow(edition_2024_expr_fragment_specifier)]
+macro_rules! table {
+    () => {
+        $crate::Table::new()
+    };
+    ($($key:expr => $value:expr),* $(,)?) => {{
+        let mut table = $crate::Table::new();
+        $(table.insert($key, $value);)*
+        table
+    }};
+}
+
 pub mod de;
 pub mod map;
 pub mod ser;
@@ -1,44 +1,1055 @@
 //! Generic TOML map types.
+//!
+//! By default tables are backed by a [`BTreeMap`], so keys are always kept in sorted order. When
+//! the `preserve_order` feature is enabled tables are backed by an [`IndexMap`] instead, and keys
+//! are kept in the order they were inserted. [`Map`] has the same API with either backend, so
+//! enabling the feature only changes the order of keys. A [`Table`] can be converted to and from a
+//! [`BTreeMap`] for any functionality not covered by this API.
+//!
+//! [`BTreeMap`]: std::collections::BTreeMap
+//! [`IndexMap`]: https://docs.rs/indexmap/latest/indexmap/map/struct.IndexMap.html
 
-use std::collections::{btree_map, BTreeMap};
+use std::borrow::Borrow;
+use std::collections::BTreeMap;
+#[cfg(not(feature = "preserve_order"))]
+use std::collections::{btree_map as map, BTreeMap as MapImpl};
+use std::hash::Hash;
+use std::iter::FusedIterator;
+use std::ops::RangeBounds;
+#[cfg(feature = "preserve_order")]
+use std::vec;
+use std::{fmt, ops};
+
+#[cfg(feature = "preserve_order")]
+use indexmap::{map, IndexMap as MapImpl};
+use serde::{de, ser};
 
 use crate::Value;
 
 /// A generic map type.
-pub type Map<K, V> = BTreeMap<K, V>;
+///
+/// This is a wrapper around either a [`BTreeMap`] or an
+/// [`IndexMap`](https://docs.rs/indexmap/latest/indexmap/map/struct.IndexMap.html) depending on
+/// whether the `preserve_order` feature is enabled.
+pub struct Map<K, V> {
+    map: MapImpl<K, V>,
+}
 
 /// A TOML table type.
 pub type Table = Map<String, Value>;
 
-/// An iterator over a [`Table`]'s values.
-pub type Iter<'a> = btree_map::Iter<'a, String, Value>;
+impl Map<String, Value> {
+    /// Creates an empty table.
+    #[must_use]
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            map: MapImpl::new(),
+        }
+    }
 
-/// A mutable iterator over a [`Table`]'s values.
-pub type IterMut<'a> = btree_map::IterMut<'a, String, Value>;
+    /// Creates an empty table with space for at least `capacity` entries.
+    ///
+    /// A [`BTreeMap`] cannot preallocate space, so the capacity is ignored and this is the same as
+    /// [`Map::new`] unless the `preserve_order` feature is enabled.
+    #[must_use]
+    #[inline]
+    pub fn with_capacity(capacity: usize) -> Self {
+        #[cfg(not(feature = "preserve_order"))]
+        let _ = capacity;
+        Self {
+            #[cfg(not(feature = "preserve_order"))]
+            map: MapImpl::new(),
+            #[cfg(feature = "preserve_order")]
+            map: MapImpl::with_capacity(capacity),
+        }
+    }
 
-/// An owning iterator over a [`Table`]'s values.
-pub type IntoIter = btree_map::IntoIter<String, Value>;
+    /// Removes all entries from the table.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.map.clear();
+    }
+
+    /// Returns a reference to the value for the given key.
+    #[inline]
+    pub fn get<Q>(&self, key: &Q) -> Option<&Value>
+    where
+        String: Borrow<Q>,
+        Q: ?Sized + Ord + Eq + Hash,
+    {
+        self.map.get(key)
+    }
+
+    /// Returns a mutable reference to the value for the given key.
+    #[inline]
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut Value>
+    where
+        String: Borrow<Q>,
+        Q: ?Sized + Ord + Eq + Hash,
+    {
+        self.map.get_mut(key)
+    }
+
+    /// Returns the key and value for the given key.
+    #[inline]
+    pub fn get_key_value<Q>(&self, key: &Q) -> Option<(&String, &Value)>
+    where
+        String: Borrow<Q>,
+        Q: ?Sized + Ord + Eq + Hash,
+    {
+        self.map.get_key_value(key)
+    }
+
+    /// Returns `true` if the table contains the given key.
+    #[inline]
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        String: Borrow<Q>,
+        Q: ?Sized + Ord + Eq + Hash,
+    {
+        self.map.contains_key(key)
+    }
+
+    /// Inserts a key-value pair into the table, returning the previous value for the key if there
+    /// was one.
+    ///
+    /// If the key was already present its position is kept when the `preserve_order` feature is
+    /// enabled.
+    #[inline]
+    pub fn insert(&mut self, key: String, value: Value) -> Option<Value> {
+        self.map.insert(key, value)
+    }
+
+    /// Removes a key from the table, returning its value if it was present.
+    ///
+    /// The order of the remaining entries is preserved when the `preserve_order` feature is
+    /// enabled.
+    #[inline]
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<Value>
+    where
+        String: Borrow<Q>,
+        Q: ?Sized + Ord + Eq + Hash,
+    {
+        #[cfg(not(feature = "preserve_order"))]
+        return self.map.remove(key);
+        #[cfg(feature = "preserve_order")]
+        return self.map.shift_remove(key);
+    }
+
+    /// Removes a key from the table, returning the stored key and value if it was present.
+    ///
+    /// The order of the remaining entries is preserved when the `preserve_order` feature is
+    /// enabled.
+    #[inline]
+    pub fn remove_entry<Q>(&mut self, key: &Q) -> Option<(String, Value)>
+    where
+        String: Borrow<Q>,
+        Q: ?Sized + Ord + Eq + Hash,
+    {
+        #[cfg(not(feature = "preserve_order"))]
+        return self.map.remove_entry(key);
+        #[cfg(feature = "preserve_order")]
+        return self.map.shift_remove_entry(key);
+    }
+
+    /// Returns the first entry in the table.
+    ///
+    /// This is the entry with the smallest key, or the first entry inserted if the
+    /// `preserve_order` feature is enabled.
+    #[must_use]
+    #[inline]
+    pub fn first_key_value(&self) -> Option<(&String, &Value)> {
+        self.map.iter().next()
+    }
+
+    /// Returns the last entry in the table.
+    ///
+    /// This is the entry with the largest key, or the last entry inserted if the `preserve_order`
+    /// feature is enabled.
+    #[must_use]
+    #[inline]
+    pub fn last_key_value(&self) -> Option<(&String, &Value)> {
+        self.map.iter().next_back()
+    }
+
+    /// Removes and returns the first entry in the table.
+    ///
+    /// See [`Map::first_key_value`] for which entry is first.
+    #[inline]
+    pub fn pop_first(&mut self) -> Option<(String, Value)> {
+        // BTreeMap::pop_first needs Rust 1.66
+        #[cfg(not(feature = "preserve_order"))]
+        return self
+            .map
+            .keys()
+            .next()
+            .cloned()
+            .and_then(|key| self.map.remove_entry(&key));
+        #[cfg(feature = "preserve_order")]
+        return self.map.shift_remove_index(0);
+    }
+
+    /// Removes and returns the last entry in the table.
+    ///
+    /// See [`Map::last_key_value`] for which entry is last.
+    #[inline]
+    pub fn pop_last(&mut self) -> Option<(String, Value)> {
+        // BTreeMap::pop_last needs Rust 1.66
+        #[cfg(not(feature = "preserve_order"))]
+        return self
+            .map
+            .keys()
+            .next_back()
+            .cloned()
+            .and_then(|key| self.map.remove_entry(&key));
+        #[cfg(feature = "preserve_order")]
+        return self.map.pop();
+    }
+
+    /// Returns an iterator over the entries whose keys are within `range`.
+    ///
+    /// The entries are returned in sorted order, or in insertion order if the `preserve_order`
+    /// feature is enabled. Without the feature this is a lookup in the tree, while with it every
+    /// entry in the table is checked.
+    ///
+    /// # Panics
+    ///
+    /// Without the `preserve_order` feature this panics if the start of the range is greater than
+    /// the end, or if they are equal and both excluded, as [`BTreeMap::range`] does.
+    #[inline]
+    pub fn range<Q, R>(&self, range: R) -> Range<'_>
+    where
+        String: Borrow<Q>,
+        Q: ?Sized + Ord + Eq + Hash,
+        R: RangeBounds<Q>,
+    {
+        #[cfg(not(feature = "preserve_order"))]
+        let iter = self.map.range(range);
+        #[cfg(feature = "preserve_order")]
+        let iter = self
+            .map
+            .iter()
+            .filter(|&(key, _)| range.contains(key.borrow()))
+            .collect::<Vec<_>>()
+            .into_iter();
+        Range { iter }
+    }
+
+    /// Moves all entries from `other` into `self`, leaving `other` empty.
+    #[inline]
+    pub fn append(&mut self, other: &mut Self) {
+        #[cfg(not(feature = "preserve_order"))]
+        self.map.append(&mut other.map);
+        #[cfg(feature = "preserve_order")]
+        self.map.extend(other.map.drain(..));
+    }
+
+    /// Returns the entry for the given key for in-place manipulation.
+    #[inline]
+    pub fn entry<S>(&mut self, key: S) -> Entry<'_>
+    where
+        S: Into<String>,
+    {
+        match self.map.entry(key.into()) {
+            map::Entry::Vacant(vacant) => Entry::Vacant(VacantEntry { vacant }),
+            map::Entry::Occupied(occupied) => Entry::Occupied(OccupiedEntry { occupied }),
+        }
+    }
+
+    /// Returns the number of entries in the table.
+    #[must_use]
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns `true` if the table contains no entries.
+    #[must_use]
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Returns an iterator over the entries of the table.
+    #[must_use]
+    #[inline]
+    pub fn iter(&self) -> Iter<'_> {
+        Iter {
+            iter: self.map.iter(),
+        }
+    }
+
+    /// Returns a mutable iterator over the entries of the table.
+    #[inline]
+    pub fn iter_mut(&mut self) -> IterMut<'_> {
+        IterMut {
+            iter: self.map.iter_mut(),
+        }
+    }
+
+    /// Returns an iterator over the keys of the table.
+    #[must_use]
+    #[inline]
+    pub fn keys(&self) -> Keys<'_> {
+        Keys {
+            iter: self.map.keys(),
+        }
+    }
+
+    /// Returns an iterator over the values of the table.
+    #[must_use]
+    #[inline]
+    pub fn values(&self) -> Values<'_> {
+        Values {
+            iter: self.map.values(),
+        }
+    }
+
+    /// Returns a mutable iterator over the values of the table.
+    #[inline]
+    pub fn values_mut(&mut self) -> ValuesMut<'_> {
+        ValuesMut {
+            iter: self.map.values_mut(),
+        }
+    }
+
+    /// Returns an owning iterator over the keys of the table.
+    #[must_use]
+    #[inline]
+    pub fn into_keys(self) -> IntoKeys {
+        IntoKeys {
+            iter: self.map.into_keys(),
+        }
+    }
+
+    /// Returns an owning iterator over the values of the table.
+    #[must_use]
+    #[inline]
+    pub fn into_values(self) -> IntoValues {
+        IntoValues {
+            iter: self.map.into_values(),
+        }
+    }
+
+    /// Retains only the entries for which `f` returns `true`.
+    #[inline]
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&String, &mut Value) -> bool,
+    {
+        self.map.retain(|key, value| f(key, value));
+    }
+}
+
+impl Default for Map<String, Value> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clone for Map<String, Value> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            map: self.map.clone(),
+        }
+    }
+
+    #[inline]
+    fn clone_from(&mut self, source: &Self) {
+        self.map.clone_from(&source.map);
+    }
+}
+
+impl PartialEq for Map<String, Value> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.map.eq(&other.map)
+    }
+}
+
+impl fmt::Debug for Map<String, Value> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.map.fmt(f)
+    }
+}
+
+/// Returns the value for the given key.
+///
+/// # Panics
+///
+/// Panics if the key is not present in the table. Use [`Map::get`] to handle a missing key.
+impl<Q> ops::Index<&Q> for Map<String, Value>
+where
+    String: Borrow<Q>,
+    Q: ?Sized + Ord + Eq + Hash,
+{
+    type Output = Value;
+
+    #[inline]
+    fn index(&self, index: &Q) -> &Self::Output {
+        self.map.index(index)
+    }
+}
+
+/// Returns a mutable reference to the value for the given key.
+///
+/// # Panics
+///
+/// Panics if the key is not present in the table. Use [`Map::get_mut`] to handle a missing key,
+/// or [`Map::entry`] to insert one.
+impl<Q> ops::IndexMut<&Q> for Map<String, Value>
+where
+    String: Borrow<Q>,
+    Q: ?Sized + Ord + Eq + Hash,
+{
+    #[allow(clippy::expect_used)]
+    #[inline]
+    fn index_mut(&mut self, index: &Q) -> &mut Self::Output {
+        self.map.get_mut(index).expect("no entry found for key")
+    }
+}
+
+impl FromIterator<(String, Value)> for Map<String, Value> {
+    #[inline]
+    fn from_iter<T>(iter: T) -> Self
+    where
+        T: IntoIterator<Item = (String, Value)>,
+    {
+        Self {
+            map: MapImpl::from_iter(iter),
+        }
+    }
+}
+
+impl Extend<(String, Value)> for Map<String, Value> {
+    #[inline]
+    fn extend<T>(&mut self, iter: T)
+    where
+        T: IntoIterator<Item = (String, Value)>,
+    {
+        self.map.extend(iter);
+    }
+}
+
+impl<const N: usize> From<[(String, Value); N]> for Map<String, Value> {
+    #[inline]
+    fn from(arr: [(String, Value); N]) -> Self {
+        Self {
+            map: MapImpl::from(arr),
+        }
+    }
+}
+
+impl From<BTreeMap<String, Value>> for Map<String, Value> {
+    #[inline]
+    fn from(map: BTreeMap<String, Value>) -> Self {
+        #[cfg(not(feature = "preserve_order"))]
+        return Self { map };
+        #[cfg(feature = "preserve_order")]
+        return map.into_iter().collect();
+    }
+}
+
+impl From<Map<String, Value>> for BTreeMap<String, Value> {
+    #[inline]
+    fn from(table: Map<String, Value>) -> Self {
+        #[cfg(not(feature = "preserve_order"))]
+        return table.map;
+        #[cfg(feature = "preserve_order")]
+        return table.map.into_iter().collect();
+    }
+}
+
+impl IntoIterator for Map<String, Value> {
+    type Item = (String, Value);
+    type IntoIter = IntoIter;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter {
+            iter: self.map.into_iter(),
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a Map<String, Value> {
+    type Item = (&'a String, &'a Value);
+    type IntoIter = Iter<'a>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a mut Map<String, Value> {
+    type Item = (&'a String, &'a mut Value);
+    type IntoIter = IterMut<'a>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl ser::Serialize for Map<String, Value> {
+    #[inline]
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        serializer.collect_map(self)
+    }
+}
+
+impl<'de> de::Deserialize<'de> for Map<String, Value> {
+    #[inline]
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        struct Visitor;
+
+        impl<'de> de::Visitor<'de> for Visitor {
+            type Value = Map<String, Value>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+                formatter.write_str("a table")
+            }
+
+            fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+            where
+                A: de::MapAccess<'de>,
+            {
+                let mut result = Map::with_capacity(map.size_hint().unwrap_or(0));
+                while let Some((key, value)) = map.next_entry()? {
+                    result.insert(key, value);
+                }
+                Ok(result)
+            }
+        }
+
+        deserializer.deserialize_map(Visitor)
+    }
+}
+
+// Implements the iterator traits for a wrapper around one of the backend's iterators
+macro_rules! delegate_iterator {
+    ($name:ident<$lt:lifetime>, $item:ty) => {
+        delegate_iterator!(@impl [<$lt>] [$name<$lt>] [$name<'_>], $item);
+    };
+    ($name:ident, $item:ty) => {
+        delegate_iterator!(@impl [] [$name] [$name], $item);
+    };
+    (@impl [$($generics:tt)*] [$($ty:tt)*] [$($anon:tt)*], $item:ty) => {
+        impl$($generics)* Iterator for $($ty)* {
+            type Item = $item;
+
+            #[inline]
+            fn next(&mut self) -> Option<Self::Item> {
+                self.iter.next()
+            }
+
+            #[inline]
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                self.iter.size_hint()
+            }
+        }
+
+        impl DoubleEndedIterator for $($anon)* {
+            #[inline]
+            fn next_back(&mut self) -> Option<Self::Item> {
+                self.iter.next_back()
+            }
+        }
+
+        impl ExactSizeIterator for $($anon)* {
+            #[inline]
+            fn len(&self) -> usize {
+                self.iter.len()
+            }
+        }
+
+        impl FusedIterator for $($anon)* {}
+    };
+}
+
+/// An iterator over a [`Table`]'s entries.
+#[derive(Debug, Clone)]
+pub struct Iter<'a> {
+    iter: map::Iter<'a, String, Value>,
+}
+
+delegate_iterator!(Iter<'a>, (&'a String, &'a Value));
+
+/// A mutable iterator over a [`Table`]'s entries.
+#[derive(Debug)]
+pub struct IterMut<'a> {
+    iter: map::IterMut<'a, String, Value>,
+}
+
+delegate_iterator!(IterMut<'a>, (&'a String, &'a mut Value));
+
+/// An owning iterator over a [`Table`]'s entries.
+#[derive(Debug)]
+pub struct IntoIter {
+    iter: map::IntoIter<String, Value>,
+}
+
+delegate_iterator!(IntoIter, (String, Value));
 
 /// An iterator over a [`Table`]'s keys.
-pub type Keys<'a> = btree_map::Keys<'a, String, Value>;
+#[derive(Debug, Clone)]
+pub struct Keys<'a> {
+    iter: map::Keys<'a, String, Value>,
+}
+
+delegate_iterator!(Keys<'a>, &'a String);
 
 /// An iterator over a [`Table`]'s values.
-pub type Values<'a> = btree_map::Values<'a, String, Value>;
+#[derive(Debug, Clone)]
+pub struct Values<'a> {
+    iter: map::Values<'a, String, Value>,
+}
+
+delegate_iterator!(Values<'a>, &'a Value);
 
 /// A mutable iterator over a [`Table`]'s values.
-pub type ValuesMut<'a> = btree_map::ValuesMut<'a, String, Value>;
+#[derive(Debug)]
+pub struct ValuesMut<'a> {
+    iter: map::ValuesMut<'a, String, Value>,
+}
+
+delegate_iterator!(ValuesMut<'a>, &'a mut Value);
 
 /// An owning iterator over a [`Table`]'s keys.
-pub type IntoKeys = btree_map::IntoKeys<String, Value>;
+#[derive(Debug)]
+pub struct IntoKeys {
+    iter: map::IntoKeys<String, Value>,
+}
+
+delegate_iterator!(IntoKeys, String);
 
 /// An owning iterator over a [`Table`]'s values.
-pub type IntoValues = btree_map::IntoValues<String, Value>;
+#[derive(Debug)]
+pub struct IntoValues {
+    iter: map::IntoValues<String, Value>,
+}
+
+delegate_iterator!(IntoValues, Value);
+
+/// An iterator over a range of a [`Table`]'s entries, returned by [`Map::range`].
+#[derive(Debug, Clone)]
+pub struct Range<'a> {
+    #[cfg(not(feature = "preserve_order"))]
+    iter: map::Range<'a, String, Value>,
+    #[cfg(feature = "preserve_order")]
+    iter: vec::IntoIter<(&'a String, &'a Value)>,
+}
+
+impl<'a> Iterator for Range<'a> {
+    type Item = (&'a String, &'a Value);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for Range<'_> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter.next_back()
+    }
+}
+
+impl FusedIterator for Range<'_> {}
 
 /// A single entry in [`Table`].
-pub type Entry<'a> = btree_map::Entry<'a, String, Value>;
+#[derive(Debug)]
+pub enum Entry<'a> {
+    /// A vacant entry.
+    Vacant(VacantEntry<'a>),
+    /// An occupied entry.
+    Occupied(OccupiedEntry<'a>),
+}
+
+impl<'a> Entry<'a> {
+    /// Returns the key of the entry.
+    #[must_use]
+    #[inline]
+    pub fn key(&self) -> &String {
+        match *self {
+            Self::Vacant(ref entry) => entry.key(),
+            Self::Occupied(ref entry) => entry.key(),
+        }
+    }
+
+    /// Inserts `default` if the entry is vacant, and returns a mutable reference to the value.
+    #[allow(clippy::must_use_candidate)] // the result is often not needed
+    #[inline]
+    pub fn or_insert(self, default: Value) -> &'a mut Value {
+        match self {
+            Self::Vacant(entry) => entry.insert(default),
+            Self::Occupied(entry) => entry.into_mut(),
+        }
+    }
+
+    /// Inserts the result of `default` if the entry is vacant, and returns a mutable reference to
+    /// the value.
+    #[inline]
+    pub fn or_insert_with<F>(self, default: F) -> &'a mut Value
+    where
+        F: FnOnce() -> Value,
+    {
+        match self {
+            Self::Vacant(entry) => entry.insert(default()),
+            Self::Occupied(entry) => entry.into_mut(),
+        }
+    }
+
+    /// Inserts the result of `default` if the entry is vacant, and returns a mutable reference to
+    /// the value. The key is passed to `default`.
+    #[inline]
+    pub fn or_insert_with_key<F>(self, default: F) -> &'a mut Value
+    where
+        F: FnOnce(&String) -> Value,
+    {
+        match self {
+            Self::Vacant(entry) => {
+                let value = default(entry.key());
+                entry.insert(value)
+            }
+            Self::Occupied(entry) => entry.into_mut(),
+        }
+    }
+
+    /// Calls `f` with the value if the entry is occupied.
+    #[must_use]
+    #[inline]
+    pub fn and_modify<F>(self, f: F) -> Self
+    where
+        F: FnOnce(&mut Value),
+    {
+        match self {
+            Self::Occupied(mut entry) => {
+                f(entry.get_mut());
+                Self::Occupied(entry)
+            }
+            Self::Vacant(entry) => Self::Vacant(entry),
+        }
+    }
+}
 
 /// A vacant entry in [`Table`].
-pub type VacantEntry<'a> = btree_map::VacantEntry<'a, String, Value>;
+#[derive(Debug)]
+pub struct VacantEntry<'a> {
+    vacant: map::VacantEntry<'a, String, Value>,
+}
+
+impl<'a> VacantEntry<'a> {
+    /// Returns the key of the entry.
+    #[must_use]
+    #[inline]
+    pub fn key(&self) -> &String {
+        self.vacant.key()
+    }
+
+    /// Takes ownership of the key.
+    #[must_use]
+    #[inline]
+    pub fn into_key(self) -> String {
+        self.vacant.into_key()
+    }
+
+    /// Inserts a value into the entry, and returns a mutable reference to it.
+    #[allow(clippy::must_use_candidate)] // the result is often not needed
+    #[inline]
+    pub fn insert(self, value: Value) -> &'a mut Value {
+        self.vacant.insert(value)
+    }
+}
 
 /// An occupied entry in [`Table`].
-pub type OccupiedEntry<'a> = btree_map::OccupiedEntry<'a, String, Value>;
+#[derive(Debug)]
+pub struct OccupiedEntry<'a> {
+    occupied: map::OccupiedEntry<'a, String, Value>,
+}
+
+impl<'a> OccupiedEntry<'a> {
+    /// Returns the key of the entry.
+    #[must_use]
+    #[inline]
+    pub fn key(&self) -> &String {
+        self.occupied.key()
+    }
+
+    /// Returns a reference to the value of the entry.
+    #[must_use]
+    #[inline]
+    pub fn get(&self) -> &Value {
+        self.occupied.get()
+    }
+
+    /// Returns a mutable reference to the value of the entry.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut Value {
+        self.occupied.get_mut()
+    }
+
+    /// Converts the entry into a mutable reference to its value.
+    #[must_use]
+    #[inline]
+    pub fn into_mut(self) -> &'a mut Value {
+        self.occupied.into_mut()
+    }
+
+    /// Replaces the value of the entry, returning the old value.
+    #[inline]
+    pub fn insert(&mut self, value: Value) -> Value {
+        self.occupied.insert(value)
+    }
+
+    /// Removes the entry from the table, returning its value.
+    ///
+    /// The order of the remaining entries is preserved when the `preserve_order` feature is
+    /// enabled.
+    #[allow(clippy::must_use_candidate)] // the result is often not needed
+    #[inline]
+    pub fn remove(self) -> Value {
+        #[cfg(not(feature = "preserve_order"))]
+        return self.occupied.remove();
+        #[cfg(feature = "preserve_order")]
+        return self.occupied.shift_remove();
+    }
+
+    /// Removes the entry from the table, returning its key and value.
+    ///
+    /// The order of the remaining entries is preserved when the `preserve_order` feature is
+    /// enabled.
+    #[allow(clippy::must_use_candidate)] // the result is often not needed
+    #[inline]
+    pub fn remove_entry(self) -> (String, Value) {
+        #[cfg(not(feature = "preserve_order"))]
+        return self.occupied.remove_entry();
+        #[cfg(feature = "preserve_order")]
+        return self.occupied.shift_remove_entry();
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage, coverage(off))]
+mod tests {
+    use assert_matches::assert_matches;
+
+    use super::*;
+
+    fn table() -> Table {
+        table! {
+            "c".into() => Value::Integer(3),
+            "a".into() => Value::Integer(1),
+            "b".into() => Value::Integer(2),
+        }
+    }
+
+    fn keys(table: &Table) -> Vec<&str> {
+        table.keys().map(String::as_str).collect()
+    }
+
+    #[test]
+    fn map_order() {
+        #[cfg(not(feature = "preserve_order"))]
+        let expected = ["a", "b", "c"];
+        #[cfg(feature = "preserve_order")]
+        let expected = ["c", "a", "b"];
+
+        let mut table = table();
+        assert_eq!(keys(&table), expected);
+        assert_eq!(table.iter().len(), 3);
+        assert_eq!(
+            table.iter().next_back().map(|(key, _)| key.as_str()),
+            Some(expected[2])
+        );
+
+        // Removing a key keeps the order of the rest
+        assert_eq!(table.remove("a"), Some(Value::Integer(1)));
+        assert_eq!(table.remove("a"), None);
+        let expected: Vec<_> = expected.into_iter().filter(|&key| key != "a").collect();
+        assert_eq!(keys(&table), expected);
+
+        let mut other = table! { "a".into() => Value::Integer(1) };
+        table.append(&mut other);
+        assert!(other.is_empty());
+        assert_eq!(table.len(), 3);
+    }
+
+    #[test]
+    fn map_access() {
+        let mut table = table();
+        assert!(table.contains_key("a"));
+        assert!(!table.contains_key("d"));
+        assert_eq!(table.get("a"), Some(&Value::Integer(1)));
+        assert_eq!(table["b"], Value::Integer(2));
+        assert_eq!(
+            table.get_key_value("c"),
+            Some((&"c".to_string(), &Value::Integer(3)))
+        );
+
+        table["b"] = Value::Integer(20);
+        *table.get_mut("c").unwrap() = Value::Integer(30);
+        assert_eq!(
+            table.insert("a".into(), Value::Integer(10)),
+            Some(Value::Integer(1))
+        );
+        assert_eq!(
+            table.remove_entry("a"),
+            Some(("a".to_string(), Value::Integer(10)))
+        );
+
+        table.retain(|key, _| key != "b");
+        assert_eq!(table.values().collect::<Vec<_>>(), [&Value::Integer(30)]);
+        for value in table.values_mut() {
+            *value = Value::Integer(0);
+        }
+        assert_eq!(
+            table.clone().into_values().collect::<Vec<_>>(),
+            [Value::Integer(0)]
+        );
+        assert_eq!(table.clone().into_keys().collect::<Vec<_>>(), ["c"]);
+
+        table.clear();
+        assert!(table.is_empty());
+        assert_eq!(table, Table::default());
+        assert_eq!(format!("{table:?}"), "{}");
+    }
+
+    #[test]
+    fn map_ends() {
+        #[cfg(not(feature = "preserve_order"))]
+        let (first, last) = ("a", "c");
+        #[cfg(feature = "preserve_order")]
+        let (first, last) = ("c", "b");
+
+        let mut table = table();
+        assert_eq!(table.first_key_value().unwrap().0, first);
+        assert_eq!(table.last_key_value().unwrap().0, last);
+        assert_eq!(table.pop_first().unwrap().0, first);
+        assert_eq!(table.pop_last().unwrap().0, last);
+        assert_eq!(table.len(), 1);
+        assert!(table.pop_first().is_some());
+        assert_eq!(table.first_key_value(), None);
+        assert_eq!(table.pop_first(), None);
+        assert_eq!(table.pop_last(), None);
+    }
+
+    #[test]
+    fn map_range() {
+        #[cfg(not(feature = "preserve_order"))]
+        let expected = ["b", "c"];
+        #[cfg(feature = "preserve_order")]
+        let expected = ["c", "b"];
+
+        let table = table();
+        let keys = |range: Range<'_>| range.map(|(key, _)| key.clone()).collect::<Vec<_>>();
+        assert_eq!(keys(table.range("b".to_owned()..)), expected);
+        assert_eq!(keys(table.range(.."b".to_owned())), ["a"]);
+        assert_eq!(keys(table.range("d".to_owned()..)), [""; 0]);
+        assert_eq!(table.range::<String, _>(..).rev().count(), 3);
+    }
+
+    #[test]
+    fn map_btreemap() {
+        let table = table();
+        let map = BTreeMap::from(table.clone());
+        assert_eq!(map.keys().collect::<Vec<_>>(), ["a", "b", "c"]);
+        assert_eq!(Table::from(map), table);
+    }
+
+    #[test]
+    fn map_entry() {
+        let mut table = table();
+
+        assert_eq!(table.entry("a").key(), "a");
+        assert_eq!(
+            table
+                .entry("f")
+                .or_insert_with_key(|key| Value::String(key.clone())),
+            &Value::String("f".into())
+        );
+        assert_matches!(table.entry("g"), Entry::Vacant(entry) => {
+            assert_eq!(entry.into_key(), "g");
+        });
+        assert_matches!(table.entry("f"), Entry::Occupied(entry) => {
+            assert_eq!(entry.remove_entry(), ("f".into(), Value::String("f".into())));
+        });
+        *table.entry("a").or_insert(Value::Integer(0)) = Value::Integer(10);
+        let _ = table.entry("d").or_insert_with(|| Value::Integer(4));
+        let _ = table
+            .entry("b")
+            .and_modify(|value| *value = Value::Integer(20))
+            .or_insert(Value::Integer(0));
+        assert_eq!(
+            keys(&table).into_iter().filter(|&key| key == "d").count(),
+            1
+        );
+        assert_eq!(table["a"], Value::Integer(10));
+        assert_eq!(table["b"], Value::Integer(20));
+        assert_eq!(table["d"], Value::Integer(4));
+
+        assert_matches!(table.entry("e"), Entry::Vacant(entry) => {
+            assert_eq!(entry.key(), "e");
+            let _ = entry.insert(Value::Integer(5));
+        });
+        assert_matches!(table.entry("e"), Entry::Occupied(mut entry) => {
+            assert_eq!(entry.key(), "e");
+            assert_eq!(entry.get(), &Value::Integer(5));
+            *entry.get_mut() = Value::Integer(50);
+            assert_eq!(entry.insert(Value::Integer(500)), Value::Integer(50));
+            assert_eq!(entry.remove(), Value::Integer(500));
+        });
+        assert!(!table.contains_key("e"));
+    }
+
+    #[test]
+    fn map_iter() {
+        let mut table = table();
+        for (_, value) in &mut table {
+            *value = Value::Boolean(true);
+        }
+        assert!((&table)
+            .into_iter()
+            .all(|(_, value)| *value == Value::Boolean(true)));
+
+        let entries: Vec<_> = table.clone().into_iter().collect();
+        assert_eq!(Table::from_iter(entries.clone()), table);
+
+        let mut extended = Table::with_capacity(3);
+        extended.extend(entries);
+        assert_eq!(extended, table);
+
+        let table = Table::from([("a".to_string(), Value::Integer(1))]);
+        assert_eq!(keys(&table), ["a"]);
+    }
+
+    #[test]
+    fn map_serde() {
+        let table = table();
+        let value: Value = crate::from_str(&crate::to_string(&table).unwrap()).unwrap();
+        assert_eq!(value, Value::Table(table.clone()));
+
+        let parsed: Table = crate::from_str("c = 3\na = 1\nb = 2").unwrap();
+        assert_eq!(parsed, table);
+
+        let result = <Table as de::Deserialize>::deserialize(Value::Integer(1));
+        assert!(result.is_err());
+    }
+}
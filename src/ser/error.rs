@@ -12,6 +12,57 @@ pub type Result<T> = std::result::Result<T, Error>;
 #[derive(Clone)]
 pub struct Error(pub(crate) ErrorKind);
 
+/// A broad category of serialization error, returned by [`Error::category`].
+///
+/// Unlike the error message, the category is stable and can be used to handle different classes of
+/// error. More categories may be added in the future.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorCategory {
+    /// The output could not be written.
+    Io,
+    /// The value being serialized cannot be represented in TOML.
+    Type,
+}
+
+impl Error {
+    /// Returns the category of the error.
+    #[must_use]
+    #[inline]
+    pub fn category(&self) -> ErrorCategory {
+        if self.is_io() {
+            ErrorCategory::Io
+        } else {
+            ErrorCategory::Type
+        }
+    }
+
+    /// Returns `true` if the error was caused by failing to write the output.
+    #[must_use]
+    #[inline]
+    pub fn is_io(&self) -> bool {
+        matches!(self.0, ErrorKind::Io(_) | ErrorKind::Fmt(_))
+    }
+
+    /// Returns `true` if the value being serialized cannot be represented in TOML.
+    ///
+    /// This includes any custom errors returned by a [`Serialize`](serde::Serialize)
+    /// implementation.
+    #[must_use]
+    #[inline]
+    pub fn is_type(&self) -> bool {
+        matches!(
+            self.0,
+            ErrorKind::UnsupportedValue(_)
+                | ErrorKind::UnsupportedType(_)
+                | ErrorKind::IntegerOutOfRange
+                | ErrorKind::UnsupportedRoot(_)
+                | ErrorKind::DuplicateKey(_)
+                | ErrorKind::Custom(_)
+        )
+    }
+}
+
 impl fmt::Display for Error {
     #[inline]
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -78,6 +129,10 @@ pub enum ErrorKind {
     UnsupportedValue(&'static str),
     /// Unsupported Rust type
     UnsupportedType(&'static str),
+    /// Integer which doesn't fit in an i64
+    IntegerOutOfRange,
+    /// Rust type which can't be the root of a document since it isn't a table
+    UnsupportedRoot(&'static str),
     /// Duplicate key in table
     DuplicateKey(Box<str>),
 
@@ -98,6 +153,11 @@ impl fmt::Display for ErrorKind {
         match *self {
             UnsupportedValue(msg) => write!(f, "unsupported value: {msg}"),
             UnsupportedType(msg) => write!(f, "unsupported type: {msg}"),
+            IntegerOutOfRange => f.write_str("integer out of range of i64"),
+            UnsupportedRoot(msg) => write!(
+                f,
+                "unsupported type for the root of a document: {msg}, expected a struct or map"
+            ),
             DuplicateKey(ref key) => write!(f, r#"duplicate key "{key}" in table"#),
             Io(ref io_error) => write!(f, "IO error: {io_error}"),
             Fmt(ref fmt_error) => write!(f, "formatting error: {fmt_error}"),
@@ -154,6 +214,50 @@ mod tests {
         );
     }
 
+    #[test]
+    fn error_classification() {
+        let error = Error(ErrorKind::Io(Arc::new(io::Error::new(
+            io::ErrorKind::NotFound,
+            "foo",
+        ))));
+        assert!(error.is_io());
+        assert!(!error.is_type());
+
+        let error = Error(ErrorKind::Fmt(fmt::Error));
+        assert!(error.is_io());
+        assert!(!error.is_type());
+
+        let error = crate::to_string(&42).unwrap_err();
+        assert!(!error.is_io());
+        assert!(error.is_type());
+
+        let error = Error(ErrorKind::IntegerOutOfRange);
+        assert!(!error.is_io());
+        assert!(error.is_type());
+
+        let error = Error::custom("foo");
+        assert!(!error.is_io());
+        assert!(error.is_type());
+    }
+
+    #[test]
+    fn error_category() {
+        let error = Error(ErrorKind::Io(Arc::new(io::Error::new(
+            io::ErrorKind::NotFound,
+            "foo",
+        ))));
+        assert_eq!(error.category(), ErrorCategory::Io);
+
+        let error = Error(ErrorKind::Fmt(fmt::Error));
+        assert_eq!(error.category(), ErrorCategory::Io);
+
+        let error = crate::to_string(&42).unwrap_err();
+        assert_eq!(error.category(), ErrorCategory::Type);
+
+        let error = Error::custom("foo");
+        assert_eq!(error.category(), ErrorCategory::Type);
+    }
+
     #[test]
     fn error_custom() {
         let error = Error::custom("foo");
@@ -189,6 +293,15 @@ mod tests {
         let kind = ErrorKind::UnsupportedType("foo");
         assert_eq!(kind.to_string(), "unsupported type: foo");
 
+        let kind = ErrorKind::IntegerOutOfRange;
+        assert_eq!(kind.to_string(), "integer out of range of i64");
+
+        let kind = ErrorKind::UnsupportedRoot("foo");
+        assert_eq!(
+            kind.to_string(),
+            "unsupported type for the root of a document: foo, expected a struct or map"
+        );
+
         let kind = ErrorKind::DuplicateKey("foo".into());
         assert_eq!(format!("{kind}"), r#"duplicate key "foo" in table"#);
 
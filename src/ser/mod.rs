@@ -1,14 +1,17 @@
 //! TOML serialization functions and trait implementations.
 
+use std::cmp::Ordering;
+use std::sync::Arc;
 use std::{fmt, io};
 
 use serde::ser;
 
 pub(crate) use self::error::ErrorKind;
-pub use self::error::{Error, Result};
+pub use self::error::{Error, ErrorCategory, Result};
 pub(crate) use self::utils::Impossible;
 pub use self::value::Serializer as ValueSerializer;
-use self::writer::{Formatter, IoWriter};
+pub(crate) use self::writer::Formatter;
+use self::writer::IoWriter;
 #[cfg(feature = "datetime")]
 use crate::value::{AnyDatetime, LocalDate, LocalDatetime, LocalTime, OffsetDatetime};
 
@@ -22,7 +25,8 @@ mod writer;
 ///
 /// # Errors
 ///
-/// Returns an error if the value cannot be serialized to a TOML document.
+/// Returns an error if the value cannot be serialized to a TOML document. The root of a TOML
+/// document is always a table, so the value must serialize as a struct or map.
 #[inline]
 pub fn to_string<T>(value: &T) -> Result<String>
 where
@@ -33,6 +37,46 @@ where
     Ok(dst)
 }
 
+/// Serializes a value to a TOML string, reusing an existing [`String`].
+///
+/// `dst` is cleared before serializing, but keeps its allocated capacity. Unlike [`to_string`],
+/// serializing many documents with the same buffer therefore only allocates until the buffer is
+/// large enough for the largest document. When serializing a [`Value`], the buffer can also be
+/// pre-sized using [`Value::estimated_serialized_len`]. Use [`append_to_string`] to keep the
+/// existing contents of `dst` instead.
+///
+/// # Errors
+///
+/// Returns an error if the value cannot be serialized to a TOML document. Some output may have
+/// been written to `dst` before the error occurred.
+///
+/// [`Value`]: crate::Value
+/// [`Value::estimated_serialized_len`]: crate::Value::estimated_serialized_len
+#[inline]
+pub fn to_string_in<T>(value: &T, dst: &mut String) -> Result<()>
+where
+    T: ser::Serialize,
+{
+    dst.clear();
+    append_to_string(value, dst)
+}
+
+/// Serializes a value to a TOML string, appending it to the existing contents of a [`String`].
+///
+/// This is useful for writing a document after some existing text, such as a comment header.
+///
+/// # Errors
+///
+/// Returns an error if the value cannot be serialized to a TOML document. Some output may have
+/// been appended to `dst` before the error occurred.
+#[inline]
+pub fn append_to_string<T>(value: &T, dst: &mut String) -> Result<()>
+where
+    T: ser::Serialize,
+{
+    value.serialize(Serializer::new(dst))
+}
+
 /// Serializes a value to an [`io::Write`].
 ///
 /// # Errors
@@ -61,10 +105,148 @@ where
     value.serialize(Serializer::from_fmt_writer(writer))
 }
 
+/// Serializes a value to a [`fmt::Formatter`].
+///
+/// This is intended for implementing [`Display`](fmt::Display) for types which should be shown
+/// as a TOML document.
+///
+/// # Errors
+///
+/// Returns an error if the value cannot be serialized to a TOML document. Since [`fmt::Error`]
+/// carries no information, use [`to_string`] instead if the cause of the error is needed.
+#[inline]
+pub fn to_fmt<T>(f: &mut fmt::Formatter<'_>, value: &T) -> fmt::Result
+where
+    T: ser::Serialize,
+{
+    to_fmt_writer(f, value).map_err(|_| fmt::Error)
+}
+
 /// A serializer for a TOML document.
+///
+/// Empty tables are never omitted. Nested tables are written as a bare `[table]` header with no
+/// keys, while tables within inline arrays are written as `{}`. Empty arrays are written as `[]`.
+/// Tables which only contain other tables are not given a header of their own by default, as they
+/// are implied by their subtables' headers; see [`Self::emit_empty_parent_tables`].
+///
+/// Floats are written in the shortest form which parses back to the same value by default (see
+/// [`Self::float_format`]), so any finite `f32` or `f64` (including subnormals) round trips
+/// through this crate bit for bit. Infinities and NaNs keep their sign, but NaN payloads are not
+/// preserved.
 #[derive(Debug)]
 pub struct Serializer<W> {
     writer: W,
+    settings: Settings,
+    preamble: Option<String>,
+}
+
+/// Options used to control the output of the [`Serializer`].
+#[allow(clippy::struct_excessive_bools)] // These are independent options, not a state machine
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Settings {
+    /// Write arrays of tables as inline arrays of inline tables
+    pub array_of_tables_inline: bool,
+    /// Insert underscores between groups of this many digits when writing numbers
+    pub integer_grouping: Option<u8>,
+    /// The notation used to write floats
+    pub float_format: FloatFormat,
+    /// Write arrays with more than this many elements with one element per line
+    pub inline_array_max_elements: Option<usize>,
+    /// Omit the newline at the end of the document
+    pub omit_trailing_newline: bool,
+    /// Order the keys of each table using this comparison function
+    pub key_order: Option<KeyOrder>,
+    /// Escape all non-ASCII characters in strings and keys
+    pub ascii_only: bool,
+    /// Collapse chains of single-key tables into dotted keys
+    pub dotted_keys: bool,
+    /// Write headers for tables which only contain other tables
+    pub emit_empty_parent_tables: bool,
+    /// How to write offset date-times with a zero offset
+    #[cfg(feature = "datetime")]
+    pub zero_offset_style: ZeroOffsetStyle,
+    /// Whether the value being serialized is an element of an array. This is not a user option,
+    /// but is used to always write nested arrays on a single line
+    pub in_array: bool,
+    /// Whether a preamble was written before the document, and so needs to be separated from it by
+    /// a blank line. This is not a user option, but is set when the preamble is written
+    pub separate_preamble: bool,
+}
+
+type Compare = dyn Fn(&str, &str) -> Ordering + Send + Sync;
+
+/// A comparison function used to order keys.
+#[derive(Clone)]
+pub(crate) struct KeyOrder(pub Arc<Compare>);
+
+// Closures don't implement Debug
+impl fmt::Debug for KeyOrder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("KeyOrder(..)")
+    }
+}
+
+/// The notation used to write floats when serializing.
+///
+/// See [`Serializer::float_format`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FloatFormat {
+    /// Write the shortest representation which parses back to the same value. This uses
+    /// exponent form only for very large or very small magnitudes, so `1e6` is written as
+    /// `1000000.0` but `1e28` is written as `1e28`.
+    #[default]
+    Shortest,
+    /// Write floats in exponent form if their magnitude is at least `10^threshold` or less than
+    /// `10^-threshold`, and in plain decimal form otherwise. For example with a threshold of `6`,
+    /// `1e6` is written as `1e6`, `1e-9` as `1e-9`, and `42.5` as `42.5`.
+    ///
+    /// The same digits are written as for [`Self::Shortest`], so floats still parse back to
+    /// exactly the same value.
+    Scientific(u8),
+}
+
+/// How a zero UTC offset is written when serializing an offset date-time.
+///
+/// See [`Serializer::zero_offset_style`].
+#[cfg(feature = "datetime")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ZeroOffsetStyle {
+    /// Write the offset as it is stored, so [`Offset::Z`] is written as `Z` and a zero
+    /// [`Offset::Custom`] is written as `+00:00`.
+    ///
+    /// [`Offset::Z`]: crate::value::Offset::Z
+    /// [`Offset::Custom`]: crate::value::Offset::Custom
+    #[default]
+    Preserve,
+    /// Always write a zero offset as `Z`. An offset of `-00:00` is not rewritten, since RFC 3339
+    /// uses it to mean the local offset is unknown.
+    Zulu,
+    /// Always write a zero offset as `+00:00`.
+    Numeric,
+}
+
+#[cfg(feature = "datetime")]
+impl ZeroOffsetStyle {
+    // Rewrites the offset of an encoded offset date-time if it is zero. The offset is always at
+    // the end of the string, so there's no need to fully parse the date-time
+    fn apply(self, mut datetime: String) -> String {
+        match self {
+            Self::Preserve => {}
+            Self::Zulu => {
+                if datetime.ends_with("+00:00") {
+                    datetime.truncate(datetime.len() - "+00:00".len());
+                    datetime.push('Z');
+                }
+            }
+            Self::Numeric => {
+                if datetime.ends_with(['Z', 'z']) {
+                    datetime.pop();
+                    datetime.push_str("+00:00");
+                }
+            }
+        }
+        datetime
+    }
 }
 
 impl<'a> Serializer<&'a mut String> {
@@ -83,7 +265,11 @@ where
     /// Create a new TOML serializer that serializes to the given writer.
     #[inline]
     pub fn from_fmt_writer(writer: W) -> Self {
-        Self { writer }
+        Self {
+            writer,
+            settings: Settings::default(),
+            preamble: None,
+        }
     }
 }
 
@@ -96,7 +282,171 @@ where
     pub fn from_io_writer(writer: W) -> Self {
         Self {
             writer: IoWriter::new(writer),
+            settings: Settings::default(),
+            preamble: None,
+        }
+    }
+}
+
+impl<W> Serializer<W> {
+    /// Sets whether arrays of tables should be written as inline arrays of inline tables rather
+    /// than using `[[...]]` headers.
+    ///
+    /// This only applies to arrays of small tables, that is ones which do not themselves contain
+    /// any subtables or arrays of tables. Arrays of larger tables are always written using
+    /// `[[...]]` headers.
+    #[must_use]
+    #[inline]
+    pub fn array_of_tables_inline(mut self, value: bool) -> Self {
+        self.settings.array_of_tables_inline = value;
+        self
+    }
+
+    /// Sets the number of digits between underscores when writing integers.
+    ///
+    /// For example `Some(3)` writes `1000000` as `1_000_000`. This also applies to the integer
+    /// part of floats. `None` (the default) or `Some(0)` disables digit grouping.
+    #[must_use]
+    #[inline]
+    pub fn integer_grouping(mut self, value: Option<u8>) -> Self {
+        self.settings.integer_grouping = value;
+        self
+    }
+
+    /// Sets the notation used to write floats.
+    ///
+    /// By default ([`FloatFormat::Shortest`]) floats are written in the shortest form which
+    /// parses back to the same value. [`FloatFormat::Scientific`] instead uses exponent form for
+    /// all large and small magnitudes beyond a threshold. Infinities and NaNs are not affected.
+    #[must_use]
+    #[inline]
+    pub fn float_format(mut self, value: FloatFormat) -> Self {
+        self.settings.float_format = value;
+        self
+    }
+
+    /// Sets the maximum number of elements in an array before it is written with one element per
+    /// line rather than on a single line.
+    ///
+    /// This only applies to arrays which are the value of a key. Arrays nested within another
+    /// array are always written on a single line. `None` (the default) writes all arrays on a
+    /// single line.
+    #[must_use]
+    #[inline]
+    pub fn inline_array_max_elements(mut self, value: Option<usize>) -> Self {
+        self.settings.inline_array_max_elements = value;
+        self
+    }
+
+    /// Sets whether the document should end with a newline.
+    ///
+    /// By default (`true`) the output always ends with a newline. Disabling this can be useful
+    /// when embedding the output in a larger document.
+    #[must_use]
+    #[inline]
+    pub fn trailing_newline(mut self, value: bool) -> Self {
+        self.settings.omit_trailing_newline = !value;
+        self
+    }
+
+    /// Sets a function used to order the keys within each table.
+    ///
+    /// By default keys are written in the order they are serialized, which for structs is the
+    /// order the fields are declared. When set, the keys of every table are sorted using `compare`
+    /// before being written. The sort is stable, so keys which compare as equal keep their
+    /// original order. Since `compare` can be a closure, it can capture state such as a list of
+    /// keys which should be written first.
+    ///
+    /// Regardless of order, keys with simple values are always written before any subtables or
+    /// arrays of tables within the same table.
+    #[must_use]
+    #[inline]
+    pub fn key_order<F>(mut self, compare: F) -> Self
+    where
+        F: Fn(&str, &str) -> Ordering + Send + Sync + 'static,
+    {
+        self.settings.key_order = Some(KeyOrder(Arc::new(compare)));
+        self
+    }
+
+    /// Sets whether all non-ASCII characters should be escaped.
+    ///
+    /// When enabled, every non-ASCII character in a string or quoted key is written as a `\uXXXX`
+    /// or `\UXXXXXXXX` escape sequence, so the output only contains ASCII characters. Strings are
+    /// always written as basic strings, which support escape sequences, so this never changes the
+    /// value of a string. Any [`preamble`](Self::preamble) is written as is.
+    #[must_use]
+    #[inline]
+    pub fn ascii_only(mut self, value: bool) -> Self {
+        self.settings.ascii_only = value;
+        self
+    }
+
+    /// Sets whether chains of tables containing a single key should be written using dotted keys.
+    ///
+    /// When enabled, a table whose only key is a simple value is written as a dotted key in its
+    /// parent table, so `{ a = { b = { c = 1 } } }` is written as `a.b.c = 1` rather than using
+    /// nested inline tables or a `[a.b]` header. Tables with more than one key, or whose only key
+    /// is an array of tables, are written as usual.
+    #[must_use]
+    #[inline]
+    pub fn dotted_keys(mut self, value: bool) -> Self {
+        self.settings.dotted_keys = value;
+        self
+    }
+
+    /// Sets whether headers are written for tables which only contain other tables.
+    ///
+    /// By default a table with no keys of its own besides other tables is not given a header,
+    /// since it is implicitly defined by the headers of its subtables. For example, `[servers]`
+    /// is omitted before `[servers.alpha]` if `servers` has no other keys. When enabled, every
+    /// level of nesting is written with its own header, which some tools require.
+    #[must_use]
+    #[inline]
+    pub fn emit_empty_parent_tables(mut self, value: bool) -> Self {
+        self.settings.emit_empty_parent_tables = value;
+        self
+    }
+
+    /// Sets how offset date-times with a zero UTC offset are written.
+    ///
+    /// By default ([`ZeroOffsetStyle::Preserve`]) the offset is written as it is stored. The other
+    /// styles write every zero offset consistently as either `Z` or `+00:00`, regardless of how
+    /// it was stored. Non-zero offsets and local date-times are not affected. The same option is
+    /// available for single values with [`ValueSerializer::zero_offset_style`].
+    #[cfg(feature = "datetime")]
+    #[must_use]
+    #[inline]
+    pub fn zero_offset_style(mut self, value: ZeroOffsetStyle) -> Self {
+        self.settings.zero_offset_style = value;
+        self
+    }
+
+    /// Sets a comment to be written at the very top of the document, before any keys.
+    ///
+    /// Each line of `text` is prefixed with `# `, unless it already starts with a `#`. This is
+    /// useful for adding a banner such as `Do not edit, this file is generated` to generated
+    /// configuration files.
+    #[must_use]
+    #[inline]
+    pub fn preamble(mut self, text: &str) -> Self {
+        self.preamble = Some(text.to_owned());
+        self
+    }
+}
+
+impl<W> Serializer<W>
+where
+    W: fmt::Write,
+{
+    // Writes the preamble (if any) and returns the writer and settings to start serializing the
+    // document
+    fn start(mut self) -> Result<(W, Settings)> {
+        if let Some(ref preamble) = self.preamble {
+            Formatter::write_preamble(preamble, &mut self.writer)?;
+            self.settings.separate_preamble = true;
         }
+        Ok((self.writer, self.settings))
     }
 }
 
@@ -113,8 +463,8 @@ where
     type SerializeStructVariant = WrappedTableSerializer<W>;
 
     utils::__serialize_unsupported!(
-        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str bytes none
-        some unit unit_struct unit_variant newtype_struct seq tuple tuple_struct
+        UnsupportedRoot: bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str bytes
+        none some unit unit_struct unit_variant newtype_struct seq tuple tuple_struct
     );
 
     #[inline]
@@ -143,10 +493,9 @@ where
         variant: &'static str,
         len: usize,
     ) -> std::result::Result<Self::SerializeTupleVariant, Self::Error> {
+        let (writer, settings) = self.start()?;
         Ok(Self::SerializeTupleVariant::start(
-            self.writer,
-            variant,
-            len,
+            writer, variant, len, settings,
         ))
     }
 
@@ -155,7 +504,8 @@ where
         self,
         len: Option<usize>,
     ) -> std::result::Result<Self::SerializeMap, Self::Error> {
-        Ok(Self::SerializeMap::start(self.writer, len))
+        let (writer, settings) = self.start()?;
+        Ok(Self::SerializeMap::start(writer, len, settings))
     }
 
     #[inline]
@@ -170,8 +520,11 @@ where
             | OffsetDatetime::WRAPPER_TYPE
             | LocalDatetime::WRAPPER_TYPE
             | LocalDate::WRAPPER_TYPE
-            | LocalTime::WRAPPER_TYPE => Err(ErrorKind::UnsupportedType(name).into()),
-            _ => Ok(Self::SerializeStruct::start(self.writer, Some(len))),
+            | LocalTime::WRAPPER_TYPE => Err(ErrorKind::UnsupportedRoot(name).into()),
+            _ => {
+                let (writer, settings) = self.start()?;
+                Ok(Self::SerializeStruct::start(writer, Some(len), settings))
+            }
         }
     }
 
@@ -183,10 +536,9 @@ where
         variant: &'static str,
         len: usize,
     ) -> std::result::Result<Self::SerializeStructVariant, Self::Error> {
+        let (writer, settings) = self.start()?;
         Ok(Self::SerializeStructVariant::start(
-            self.writer,
-            variant,
-            len,
+            writer, variant, len, settings,
         ))
     }
 }
@@ -201,11 +553,11 @@ pub struct WrappedArraySerializer<W> {
 
 impl<W> WrappedArraySerializer<W> {
     #[inline]
-    fn start(writer: W, key: &'static str, len: usize) -> Self {
+    fn start(writer: W, key: &'static str, len: usize, settings: Settings) -> Self {
         Self {
             writer,
             key,
-            arr: tree::ArraySerializer::start(Some(len)),
+            arr: tree::ArraySerializer::start(Some(len), settings),
         }
     }
 }
@@ -229,18 +581,21 @@ where
 
     #[inline]
     fn end(mut self) -> Result<Self::Ok> {
-        match self.arr.end_inner()? {
-            tree::Array::Inline(value) => {
-                Formatter::write_inline(self.key, &value, &mut self.writer)?;
-            }
-            tree::Array::Table(array) => {
-                Formatter::write_array_of_tables(
-                    &array,
+        let trailing_newline = !self.arr.settings.omit_trailing_newline;
+        let separate_preamble = self.arr.settings.separate_preamble;
+        let empty_parent_tables = self.arr.settings.emit_empty_parent_tables;
+        let array = self.arr.end_inner()?;
+        Formatter::write_document(separate_preamble, trailing_newline, &mut self.writer, |f| {
+            match array {
+                tree::Array::Inline(ref value) => Formatter::write_inline(self.key, value, f),
+                tree::Array::Table(ref array) => Formatter::write_array_of_tables(
+                    array,
                     &[&self.key.to_string()],
-                    &mut self.writer,
-                )?;
+                    empty_parent_tables,
+                    f,
+                ),
             }
-        }
+        })?;
         Ok(())
     }
 }
@@ -254,10 +609,10 @@ pub struct TableSerializer<W> {
 
 impl<W> TableSerializer<W> {
     #[inline]
-    fn start(writer: W, len: Option<usize>) -> Self {
+    fn start(writer: W, len: Option<usize>, settings: Settings) -> Self {
         Self {
             writer,
-            table: tree::TableSerializer::start(len),
+            table: tree::TableSerializer::start(len, settings),
         }
     }
 }
@@ -296,7 +651,13 @@ where
 
     #[inline]
     fn end(mut self) -> Result<Self::Ok> {
-        Formatter::write_table(&self.table.end_inner(), &[], &mut self.writer)?;
+        let trailing_newline = !self.table.settings.omit_trailing_newline;
+        let separate_preamble = self.table.settings.separate_preamble;
+        let empty_parent_tables = self.table.settings.emit_empty_parent_tables;
+        let table = self.table.end_inner();
+        Formatter::write_document(separate_preamble, trailing_newline, &mut self.writer, |f| {
+            Formatter::write_table(&table, &[], empty_parent_tables, f)
+        })?;
         Ok(())
     }
 }
@@ -332,11 +693,11 @@ pub struct WrappedTableSerializer<W> {
 
 impl<W> WrappedTableSerializer<W> {
     #[inline]
-    fn start(writer: W, key: &'static str, len: usize) -> Self {
+    fn start(writer: W, key: &'static str, len: usize, settings: Settings) -> Self {
         Self {
             writer,
             key,
-            table: tree::TableSerializer::start(Some(len)),
+            table: tree::TableSerializer::start(Some(len), settings),
         }
     }
 }
@@ -360,11 +721,13 @@ where
 
     #[inline]
     fn end(mut self) -> Result<Self::Ok> {
-        Formatter::write_table(
-            &self.table.end_inner(),
-            &[&self.key.to_owned()],
-            &mut self.writer,
-        )?;
+        let trailing_newline = !self.table.settings.omit_trailing_newline;
+        let separate_preamble = self.table.settings.separate_preamble;
+        let empty_parent_tables = self.table.settings.emit_empty_parent_tables;
+        let table = self.table.end_inner();
+        Formatter::write_document(separate_preamble, trailing_newline, &mut self.writer, |f| {
+            Formatter::write_table(&table, &[&self.key.to_owned()], empty_parent_tables, f)
+        })?;
         Ok(())
     }
 }
@@ -514,6 +877,38 @@ mod tests {
         );
     }
 
+    #[test]
+    fn ser_to_string_in() {
+        #[derive(serde::Serialize)]
+        struct Struct {
+            a: u32,
+        }
+
+        let mut buf = String::from("# header\n");
+        to_string_in(&Struct { a: 1 }, &mut buf).unwrap();
+        assert_eq!(buf, "a = 1\n");
+
+        let capacity = buf.capacity();
+        to_string_in(&Struct { a: 2 }, &mut buf).unwrap();
+        assert_eq!(buf, "a = 2\n");
+        assert_eq!(buf.capacity(), capacity);
+    }
+
+    #[test]
+    fn ser_append_to_string() {
+        #[derive(serde::Serialize)]
+        struct Struct {
+            a: u32,
+        }
+
+        let mut buf = String::from("# header\n");
+        append_to_string(&Struct { a: 1 }, &mut buf).unwrap();
+        assert_eq!(buf, "# header\na = 1\n");
+
+        append_to_string(&Struct { a: 2 }, &mut buf).unwrap();
+        assert_eq!(buf, "# header\na = 1\na = 2\n");
+    }
+
     #[test]
     fn ser_to_io_writer() {
         let mut result = Vec::new();
@@ -703,6 +1098,49 @@ mod tests {
         );
     }
 
+    #[test]
+    fn ser_to_fmt() {
+        use std::collections::BTreeMap;
+
+        struct Config(BTreeMap<&'static str, i32>);
+
+        impl fmt::Display for Config {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                to_fmt(f, &self.0)
+            }
+        }
+
+        struct Invalid;
+
+        impl fmt::Display for Invalid {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                to_fmt(f, &42)
+            }
+        }
+
+        let config = Config(btreemap! { "a" => 1, "b" => 2 });
+        assert_eq!(config.to_string(), "a = 1\nb = 2\n");
+
+        let mut buf = String::new();
+        assert!(fmt::write(&mut buf, format_args!("{Invalid}")).is_err());
+    }
+
+    #[test]
+    fn ser_to_string_bytes() {
+        #[derive(Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+        struct Data {
+            #[serde(with = "serde_bytes")]
+            data: Vec<u8>,
+        }
+
+        let data = Data {
+            data: vec![1, 2, 255],
+        };
+        let result = to_string(&data).unwrap();
+        assert_eq!(result, "data = [1, 2, 255]\n");
+        assert_eq!(crate::from_str::<Data>(&result).unwrap(), data);
+    }
+
     #[test]
     fn serializer_new() {
         let mut buf = String::new();
@@ -725,77 +1163,1235 @@ mod tests {
     }
 
     #[test]
-    fn serializer_serialize_newtype_variant() {
+    fn serializer_array_of_tables_inline() {
+        use ser::Serialize as _;
+
+        #[derive(serde::Serialize)]
+        struct Point {
+            x: i32,
+            y: i32,
+        }
+
+        #[derive(serde::Serialize)]
+        struct Shape {
+            name: String,
+            points: Vec<Point>,
+        }
+
+        let shape = Shape {
+            name: "line".into(),
+            points: vec![Point { x: 1, y: 2 }, Point { x: 3, y: 4 }],
+        };
+
+        let mut buf = String::new();
+        let serializer = Serializer::new(&mut buf);
+        assert!(!serializer.settings.array_of_tables_inline);
+        shape.serialize(serializer).unwrap();
+        assert_eq!(
+            buf,
+            indoc! {r#"
+                name = "line"
+
+                [[points]]
+                x = 1
+                y = 2
+
+                [[points]]
+                x = 3
+                y = 4
+            "#}
+        );
+
         let mut buf = String::new();
-        let serializer = Serializer { writer: &mut buf };
+        let serializer = Serializer::new(&mut buf).array_of_tables_inline(true);
+        assert!(serializer.settings.array_of_tables_inline);
+        shape.serialize(serializer).unwrap();
+        assert_eq!(
+            buf,
+            indoc! {r#"
+                name = "line"
+                points = [{ x = 1, y = 2 }, { x = 3, y = 4 }]
+            "#}
+        );
 
-        serializer
-            .serialize_newtype_variant("name", 0, "foo", &42)
-            .unwrap();
+        // Tables containing subtables still use headers
+        let value = btreemap! {
+            "shapes" => vec![btreemap! { "a b" => btreemap! { "c" => 1 } }],
+        };
+        let mut buf = String::new();
+        let serializer = Serializer::new(&mut buf).array_of_tables_inline(true);
+        value.serialize(serializer).unwrap();
+        assert_eq!(
+            buf,
+            indoc! {r#"
+                [[shapes]]
+                [shapes."a b"]
+                c = 1
+            "#}
+        );
 
+        let value = btreemap! {
+            "shapes" => vec![btreemap! { "a b" => 1 }],
+        };
+        let mut buf = String::new();
+        let serializer = Serializer::new(&mut buf).array_of_tables_inline(true);
+        value.serialize(serializer).unwrap();
         assert_eq!(
             buf,
-            indoc! {r"
-                foo = 42
-            "}
+            indoc! {r#"
+                shapes = [{ "a b" = 1 }]
+            "#}
         );
     }
 
     #[test]
-    fn serializer_serialize_tuple_variant() {
+    fn serializer_ascii_only() {
+        use ser::Serialize as _;
+
+        #[derive(Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+        struct Menu {
+            name: String,
+            items: std::collections::BTreeMap<String, String>,
+        }
+
+        let menu = Menu {
+            name: "Café 😎".into(),
+            items: btreemap! { "crème brûlée".into() => "dessert\nsucré".into() },
+        };
+
         let mut buf = String::new();
-        let serializer = Serializer { writer: &mut buf };
+        let serializer = Serializer::new(&mut buf).ascii_only(true);
+        assert!(serializer.settings.ascii_only);
+        menu.serialize(serializer).unwrap();
+        assert_eq!(
+            buf,
+            indoc! {r#"
+                name = "Caf\u00e9 \U0001f60e"
 
-        let seq = serializer
-            .serialize_tuple_variant("name", 0, "foo", 2)
-            .unwrap();
+                [items]
+                "cr\u00e8me br\u00fbl\u00e9e" = """
+                dessert
+                sucr\u00e9"""
+            "#}
+        );
+        assert_eq!(crate::from_str::<Menu>(&buf).unwrap(), menu);
 
-        assert_matches!(seq, WrappedArraySerializer {
-            writer: _,
-            key: "foo",
-            arr: tree::ArraySerializer { arr },
-        } if arr.capacity() == 2);
+        let mut buf = String::new();
+        let serializer = Serializer::new(&mut buf).ascii_only(false);
+        menu.serialize(serializer).unwrap();
+        assert_eq!(
+            buf,
+            indoc! {r#"
+                name = "Café 😎"
+
+                [items]
+                "crème brûlée" = """
+                dessert
+                sucré"""
+            "#}
+        );
     }
 
     #[test]
-    fn serializer_serialize_map() {
+    fn serializer_dotted_keys() {
+        use std::collections::BTreeMap;
+
+        use ser::Serialize as _;
+
+        type Chain = BTreeMap<String, BTreeMap<String, BTreeMap<String, i32>>>;
+
+        #[derive(Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+        struct Config {
+            name: String,
+            chain: Chain,
+            inline: (i32, Chain),
+            server: BTreeMap<String, i32>,
+            nested: BTreeMap<String, BTreeMap<String, i32>>,
+        }
+
+        let chain = btreemap! {
+            "a".into() => btreemap! { "b".into() => btreemap! { "c".into() => 1 } },
+        };
+        let config = Config {
+            name: "example".into(),
+            chain: chain.clone(),
+            inline: (1, chain),
+            server: btreemap! { "host".into() => 1, "port".into() => 2 },
+            nested: btreemap! {
+                "x".into() => btreemap! { "y".into() => 1, "z".into() => 2 },
+            },
+        };
+
         let mut buf = String::new();
-        let serializer = Serializer { writer: &mut buf };
+        let serializer = Serializer::new(&mut buf).dotted_keys(true);
+        assert!(serializer.settings.dotted_keys);
+        config.serialize(serializer).unwrap();
+        assert_eq!(
+            buf,
+            indoc! {r#"
+                name = "example"
+                chain.a.b.c = 1
+                inline = [1, { a.b.c = 1 }]
 
-        let seq = serializer.serialize_map(Some(2)).unwrap();
+                [server]
+                host = 1
+                port = 2
 
-        assert_matches!(seq, TableSerializer {
-            writer: _,
-            table: tree::TableSerializer { table, .. }
-        } if table.capacity() == 2);
-    }
+                [nested.x]
+                y = 1
+                z = 2
+            "#}
+        );
+        assert_eq!(crate::from_str::<Config>(&buf).unwrap(), config);
 
-    #[test]
-    fn serializer_serialize_struct() {
         let mut buf = String::new();
-        let serializer = Serializer { writer: &mut buf };
+        let serializer = Serializer::new(&mut buf).dotted_keys(false);
+        config.serialize(serializer).unwrap();
+        assert_eq!(
+            buf,
+            indoc! {r#"
+                name = "example"
+                inline = [1, { a = { b = { c = 1 } } }]
 
-        let seq = serializer.serialize_struct("name", 2).unwrap();
+                [chain.a.b]
+                c = 1
 
-        assert_matches!(seq, TableSerializer {
-            writer: _,
-            table: tree::TableSerializer { table, .. }
-        } if table.capacity() == 2);
+                [server]
+                host = 1
+                port = 2
 
-        #[cfg(feature = "datetime")]
-        {
-            let mut buf = String::new();
-            let serializer = Serializer { writer: &mut buf };
+                [nested.x]
+                y = 1
+                z = 2
+            "#}
+        );
+    }
 
-            let seq = serializer.serialize_struct(OffsetDatetime::WRAPPER_TYPE, 1);
-            assert_matches!(seq, Err(Error(ErrorKind::UnsupportedType(..))));
-        }
+    #[test]
+    fn serializer_numeric_keys() {
+        use crate::Value;
+
+        // All-digit and dashed keys are valid bare keys, but a dot must be quoted
+        let value = Value::Table(table! {
+            "1234".into() => Value::Boolean(true),
+            "3-14".into() => Value::Integer(1),
+            "3.14".into() => Value::Integer(2),
+            "key-2".into() => Value::Integer(3),
+        });
+
+        let result = to_string(&value).unwrap();
+        assert_eq!(result, "1234 = true\n3-14 = 1\n\"3.14\" = 2\nkey-2 = 3\n");
+        assert_eq!(crate::from_str::<Value>(&result).unwrap(), value);
     }
 
     #[test]
-    fn serializer_serialize_struct_variant() {
-        let mut buf = String::new();
-        let serializer = Serializer { writer: &mut buf };
+    fn serializer_empty_keys() {
+        use crate::Value;
+
+        let value = Value::Table(table! {
+            String::new() => Value::Integer(1),
+            "a".into() => Value::Table(table! {
+                String::new() => Value::Table(table! {
+                    " ".into() => Value::Integer(2),
+                }),
+            }),
+        });
+
+        let result = to_string(&value).unwrap();
+        assert_eq!(
+            result,
+            indoc! {r#"
+                "" = 1
+
+                [a.""]
+                " " = 2
+            "#}
+        );
+        assert_eq!(crate::from_str::<Value>(&result).unwrap(), value);
+
+        // Both kinds of quoted empty keys are accepted when deserializing
+        let result: Value = crate::from_str("'' = 1\n[a]\n\"\" = 2").unwrap();
+        assert_eq!(
+            result,
+            Value::Table(table! {
+                String::new() => Value::Integer(1),
+                "a".into() => Value::Table(table! {
+                    String::new() => Value::Integer(2),
+                }),
+            })
+        );
+    }
+
+    #[test]
+    fn serializer_integer_keys() {
+        use std::collections::BTreeMap;
+
+        #[derive(Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+        struct Server {
+            name: String,
+        }
+
+        let servers: BTreeMap<u16, Server> = btreemap! {
+            443 => Server { name: "https".into() },
+            8080 => Server { name: "proxy".into() },
+        };
+        let toml = indoc! {r#"
+            [443]
+            name = "https"
+
+            [8080]
+            name = "proxy"
+        "#};
+        assert_eq!(to_string(&servers).unwrap(), toml);
+
+        let result: BTreeMap<String, Server> = crate::from_str(toml).unwrap();
+        assert_eq!(
+            result.keys().map(String::as_str).collect::<Vec<_>>(),
+            ["443", "8080"]
+        );
+        let result: BTreeMap<u16, Server> = crate::from_str(toml).unwrap();
+        assert_eq!(result, servers);
+
+        let map: BTreeMap<i32, i32> = btreemap! { -1 => 2 };
+        assert_eq!(to_string(&map).unwrap(), "-1 = 2\n");
+
+        // Other non-string keys are still unsupported
+        let map: BTreeMap<bool, i32> = btreemap! { true => 2 };
+        assert_matches!(
+            to_string(&map),
+            Err(Error(ErrorKind::UnsupportedType("bool")))
+        );
+    }
+
+    #[test]
+    fn serializer_kebab_case_keys() {
+        #[derive(Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+        #[serde(rename_all = "kebab-case")]
+        struct Pool {
+            connection_max: u32,
+            idle_timeout_secs: u32,
+        }
+
+        #[derive(Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+        #[serde(rename_all = "kebab-case")]
+        struct Config {
+            server_name: String,
+            connection_pool: Pool,
+        }
+
+        let config = Config {
+            server_name: "example".into(),
+            connection_pool: Pool {
+                connection_max: 16,
+                idle_timeout_secs: 30,
+            },
+        };
+        let toml = indoc! {r#"
+            server-name = "example"
+
+            [connection-pool]
+            connection-max = 16
+            idle-timeout-secs = 30
+        "#};
+
+        assert_eq!(to_string(&config).unwrap(), toml);
+        assert_eq!(crate::from_str::<Config>(toml).unwrap(), config);
+    }
+
+    #[test]
+    fn serializer_skip_serializing_if() {
+        #[derive(Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+        struct Options {
+            #[serde(skip_serializing_if = "Option::is_none")]
+            a: Option<i32>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            b: Option<i32>,
+        }
+
+        #[derive(Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+        struct Config {
+            name: String,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            port: Option<u16>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            host: Option<String>,
+            options: Options,
+            list: Vec<Options>,
+        }
+
+        let config = Config {
+            name: "example".into(),
+            port: None,
+            host: Some("localhost".into()),
+            options: Options { a: None, b: None },
+            list: vec![
+                Options { a: None, b: None },
+                Options {
+                    a: Some(1),
+                    b: None,
+                },
+            ],
+        };
+        let toml = indoc! {r#"
+            name = "example"
+            host = "localhost"
+
+            [options]
+
+            [[list]]
+
+            [[list]]
+            a = 1
+        "#};
+
+        assert_eq!(to_string(&config).unwrap(), toml);
+        assert_eq!(crate::from_str::<Config>(toml).unwrap(), config);
+
+        // A struct with every field skipped is an empty document
+        assert_eq!(to_string(&Options { a: None, b: None }).unwrap(), "");
+    }
+
+    #[test]
+    fn serializer_keys_before_subtables() {
+        use ser::Serialize as _;
+
+        // Fields holding tables are declared before fields holding plain values, so serde
+        // delivers them first, but TOML requires a table's own keys to come before any subtables
+        #[derive(Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+        struct Config {
+            server: Server,
+            plugins: Vec<Plugin>,
+            name: String,
+            ports: Vec<u16>,
+        }
+
+        #[derive(Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+        struct Server {
+            tls: Tls,
+            host: String,
+        }
+
+        #[derive(Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+        struct Tls {
+            enabled: bool,
+        }
+
+        #[derive(Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+        struct Plugin {
+            options: Tls,
+            name: String,
+        }
+
+        let config = Config {
+            server: Server {
+                tls: Tls { enabled: true },
+                host: "localhost".into(),
+            },
+            plugins: vec![Plugin {
+                options: Tls { enabled: false },
+                name: "auth".into(),
+            }],
+            name: "example".into(),
+            ports: vec![80, 443],
+        };
+
+        let mut buf = String::new();
+        config.serialize(Serializer::new(&mut buf)).unwrap();
+        assert_eq!(
+            buf,
+            indoc! {r#"
+                name = "example"
+                ports = [80, 443]
+
+                [server]
+                host = "localhost"
+
+                [server.tls]
+                enabled = true
+
+                [[plugins]]
+                name = "auth"
+
+                [plugins.options]
+                enabled = false
+            "#}
+        );
+        assert_eq!(crate::from_str::<Config>(&buf).unwrap(), config);
+
+        // Sorting keys does not move subtables before keys either
+        let mut buf = String::new();
+        let serializer = Serializer::new(&mut buf).key_order(str::cmp);
+        config.serialize(serializer).unwrap();
+        assert_eq!(
+            buf,
+            indoc! {r#"
+                name = "example"
+                ports = [80, 443]
+
+                [[plugins]]
+                name = "auth"
+
+                [plugins.options]
+                enabled = false
+
+                [server]
+                host = "localhost"
+
+                [server.tls]
+                enabled = true
+            "#}
+        );
+        assert_eq!(crate::from_str::<Config>(&buf).unwrap(), config);
+    }
+
+    #[test]
+    fn serializer_emit_empty_parent_tables() {
+        use ser::Serialize as _;
+
+        #[derive(Debug, serde::Serialize)]
+        struct Config {
+            name: String,
+            servers: Servers,
+        }
+
+        #[derive(Debug, serde::Serialize)]
+        struct Servers {
+            alpha: Server,
+            beta: Server,
+        }
+
+        #[derive(Debug, serde::Serialize)]
+        struct Server {
+            ip: String,
+        }
+
+        let config = Config {
+            name: "example".into(),
+            servers: Servers {
+                alpha: Server {
+                    ip: "10.0.0.1".into(),
+                },
+                beta: Server {
+                    ip: "10.0.0.2".into(),
+                },
+            },
+        };
+
+        let mut buf = String::new();
+        let serializer = Serializer::new(&mut buf);
+        assert!(!serializer.settings.emit_empty_parent_tables);
+        config.serialize(serializer).unwrap();
+        assert_eq!(
+            buf,
+            indoc! {r#"
+                name = "example"
+
+                [servers.alpha]
+                ip = "10.0.0.1"
+
+                [servers.beta]
+                ip = "10.0.0.2"
+            "#}
+        );
+
+        let mut buf = String::new();
+        let serializer = Serializer::new(&mut buf).emit_empty_parent_tables(true);
+        assert!(serializer.settings.emit_empty_parent_tables);
+        config.serialize(serializer).unwrap();
+        assert_eq!(
+            buf,
+            indoc! {r#"
+                name = "example"
+
+                [servers]
+
+                [servers.alpha]
+                ip = "10.0.0.1"
+
+                [servers.beta]
+                ip = "10.0.0.2"
+            "#}
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "datetime")]
+    fn serializer_zero_offset_style() {
+        use ser::Serialize as _;
+
+        use crate::value::Datetime;
+
+        #[derive(Debug, serde::Serialize)]
+        struct Times {
+            zulu: OffsetDatetime,
+            numeric: OffsetDatetime,
+            other: OffsetDatetime,
+            any: AnyDatetime,
+            datetime: Datetime,
+            local: LocalDatetime,
+        }
+
+        let datetime = |offset| OffsetDatetime {
+            offset,
+            ..OffsetDatetime::EXAMPLE
+        };
+        let times = Times {
+            zulu: datetime(Offset::Z),
+            numeric: datetime(Offset::Custom { minutes: 0 }),
+            other: datetime(Offset::Custom { minutes: -90 }),
+            any: AnyDatetime::OffsetDatetime(datetime(Offset::Z)),
+            datetime: datetime(Offset::Custom { minutes: 0 }).into(),
+            local: LocalDatetime::EXAMPLE,
+        };
+
+        let mut buf = String::new();
+        let serializer = Serializer::new(&mut buf);
+        assert_eq!(
+            serializer.settings.zero_offset_style,
+            ZeroOffsetStyle::Preserve
+        );
+        times.serialize(serializer).unwrap();
+        assert_eq!(
+            buf,
+            indoc! {r"
+                zulu = 2023-01-02T03:04:05.006Z
+                numeric = 2023-01-02T03:04:05.006+00:00
+                other = 2023-01-02T03:04:05.006-01:30
+                any = 2023-01-02T03:04:05.006Z
+                datetime = 2023-01-02T03:04:05.006+00:00
+                local = 2023-01-02T03:04:05.006
+            "}
+        );
+
+        let mut buf = String::new();
+        let serializer = Serializer::new(&mut buf).zero_offset_style(ZeroOffsetStyle::Zulu);
+        times.serialize(serializer).unwrap();
+        assert_eq!(
+            buf,
+            indoc! {r"
+                zulu = 2023-01-02T03:04:05.006Z
+                numeric = 2023-01-02T03:04:05.006Z
+                other = 2023-01-02T03:04:05.006-01:30
+                any = 2023-01-02T03:04:05.006Z
+                datetime = 2023-01-02T03:04:05.006Z
+                local = 2023-01-02T03:04:05.006
+            "}
+        );
+
+        let mut buf = String::new();
+        let serializer = Serializer::new(&mut buf).zero_offset_style(ZeroOffsetStyle::Numeric);
+        times.serialize(serializer).unwrap();
+        assert_eq!(
+            buf,
+            indoc! {r"
+                zulu = 2023-01-02T03:04:05.006+00:00
+                numeric = 2023-01-02T03:04:05.006+00:00
+                other = 2023-01-02T03:04:05.006-01:30
+                any = 2023-01-02T03:04:05.006+00:00
+                datetime = 2023-01-02T03:04:05.006+00:00
+                local = 2023-01-02T03:04:05.006
+            "}
+        );
+    }
+
+    #[cfg(feature = "datetime")]
+    #[test]
+    fn zero_offset_style_apply() {
+        let apply = |style: ZeroOffsetStyle, datetime: &str| style.apply(datetime.to_owned());
+
+        assert_eq!(
+            apply(ZeroOffsetStyle::Preserve, "12:00:00+00:00"),
+            "12:00:00+00:00"
+        );
+        assert_eq!(apply(ZeroOffsetStyle::Zulu, "12:00:00+00:00"), "12:00:00Z");
+        assert_eq!(
+            apply(ZeroOffsetStyle::Numeric, "12:00:00z"),
+            "12:00:00+00:00"
+        );
+
+        // An offset of -00:00 means the local offset is unknown, which is not the same as Z
+        assert_eq!(
+            apply(ZeroOffsetStyle::Zulu, "12:00:00-00:00"),
+            "12:00:00-00:00"
+        );
+        assert_eq!(
+            apply(ZeroOffsetStyle::Numeric, "12:00:00-00:00"),
+            "12:00:00-00:00"
+        );
+    }
+
+    #[test]
+    fn serializer_integer_grouping() {
+        use ser::Serialize as _;
+
+        #[derive(Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+        struct Numbers {
+            positive: i64,
+            negative: i64,
+            small: u8,
+            float: f64,
+        }
+
+        let numbers = Numbers {
+            positive: 1_000_000,
+            negative: -12_345,
+            small: 123,
+            float: 1_234_567.5,
+        };
+
+        let mut buf = String::new();
+        let serializer = Serializer::new(&mut buf).integer_grouping(Some(3));
+        assert_eq!(serializer.settings.integer_grouping, Some(3));
+        numbers.serialize(serializer).unwrap();
+        assert_eq!(
+            buf,
+            indoc! {r"
+                positive = 1_000_000
+                negative = -12_345
+                small = 123
+                float = 1_234_567.5
+            "}
+        );
+        assert_eq!(crate::from_str::<Numbers>(&buf).unwrap(), numbers);
+
+        let mut buf = String::new();
+        let serializer = Serializer::new(&mut buf).integer_grouping(None);
+        numbers.serialize(serializer).unwrap();
+        assert_eq!(
+            buf,
+            indoc! {r"
+                positive = 1000000
+                negative = -12345
+                small = 123
+                float = 1234567.5
+            "}
+        );
+    }
+
+    #[test]
+    fn serializer_float_format() {
+        use ser::Serialize as _;
+
+        #[derive(serde::Serialize)]
+        struct Data {
+            small: f64,
+            large: f64,
+            mid: f64,
+        }
+
+        let data = Data {
+            small: 1e-9,
+            large: 1e6,
+            mid: 42.5,
+        };
+
+        let mut buf = String::new();
+        data.serialize(Serializer::new(&mut buf)).unwrap();
+        assert_eq!(buf, "small = 1e-9\nlarge = 1000000.0\nmid = 42.5\n");
+
+        let mut buf = String::new();
+        data.serialize(Serializer::new(&mut buf).float_format(FloatFormat::Scientific(6)))
+            .unwrap();
+        assert_eq!(buf, "small = 1e-9\nlarge = 1e6\nmid = 42.5\n");
+    }
+
+    #[test]
+    fn serializer_inline_array_max_elements() {
+        use std::collections::BTreeMap;
+
+        use ser::Serialize as _;
+
+        let value = btreemap! {
+            "long" => vec![vec![1, 2, 3], vec![4], vec![5, 6]],
+            "short" => vec![vec![1, 2, 3]],
+        };
+
+        let mut buf = String::new();
+        let serializer = Serializer::new(&mut buf).inline_array_max_elements(Some(2));
+        assert_eq!(serializer.settings.inline_array_max_elements, Some(2));
+        value.serialize(serializer).unwrap();
+        assert_eq!(
+            buf,
+            indoc! {r"
+                long = [
+                    [1, 2, 3],
+                    [4],
+                    [5, 6],
+                ]
+                short = [[1, 2, 3]]
+            "}
+        );
+        assert_eq!(
+            crate::from_str::<BTreeMap<String, Vec<Vec<i32>>>>(&buf).unwrap(),
+            value
+                .iter()
+                .map(|(&k, v)| (k.to_owned(), v.clone()))
+                .collect::<BTreeMap<_, _>>()
+        );
+
+        let mut buf = String::new();
+        let serializer = Serializer::new(&mut buf).inline_array_max_elements(None);
+        value.serialize(serializer).unwrap();
+        assert_eq!(
+            buf,
+            indoc! {r"
+                long = [[1, 2, 3], [4], [5, 6]]
+                short = [[1, 2, 3]]
+            "}
+        );
+
+        // Inline tables are split onto their own lines in the same way
+        let points = btreemap! {
+            "long" => vec![
+                btreemap! { "x" => 1, "y" => 2 },
+                btreemap! { "x" => 3, "y" => 4 },
+                btreemap! { "x" => 5, "y" => 6 },
+            ],
+            "short" => vec![btreemap! { "x" => 1, "y" => 2 }],
+        };
+
+        let mut buf = String::new();
+        let serializer = Serializer::new(&mut buf)
+            .array_of_tables_inline(true)
+            .inline_array_max_elements(Some(2));
+        points.serialize(serializer).unwrap();
+        assert_eq!(
+            buf,
+            indoc! {r"
+                long = [
+                    { x = 1, y = 2 },
+                    { x = 3, y = 4 },
+                    { x = 5, y = 6 },
+                ]
+                short = [{ x = 1, y = 2 }]
+            "}
+        );
+        assert_eq!(
+            crate::from_str::<BTreeMap<String, Vec<BTreeMap<String, i32>>>>(&buf)
+                .unwrap()
+                .len(),
+            2
+        );
+    }
+
+    #[test]
+    fn serializer_trailing_newline() {
+        use ser::{Serialize as _, SerializeTupleVariant as _};
+
+        let value = btreemap! { "a" => btreemap! { "b" => 1 }, "c" => btreemap! { "d" => 2 } };
+
+        let mut buf = String::new();
+        let serializer = Serializer::new(&mut buf);
+        assert!(!serializer.settings.omit_trailing_newline);
+        value.serialize(serializer).unwrap();
+        assert_eq!(buf, "[a]\nb = 1\n\n[c]\nd = 2\n");
+
+        let mut buf = String::new();
+        let serializer = Serializer::new(&mut buf).trailing_newline(false);
+        assert!(serializer.settings.omit_trailing_newline);
+        value.serialize(serializer).unwrap();
+        assert_eq!(buf, "[a]\nb = 1\n\n[c]\nd = 2");
+
+        let mut buf = String::new();
+        let serializer = Serializer::new(&mut buf).trailing_newline(false);
+        let mut seq = serializer
+            .serialize_tuple_variant("name", 0, "foo", 2)
+            .unwrap();
+        seq.serialize_field(&1).unwrap();
+        seq.serialize_field(&2).unwrap();
+        seq.end().unwrap();
+        assert_eq!(buf, "foo = [1, 2]");
+    }
+
+    #[test]
+    fn serializer_empty_tables_and_arrays() {
+        use std::collections::BTreeMap;
+
+        #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq)]
+        struct Empty {}
+
+        #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq)]
+        struct Outer {
+            array: Vec<i32>,
+            inline: (i32, BTreeMap<String, i32>),
+            tables: Vec<Empty>,
+            table: Empty,
+            map: BTreeMap<String, i32>,
+            nested: BTreeMap<String, BTreeMap<String, i32>>,
+        }
+
+        let value = Outer {
+            array: vec![],
+            inline: (1, BTreeMap::new()),
+            tables: vec![Empty {}, Empty {}],
+            table: Empty {},
+            map: BTreeMap::new(),
+            nested: btreemap! { "a".into() => BTreeMap::new() },
+        };
+        let result = to_string(&value).unwrap();
+        assert_eq!(
+            result,
+            indoc! {r"
+                array = []
+                inline = [1, {}]
+
+                [[tables]]
+
+                [[tables]]
+
+                [table]
+
+                [map]
+
+                [nested.a]
+            "}
+        );
+        assert_eq!(crate::from_str::<Outer>(&result).unwrap(), value);
+
+        assert_eq!(to_string(&Empty {}).unwrap(), "");
+    }
+
+    #[test]
+    fn serializer_json_value() {
+        let value = serde_json::json!({
+            "name": "soml",
+            "version": 1,
+            "ratio": 0.5,
+            "whole": 2.0,
+            "tags": ["toml", "serde"],
+            "mixed": [1, 2.5, "three", [4]],
+            "package": { "edition": 2021, "publish": false },
+            "bins": [{ "name": "a" }, { "name": "b" }],
+        });
+        let result = to_string(&value).unwrap();
+        assert_eq!(
+            result,
+            indoc! {r#"
+                mixed = [1, 2.5, "three", [4]]
+                name = "soml"
+                ratio = 0.5
+                tags = ["toml", "serde"]
+                version = 1
+                whole = 2.0
+
+                [[bins]]
+                name = "a"
+
+                [[bins]]
+                name = "b"
+
+                [package]
+                edition = 2021
+                publish = false
+            "#}
+        );
+        assert_eq!(
+            crate::from_str::<serde_json::Value>(&result).unwrap(),
+            value
+        );
+
+        // TOML has no null, so nulls can't be serialized anywhere in the document
+        assert_matches!(
+            to_string(&serde_json::json!(null)),
+            Err(Error(ErrorKind::UnsupportedRoot("()")))
+        );
+        for value in [
+            serde_json::json!({ "a": null }),
+            serde_json::json!({ "a": [1, null] }),
+            serde_json::json!({ "a": { "b": null } }),
+        ] {
+            assert_matches!(
+                to_string(&value),
+                Err(Error(ErrorKind::UnsupportedType("()")))
+            );
+        }
+
+        // JSON numbers map to the closest TOML type
+        assert_matches!(
+            to_string(&serde_json::json!({ "a": 1.5e300 })).as_deref(),
+            Ok("a = 1.5e300\n")
+        );
+        assert_matches!(
+            to_string(&serde_json::json!({ "a": -9_223_372_036_854_775_808_i64 })).as_deref(),
+            Ok("a = -9223372036854775808\n")
+        );
+    }
+
+    #[test]
+    fn serializer_key_order() {
+        use std::collections::BTreeMap;
+
+        use ser::Serialize as _;
+
+        #[derive(serde::Serialize)]
+        struct Package {
+            version: &'static str,
+            authors: Vec<&'static str>,
+            name: &'static str,
+        }
+
+        #[derive(serde::Serialize)]
+        struct Manifest {
+            dependencies: BTreeMap<&'static str, &'static str>,
+            package: Package,
+            mixed: (i32, BTreeMap<&'static str, i32>),
+        }
+
+        // Puts `name` first, then the remaining keys in reverse alphabetical order
+        fn compare(a: &str, b: &str) -> Ordering {
+            (b == "name").cmp(&(a == "name")).then_with(|| b.cmp(a))
+        }
+
+        let value = Manifest {
+            dependencies: btreemap! { "a" => "1.0", "c" => "2.0", "b" => "3.0" },
+            package: Package {
+                version: "0.1.0",
+                authors: vec!["Tom"],
+                name: "soml",
+            },
+            mixed: (1, btreemap! { "x" => 1, "z" => 2, "y" => 3 }),
+        };
+
+        let mut buf = String::new();
+        let serializer = Serializer::new(&mut buf);
+        assert!(serializer.settings.key_order.is_none());
+        value.serialize(serializer).unwrap();
+        assert_eq!(
+            buf,
+            indoc! {r#"
+                mixed = [1, { x = 1, y = 3, z = 2 }]
+
+                [dependencies]
+                a = "1.0"
+                b = "3.0"
+                c = "2.0"
+
+                [package]
+                version = "0.1.0"
+                authors = ["Tom"]
+                name = "soml"
+            "#}
+        );
+
+        let mut buf = String::new();
+        let serializer = Serializer::new(&mut buf).key_order(compare);
+        assert!(serializer.settings.key_order.is_some());
+        value.serialize(serializer).unwrap();
+        assert_eq!(
+            buf,
+            indoc! {r#"
+                mixed = [1, { z = 2, y = 3, x = 1 }]
+
+                [package]
+                name = "soml"
+                version = "0.1.0"
+                authors = ["Tom"]
+
+                [dependencies]
+                c = "2.0"
+                b = "3.0"
+                a = "1.0"
+            "#}
+        );
+
+        // The closure can capture state, and is given the keys before they are quoted or escaped
+        let priority = ["zürich", "a b", "x"];
+        let rank = move |key: &str| priority.iter().position(|&k| k == key);
+        let value = btreemap! { "x" => 1, "a b" => 2, "other" => 3, "zürich" => 4 };
+        for (ascii_only, zurich) in [(false, r#""zürich""#), (true, r#""z\u00fcrich""#)] {
+            let mut buf = String::new();
+            let serializer =
+                Serializer::new(&mut buf)
+                    .ascii_only(ascii_only)
+                    .key_order(move |a, b| match (rank(a), rank(b)) {
+                        (Some(a), Some(b)) => a.cmp(&b),
+                        (a, b) => b.is_some().cmp(&a.is_some()).then_with(|| a.cmp(&b)),
+                    });
+            value.serialize(serializer).unwrap();
+            let keys: Vec<_> = buf
+                .lines()
+                .filter_map(|line| line.split_once(" = "))
+                .map(|(key, _)| key)
+                .collect();
+            assert_eq!(keys, [zurich, r#""a b""#, "x", "other"]);
+        }
+
+        assert_eq!(format!("{:?}", KeyOrder(Arc::new(compare))), "KeyOrder(..)");
+    }
+
+    #[test]
+    fn serializer_preamble() {
+        use std::collections::BTreeMap;
+
+        use ser::{Serialize as _, SerializeStructVariant as _};
+
+        let value = btreemap! { "a" => 1, "b" => 2 };
+
+        let mut buf = String::new();
+        let serializer = Serializer::new(&mut buf).preamble("Generated file\n# Do not edit");
+        assert_eq!(
+            serializer.preamble.as_deref(),
+            Some("Generated file\n# Do not edit")
+        );
+        value.serialize(serializer).unwrap();
+        assert_eq!(
+            buf,
+            indoc! {r"
+                # Generated file
+                # Do not edit
+
+                a = 1
+                b = 2
+            "}
+        );
+        assert_eq!(
+            crate::from_str::<BTreeMap<String, i32>>(&buf).unwrap(),
+            btreemap! { "a".into() => 1, "b".into() => 2 }
+        );
+
+        let mut buf = String::new();
+        let serializer = Serializer::new(&mut buf).preamble("Generated file");
+        serializer
+            .serialize_struct_variant("name", 0, "foo", 1)
+            .unwrap()
+            .end()
+            .unwrap();
+        assert_eq!(
+            buf,
+            indoc! {r"
+                # Generated file
+
+                [foo]
+            "}
+        );
+
+        // The blank line after the preamble is omitted along with the trailing newline when the
+        // document is empty
+        let mut buf = String::new();
+        let serializer = Serializer::new(&mut buf)
+            .preamble("Generated file")
+            .trailing_newline(false);
+        BTreeMap::<String, i32>::new()
+            .serialize(serializer)
+            .unwrap();
+        assert_eq!(buf, "# Generated file\n");
+
+        let mut buf = String::new();
+        let serializer = Serializer::new(&mut buf)
+            .preamble("Generated file")
+            .trailing_newline(false);
+        value.serialize(serializer).unwrap();
+        assert_eq!(buf, "# Generated file\n\na = 1\nb = 2");
+
+        let mut buf = String::new();
+        let serializer = Serializer::new(&mut buf).preamble("Generated file");
+        BTreeMap::<String, i32>::new()
+            .serialize(serializer)
+            .unwrap();
+        assert_eq!(buf, "# Generated file\n\n");
+    }
+
+    #[test]
+    fn serializer_unsupported_root() {
+        assert_matches!(
+            to_string(&42),
+            Err(Error(ErrorKind::UnsupportedRoot("i32")))
+        );
+        assert_matches!(
+            to_string(&"x"),
+            Err(Error(ErrorKind::UnsupportedRoot("str")))
+        );
+        assert_matches!(
+            to_string(&vec![1]),
+            Err(Error(ErrorKind::UnsupportedRoot("slice")))
+        );
+        assert_matches!(
+            to_string(&None::<i32>),
+            Err(Error(ErrorKind::UnsupportedRoot("Option")))
+        );
+        assert_matches!(
+            to_string(&Some(1)),
+            Err(Error(ErrorKind::UnsupportedRoot("Option")))
+        );
+
+        assert_eq!(
+            to_string(&42).unwrap_err().to_string(),
+            "unsupported type for the root of a document: i32, expected a struct or map"
+        );
+    }
+
+    #[test]
+    fn serializer_serialize_newtype_variant() {
+        let mut buf = String::new();
+        let serializer = Serializer::new(&mut buf);
+
+        serializer
+            .serialize_newtype_variant("name", 0, "foo", &42)
+            .unwrap();
+
+        assert_eq!(
+            buf,
+            indoc! {r"
+                foo = 42
+            "}
+        );
+    }
+
+    #[test]
+    fn serializer_serialize_tuple_variant() {
+        let mut buf = String::new();
+        let serializer = Serializer::new(&mut buf);
+
+        let seq = serializer
+            .serialize_tuple_variant("name", 0, "foo", 2)
+            .unwrap();
+
+        assert_matches!(seq, WrappedArraySerializer {
+            writer: _,
+            key: "foo",
+            arr: tree::ArraySerializer { arr, .. },
+        } if arr.capacity() == 2);
+    }
+
+    #[test]
+    fn serializer_serialize_map() {
+        let mut buf = String::new();
+        let serializer = Serializer::new(&mut buf);
+
+        let seq = serializer.serialize_map(Some(2)).unwrap();
+
+        assert_matches!(seq, TableSerializer {
+            writer: _,
+            table: tree::TableSerializer { table, .. },
+        } if table.capacity() == 2);
+    }
+
+    #[test]
+    fn serializer_serialize_struct() {
+        let mut buf = String::new();
+        let serializer = Serializer::new(&mut buf);
+
+        let seq = serializer.serialize_struct("name", 2).unwrap();
+
+        assert_matches!(seq, TableSerializer {
+            writer: _,
+            table: tree::TableSerializer { table, .. },
+        } if table.capacity() == 2);
+
+        #[cfg(feature = "datetime")]
+        {
+            let mut buf = String::new();
+            let serializer = Serializer::new(&mut buf);
+
+            let seq = serializer.serialize_struct(OffsetDatetime::WRAPPER_TYPE, 1);
+            assert_matches!(seq, Err(Error(ErrorKind::UnsupportedRoot(..))));
+        }
+    }
+
+    #[test]
+    fn serializer_serialize_struct_variant() {
+        let mut buf = String::new();
+        let serializer = Serializer::new(&mut buf);
 
         let seq = serializer
             .serialize_struct_variant("name", 0, "foo", 2)
@@ -813,7 +2409,7 @@ mod tests {
         use ser::SerializeTupleVariant as _;
 
         let mut buf = String::new();
-        let mut array = WrappedArraySerializer::start(&mut buf, "foo", 2);
+        let mut array = WrappedArraySerializer::start(&mut buf, "foo", 2, Settings::default());
         assert_eq!(array.key, "foo");
         assert!(array.arr.arr.is_empty());
         assert_eq!(array.arr.arr.capacity(), 2);
@@ -833,7 +2429,7 @@ mod tests {
         );
 
         let mut buf = String::new();
-        let mut array = WrappedArraySerializer::start(&mut buf, "foo", 2);
+        let mut array = WrappedArraySerializer::start(&mut buf, "foo", 2, Settings::default());
         assert_eq!(array.key, "foo");
         assert!(array.arr.arr.is_empty());
         assert_eq!(array.arr.arr.capacity(), 2);
@@ -862,7 +2458,7 @@ mod tests {
         use ser::SerializeMap as _;
 
         let mut buf = String::new();
-        let mut table = TableSerializer::start(&mut buf, None);
+        let mut table = TableSerializer::start(&mut buf, None, Settings::default());
         assert!(table.table.table.is_empty());
         assert_eq!(table.table.table.capacity(), 0);
 
@@ -890,7 +2486,7 @@ mod tests {
         use ser::SerializeStruct as _;
 
         let mut buf = String::new();
-        let mut table = TableSerializer::start(&mut buf, Some(2));
+        let mut table = TableSerializer::start(&mut buf, Some(2), Settings::default());
         assert!(table.table.table.is_empty());
         assert_eq!(table.table.table.capacity(), 2);
 
@@ -915,7 +2511,7 @@ mod tests {
         use ser::SerializeStructVariant as _;
 
         let mut buf = String::new();
-        let mut table = WrappedTableSerializer::start(&mut buf, "foo", 2);
+        let mut table = WrappedTableSerializer::start(&mut buf, "foo", 2, Settings::default());
         assert_eq!(table.key, "foo");
         assert!(table.table.table.is_empty());
         assert_eq!(table.table.table.capacity(), 2);
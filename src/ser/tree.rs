@@ -2,7 +2,9 @@ use std::result::Result as StdResult;
 
 use serde::ser;
 
-use crate::ser::{utils, writer, Error, ErrorKind, Result};
+#[cfg(feature = "datetime")]
+use crate::ser::ZeroOffsetStyle;
+use crate::ser::{utils, writer, Error, ErrorKind, KeyOrder, Result, Settings};
 #[cfg(feature = "datetime")]
 use crate::value::{AnyDatetime, LocalDate, LocalDatetime, LocalTime, OffsetDatetime};
 
@@ -14,6 +16,19 @@ pub enum Value {
     Table(Table),
 }
 
+impl Value {
+    // A table which only contains inline values, i.e. one which can reasonably be written as an
+    // inline table
+    fn is_small_table(&self) -> bool {
+        match *self {
+            Self::Table(Table::Table(ref table)) => {
+                table.iter().all(|kv| matches!(kv.1, Self::Inline(_)))
+            }
+            Self::Inline(_) | Self::Table(Table::Array(_)) => false,
+        }
+    }
+}
+
 impl ser::Serialize for Value {
     fn serialize<S>(&self, serializer: S) -> StdResult<S::Ok, S::Error>
     where
@@ -78,8 +93,17 @@ pub enum Array {
     Table(Vec<Vec<(String, Value)>>),
 }
 
-#[derive(Debug)]
-struct Serializer;
+#[derive(Debug, Default)]
+struct Serializer {
+    settings: Settings,
+}
+
+impl Serializer {
+    #[inline]
+    pub const fn new(settings: Settings) -> Self {
+        Self { settings }
+    }
+}
 
 impl ser::Serializer for Serializer {
     type Ok = Value;
@@ -124,7 +148,7 @@ impl ser::Serializer for Serializer {
 
     #[inline]
     fn serialize_i128(self, value: i128) -> Result<Self::Ok> {
-        self.serialize_integer(&value)
+        self.serialize_integer(&utils::int_to_i64(value)?)
     }
 
     #[inline]
@@ -149,7 +173,7 @@ impl ser::Serializer for Serializer {
 
     #[inline]
     fn serialize_u128(self, value: u128) -> Result<Self::Ok> {
-        self.serialize_integer(&value)
+        self.serialize_integer(&utils::int_to_i64(value)?)
     }
 
     #[inline]
@@ -169,7 +193,7 @@ impl ser::Serializer for Serializer {
 
     fn serialize_str(self, value: &str) -> Result<Self::Ok> {
         let mut buf = String::new();
-        writer::Formatter::write_string(value, &mut buf)?;
+        writer::Formatter::write_string(value, self.settings.ascii_only, &mut buf)?;
         Ok(Value::Inline(buf))
     }
 
@@ -243,12 +267,12 @@ impl ser::Serializer for Serializer {
 
     #[inline]
     fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq> {
-        Ok(Self::SerializeSeq::start(len))
+        Ok(Self::SerializeSeq::start(len, self.settings))
     }
 
     #[inline]
     fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple> {
-        Ok(Self::SerializeTuple::start(Some(len)))
+        Ok(Self::SerializeTuple::start(Some(len), self.settings))
     }
 
     #[inline]
@@ -257,7 +281,7 @@ impl ser::Serializer for Serializer {
         _name: &'static str,
         len: usize,
     ) -> Result<Self::SerializeTupleStruct> {
-        Ok(Self::SerializeTupleStruct::start(Some(len)))
+        Ok(Self::SerializeTupleStruct::start(Some(len), self.settings))
     }
 
     #[inline]
@@ -268,21 +292,25 @@ impl ser::Serializer for Serializer {
         variant: &'static str,
         len: usize,
     ) -> Result<Self::SerializeTupleVariant> {
-        Ok(Self::SerializeTupleVariant::start(variant, len))
+        Ok(Self::SerializeTupleVariant::start(
+            variant,
+            len,
+            self.settings,
+        ))
     }
 
     #[inline]
     fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap> {
-        Ok(Self::SerializeMap::start(len))
+        Ok(Self::SerializeMap::start(len, self.settings))
     }
 
     #[inline]
     fn serialize_struct(self, name: &'static str, len: usize) -> Result<Self::SerializeStruct> {
         match name {
             #[cfg(feature = "datetime")]
-            name => Ok(Self::SerializeStruct::start(name, len)),
+            name => Ok(Self::SerializeStruct::start(name, len, self.settings)),
             #[cfg(not(feature = "datetime"))]
-            _ => Ok(Self::SerializeStruct::start(Some(len))),
+            _ => Ok(Self::SerializeStruct::start(Some(len), self.settings)),
         }
     }
 
@@ -294,24 +322,33 @@ impl ser::Serializer for Serializer {
         variant: &'static str,
         len: usize,
     ) -> Result<Self::SerializeStructVariant> {
-        Ok(Self::SerializeStructVariant::start(variant, len))
+        Ok(Self::SerializeStructVariant::start(
+            variant,
+            len,
+            self.settings,
+        ))
     }
 }
 
 impl Serializer {
-    #[allow(clippy::unnecessary_wraps, clippy::unused_self)]
+    #[allow(clippy::unnecessary_wraps)]
     #[inline]
     fn serialize_integer<T: writer::Integer>(self, value: &T) -> Result<Value> {
         let mut buf = String::new();
-        writer::Formatter::write_integer(value, &mut buf)?;
+        writer::Formatter::write_integer(value, self.settings.integer_grouping, &mut buf)?;
         Ok(Value::Inline(buf))
     }
 
-    #[allow(clippy::unnecessary_wraps, clippy::unused_self)]
+    #[allow(clippy::unnecessary_wraps)]
     #[inline]
     fn serialize_float<T: writer::Float>(self, value: &T) -> Result<Value> {
         let mut buf = String::new();
-        writer::Formatter::write_float(value, &mut buf)?;
+        writer::Formatter::write_float(
+            value,
+            self.settings.integer_grouping,
+            self.settings.float_format,
+            &mut buf,
+        )?;
         Ok(Value::Inline(buf))
     }
 }
@@ -319,22 +356,26 @@ impl Serializer {
 #[derive(Debug)]
 pub struct ArraySerializer {
     pub arr: Vec<Value>,
+    pub settings: Settings,
 }
 
 impl ArraySerializer {
-    pub fn start(len: Option<usize>) -> Self {
+    pub fn start(len: Option<usize>, settings: Settings) -> Self {
         Self {
             arr: len.map_or_else(Vec::new, Vec::with_capacity),
+            settings,
         }
     }
 
     pub fn end_inner(self) -> Result<Array> {
-        // If all elements are tables, we can return an array of tables
+        // If all elements are tables, we can return an array of tables. Unless we're asked to
+        // write arrays of tables inline, and none of the tables contain any subtables
         if !self.arr.is_empty()
             && self
                 .arr
                 .iter()
                 .all(|v| matches!(*v, Value::Table(Table::Table(_))))
+            && !(self.settings.array_of_tables_inline && self.arr.iter().all(Value::is_small_table))
         {
             Ok(Array::Table(
                 self.arr
@@ -346,12 +387,23 @@ impl ArraySerializer {
                     .collect(),
             ))
         }
-        // Otherwise format it as an inline array
+        // Otherwise format it as an inline array, split over multiple lines if it has too many
+        // elements. Nested arrays are always written on a single line
         else {
-            use ser::{SerializeSeq as _, Serializer as _};
+            use ser::SerializeSeq as _;
+
+            let multiline = !self.settings.in_array
+                && self
+                    .settings
+                    .inline_array_max_elements
+                    .map_or(false, |max| self.arr.len() > max);
 
             let mut buf = String::new();
-            let mut ser = InlineSerializer::new(&mut buf).serialize_seq(Some(self.arr.len()))?;
+            let mut ser = if multiline {
+                InlineArraySerializer::start_multiline(&mut buf)
+            } else {
+                InlineArraySerializer::start(&mut buf)
+            };
             for el in &self.arr {
                 ser.serialize_element(el)?;
             }
@@ -370,7 +422,11 @@ impl ser::SerializeSeq for ArraySerializer {
     where
         T: ?Sized + ser::Serialize,
     {
-        self.arr.push(value.serialize(Serializer)?);
+        let settings = Settings {
+            in_array: true,
+            ..self.settings.clone()
+        };
+        self.arr.push(value.serialize(Serializer::new(settings))?);
         Ok(())
     }
 
@@ -427,10 +483,10 @@ struct WrappedArraySerializer {
 
 impl WrappedArraySerializer {
     #[inline]
-    pub fn start(key: &'static str, len: usize) -> Self {
+    pub fn start(key: &'static str, len: usize, settings: Settings) -> Self {
         Self {
             key,
-            arr: ArraySerializer::start(Some(len)),
+            arr: ArraySerializer::start(Some(len), settings),
         }
     }
 
@@ -464,22 +520,63 @@ impl ser::SerializeTupleVariant for WrappedArraySerializer {
 #[derive(Debug)]
 pub struct TableSerializer {
     pub table: Vec<(String, Value)>,
+    // The keys before they were quoted or escaped, which are only kept if they need to be sorted
+    raw_keys: Vec<String>,
     key: Option<String>,
+    pub settings: Settings,
 }
 
 impl TableSerializer {
     #[inline]
-    pub fn start(len: Option<usize>) -> Self {
+    pub fn start(len: Option<usize>, settings: Settings) -> Self {
         Self {
             table: len.map_or_else(Vec::new, Vec::with_capacity),
+            raw_keys: Vec::new(),
             key: None,
+            settings: Settings {
+                in_array: false,
+                ..settings
+            },
         }
     }
 
     #[inline]
-    pub fn end_inner(self) -> Vec<(String, Value)> {
+    pub fn end_inner(mut self) -> Vec<(String, Value)> {
+        if self.settings.dotted_keys {
+            // Subtables have already been collapsed, so a whole chain of single-key tables is
+            // collapsed one level at a time
+            for &mut (ref mut key, ref mut value) in &mut self.table {
+                if let Value::Table(Table::Table(ref mut table)) = *value {
+                    if let [(_, Value::Inline(_))] = **table {
+                        if let Some((subkey, subvalue)) = table.pop() {
+                            *key = format!("{key}.{subkey}");
+                            *value = subvalue;
+                        }
+                    }
+                }
+            }
+        }
+        if let Some(KeyOrder(ref compare)) = self.settings.key_order {
+            let mut entries: Vec<_> = self.raw_keys.into_iter().zip(self.table).collect();
+            entries.sort_by(|a, b| compare(&a.0, &b.0));
+            return entries.into_iter().map(|(_, entry)| entry).collect();
+        }
         self.table
     }
+
+    fn serialize_key_inner<T>(&mut self, key: &T) -> Result<String>
+    where
+        T: ?Sized + ser::Serialize,
+    {
+        let mut raw = String::new();
+        key.serialize(utils::KeySerializer::new(&mut raw).raw(true))?;
+        let mut buf = String::new();
+        writer::Formatter::write_key(&raw, self.settings.ascii_only, &mut buf)?;
+        if self.settings.key_order.is_some() {
+            self.raw_keys.push(raw);
+        }
+        Ok(buf)
+    }
 }
 
 impl ser::SerializeMap for TableSerializer {
@@ -490,9 +587,7 @@ impl ser::SerializeMap for TableSerializer {
     where
         T: ?Sized + ser::Serialize,
     {
-        let mut buf = String::new();
-        key.serialize(utils::KeySerializer::new(&mut buf))?;
-        self.key = Some(buf);
+        self.key = Some(self.serialize_key_inner(key)?);
         Ok(())
     }
 
@@ -505,7 +600,10 @@ impl ser::SerializeMap for TableSerializer {
             panic!("TableSerializer::serialize_value called without calling TableSerializer::serialize_key first")
         };
 
-        self.table.push((key, value.serialize(Serializer)?));
+        self.table.push((
+            key,
+            value.serialize(Serializer::new(self.settings.clone()))?,
+        ));
         Ok(())
     }
 
@@ -514,9 +612,11 @@ impl ser::SerializeMap for TableSerializer {
         K: ?Sized + ser::Serialize,
         V: ?Sized + ser::Serialize,
     {
-        let mut buf = String::new();
-        key.serialize(utils::KeySerializer::new(&mut buf))?;
-        self.table.push((buf, value.serialize(Serializer)?));
+        let key = self.serialize_key_inner(key)?;
+        self.table.push((
+            key,
+            value.serialize(Serializer::new(self.settings.clone()))?,
+        ));
         Ok(())
     }
 
@@ -548,8 +648,8 @@ impl ser::SerializeStruct for TableSerializer {
 #[derive(Debug)]
 enum TableOrDatetimeSerializer {
     // Used if type name is AnyDatetime::WRAPPER_TYPE. To detect the date-time type we use the field
-    AnyDatetime,
-    OffsetDatetime(Option<String>),
+    AnyDatetime(ZeroOffsetStyle),
+    OffsetDatetime(Option<String>, ZeroOffsetStyle),
     LocalDatetime(Option<String>),
     LocalDate(Option<String>),
     LocalTime(Option<String>),
@@ -559,14 +659,14 @@ enum TableOrDatetimeSerializer {
 #[cfg(feature = "datetime")]
 impl TableOrDatetimeSerializer {
     #[inline]
-    pub fn start(name: &'static str, len: usize) -> Self {
+    pub fn start(name: &'static str, len: usize, settings: Settings) -> Self {
         match name {
-            AnyDatetime::WRAPPER_TYPE => Self::AnyDatetime,
-            OffsetDatetime::WRAPPER_TYPE => Self::OffsetDatetime(None),
+            AnyDatetime::WRAPPER_TYPE => Self::AnyDatetime(settings.zero_offset_style),
+            OffsetDatetime::WRAPPER_TYPE => Self::OffsetDatetime(None, settings.zero_offset_style),
             LocalDatetime::WRAPPER_TYPE => Self::LocalDatetime(None),
             LocalDate::WRAPPER_TYPE => Self::LocalDate(None),
             LocalTime::WRAPPER_TYPE => Self::LocalTime(None),
-            _ => Self::Table(TableSerializer::start(Some(len))),
+            _ => Self::Table(TableSerializer::start(Some(len), settings)),
         }
     }
 }
@@ -583,15 +683,15 @@ impl ser::SerializeStruct for TableOrDatetimeSerializer {
     {
         match *self {
             // For AnyDatetime use the key to determine the type
-            Self::OffsetDatetime(None) | Self::AnyDatetime
+            Self::OffsetDatetime(None, style) | Self::AnyDatetime(style)
                 if key == OffsetDatetime::WRAPPER_FIELD =>
             {
                 let mut buf = String::new();
                 value.serialize(utils::RawStringSerializer { writer: &mut buf })?;
-                *self = Self::OffsetDatetime(Some(buf));
+                *self = Self::OffsetDatetime(Some(buf), style);
                 Ok(())
             }
-            Self::LocalDatetime(None) | Self::AnyDatetime
+            Self::LocalDatetime(None) | Self::AnyDatetime(_)
                 if key == LocalDatetime::WRAPPER_FIELD =>
             {
                 let mut buf = String::new();
@@ -599,27 +699,27 @@ impl ser::SerializeStruct for TableOrDatetimeSerializer {
                 *self = Self::LocalDatetime(Some(buf));
                 Ok(())
             }
-            Self::LocalDate(None) | Self::AnyDatetime if key == LocalDate::WRAPPER_FIELD => {
+            Self::LocalDate(None) | Self::AnyDatetime(_) if key == LocalDate::WRAPPER_FIELD => {
                 let mut buf = String::new();
                 value.serialize(utils::RawStringSerializer { writer: &mut buf })?;
                 *self = Self::LocalDate(Some(buf));
                 Ok(())
             }
-            Self::LocalTime(None) | Self::AnyDatetime if key == LocalTime::WRAPPER_FIELD => {
+            Self::LocalTime(None) | Self::AnyDatetime(_) if key == LocalTime::WRAPPER_FIELD => {
                 let mut buf = String::new();
                 value.serialize(utils::RawStringSerializer { writer: &mut buf })?;
                 *self = Self::LocalTime(Some(buf));
                 Ok(())
             }
-            Self::OffsetDatetime(Some(_))
+            Self::OffsetDatetime(Some(_), _)
             | Self::LocalDatetime(Some(_))
             | Self::LocalDate(Some(_))
             | Self::LocalTime(Some(_)) => Err(ErrorKind::UnsupportedValue(
                 "date-time wrapper with more than one member",
             )
             .into()),
-            Self::AnyDatetime
-            | Self::OffsetDatetime(_)
+            Self::AnyDatetime(_)
+            | Self::OffsetDatetime(..)
             | Self::LocalDatetime(_)
             | Self::LocalDate(_)
             | Self::LocalTime(_) => Err(ErrorKind::UnsupportedValue(key).into()),
@@ -630,12 +730,12 @@ impl ser::SerializeStruct for TableOrDatetimeSerializer {
     #[inline]
     fn end(self) -> Result<Self::Ok> {
         match self {
-            Self::OffsetDatetime(Some(str))
-            | Self::LocalDatetime(Some(str))
+            Self::OffsetDatetime(Some(str), style) => Ok(Value::Inline(style.apply(str))),
+            Self::LocalDatetime(Some(str))
             | Self::LocalDate(Some(str))
             | Self::LocalTime(Some(str)) => Ok(Value::Inline(str)),
-            Self::AnyDatetime
-            | Self::OffsetDatetime(None)
+            Self::AnyDatetime(_)
+            | Self::OffsetDatetime(None, _)
             | Self::LocalDatetime(None)
             | Self::LocalDate(None)
             | Self::LocalTime(None) => {
@@ -654,10 +754,10 @@ struct WrappedTableSerializer {
 
 impl WrappedTableSerializer {
     #[inline]
-    pub fn start(key: &'static str, len: usize) -> Self {
+    pub fn start(key: &'static str, len: usize, settings: Settings) -> Self {
         Self {
             key,
-            table: TableSerializer::start(Some(len)),
+            table: TableSerializer::start(Some(len), settings),
         }
     }
 }
@@ -734,13 +834,28 @@ impl<'a> ser::Serializer for InlineSerializer<'a> {
 pub struct InlineArraySerializer<'a> {
     buf: &'a mut String,
     first: bool,
+    multiline: bool,
 }
 
 impl<'a> InlineArraySerializer<'a> {
     #[inline]
     pub fn start(buf: &'a mut String) -> Self {
         buf.push('[');
-        Self { buf, first: true }
+        Self {
+            buf,
+            first: true,
+            multiline: false,
+        }
+    }
+
+    #[inline]
+    pub fn start_multiline(buf: &'a mut String) -> Self {
+        buf.push('[');
+        Self {
+            buf,
+            first: true,
+            multiline: true,
+        }
     }
 }
 
@@ -752,16 +867,29 @@ impl ser::SerializeSeq for InlineArraySerializer<'_> {
     where
         T: ?Sized + ser::Serialize,
     {
-        if !self.first {
-            self.buf.push_str(", ");
-        }
+        let separator = if self.multiline {
+            "\n    "
+        } else if self.first {
+            ""
+        } else {
+            ", "
+        };
+        self.buf.push_str(separator);
         self.first = false;
 
-        value.serialize(InlineSerializer::new(self.buf))
+        value.serialize(InlineSerializer::new(self.buf))?;
+
+        if self.multiline {
+            self.buf.push(',');
+        }
+        Ok(())
     }
 
     #[inline]
     fn end(self) -> Result<Self::Ok> {
+        if self.multiline {
+            self.buf.push('\n');
+        }
         self.buf.push(']');
         Ok(())
     }
@@ -776,7 +904,7 @@ pub struct InlineTableSerializer<'a> {
 impl<'a> InlineTableSerializer<'a> {
     #[inline]
     pub fn start(buf: &'a mut String) -> Self {
-        buf.push_str("{ ");
+        buf.push('{');
         Self { buf, first: true }
     }
 }
@@ -789,12 +917,11 @@ impl ser::SerializeMap for InlineTableSerializer<'_> {
     where
         T: ?Sized + ser::Serialize,
     {
-        if !self.first {
-            self.buf.push_str(", ");
-        }
+        self.buf.push_str(if self.first { " " } else { ", " });
         self.first = false;
 
-        key.serialize(utils::KeySerializer::new(self.buf))
+        // Keys have already been formatted by `TableSerializer`
+        key.serialize(InlineSerializer::new(self.buf))
     }
 
     fn serialize_value<T>(&mut self, value: &T) -> Result<()>
@@ -807,7 +934,8 @@ impl ser::SerializeMap for InlineTableSerializer<'_> {
 
     #[inline]
     fn end(self) -> Result<Self::Ok> {
-        self.buf.push_str(" }");
+        // Empty tables are written as `{}` rather than `{  }`
+        self.buf.push_str(if self.first { "}" } else { " }" });
         Ok(())
     }
 }
@@ -883,112 +1011,139 @@ mod tests {
 
     #[test]
     fn serializer_serialize_bool() {
-        assert_matches!(Serializer.serialize_bool(true), Ok(Value::Inline(v)) if v == "true");
-        assert_matches!(Serializer.serialize_bool(false), Ok(Value::Inline(v)) if v == "false");
+        assert_matches!(Serializer::default().serialize_bool(true), Ok(Value::Inline(v)) if v == "true");
+        assert_matches!(Serializer::default().serialize_bool(false), Ok(Value::Inline(v)) if v == "false");
     }
 
     #[test]
     fn serializer_serialize_i8() {
-        assert_matches!(Serializer.serialize_i8(42), Ok(Value::Inline(v)) if v == "42");
-        assert_matches!(Serializer.serialize_i8(-12), Ok(Value::Inline(v)) if v == "-12");
+        assert_matches!(Serializer::default().serialize_i8(42), Ok(Value::Inline(v)) if v == "42");
+        assert_matches!(Serializer::default().serialize_i8(-12), Ok(Value::Inline(v)) if v == "-12");
     }
 
     #[test]
     fn serializer_serialize_i16() {
-        assert_matches!(Serializer.serialize_i16(42), Ok(Value::Inline(v)) if v == "42");
-        assert_matches!(Serializer.serialize_i16(-12), Ok(Value::Inline(v)) if v == "-12");
+        assert_matches!(Serializer::default().serialize_i16(42), Ok(Value::Inline(v)) if v == "42");
+        assert_matches!(Serializer::default().serialize_i16(-12), Ok(Value::Inline(v)) if v == "-12");
     }
 
     #[test]
     fn serializer_serialize_i32() {
-        assert_matches!(Serializer.serialize_i32(42), Ok(Value::Inline(v)) if v == "42");
-        assert_matches!(Serializer.serialize_i32(-12), Ok(Value::Inline(v)) if v == "-12");
+        assert_matches!(Serializer::default().serialize_i32(42), Ok(Value::Inline(v)) if v == "42");
+        assert_matches!(Serializer::default().serialize_i32(-12), Ok(Value::Inline(v)) if v == "-12");
     }
 
     #[test]
     fn serializer_serialize_i64() {
-        assert_matches!(Serializer.serialize_i64(42), Ok(Value::Inline(v)) if v == "42");
-        assert_matches!(Serializer.serialize_i64(-12), Ok(Value::Inline(v)) if v == "-12");
+        assert_matches!(Serializer::default().serialize_i64(42), Ok(Value::Inline(v)) if v == "42");
+        assert_matches!(Serializer::default().serialize_i64(-12), Ok(Value::Inline(v)) if v == "-12");
+
+        let serializer = Serializer::new(Settings {
+            integer_grouping: Some(3),
+            ..Settings::default()
+        });
+        assert_matches!(serializer.serialize_i64(-1_234_567), Ok(Value::Inline(v)) if v == "-1_234_567");
     }
 
     #[test]
     fn serializer_serialize_i128() {
-        assert_matches!(Serializer.serialize_i128(42), Ok(Value::Inline(v)) if v == "42");
-        assert_matches!(Serializer.serialize_i128(-12), Ok(Value::Inline(v)) if v == "-12");
+        assert_matches!(Serializer::default().serialize_i128(42), Ok(Value::Inline(v)) if v == "42");
+        assert_matches!(Serializer::default().serialize_i128(-12), Ok(Value::Inline(v)) if v == "-12");
+        assert_matches!(
+            Serializer::default().serialize_i128(i64::MIN.into()),
+            Ok(Value::Inline(v)) if v == "-9223372036854775808"
+        );
+        assert_matches!(
+            Serializer::default().serialize_i128(i128::from(i64::MAX) + 1),
+            Err(Error(ErrorKind::IntegerOutOfRange))
+        );
     }
 
     #[test]
     fn serializer_serialize_u8() {
-        assert_matches!(Serializer.serialize_u8(42), Ok(Value::Inline(v)) if v == "42");
-        assert_matches!(Serializer.serialize_u8(12), Ok(Value::Inline(v)) if v == "12");
+        assert_matches!(Serializer::default().serialize_u8(42), Ok(Value::Inline(v)) if v == "42");
+        assert_matches!(Serializer::default().serialize_u8(12), Ok(Value::Inline(v)) if v == "12");
     }
 
     #[test]
     fn serializer_serialize_u16() {
-        assert_matches!(Serializer.serialize_u16(42), Ok(Value::Inline(v)) if v == "42");
-        assert_matches!(Serializer.serialize_u16(12), Ok(Value::Inline(v)) if v == "12");
+        assert_matches!(Serializer::default().serialize_u16(42), Ok(Value::Inline(v)) if v == "42");
+        assert_matches!(Serializer::default().serialize_u16(12), Ok(Value::Inline(v)) if v == "12");
     }
 
     #[test]
     fn serializer_serialize_u32() {
-        assert_matches!(Serializer.serialize_u32(42), Ok(Value::Inline(v)) if v == "42");
-        assert_matches!(Serializer.serialize_u32(12), Ok(Value::Inline(v)) if v == "12");
+        assert_matches!(Serializer::default().serialize_u32(42), Ok(Value::Inline(v)) if v == "42");
+        assert_matches!(Serializer::default().serialize_u32(12), Ok(Value::Inline(v)) if v == "12");
     }
 
     #[test]
     fn serializer_serialize_u64() {
-        assert_matches!(Serializer.serialize_u64(42), Ok(Value::Inline(v)) if v == "42");
-        assert_matches!(Serializer.serialize_u64(12), Ok(Value::Inline(v)) if v == "12");
+        assert_matches!(Serializer::default().serialize_u64(42), Ok(Value::Inline(v)) if v == "42");
+        assert_matches!(Serializer::default().serialize_u64(12), Ok(Value::Inline(v)) if v == "12");
     }
 
     #[test]
     fn serializer_serialize_u128() {
-        assert_matches!(Serializer.serialize_u128(42), Ok(Value::Inline(v)) if v == "42");
-        assert_matches!(Serializer.serialize_u128(12), Ok(Value::Inline(v)) if v == "12");
+        assert_matches!(Serializer::default().serialize_u128(42), Ok(Value::Inline(v)) if v == "42");
+        assert_matches!(Serializer::default().serialize_u128(12), Ok(Value::Inline(v)) if v == "12");
+        assert_matches!(
+            Serializer::default().serialize_u128(i64::MAX as u128),
+            Ok(Value::Inline(v)) if v == "9223372036854775807"
+        );
+        assert_matches!(
+            Serializer::default().serialize_u128(i64::MAX as u128 + 1),
+            Err(Error(ErrorKind::IntegerOutOfRange))
+        );
     }
 
     #[test]
     fn serializer_serialize_f32() {
-        assert_matches!(Serializer.serialize_f32(42.0), Ok(Value::Inline(v)) if v == "42.0");
-        assert_matches!(Serializer.serialize_f32(-12.0), Ok(Value::Inline(v)) if v == "-12.0");
-        assert_matches!(Serializer.serialize_f32(1e28), Ok(Value::Inline(v)) if v == "1e28");
-        assert_matches!(Serializer.serialize_f32(0.5e-9), Ok(Value::Inline(v)) if v == "5e-10");
+        assert_matches!(Serializer::default().serialize_f32(42.0), Ok(Value::Inline(v)) if v == "42.0");
+        assert_matches!(Serializer::default().serialize_f32(-12.0), Ok(Value::Inline(v)) if v == "-12.0");
+        assert_matches!(Serializer::default().serialize_f32(1e28), Ok(Value::Inline(v)) if v == "1e28");
+        assert_matches!(Serializer::default().serialize_f32(0.5e-9), Ok(Value::Inline(v)) if v == "5e-10");
         assert_matches!(
-            Serializer.serialize_f32(f32::INFINITY),
+            Serializer::default().serialize_f32(f32::INFINITY),
             Ok(Value::Inline(v)) if v == "inf"
         );
         assert_matches!(
-            Serializer.serialize_f32(f32::NEG_INFINITY),
+            Serializer::default().serialize_f32(f32::NEG_INFINITY),
             Ok(Value::Inline(v)) if v == "-inf"
         );
-        assert_matches!(Serializer.serialize_f32(f32::NAN), Ok(Value::Inline(v)) if v == "nan");
-        assert_matches!(Serializer.serialize_f32(-f32::NAN), Ok(Value::Inline(v)) if v == "-nan");
+        assert_matches!(Serializer::default().serialize_f32(f32::NAN), Ok(Value::Inline(v)) if v == "nan");
+        assert_matches!(Serializer::default().serialize_f32(-f32::NAN), Ok(Value::Inline(v)) if v == "-nan");
     }
 
     #[test]
     fn serializer_serialize_f64() {
-        assert_matches!(Serializer.serialize_f64(42.0), Ok(Value::Inline(v)) if v == "42.0");
-        assert_matches!(Serializer.serialize_f64(-12.0), Ok(Value::Inline(v)) if v == "-12.0");
-        assert_matches!(Serializer.serialize_f64(1e28), Ok(Value::Inline(v)) if v == "1e28");
-        assert_matches!(Serializer.serialize_f64(0.5e-9), Ok(Value::Inline(v)) if v == "5e-10");
+        assert_matches!(Serializer::default().serialize_f64(42.0), Ok(Value::Inline(v)) if v == "42.0");
+        assert_matches!(Serializer::default().serialize_f64(-12.0), Ok(Value::Inline(v)) if v == "-12.0");
+        assert_matches!(Serializer::default().serialize_f64(1e28), Ok(Value::Inline(v)) if v == "1e28");
+        assert_matches!(Serializer::default().serialize_f64(0.5e-9), Ok(Value::Inline(v)) if v == "5e-10");
+        assert_matches!(
+            Serializer::new(Settings { integer_grouping: Some(3), ..Settings::default() })
+                .serialize_f64(12_345.5),
+            Ok(Value::Inline(v)) if v == "12_345.5"
+        );
         assert_matches!(
-            Serializer.serialize_f64(f64::INFINITY),
+            Serializer::default().serialize_f64(f64::INFINITY),
             Ok(Value::Inline(v)) if v == "inf"
         );
         assert_matches!(
-            Serializer.serialize_f64(f64::NEG_INFINITY),
+            Serializer::default().serialize_f64(f64::NEG_INFINITY),
             Ok(Value::Inline(v)) if v == "-inf"
         );
-        assert_matches!(Serializer.serialize_f64(f64::NAN), Ok(Value::Inline(v)) if v == "nan");
-        assert_matches!(Serializer.serialize_f64(-f64::NAN), Ok(Value::Inline(v)) if v == "-nan");
+        assert_matches!(Serializer::default().serialize_f64(f64::NAN), Ok(Value::Inline(v)) if v == "nan");
+        assert_matches!(Serializer::default().serialize_f64(-f64::NAN), Ok(Value::Inline(v)) if v == "-nan");
     }
 
     #[test]
     fn serializer_serialize_char() {
-        assert_matches!(Serializer.serialize_char('a'), Ok(Value::Inline(v)) if v == r#""a""#);
-        assert_matches!(Serializer.serialize_char('😎'), Ok(Value::Inline(v)) if v == r#""😎""#);
+        assert_matches!(Serializer::default().serialize_char('a'), Ok(Value::Inline(v)) if v == r#""a""#);
+        assert_matches!(Serializer::default().serialize_char('😎'), Ok(Value::Inline(v)) if v == r#""😎""#);
         assert_matches!(
-            Serializer.serialize_char('\n'),
+            Serializer::default().serialize_char('\n'),
             Ok(Value::Inline(v))
                 if v == indoc! {r#"
                     """
@@ -999,10 +1154,10 @@ mod tests {
 
     #[test]
     fn serializer_serialize_str() {
-        assert_matches!(Serializer.serialize_str("foo"), Ok(Value::Inline(v)) if v == r#""foo""#);
-        assert_matches!(Serializer.serialize_str("😎"), Ok(Value::Inline(v)) if v == r#""😎""#);
+        assert_matches!(Serializer::default().serialize_str("foo"), Ok(Value::Inline(v)) if v == r#""foo""#);
+        assert_matches!(Serializer::default().serialize_str("😎"), Ok(Value::Inline(v)) if v == r#""😎""#);
         assert_matches!(
-            Serializer.serialize_str("abc\ndef\n"),
+            Serializer::default().serialize_str("abc\ndef\n"),
             Ok(Value::Inline(v))
                 if v == indoc! {r#"
                     """
@@ -1015,15 +1170,15 @@ mod tests {
     #[test]
     fn serializer_serialize_bytes() {
         assert_matches!(
-            Serializer.serialize_bytes(b"foo"),
+            Serializer::default().serialize_bytes(b"foo"),
             Ok(Value::Inline(v)) if v == "[102, 111, 111]"
         );
         assert_matches!(
-            Serializer.serialize_bytes(b"\xF0\x9F\x98\x8E"),
+            Serializer::default().serialize_bytes(b"\xF0\x9F\x98\x8E"),
             Ok(Value::Inline(v)) if v == "[240, 159, 152, 142]"
         );
         assert_matches!(
-            Serializer.serialize_bytes(b"abc\ndef\n"),
+            Serializer::default().serialize_bytes(b"abc\ndef\n"),
             Ok(Value::Inline(v)) if v == "[97, 98, 99, 10, 100, 101, 102, 10]"
         );
     }
@@ -1031,21 +1186,21 @@ mod tests {
     #[test]
     fn serializer_serialize_none() {
         assert_matches!(
-            Serializer.serialize_none(),
+            Serializer::default().serialize_none(),
             Err(Error(ErrorKind::UnsupportedValue(..)))
         );
     }
 
     #[test]
     fn serializer_serialize_some() {
-        assert_matches!(Serializer.serialize_some(&42), Ok(Value::Inline(v)) if v == "42");
-        assert_matches!(Serializer.serialize_some("foo"), Ok(Value::Inline(v)) if v == r#""foo""#);
+        assert_matches!(Serializer::default().serialize_some(&42), Ok(Value::Inline(v)) if v == "42");
+        assert_matches!(Serializer::default().serialize_some("foo"), Ok(Value::Inline(v)) if v == r#""foo""#);
     }
 
     #[test]
     fn serializer_serialize_unit() {
         assert_matches!(
-            Serializer.serialize_unit(),
+            Serializer::default().serialize_unit(),
             Err(Error(ErrorKind::UnsupportedType(..)))
         );
     }
@@ -1053,7 +1208,7 @@ mod tests {
     #[test]
     fn serializer_serialize_unit_struct() {
         assert_matches!(
-            Serializer.serialize_unit_struct("name"),
+            Serializer::default().serialize_unit_struct("name"),
             Err(Error(ErrorKind::UnsupportedType(..)))
         );
     }
@@ -1061,7 +1216,7 @@ mod tests {
     #[test]
     fn serializer_serialize_unit_variant() {
         assert_matches!(
-            Serializer.serialize_unit_variant("name", 0, "foo"),
+            Serializer::default().serialize_unit_variant("name", 0, "foo"),
             Ok(Value::Inline(v)) if v == r#""foo""#
         );
     }
@@ -1069,7 +1224,7 @@ mod tests {
     #[test]
     fn serializer_serialize_newtype_struct() {
         assert_matches!(
-            Serializer.serialize_newtype_struct("name", &42),
+            Serializer::default().serialize_newtype_struct("name", &42),
             Ok(Value::Inline(v)) if v == "42"
         );
     }
@@ -1077,7 +1232,7 @@ mod tests {
     #[test]
     fn serializer_serialize_newtype_variant() {
         assert_matches!(
-            Serializer.serialize_newtype_variant("name", 0, "foo", &42),
+            Serializer::default().serialize_newtype_variant("name", 0, "foo", &42),
             Ok(Value::Table(Table::Table(t)))
                 if matches!(t[..], [(ref k, Value::Inline(ref v))] if k == "foo" && v == "42")
         );
@@ -1086,34 +1241,34 @@ mod tests {
     #[test]
     fn serializer_serialize_seq() {
         assert_matches!(
-            Serializer.serialize_seq(Some(2)),
-            Ok(ArraySerializer { arr }) if arr.capacity() == 2
+            Serializer::default().serialize_seq(Some(2)),
+            Ok(ArraySerializer { arr, .. }) if arr.capacity() == 2
         );
     }
 
     #[test]
     fn serializer_serialize_tuple() {
         assert_matches!(
-            Serializer.serialize_tuple(2),
-            Ok(ArraySerializer { arr }) if arr.capacity() == 2
+            Serializer::default().serialize_tuple(2),
+            Ok(ArraySerializer { arr, .. }) if arr.capacity() == 2
         );
     }
 
     #[test]
     fn serializer_serialize_tuple_struct() {
         assert_matches!(
-            Serializer.serialize_tuple_struct("name", 2),
-            Ok(ArraySerializer { arr }) if arr.capacity() == 2
+            Serializer::default().serialize_tuple_struct("name", 2),
+            Ok(ArraySerializer { arr, .. }) if arr.capacity() == 2
         );
     }
 
     #[test]
     fn serializer_serialize_tuple_variant() {
         assert_matches!(
-            Serializer.serialize_tuple_variant("name", 0, "foo", 2),
+            Serializer::default().serialize_tuple_variant("name", 0, "foo", 2),
             Ok(WrappedArraySerializer {
                 key,
-                arr: ArraySerializer { arr },
+                arr: ArraySerializer { arr, .. },
             }) if key == "foo" && arr.capacity() == 2
         );
     }
@@ -1121,8 +1276,8 @@ mod tests {
     #[test]
     fn serializer_serialize_map() {
         assert_matches!(
-            Serializer.serialize_map(Some(2)),
-            Ok(TableSerializer { table, key: None }) if table.capacity() == 2
+            Serializer::default().serialize_map(Some(2)),
+            Ok(TableSerializer { table, key: None, .. }) if table.capacity() == 2
         );
     }
 
@@ -1130,15 +1285,15 @@ mod tests {
     fn serializer_serialize_struct() {
         #[cfg(feature = "datetime")]
         assert_matches!(
-            Serializer.serialize_struct("name", 2),
-            Ok(TableOrDatetimeSerializer::Table(TableSerializer { table, key: None }))
+            Serializer::default().serialize_struct("name", 2),
+            Ok(TableOrDatetimeSerializer::Table(TableSerializer { table, key: None, .. }))
                 if table.capacity() == 2
         );
 
         #[cfg(not(feature = "datetime"))]
         assert_matches!(
-            Serializer.serialize_struct("name", 2),
-            Ok(TableSerializer { table, key: None })
+            Serializer::default().serialize_struct("name", 2),
+            Ok(TableSerializer { table, key: None, .. })
                 if table.capacity() == 2
         );
     }
@@ -1146,10 +1301,10 @@ mod tests {
     #[test]
     fn serializer_serialize_struct_variant() {
         assert_matches!(
-            Serializer.serialize_struct_variant("name", 0, "foo", 2),
+            Serializer::default().serialize_struct_variant("name", 0, "foo", 2),
             Ok(WrappedTableSerializer {
                 key,
-                table: TableSerializer { table, key: None }
+                table: TableSerializer { table, key: None, .. }
             }) if key == "foo" && table.capacity() == 2
         );
     }
@@ -1158,7 +1313,7 @@ mod tests {
     fn array_serializer_seq() {
         use ser::SerializeSeq as _;
 
-        let mut array = ArraySerializer::start(None);
+        let mut array = ArraySerializer::start(None, Settings::default());
         assert!(array.arr.is_empty());
         assert_eq!(array.arr.capacity(), 0);
 
@@ -1176,7 +1331,7 @@ mod tests {
     fn array_serializer_tuple() {
         use ser::SerializeTuple as _;
 
-        let mut array = ArraySerializer::start(Some(2));
+        let mut array = ArraySerializer::start(Some(2), Settings::default());
         assert!(array.arr.is_empty());
         assert_eq!(array.arr.capacity(), 2);
 
@@ -1193,7 +1348,7 @@ mod tests {
     fn array_serializer_tuple_struct() {
         use ser::SerializeTupleStruct as _;
 
-        let mut array = ArraySerializer::start(Some(2));
+        let mut array = ArraySerializer::start(Some(2), Settings::default());
         assert!(array.arr.is_empty());
         assert_eq!(array.arr.capacity(), 2);
 
@@ -1210,7 +1365,7 @@ mod tests {
     fn array_serializer_array_of_tables() {
         use ser::SerializeSeq as _;
 
-        let mut array = ArraySerializer::start(None);
+        let mut array = ArraySerializer::start(None, Settings::default());
         assert!(array.arr.is_empty());
         assert_eq!(array.arr.capacity(), 0);
 
@@ -1241,11 +1396,79 @@ mod tests {
         );
     }
 
+    #[test]
+    fn array_serializer_array_of_tables_inline() {
+        use ser::SerializeSeq as _;
+
+        let settings = Settings {
+            array_of_tables_inline: true,
+            ..Settings::default()
+        };
+
+        let mut array = ArraySerializer::start(None, settings.clone());
+        array
+            .serialize_element(&hashmap! { "foo" => "bar" })
+            .unwrap();
+        array
+            .serialize_element(&hashmap! { "baz" => "qux" })
+            .unwrap();
+        assert_matches!(
+            array.end().unwrap(),
+            Value::Inline(v) if v == r#"[{ foo = "bar" }, { baz = "qux" }]"#
+        );
+
+        let mut array = ArraySerializer::start(None, settings);
+        array
+            .serialize_element(&hashmap! { "foo" => hashmap! { "bar" => "baz" } })
+            .unwrap();
+        assert_matches!(array.end().unwrap(), Value::Table(Table::Array(a)) if a.len() == 1);
+    }
+
+    #[test]
+    fn array_serializer_inline_array_max_elements() {
+        use ser::SerializeSeq as _;
+
+        let settings = Settings {
+            inline_array_max_elements: Some(2),
+            ..Settings::default()
+        };
+
+        let mut array = ArraySerializer::start(None, settings.clone());
+        array.serialize_element(&1).unwrap();
+        array.serialize_element(&2).unwrap();
+        assert_matches!(array.end().unwrap(), Value::Inline(v) if v == "[1, 2]");
+
+        let mut array = ArraySerializer::start(None, settings.clone());
+        array.serialize_element(&1).unwrap();
+        array.serialize_element(&2).unwrap();
+        array.serialize_element(&[3, 4, 5]).unwrap();
+        assert_matches!(
+            array.end().unwrap(),
+            Value::Inline(v) if v == "[\n    1,\n    2,\n    [3, 4, 5],\n]"
+        );
+
+        // Tables in an array of tables can still contain multiline arrays
+        let mut array = ArraySerializer::start(None, settings);
+        array
+            .serialize_element(&hashmap! { "foo" => [1, 2, 3] })
+            .unwrap();
+        assert_matches!(
+            array.end().unwrap(),
+            Value::Table(Table::Array(a)) if matches!(
+                a[..],
+                [ref t] if matches!(
+                    t[..],
+                    [(ref k, Value::Inline(ref v))] if k == "foo" && v == "[\n    1,\n    2,\n    3,\n]"
+                )
+            )
+        );
+    }
+
     #[test]
     fn wrapped_array_serializer() {
         use ser::SerializeTupleVariant as _;
 
-        let mut array = WrappedArraySerializer::start("foo", 2);
+        let mut array = WrappedArraySerializer::start("foo", 2, Settings::default());
         assert_eq!(array.key, "foo");
         assert!(array.arr.arr.is_empty());
         assert_eq!(array.arr.arr.capacity(), 2);
@@ -1268,7 +1491,7 @@ mod tests {
     fn table_serializer_map() {
         use ser::SerializeMap as _;
 
-        let mut table = TableSerializer::start(None);
+        let mut table = TableSerializer::start(None, Settings::default());
         assert!(table.key.is_none());
         assert!(table.table.is_empty());
         assert_eq!(table.table.capacity(), 0);
@@ -1300,7 +1523,7 @@ mod tests {
     fn table_serializer_struct() {
         use ser::SerializeStruct as _;
 
-        let mut table = TableSerializer::start(Some(2));
+        let mut table = TableSerializer::start(Some(2), Settings::default());
         assert!(table.key.is_none());
         assert!(table.table.is_empty());
         assert_eq!(table.table.capacity(), 2);
@@ -1332,7 +1555,7 @@ mod tests {
         use ser::SerializeStruct as _;
         use TableOrDatetimeSerializer as TODS;
 
-        let mut table = TODS::start("foo", 2);
+        let mut table = TODS::start("foo", 2, Settings::default());
         assert_matches!(table, TODS::Table(ref table) if table.key.is_none());
         assert_matches!(table, TODS::Table(ref table) if table.table.is_empty());
         assert_matches!(table, TODS::Table(ref table) if table.table.capacity() == 2);
@@ -1364,8 +1587,8 @@ mod tests {
         use ser::SerializeStruct as _;
         use TableOrDatetimeSerializer as TODS;
 
-        let mut table = TODS::start(OffsetDatetime::WRAPPER_TYPE, 1);
-        assert_matches!(table, TODS::OffsetDatetime(None));
+        let mut table = TODS::start(OffsetDatetime::WRAPPER_TYPE, 1, Settings::default());
+        assert_matches!(table, TODS::OffsetDatetime(None, _));
 
         table
             .serialize_field(
@@ -1375,7 +1598,7 @@ mod tests {
             .unwrap();
         assert_matches!(
             table,
-            TODS::OffsetDatetime(Some(ref d)) if d == OffsetDatetime::EXAMPLE_STR
+            TODS::OffsetDatetime(Some(ref d), _) if d == OffsetDatetime::EXAMPLE_STR
         );
 
         assert_matches!(table.end().unwrap(), Value::Inline(v) if v == OffsetDatetime::EXAMPLE_STR);
@@ -1387,7 +1610,7 @@ mod tests {
         use ser::SerializeStruct as _;
         use TableOrDatetimeSerializer as TODS;
 
-        let mut table = TODS::start(LocalDatetime::WRAPPER_TYPE, 1);
+        let mut table = TODS::start(LocalDatetime::WRAPPER_TYPE, 1, Settings::default());
         assert_matches!(table, TODS::LocalDatetime(None));
 
         table
@@ -1407,7 +1630,7 @@ mod tests {
         use ser::SerializeStruct as _;
         use TableOrDatetimeSerializer as TODS;
 
-        let mut table = TODS::start(LocalDate::WRAPPER_TYPE, 1);
+        let mut table = TODS::start(LocalDate::WRAPPER_TYPE, 1, Settings::default());
         assert_matches!(table, TODS::LocalDate(None));
 
         table
@@ -1427,7 +1650,7 @@ mod tests {
         use ser::SerializeStruct as _;
         use TableOrDatetimeSerializer as TODS;
 
-        let mut table = TODS::start(LocalTime::WRAPPER_TYPE, 1);
+        let mut table = TODS::start(LocalTime::WRAPPER_TYPE, 1, Settings::default());
         assert_matches!(table, TODS::LocalTime(None));
 
         table
@@ -1447,8 +1670,8 @@ mod tests {
         use ser::SerializeStruct as _;
         use TableOrDatetimeSerializer as TODS;
 
-        let mut table = TODS::start(AnyDatetime::WRAPPER_TYPE, 1);
-        assert_matches!(table, TODS::AnyDatetime);
+        let mut table = TODS::start(AnyDatetime::WRAPPER_TYPE, 1, Settings::default());
+        assert_matches!(table, TODS::AnyDatetime(_));
 
         table
             .serialize_field(
@@ -1458,13 +1681,13 @@ mod tests {
             .unwrap();
         assert_matches!(
             table,
-            TODS::OffsetDatetime(Some(ref d)) if d == OffsetDatetime::EXAMPLE_STR
+            TODS::OffsetDatetime(Some(ref d), _) if d == OffsetDatetime::EXAMPLE_STR
         );
 
         assert_matches!(table.end().unwrap(), Value::Inline(v) if v == OffsetDatetime::EXAMPLE_STR);
 
-        let mut table = TODS::start(AnyDatetime::WRAPPER_TYPE, 1);
-        assert_matches!(table, TODS::AnyDatetime);
+        let mut table = TODS::start(AnyDatetime::WRAPPER_TYPE, 1, Settings::default());
+        assert_matches!(table, TODS::AnyDatetime(_));
 
         table
             .serialize_field(
@@ -1476,8 +1699,8 @@ mod tests {
 
         assert_matches!(table.end().unwrap(), Value::Inline(v) if v == LocalDatetime::EXAMPLE_STR);
 
-        let mut table = TODS::start(AnyDatetime::WRAPPER_TYPE, 1);
-        assert_matches!(table, TODS::AnyDatetime);
+        let mut table = TODS::start(AnyDatetime::WRAPPER_TYPE, 1, Settings::default());
+        assert_matches!(table, TODS::AnyDatetime(_));
 
         table
             .serialize_field(
@@ -1489,8 +1712,8 @@ mod tests {
 
         assert_matches!(table.end().unwrap(), Value::Inline(v) if v == LocalDate::EXAMPLE_STR);
 
-        let mut table = TODS::start(AnyDatetime::WRAPPER_TYPE, 1);
-        assert_matches!(table, TODS::AnyDatetime);
+        let mut table = TODS::start(AnyDatetime::WRAPPER_TYPE, 1, Settings::default());
+        assert_matches!(table, TODS::AnyDatetime(_));
 
         table
             .serialize_field(
@@ -1509,7 +1732,7 @@ mod tests {
         use ser::SerializeStruct as _;
         use TableOrDatetimeSerializer as TODS;
 
-        let mut table = TODS::start(OffsetDatetime::WRAPPER_TYPE, 2);
+        let mut table = TODS::start(OffsetDatetime::WRAPPER_TYPE, 2, Settings::default());
 
         table
             .serialize_field(
@@ -1531,7 +1754,7 @@ mod tests {
         use ser::SerializeStruct as _;
         use TableOrDatetimeSerializer as TODS;
 
-        let mut table = TODS::start(OffsetDatetime::WRAPPER_TYPE, 2);
+        let mut table = TODS::start(OffsetDatetime::WRAPPER_TYPE, 2, Settings::default());
 
         let result = table.serialize_field(
             LocalDatetime::WRAPPER_FIELD,
@@ -1546,7 +1769,7 @@ mod tests {
         use ser::SerializeStruct as _;
         use TableOrDatetimeSerializer as TODS;
 
-        let mut table = TODS::start(OffsetDatetime::WRAPPER_TYPE, 2);
+        let mut table = TODS::start(OffsetDatetime::WRAPPER_TYPE, 2, Settings::default());
 
         let result = table.serialize_field(OffsetDatetime::WRAPPER_FIELD, &42);
         assert!(result.is_err());
@@ -1558,7 +1781,7 @@ mod tests {
         use ser::SerializeStruct as _;
         use TableOrDatetimeSerializer as TODS;
 
-        let table = TODS::start(OffsetDatetime::WRAPPER_TYPE, 2);
+        let table = TODS::start(OffsetDatetime::WRAPPER_TYPE, 2, Settings::default());
         assert!(table.end().is_err());
     }
 
@@ -1566,7 +1789,7 @@ mod tests {
     fn wrapped_table_serializer() {
         use ser::SerializeStructVariant as _;
 
-        let mut table = WrappedTableSerializer::start("foo", 2);
+        let mut table = WrappedTableSerializer::start("foo", 2, Settings::default());
         assert_eq!(table.key, "foo");
         assert!(table.table.key.is_none());
         assert!(table.table.table.is_empty());
@@ -1636,6 +1859,15 @@ mod tests {
 
         array.end().unwrap();
         assert_eq!(buf, r#"[42, "foo"]"#);
+
+        let mut buf = String::new();
+        let mut array = InlineArraySerializer::start_multiline(&mut buf);
+
+        array.serialize_element("42").unwrap();
+        array.serialize_element(r#""foo""#).unwrap();
+
+        array.end().unwrap();
+        assert_eq!(buf, "[\n    42,\n    \"foo\",\n]");
     }
 
     #[test]
@@ -1651,5 +1883,9 @@ mod tests {
 
         table.end().unwrap();
         assert_eq!(buf, r#"{ foo = 42, bar = "baz" }"#);
+
+        let mut buf = String::new();
+        InlineTableSerializer::start(&mut buf).end().unwrap();
+        assert_eq!(buf, "{}");
     }
 }
@@ -8,6 +8,7 @@ use crate::ser::{writer, ErrorKind};
 // Serializes something to a TOML key
 pub struct KeySerializer<'a, W> {
     writer: &'a mut W,
+    raw: bool,
 }
 
 impl<'a, W> KeySerializer<'a, W>
@@ -17,7 +18,13 @@ where
     /// Creates a new `KeySerializer` with the given writer.
     #[inline]
     pub fn new(writer: &'a mut W) -> Self {
-        Self { writer }
+        Self { writer, raw: false }
+    }
+
+    /// Sets whether string keys should be written as is, without quoting or escaping.
+    #[inline]
+    pub fn raw(self, raw: bool) -> Self {
+        Self { raw, ..self }
     }
 }
 
@@ -95,7 +102,11 @@ where
 
     #[inline]
     fn serialize_str(self, value: &str) -> Result<Self::Ok> {
-        writer::Formatter::write_key(value, self.writer)?;
+        if self.raw {
+            self.writer.write_str(value)?;
+        } else {
+            writer::Formatter::write_key(value, false, self.writer)?;
+        }
         Ok(())
     }
 
@@ -310,16 +321,28 @@ where
     }
 }
 
+// Converts a 128-bit integer to an i64. TOML integers are 64-bit, so we can only serialize values
+// in range of i64
+pub fn int_to_i64<T>(value: T) -> Result<i64>
+where
+    i64: TryFrom<T>,
+{
+    i64::try_from(value).map_err(|_| ErrorKind::IntegerOutOfRange.into())
+}
+
 // Helper for unimplemented Serializer methods
 // Adapted from: https://github.com/serde-rs/serde/blob/04ff3e8/serde/src/private/doc.rs#L47
 #[doc(hidden)]
 #[macro_export]
 macro_rules! __serialize_unsupported {
-    ($($func:ident)*) => {
+    ($kind:ident: $($func:ident)*) => {
         $(
-            $crate::__serialize_unsupported_helper!($func);
+            $crate::__serialize_unsupported_helper!($kind, $func);
         )*
     };
+    ($($func:ident)*) => {
+        $crate::__serialize_unsupported!(UnsupportedType: $($func)*);
+    };
 }
 pub(crate) use __serialize_unsupported;
 use serde::ser;
@@ -328,23 +351,23 @@ use serde::ser;
 #[macro_export]
 #[allow(edition_2024_expr_fragment_specifier)]
 macro_rules! __serialize_unsupported_method {
-    ($func:ident $(<$t:ident>)* ($($arg:ty),*) -> $ret:ident, $msg:expr) => {
+    ($kind:ident, $func:ident $(<$t:ident>)* ($($arg:ty),*) -> $ret:ident, $msg:expr) => {
         #[inline]
         fn $func $(<$t>)* (self $(, _: $arg)*) -> $crate::ser::Result<Self::$ret>
         where
             $($t: ?Sized + ::serde::Serialize,)*
         {
-            Err($crate::ser::ErrorKind::UnsupportedType($msg).into())
+            Err($crate::ser::ErrorKind::$kind($msg).into())
         }
     };
 
-    ($func:ident $(<$t:ident>)* (name: $name:ty $(, $arg:ty)*) -> $ret:ident) => {
+    ($kind:ident, $func:ident $(<$t:ident>)* (name: $name:ty $(, $arg:ty)*) -> $ret:ident) => {
         #[inline]
         fn $func $(<$t>)* (self, name: $name $(, _: $arg)*) -> $crate::ser::Result<Self::$ret>
         where
             $($t: ?Sized + ::serde::Serialize,)*
         {
-            Err($crate::ser::ErrorKind::UnsupportedType(name).into())
+            Err($crate::ser::ErrorKind::$kind(name).into())
         }
     };
 }
@@ -352,102 +375,102 @@ macro_rules! __serialize_unsupported_method {
 #[doc(hidden)]
 #[macro_export]
 macro_rules! __serialize_unsupported_helper {
-    (bool) => {
-        $crate::__serialize_unsupported_method!(serialize_bool(bool) -> Ok, "bool");
+    ($kind:ident, bool) => {
+        $crate::__serialize_unsupported_method!($kind, serialize_bool(bool) -> Ok, "bool");
     };
-    (i8) => {
-        $crate::__serialize_unsupported_method!(serialize_i8(i8) -> Ok, "i8");
+    ($kind:ident, i8) => {
+        $crate::__serialize_unsupported_method!($kind, serialize_i8(i8) -> Ok, "i8");
     };
-    (i16) => {
-        $crate::__serialize_unsupported_method!(serialize_i16(i16) -> Ok, "i16");
+    ($kind:ident, i16) => {
+        $crate::__serialize_unsupported_method!($kind, serialize_i16(i16) -> Ok, "i16");
     };
-    (i32) => {
-        $crate::__serialize_unsupported_method!(serialize_i32(i32) -> Ok, "i32");
+    ($kind:ident, i32) => {
+        $crate::__serialize_unsupported_method!($kind, serialize_i32(i32) -> Ok, "i32");
     };
-    (i64) => {
-        $crate::__serialize_unsupported_method!(serialize_i64(i64) -> Ok, "i64");
+    ($kind:ident, i64) => {
+        $crate::__serialize_unsupported_method!($kind, serialize_i64(i64) -> Ok, "i64");
     };
-    (i128) => {
-        $crate::__serialize_unsupported_method!(serialize_i128(i128) -> Ok, "i128");
+    ($kind:ident, i128) => {
+        $crate::__serialize_unsupported_method!($kind, serialize_i128(i128) -> Ok, "i128");
     };
-    (u8) => {
-        $crate::__serialize_unsupported_method!(serialize_u8(u8) -> Ok, "u8");
+    ($kind:ident, u8) => {
+        $crate::__serialize_unsupported_method!($kind, serialize_u8(u8) -> Ok, "u8");
     };
-    (u16) => {
-        $crate::__serialize_unsupported_method!(serialize_u16(u16) -> Ok, "u16");
+    ($kind:ident, u16) => {
+        $crate::__serialize_unsupported_method!($kind, serialize_u16(u16) -> Ok, "u16");
     };
-    (u32) => {
-        $crate::__serialize_unsupported_method!(serialize_u32(u32) -> Ok, "u32");
+    ($kind:ident, u32) => {
+        $crate::__serialize_unsupported_method!($kind, serialize_u32(u32) -> Ok, "u32");
     };
-    (u64) => {
-        $crate::__serialize_unsupported_method!(serialize_u64(u64) -> Ok, "u64");
+    ($kind:ident, u64) => {
+        $crate::__serialize_unsupported_method!($kind, serialize_u64(u64) -> Ok, "u64");
     };
-    (u128) => {
-        $crate::__serialize_unsupported_method!(serialize_u128(u128) -> Ok, "u128");
+    ($kind:ident, u128) => {
+        $crate::__serialize_unsupported_method!($kind, serialize_u128(u128) -> Ok, "u128");
     };
-    (f32) => {
-        $crate::__serialize_unsupported_method!(serialize_f32(f32) -> Ok, "f32");
+    ($kind:ident, f32) => {
+        $crate::__serialize_unsupported_method!($kind, serialize_f32(f32) -> Ok, "f32");
     };
-    (f64) => {
-        $crate::__serialize_unsupported_method!(serialize_f64(f64) -> Ok, "f64");
+    ($kind:ident, f64) => {
+        $crate::__serialize_unsupported_method!($kind, serialize_f64(f64) -> Ok, "f64");
     };
-    (char) => {
-        $crate::__serialize_unsupported_method!(serialize_char(char) -> Ok, "char");
+    ($kind:ident, char) => {
+        $crate::__serialize_unsupported_method!($kind, serialize_char(char) -> Ok, "char");
     };
-    (str) => {
-        $crate::__serialize_unsupported_method!(serialize_str(&str) -> Ok, "str");
+    ($kind:ident, str) => {
+        $crate::__serialize_unsupported_method!($kind, serialize_str(&str) -> Ok, "str");
     };
-    (bytes) => {
-        $crate::__serialize_unsupported_method!(serialize_bytes(&[u8]) -> Ok, "[u8]");
+    ($kind:ident, bytes) => {
+        $crate::__serialize_unsupported_method!($kind, serialize_bytes(&[u8]) -> Ok, "[u8]");
     };
-    (none) => {
-        $crate::__serialize_unsupported_method!(serialize_none() -> Ok, "Option");
+    ($kind:ident, none) => {
+        $crate::__serialize_unsupported_method!($kind, serialize_none() -> Ok, "Option");
     };
-    (some) => {
-        $crate::__serialize_unsupported_method!(serialize_some<T>(&T) -> Ok, "Option");
+    ($kind:ident, some) => {
+        $crate::__serialize_unsupported_method!($kind, serialize_some<T>(&T) -> Ok, "Option");
     };
-    (unit) => {
-        $crate::__serialize_unsupported_method!(serialize_unit() -> Ok, "()");
+    ($kind:ident, unit) => {
+        $crate::__serialize_unsupported_method!($kind, serialize_unit() -> Ok, "()");
     };
-    (unit_struct) => {
-        $crate::__serialize_unsupported_method!(serialize_unit_struct(name: &'static str) -> Ok);
+    ($kind:ident, unit_struct) => {
+        $crate::__serialize_unsupported_method!($kind, serialize_unit_struct(name: &'static str) -> Ok);
     };
-    (unit_variant) => {
-        $crate::__serialize_unsupported_method!(serialize_unit_variant(name: &'static str, u32, &str) -> Ok);
+    ($kind:ident, unit_variant) => {
+        $crate::__serialize_unsupported_method!($kind, serialize_unit_variant(name: &'static str, u32, &str) -> Ok);
     };
-    (newtype_struct) => {
-        $crate::__serialize_unsupported_method!(serialize_newtype_struct<T>(name: &'static str, &T) -> Ok);
+    ($kind:ident, newtype_struct) => {
+        $crate::__serialize_unsupported_method!($kind, serialize_newtype_struct<T>(name: &'static str, &T) -> Ok);
     };
-    (newtype_variant) => {
-        $crate::__serialize_unsupported_method!(serialize_newtype_variant<T>(name: &'static str, u32, &str, &T) -> Ok);
+    ($kind:ident, newtype_variant) => {
+        $crate::__serialize_unsupported_method!($kind, serialize_newtype_variant<T>(name: &'static str, u32, &str, &T) -> Ok);
     };
-    (seq) => {
+    ($kind:ident, seq) => {
         type SerializeSeq = $crate::ser::Impossible<Self::Ok, Self::Error>;
-        $crate::__serialize_unsupported_method!(serialize_seq(Option<usize>) -> SerializeSeq, "slice");
+        $crate::__serialize_unsupported_method!($kind, serialize_seq(Option<usize>) -> SerializeSeq, "slice");
     };
-    (tuple) => {
+    ($kind:ident, tuple) => {
         type SerializeTuple = $crate::ser::Impossible<Self::Ok, Self::Error>;
-        $crate::__serialize_unsupported_method!(serialize_tuple(usize) -> SerializeTuple, "tuple");
+        $crate::__serialize_unsupported_method!($kind, serialize_tuple(usize) -> SerializeTuple, "tuple");
     };
-    (tuple_struct) => {
+    ($kind:ident, tuple_struct) => {
         type SerializeTupleStruct = $crate::ser::Impossible<Self::Ok, Self::Error>;
-        $crate::__serialize_unsupported_method!(serialize_tuple_struct(name: &'static str, usize) -> SerializeTupleStruct);
+        $crate::__serialize_unsupported_method!($kind, serialize_tuple_struct(name: &'static str, usize) -> SerializeTupleStruct);
     };
-    (tuple_variant) => {
+    ($kind:ident, tuple_variant) => {
         type SerializeTupleVariant = $crate::ser::Impossible<Self::Ok, Self::Error>;
-        $crate::__serialize_unsupported_method!(serialize_tuple_variant(name: &'static str, u32, &str, usize) -> SerializeTupleVariant);
+        $crate::__serialize_unsupported_method!($kind, serialize_tuple_variant(name: &'static str, u32, &str, usize) -> SerializeTupleVariant);
     };
-    (map) => {
+    ($kind:ident, map) => {
         type SerializeMap = $crate::ser::Impossible<Self::Ok, Self::Error>;
-        $crate::__serialize_unsupported_method!(serialize_map(Option<usize>) -> SerializeMap, "map");
+        $crate::__serialize_unsupported_method!($kind, serialize_map(Option<usize>) -> SerializeMap, "map");
     };
-    (struct) => {
+    ($kind:ident, struct) => {
         type SerializeStruct = $crate::ser::Impossible<Self::Ok, Self::Error>;
-        $crate::__serialize_unsupported_method!(serialize_struct(name: &'static str, usize) -> SerializeStruct);
+        $crate::__serialize_unsupported_method!($kind, serialize_struct(name: &'static str, usize) -> SerializeStruct);
     };
-    (struct_variant) => {
+    ($kind:ident, struct_variant) => {
         type SerializeStructVariant = $crate::ser::Impossible<Self::Ok, Self::Error>;
-        $crate::__serialize_unsupported_method!(serialize_struct_variant(name: &'static str, u32, &str, usize) -> SerializeStructVariant);
+        $crate::__serialize_unsupported_method!($kind, serialize_struct_variant(name: &'static str, u32, &str, usize) -> SerializeStructVariant);
     };
 }
 
@@ -459,6 +482,23 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn int_to_i64_range() {
+        assert_matches!(int_to_i64(i128::from(i64::MIN)), Ok(i64::MIN));
+        assert_matches!(
+            int_to_i64(u128::from(i64::MAX.unsigned_abs())),
+            Ok(i64::MAX)
+        );
+        assert_matches!(
+            int_to_i64(i128::from(i64::MIN) - 1),
+            Err(Error(ErrorKind::IntegerOutOfRange))
+        );
+        assert_matches!(
+            int_to_i64(u128::MAX),
+            Err(Error(ErrorKind::IntegerOutOfRange))
+        );
+    }
+
     #[test]
     fn key_serializer() {
         let mut buf = String::new();
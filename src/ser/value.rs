@@ -3,7 +3,9 @@ use std::fmt;
 use serde::ser;
 
 use crate::ser::writer::Formatter;
-use crate::ser::{utils, writer, Error, ErrorKind, Result};
+#[cfg(feature = "datetime")]
+use crate::ser::ZeroOffsetStyle;
+use crate::ser::{utils, writer, Error, ErrorKind, FloatFormat, Result};
 #[cfg(feature = "datetime")]
 use crate::value::{AnyDatetime, LocalDate, LocalDatetime, LocalTime, OffsetDatetime};
 
@@ -11,6 +13,15 @@ use crate::value::{AnyDatetime, LocalDate, LocalDatetime, LocalTime, OffsetDatet
 #[derive(Debug)]
 pub struct Serializer<'a, W> {
     writer: &'a mut W,
+    options: Options,
+}
+
+// Options used to control the output of the value serializer, which also apply to any nested
+// values
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Options {
+    #[cfg(feature = "datetime")]
+    zero_offset_style: ZeroOffsetStyle,
 }
 
 impl<'a, W> Serializer<'a, W>
@@ -20,7 +31,23 @@ where
     /// Creates a new serializer that writes to the given writer.
     #[inline]
     pub fn new(writer: &'a mut W) -> Self {
-        Self { writer }
+        Self::with_options(writer, Options::default())
+    }
+
+    #[inline]
+    fn with_options(writer: &'a mut W, options: Options) -> Self {
+        Self { writer, options }
+    }
+
+    /// Sets how offset date-times with a zero UTC offset are written.
+    ///
+    /// See [`Serializer::zero_offset_style`](crate::Serializer::zero_offset_style) for details.
+    #[cfg(feature = "datetime")]
+    #[must_use]
+    #[inline]
+    pub fn zero_offset_style(mut self, value: ZeroOffsetStyle) -> Self {
+        self.options.zero_offset_style = value;
+        self
     }
 }
 
@@ -71,7 +98,7 @@ where
 
     #[inline]
     fn serialize_i128(self, value: i128) -> Result<Self::Ok> {
-        self.serialize_integer(&value)
+        self.serialize_integer(&utils::int_to_i64(value)?)
     }
 
     #[inline]
@@ -96,7 +123,7 @@ where
 
     #[inline]
     fn serialize_u128(self, value: u128) -> Result<Self::Ok> {
-        self.serialize_integer(&value)
+        self.serialize_integer(&utils::int_to_i64(value)?)
     }
 
     #[inline]
@@ -116,7 +143,7 @@ where
 
     #[inline]
     fn serialize_str(self, value: &str) -> Result<Self::Ok> {
-        Formatter::write_string(value, self.writer)?;
+        Formatter::write_string(value, false, self.writer)?;
         Ok(())
     }
 
@@ -192,12 +219,12 @@ where
 
     #[inline]
     fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq> {
-        Self::SerializeSeq::start(self.writer)
+        Self::SerializeSeq::start(self.writer, self.options)
     }
 
     #[inline]
     fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple> {
-        Self::SerializeTuple::start(self.writer)
+        Self::SerializeTuple::start(self.writer, self.options)
     }
 
     #[inline]
@@ -206,7 +233,7 @@ where
         _name: &'static str,
         _len: usize,
     ) -> Result<Self::SerializeTupleStruct> {
-        Self::SerializeTupleStruct::start(self.writer)
+        Self::SerializeTupleStruct::start(self.writer, self.options)
     }
 
     #[inline]
@@ -217,21 +244,21 @@ where
         variant: &'static str,
         _len: usize,
     ) -> Result<Self::SerializeTupleVariant> {
-        Self::SerializeTupleVariant::start(variant, self.writer)
+        Self::SerializeTupleVariant::start(variant, self.writer, self.options)
     }
 
     #[inline]
     fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap> {
-        Self::SerializeMap::start(self.writer)
+        Self::SerializeMap::start(self.writer, self.options)
     }
 
     #[inline]
     fn serialize_struct(self, name: &'static str, _len: usize) -> Result<Self::SerializeStruct> {
         match name {
             #[cfg(feature = "datetime")]
-            name => Self::SerializeStruct::start(name, self.writer),
+            name => Self::SerializeStruct::start(name, self.writer, self.options),
             #[cfg(not(feature = "datetime"))]
-            _ => Self::SerializeStruct::start(self.writer),
+            _ => Self::SerializeStruct::start(self.writer, self.options),
         }
     }
 
@@ -243,7 +270,7 @@ where
         variant: &'static str,
         _len: usize,
     ) -> Result<Self::SerializeStructVariant> {
-        Self::SerializeStructVariant::start(variant, self.writer)
+        Self::SerializeStructVariant::start(variant, self.writer, self.options)
     }
 }
 
@@ -257,14 +284,14 @@ where
         self,
         value: &T,
     ) -> Result<<Self as ser::Serializer>::Ok> {
-        Formatter::write_integer(value, self.writer)?;
+        Formatter::write_integer(value, None, self.writer)?;
         Ok(())
     }
 
     #[allow(clippy::unnecessary_wraps, clippy::unused_self)]
     #[inline]
     fn serialize_float<T: writer::Float>(self, value: &T) -> Result<<Self as ser::Serializer>::Ok> {
-        Formatter::write_float(value, self.writer)?;
+        Formatter::write_float(value, None, FloatFormat::Shortest, self.writer)?;
         Ok(())
     }
 }
@@ -273,18 +300,20 @@ where
 pub struct ArraySerializer<'a, W> {
     writer: &'a mut W,
     first: bool,
+    options: Options,
 }
 
 impl<'a, W> ArraySerializer<'a, W>
 where
     W: fmt::Write,
 {
-    pub fn start(writer: &'a mut W) -> Result<Self> {
+    pub fn start(writer: &'a mut W, options: Options) -> Result<Self> {
         writer.write_str("[")?;
 
         Ok(Self {
             writer,
             first: true,
+            options,
         })
     }
 }
@@ -305,7 +334,7 @@ where
         }
         self.first = false;
 
-        value.serialize(Serializer::new(self.writer))
+        value.serialize(Serializer::with_options(self.writer, self.options))
     }
 
     #[inline]
@@ -361,6 +390,7 @@ where
 pub struct WrappedArraySerializer<'a, W> {
     writer: &'a mut W,
     first: bool,
+    options: Options,
 }
 
 impl<'a, W> WrappedArraySerializer<'a, W>
@@ -368,7 +398,7 @@ where
     W: fmt::Write,
 {
     #[inline]
-    pub fn start(key: &'static str, writer: &'a mut W) -> Result<Self> {
+    pub fn start(key: &'static str, writer: &'a mut W, options: Options) -> Result<Self> {
         use serde::Serializer as _;
 
         writer.write_str("{ ")?;
@@ -378,6 +408,7 @@ where
         Ok(Self {
             writer,
             first: true,
+            options,
         })
     }
 }
@@ -399,7 +430,7 @@ where
         }
         self.first = false;
 
-        value.serialize(Serializer::new(self.writer))
+        value.serialize(Serializer::with_options(self.writer, self.options))
     }
 
     #[inline]
@@ -413,6 +444,7 @@ where
 pub struct TableSerializer<'a, W> {
     writer: &'a mut W,
     first: bool,
+    options: Options,
 }
 
 impl<'a, W> TableSerializer<'a, W>
@@ -420,12 +452,13 @@ where
     W: fmt::Write,
 {
     #[inline]
-    pub fn start(writer: &'a mut W) -> Result<Self> {
-        writer.write_str("{ ")?;
+    pub fn start(writer: &'a mut W, options: Options) -> Result<Self> {
+        writer.write_str("{")?;
 
         Ok(Self {
             writer,
             first: true,
+            options,
         })
     }
 }
@@ -441,9 +474,7 @@ where
     where
         T: ?Sized + ser::Serialize,
     {
-        if !self.first {
-            self.writer.write_str(", ")?;
-        }
+        self.writer.write_str(if self.first { " " } else { ", " })?;
         self.first = false;
 
         key.serialize(utils::KeySerializer::new(self.writer))
@@ -454,12 +485,13 @@ where
         T: ?Sized + ser::Serialize,
     {
         self.writer.write_str(" = ")?;
-        value.serialize(Serializer::new(self.writer))
+        value.serialize(Serializer::with_options(self.writer, self.options))
     }
 
     #[inline]
     fn end(self) -> Result<Self::Ok> {
-        self.writer.write_str(" }")?;
+        // Empty tables are written as `{}` rather than `{  }`
+        self.writer.write_str(if self.first { "}" } else { " }" })?;
         Ok(())
     }
 }
@@ -488,11 +520,28 @@ where
 #[cfg(feature = "datetime")]
 #[derive(Debug)]
 pub enum TableOrDatetimeSerializer<'a, W> {
-    OffsetDatetime { writer: &'a mut W, empty: bool },
-    LocalDatetime { writer: &'a mut W, empty: bool },
-    LocalDate { writer: &'a mut W, empty: bool },
-    LocalTime { writer: &'a mut W, empty: bool },
-    AnyDatetime { writer: &'a mut W, empty: bool },
+    OffsetDatetime {
+        writer: &'a mut W,
+        empty: bool,
+        zero_offset_style: ZeroOffsetStyle,
+    },
+    LocalDatetime {
+        writer: &'a mut W,
+        empty: bool,
+    },
+    LocalDate {
+        writer: &'a mut W,
+        empty: bool,
+    },
+    LocalTime {
+        writer: &'a mut W,
+        empty: bool,
+    },
+    AnyDatetime {
+        writer: &'a mut W,
+        empty: bool,
+        zero_offset_style: ZeroOffsetStyle,
+    },
     Table(TableSerializer<'a, W>),
 }
 
@@ -502,15 +551,17 @@ where
     W: fmt::Write,
 {
     #[inline]
-    pub fn start(name: &'static str, writer: &'a mut W) -> Result<Self> {
+    pub fn start(name: &'static str, writer: &'a mut W, options: Options) -> Result<Self> {
         Ok(match name {
             AnyDatetime::WRAPPER_TYPE => Self::AnyDatetime {
                 writer,
                 empty: true,
+                zero_offset_style: options.zero_offset_style,
             },
             OffsetDatetime::WRAPPER_TYPE => Self::OffsetDatetime {
                 writer,
                 empty: true,
+                zero_offset_style: options.zero_offset_style,
             },
             LocalDatetime::WRAPPER_TYPE => Self::LocalDatetime {
                 writer,
@@ -524,7 +575,7 @@ where
                 writer,
                 empty: true,
             },
-            _ => Self::Table(TableSerializer::start(writer)?),
+            _ => Self::Table(TableSerializer::start(writer, options)?),
         })
     }
 }
@@ -547,22 +598,28 @@ where
             Self::AnyDatetime {
                 ref mut writer,
                 empty: ref mut empty @ true,
-            } if matches!(
-                key,
-                OffsetDatetime::WRAPPER_FIELD
-                    | LocalDatetime::WRAPPER_FIELD
-                    | LocalDate::WRAPPER_FIELD
-                    | LocalTime::WRAPPER_FIELD
-            ) =>
-            {
-                value.serialize(utils::RawStringSerializer { writer })?;
+                zero_offset_style,
+            }
+            | Self::OffsetDatetime {
+                ref mut writer,
+                empty: ref mut empty @ true,
+                zero_offset_style,
+            } if key == OffsetDatetime::WRAPPER_FIELD => {
+                let mut buf = String::new();
+                value.serialize(utils::RawStringSerializer { writer: &mut buf })?;
+                writer.write_str(&zero_offset_style.apply(buf))?;
                 *empty = false;
                 Ok(())
             }
-            Self::OffsetDatetime {
+            Self::AnyDatetime {
                 ref mut writer,
                 empty: ref mut empty @ true,
-            } if key == OffsetDatetime::WRAPPER_FIELD => {
+                ..
+            } if matches!(
+                key,
+                LocalDatetime::WRAPPER_FIELD | LocalDate::WRAPPER_FIELD | LocalTime::WRAPPER_FIELD
+            ) =>
+            {
                 value.serialize(utils::RawStringSerializer { writer })?;
                 *empty = false;
                 Ok(())
@@ -632,6 +689,7 @@ where
 pub struct WrappedTableSerializer<'a, W> {
     writer: &'a mut W,
     first: bool,
+    options: Options,
 }
 
 impl<'a, W> WrappedTableSerializer<'a, W>
@@ -639,16 +697,17 @@ where
     W: fmt::Write,
 {
     #[inline]
-    pub fn start(key: &'static str, writer: &'a mut W) -> Result<Self> {
+    pub fn start(key: &'static str, writer: &'a mut W, options: Options) -> Result<Self> {
         use serde::Serializer as _;
 
         writer.write_str("{ ")?;
         utils::KeySerializer::new(writer).serialize_str(key)?;
-        writer.write_str(" = { ")?;
+        writer.write_str(" = {")?;
 
         Ok(Self {
             writer,
             first: true,
+            options,
         })
     }
 }
@@ -667,21 +726,20 @@ where
     {
         use serde::Serializer as _;
 
-        if !self.first {
-            self.writer.write_str(", ")?;
-        }
+        self.writer.write_str(if self.first { " " } else { ", " })?;
         self.first = false;
 
         utils::KeySerializer::new(self.writer).serialize_str(key)?;
 
         self.writer.write_str(" = ")?;
 
-        value.serialize(Serializer::new(self.writer))
+        value.serialize(Serializer::with_options(self.writer, self.options))
     }
 
     #[inline]
     fn end(self) -> Result<Self::Ok> {
-        self.writer.write_str(" } }")?;
+        self.writer
+            .write_str(if self.first { "} }" } else { " } }" })?;
         Ok(())
     }
 }
@@ -781,6 +839,18 @@ mod tests {
         let serializer = Serializer::new(&mut buf);
         serializer.serialize_i128(-12).unwrap();
         assert_eq!(buf, "-12");
+
+        let mut buf = String::new();
+        let serializer = Serializer::new(&mut buf);
+        serializer.serialize_i128(i64::MIN.into()).unwrap();
+        assert_eq!(buf, "-9223372036854775808");
+
+        let mut buf = String::new();
+        let serializer = Serializer::new(&mut buf);
+        assert_matches!(
+            serializer.serialize_i128(i128::from(i64::MIN) - 1),
+            Err(Error(ErrorKind::IntegerOutOfRange))
+        );
     }
 
     #[test]
@@ -846,6 +916,18 @@ mod tests {
         let serializer = Serializer::new(&mut buf);
         serializer.serialize_u128(12).unwrap();
         assert_eq!(buf, "12");
+
+        let mut buf = String::new();
+        let serializer = Serializer::new(&mut buf);
+        serializer.serialize_u128(i64::MAX as u128).unwrap();
+        assert_eq!(buf, "9223372036854775807");
+
+        let mut buf = String::new();
+        let serializer = Serializer::new(&mut buf);
+        assert_matches!(
+            serializer.serialize_u128(u128::MAX),
+            Err(Error(ErrorKind::IntegerOutOfRange))
+        );
     }
 
     #[test]
@@ -903,6 +985,11 @@ mod tests {
         serializer.serialize_f64(-12.0).unwrap();
         assert_eq!(buf, "-12.0");
 
+        let mut buf = String::new();
+        let serializer = Serializer::new(&mut buf);
+        serializer.serialize_f64(-0.0).unwrap();
+        assert_eq!(buf, "-0.0");
+
         let mut buf = String::new();
         let serializer = Serializer::new(&mut buf);
         serializer.serialize_f64(1e28).unwrap();
@@ -1145,6 +1232,12 @@ mod tests {
         seq.end().unwrap();
 
         assert_eq!(buf, r#"{ foo = 42, bar = "baz" }"#);
+
+        let mut buf = String::new();
+        let serializer = Serializer::new(&mut buf);
+        serializer.serialize_map(Some(0)).unwrap().end().unwrap();
+
+        assert_eq!(buf, "{}");
     }
 
     #[test]
@@ -1177,6 +1270,16 @@ mod tests {
         seq.end().unwrap();
 
         assert_eq!(buf, r#"{ foo = { bar = 42, baz = "qux" } }"#);
+
+        let mut buf = String::new();
+        let serializer = Serializer::new(&mut buf);
+        serializer
+            .serialize_struct_variant("name", 0, "foo", 0)
+            .unwrap()
+            .end()
+            .unwrap();
+
+        assert_eq!(buf, "{ foo = {} }");
     }
 
     #[cfg(feature = "datetime")]
@@ -1289,6 +1392,67 @@ mod tests {
         assert_eq!(buf, LocalTime::EXAMPLE_STR);
     }
 
+    #[cfg(feature = "datetime")]
+    #[test]
+    fn serializer_zero_offset_style() {
+        use std::collections::BTreeMap;
+
+        use ser::Serialize as _;
+
+        use crate::value::Offset;
+
+        let datetime = |offset| OffsetDatetime {
+            offset,
+            ..OffsetDatetime::EXAMPLE
+        };
+
+        let mut buf = String::new();
+        let serializer = Serializer::new(&mut buf);
+        assert_eq!(
+            serializer.options.zero_offset_style,
+            ZeroOffsetStyle::Preserve
+        );
+        datetime(Offset::Custom { minutes: 0 })
+            .serialize(serializer)
+            .unwrap();
+        assert_eq!(buf, "2023-01-02T03:04:05.006+00:00");
+
+        let mut buf = String::new();
+        let serializer = Serializer::new(&mut buf).zero_offset_style(ZeroOffsetStyle::Zulu);
+        datetime(Offset::Custom { minutes: 0 })
+            .serialize(serializer)
+            .unwrap();
+        assert_eq!(buf, "2023-01-02T03:04:05.006Z");
+
+        let mut buf = String::new();
+        let serializer = Serializer::new(&mut buf).zero_offset_style(ZeroOffsetStyle::Numeric);
+        AnyDatetime::OffsetDatetime(datetime(Offset::Z))
+            .serialize(serializer)
+            .unwrap();
+        assert_eq!(buf, "2023-01-02T03:04:05.006+00:00");
+
+        let mut buf = String::new();
+        let serializer = Serializer::new(&mut buf).zero_offset_style(ZeroOffsetStyle::Zulu);
+        let table = BTreeMap::from([("a", [datetime(Offset::Custom { minutes: 0 })])]);
+        table.serialize(serializer).unwrap();
+        assert_eq!(buf, "{ a = [2023-01-02T03:04:05.006Z] }");
+
+        // An offset of -00:00 is left as is
+        let mut buf = String::new();
+        let serializer = Serializer::new(&mut buf).zero_offset_style(ZeroOffsetStyle::Zulu);
+        let mut seq = serializer
+            .serialize_struct(OffsetDatetime::WRAPPER_TYPE, 1)
+            .unwrap();
+        ser::SerializeStruct::serialize_field(
+            &mut seq,
+            OffsetDatetime::WRAPPER_FIELD,
+            Bytes::new(b"2023-01-02T03:04:05-00:00"),
+        )
+        .unwrap();
+        ser::SerializeStruct::end(seq).unwrap();
+        assert_eq!(buf, "2023-01-02T03:04:05-00:00");
+    }
+
     #[cfg(feature = "datetime")]
     #[test]
     #[allow(clippy::too_many_lines)]
@@ -1,6 +1,6 @@
 use std::{fmt, io};
 
-use crate::ser::tree;
+use crate::ser::{tree, FloatFormat};
 
 #[derive(Debug)]
 pub struct IoWriter<T: io::Write> {
@@ -32,29 +32,63 @@ where
     }
 }
 
+// Writer adaptor which holds back a trailing newline until something else is written after it,
+// so the final newline of the output is never written
+struct TrimTrailingNewline<'a> {
+    writer: &'a mut dyn fmt::Write,
+    pending: bool,
+}
+
+impl fmt::Write for TrimTrailingNewline<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        if s.is_empty() {
+            return Ok(());
+        }
+        if self.pending {
+            self.writer.write_str("\n")?;
+        }
+        let (s, pending) = s.strip_suffix('\n').map_or((s, false), |s| (s, true));
+        self.pending = pending;
+        self.writer.write_str(s)
+    }
+}
+
 pub struct Formatter;
 
 impl Formatter {
-    pub fn write_key(key: &str, f: &mut dyn fmt::Write) -> fmt::Result {
+    pub fn write_key(key: &str, ascii_only: bool, f: &mut dyn fmt::Write) -> fmt::Result {
         let is_bare_key = |b| matches!(b, b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'_' | b'-');
 
         if !key.is_empty() && key.bytes().all(is_bare_key) {
             f.write_str(key)
         } else {
-            Self::write_basic_string(key, f)
+            Self::write_basic_string(key, ascii_only, f)
+        }
+    }
+
+    // Appends `key` to the dotted `path`, quoting it if it is not a valid bare key
+    pub fn push_key(path: &mut String, key: &str) {
+        if !path.is_empty() {
+            path.push('.');
         }
+        // Writing to a String never fails
+        let _ = Self::write_key(key, false, path);
     }
 
-    pub fn write_string(value: &str, f: &mut dyn fmt::Write) -> fmt::Result {
+    pub fn write_string(value: &str, ascii_only: bool, f: &mut dyn fmt::Write) -> fmt::Result {
         // TODO also test where literal strings might be better?
         if value.contains('\n') {
-            Self::write_multiline_basic_string(value, f)
+            Self::write_multiline_basic_string(value, ascii_only, f)
         } else {
-            Self::write_basic_string(value, f)
+            Self::write_basic_string(value, ascii_only, f)
         }
     }
 
-    pub fn write_basic_string(value: &str, f: &mut dyn fmt::Write) -> fmt::Result {
+    pub fn write_basic_string(
+        value: &str,
+        ascii_only: bool,
+        f: &mut dyn fmt::Write,
+    ) -> fmt::Result {
         #[allow(clippy::trivially_copy_pass_by_ref)] // makes the function more ergonomic to use
         const fn is_escape(ch: &u8) -> bool {
             matches!(*ch, 0x00..=0x1f | b'\"' | b'\\' | 0x7f)
@@ -67,7 +101,7 @@ impl Formatter {
             let esc_pos = rest
                 .as_bytes()
                 .iter()
-                .position(is_escape)
+                .position(|ch| is_escape(ch) || (ascii_only && !ch.is_ascii()))
                 .unwrap_or(rest.len());
             f.write_str(&rest[..esc_pos])?;
             rest = &rest[esc_pos..];
@@ -90,6 +124,9 @@ impl Formatter {
                 '\\' => f.write_str("\\\\")?,
                 // Other control characters
                 '\x00'..='\x1f' | '\x7f' => write!(f, "\\u{:04x}", u32::from(ch))?,
+                // Non-ASCII characters with ascii_only
+                '\u{80}'..='\u{ffff}' if ascii_only => write!(f, "\\u{:04x}", u32::from(ch))?,
+                '\u{10000}'.. if ascii_only => write!(f, "\\U{:08x}", u32::from(ch))?,
                 // Other characters (unreachable)
                 ch => unreachable!("unexpected character: {ch}"),
             }
@@ -99,7 +136,11 @@ impl Formatter {
         f.write_str(r#"""#)
     }
 
-    pub fn write_multiline_basic_string(value: &str, f: &mut dyn fmt::Write) -> fmt::Result {
+    pub fn write_multiline_basic_string(
+        value: &str,
+        ascii_only: bool,
+        f: &mut dyn fmt::Write,
+    ) -> fmt::Result {
         #[allow(clippy::trivially_copy_pass_by_ref)] // makes the function more ergonomic to use
         const fn is_escape(ch: &u8) -> bool {
             matches!(*ch, 0x00..=0x08 | 0x0b..=0x1f | b'\"' | b'\\' | 0x7f)
@@ -113,7 +154,7 @@ impl Formatter {
             let esc_pos = rest
                 .as_bytes()
                 .iter()
-                .position(is_escape)
+                .position(|ch| is_escape(ch) || (ascii_only && !ch.is_ascii()))
                 .unwrap_or(rest.len());
             f.write_str(&rest[..esc_pos])?;
             rest = &rest[esc_pos..];
@@ -133,6 +174,9 @@ impl Formatter {
                 '\\' => f.write_str("\\\\")?,
                 // Other control characters
                 '\x00'..='\x1f' | '\x7f' => write!(f, "\\u{:04x}", u32::from(ch))?,
+                // Non-ASCII characters with ascii_only
+                '\u{80}'..='\u{ffff}' if ascii_only => write!(f, "\\u{:04x}", u32::from(ch))?,
+                '\u{10000}'.. if ascii_only => write!(f, "\\U{:08x}", u32::from(ch))?,
                 // Other characters (unreachable)
                 ch => unreachable!("unexpected character: {ch}"),
             }
@@ -143,13 +187,150 @@ impl Formatter {
     }
 
     #[inline]
-    pub fn write_integer<I: Integer>(value: &I, f: &mut dyn fmt::Write) -> fmt::Result {
-        value.fmt(f)
+    pub fn write_integer<I: Integer>(
+        value: &I,
+        grouping: Option<u8>,
+        f: &mut dyn fmt::Write,
+    ) -> fmt::Result {
+        match grouping {
+            Some(size) if size > 0 => {
+                let mut buf = String::new();
+                value.fmt(&mut buf)?;
+                Self::write_grouped_digits(&buf, size, f)
+            }
+            _ => value.fmt(f),
+        }
     }
 
     #[inline]
-    pub fn write_float<F: Float>(value: &F, f: &mut dyn fmt::Write) -> fmt::Result {
-        value.fmt(f)
+    pub fn write_float<F: Float>(
+        value: &F,
+        grouping: Option<u8>,
+        format: FloatFormat,
+        f: &mut dyn fmt::Write,
+    ) -> fmt::Result {
+        let grouping = grouping.filter(|&size| size > 0);
+        if grouping.is_none() && format == FloatFormat::Shortest {
+            return value.fmt(f);
+        }
+
+        let mut buf = String::new();
+        value.fmt(&mut buf)?;
+        if let FloatFormat::Scientific(threshold) = format {
+            buf = Self::scientific_float(&buf, threshold);
+        }
+        match grouping {
+            Some(size) => Self::write_grouped_digits(&buf, size, f),
+            None => f.write_str(&buf),
+        }
+    }
+
+    // Rewrites a float in the shortest form given by `Float::fmt` into exponent form if its
+    // decimal exponent is at least `threshold` or less than `-threshold`, or plain decimal form
+    // otherwise. The significant digits are kept as is, so the value still round trips
+    fn scientific_float(number: &str, threshold: u8) -> String {
+        // Infinities and NaNs are written unchanged
+        if number.ends_with("inf") || number.ends_with("nan") {
+            return number.to_owned();
+        }
+
+        let (sign, number) = number
+            .strip_prefix('-')
+            .map_or(("", number), |number| ("-", number));
+        let (mantissa, exp) = number.split_once('e').unwrap_or((number, "0"));
+        let exp: isize = exp.parse().unwrap_or(0);
+        let (int, frac) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+
+        // The value is 0.{digits} * 10^point. The lengths here are always tiny, so converting
+        // them to isize can't fail
+        let digits = format!("{int}{frac}");
+        let leading = digits.len() - digits.trim_start_matches('0').len();
+        let digits = digits.trim_matches('0');
+        let len = |len: usize| isize::try_from(len).unwrap_or(isize::MAX);
+        let point = len(int.len()) + exp - len(leading);
+
+        if digits.is_empty() {
+            return format!("{sign}0.0");
+        }
+
+        let exp = point - 1;
+        let threshold = isize::from(threshold);
+        if exp >= threshold || exp < -threshold {
+            let (first, rest) = digits.split_at(1);
+            if rest.is_empty() {
+                format!("{sign}{first}e{exp}")
+            } else {
+                format!("{sign}{first}.{rest}e{exp}")
+            }
+        } else if point <= 0 {
+            let zeros = "0".repeat(point.unsigned_abs());
+            format!("{sign}0.{zeros}{digits}")
+        } else if point.unsigned_abs() >= digits.len() {
+            let zeros = "0".repeat(point.unsigned_abs() - digits.len());
+            format!("{sign}{digits}{zeros}.0")
+        } else {
+            let (int, frac) = digits.split_at(point.unsigned_abs());
+            format!("{sign}{int}.{frac}")
+        }
+    }
+
+    // Inserts underscores between every group of `size` digits (counting from the right) of the
+    // leading integer part of a number. Any sign is kept as is, and anything after the integer
+    // part (i.e. a float's fraction or exponent) is written unchanged.
+    fn write_grouped_digits(number: &str, size: u8, f: &mut dyn fmt::Write) -> fmt::Result {
+        let (sign, number) = number
+            .strip_prefix('-')
+            .map_or(("", number), |number| ("-", number));
+        let len = number
+            .find(|ch: char| !ch.is_ascii_digit())
+            .unwrap_or(number.len());
+        let (digits, rest) = number.split_at(len);
+
+        f.write_str(sign)?;
+        for (i, ch) in digits.chars().enumerate() {
+            if i > 0 && (len - i) % usize::from(size) == 0 {
+                f.write_char('_')?;
+            }
+            f.write_char(ch)?;
+        }
+        f.write_str(rest)
+    }
+
+    pub fn write_preamble(text: &str, f: &mut dyn fmt::Write) -> fmt::Result {
+        for line in text.lines() {
+            if line.starts_with('#') {
+                f.write_str(line)?;
+            } else if line.is_empty() {
+                f.write_str("#")?;
+            } else {
+                f.write_str("# ")?;
+                f.write_str(line)?;
+            }
+            f.write_str("\n")?;
+        }
+        Ok(())
+    }
+
+    // Writes the document using `write`, first separating it from the preamble (if one was
+    // written) with a blank line. When the trailing newline is omitted the separator is held back
+    // like a trailing newline, so it is not written if the document is empty
+    pub fn write_document(
+        separate_preamble: bool,
+        trailing_newline: bool,
+        f: &mut dyn fmt::Write,
+        write: impl FnOnce(&mut dyn fmt::Write) -> fmt::Result,
+    ) -> fmt::Result {
+        if trailing_newline {
+            if separate_preamble {
+                f.write_str("\n")?;
+            }
+            write(f)
+        } else {
+            write(&mut TrimTrailingNewline {
+                writer: f,
+                pending: separate_preamble,
+            })
+        }
     }
 
     #[inline]
@@ -184,17 +365,22 @@ impl Formatter {
     pub fn write_table(
         table: &[(String, tree::Value)],
         path: &[&String],
+        empty_parents: bool,
         f: &mut dyn fmt::Write,
     ) -> fmt::Result {
         let (inlines, subtables) = split_inlines_and_subtables(table);
 
         // The table header is only needed if the table has inlines (key/value pairs); but if the
         // table is completely empty (no inlines nor subtables) then a reader would have no idea
-        // about the existence of the table, so we also write the header in that case.
-        let need_header = !inlines.is_empty() || subtables.is_empty();
+        // about the existence of the table, so we also write the header in that case. Headers of
+        // tables with only subtables are implied by the subtables' headers, but can still be
+        // written if requested.
+        let need_header = !inlines.is_empty() || subtables.is_empty() || empty_parents;
 
-        // We need a newline between inlines and subtables only if both exist
-        let need_nl = !inlines.is_empty() && !subtables.is_empty();
+        // We need a newline between the header or inlines and subtables only if both exist. The
+        // root table has no header.
+        let need_nl =
+            (!inlines.is_empty() || (need_header && !path.is_empty())) && !subtables.is_empty();
 
         if need_header {
             Self::write_table_header(path, f)?;
@@ -203,12 +389,13 @@ impl Formatter {
         if need_nl {
             writeln!(f)?;
         }
-        Self::write_subtables(&subtables, path, f)
+        Self::write_subtables(&subtables, path, empty_parents, f)
     }
 
     pub fn write_array_of_tables(
         array: &[Vec<(String, tree::Value)>],
         path: &[&String],
+        empty_parents: bool,
         f: &mut dyn fmt::Write,
     ) -> fmt::Result {
         if let Some((first, rest)) = array.split_first() {
@@ -225,7 +412,7 @@ impl Formatter {
             if need_nl {
                 writeln!(f)?;
             }
-            Self::write_subtables(&subtables, path, f)?;
+            Self::write_subtables(&subtables, path, empty_parents, f)?;
 
             for table in rest {
                 writeln!(f)?; // Newline between subtables
@@ -243,7 +430,7 @@ impl Formatter {
                 if need_nl {
                     writeln!(f)?;
                 }
-                Self::write_subtables(&subtables, path, f)?;
+                Self::write_subtables(&subtables, path, empty_parents, f)?;
             }
         }
 
@@ -267,15 +454,16 @@ impl Formatter {
     pub fn write_subtables(
         subtables: &[(&String, &tree::Table)],
         path: &[&String],
+        empty_parents: bool,
         f: &mut dyn fmt::Write,
     ) -> fmt::Result {
         if let Some((first, rest)) = subtables.split_first() {
             let (key, table) = *first;
-            Self::write_subtable(key, table, path, f)?;
+            Self::write_subtable(key, table, path, empty_parents, f)?;
 
             for &(key, table) in rest {
                 writeln!(f)?;
-                Self::write_subtable(key, table, path, f)?;
+                Self::write_subtable(key, table, path, empty_parents, f)?;
             }
         }
 
@@ -286,6 +474,7 @@ impl Formatter {
         key: &String,
         table: &tree::Table,
         path: &[&String],
+        empty_parents: bool,
         f: &mut dyn fmt::Write,
     ) -> fmt::Result {
         let path = {
@@ -294,8 +483,10 @@ impl Formatter {
             tmp
         };
         match *table {
-            tree::Table::Array(ref array) => Self::write_array_of_tables(array, &path, f),
-            tree::Table::Table(ref table) => Self::write_table(table, &path, f),
+            tree::Table::Array(ref array) => {
+                Self::write_array_of_tables(array, &path, empty_parents, f)
+            }
+            tree::Table::Table(ref table) => Self::write_table(table, &path, empty_parents, f),
         }
     }
 }
@@ -348,6 +539,8 @@ macro_rules! impl_float {
                     // and we want to preserve the sign
                     f.write_str(if self.is_sign_positive() { "nan" } else { "-nan" })
                 } else {
+                    // Ryu gives the shortest representation which parses back to exactly the same
+                    // value, so finite floats always round trip bit for bit
                     let mut buf = ryu::Buffer::new();
                     f.write_str(buf.format(*self))
                 }
@@ -361,6 +554,8 @@ impl_float!(f32 f64);
 #[cfg(test)]
 #[cfg_attr(coverage, coverage(off))]
 mod tests {
+    use std::collections::HashMap;
+
     use fmt::Write as _;
     use indoc::indoc;
 
@@ -381,30 +576,51 @@ mod tests {
     #[test]
     fn formatter_write_key() {
         let mut buf = String::new();
-        Formatter::write_key("foo", &mut buf).unwrap();
+        Formatter::write_key("foo", false, &mut buf).unwrap();
         assert_eq!(buf, "foo");
 
         let mut buf = String::new();
-        Formatter::write_key("abc.123", &mut buf).unwrap();
+        Formatter::write_key("abc.123", false, &mut buf).unwrap();
         assert_eq!(buf, r#""abc.123""#);
 
         let mut buf = String::new();
-        Formatter::write_key("😎", &mut buf).unwrap();
+        Formatter::write_key("😎", false, &mut buf).unwrap();
         assert_eq!(buf, r#""😎""#);
+
+        let mut buf = String::new();
+        Formatter::write_key("café", true, &mut buf).unwrap();
+        assert_eq!(buf, r#""caf\u00e9""#);
+
+        let mut buf = String::new();
+        Formatter::write_key("foo", true, &mut buf).unwrap();
+        assert_eq!(buf, "foo");
+
+        // Empty and whitespace keys can only be written quoted
+        let mut buf = String::new();
+        Formatter::write_key("", false, &mut buf).unwrap();
+        assert_eq!(buf, r#""""#);
+
+        let mut buf = String::new();
+        Formatter::write_key(" ", false, &mut buf).unwrap();
+        assert_eq!(buf, r#"" ""#);
     }
 
     #[test]
     fn formatter_write_string() {
         let mut buf = String::new();
-        Formatter::write_string("foo", &mut buf).unwrap();
+        Formatter::write_string("foo", false, &mut buf).unwrap();
         assert_eq!(buf, r#""foo""#);
 
         let mut buf = String::new();
-        Formatter::write_string("😎", &mut buf).unwrap();
+        Formatter::write_string("😎", false, &mut buf).unwrap();
         assert_eq!(buf, r#""😎""#);
 
         let mut buf = String::new();
-        Formatter::write_string("abc\ndef\n", &mut buf).unwrap();
+        Formatter::write_string("😎", true, &mut buf).unwrap();
+        assert_eq!(buf, r#""\U0001f60e""#);
+
+        let mut buf = String::new();
+        Formatter::write_string("abc\ndef\n", false, &mut buf).unwrap();
         assert_eq!(
             buf,
             indoc! {r#"
@@ -418,33 +634,51 @@ mod tests {
     #[test]
     fn formatter_write_basic_str() {
         let mut buf = String::new();
-        Formatter::write_basic_string("foo", &mut buf).unwrap();
+        Formatter::write_basic_string("foo", false, &mut buf).unwrap();
         assert_eq!(buf, r#""foo""#);
 
         let mut buf = String::new();
-        Formatter::write_basic_string("😎", &mut buf).unwrap();
+        Formatter::write_basic_string("😎", false, &mut buf).unwrap();
         assert_eq!(buf, r#""😎""#);
 
         let mut buf = String::new();
-        Formatter::write_basic_string("abc\ndef\n", &mut buf).unwrap();
+        Formatter::write_basic_string("abc\ndef\n", false, &mut buf).unwrap();
         assert_eq!(buf, r#""abc\ndef\n""#);
 
         let mut buf = String::new();
-        Formatter::write_basic_string("\x08\x09\x0A\x0C\x0D\"\\", &mut buf).unwrap();
+        Formatter::write_basic_string("\x08\x09\x0A\x0C\x0D\"\\", false, &mut buf).unwrap();
         assert_eq!(buf, r#""\b\t\n\f\r\"\\""#);
 
         let mut buf = String::new();
-        Formatter::write_basic_string("\x00\x01\x02\x03\x04\x05\x06\x07\x0B\x0E\x0F\x10\x11\x12\x13\x14\x15\x16\x17\x18\x19\x1A\x1B\x1C\x1D\x1E\x1F", &mut buf).unwrap();
+        Formatter::write_basic_string("\x00\x01\x02\x03\x04\x05\x06\x07\x0B\x0E\x0F\x10\x11\x12\x13\x14\x15\x16\x17\x18\x19\x1A\x1B\x1C\x1D\x1E\x1F", false, &mut buf).unwrap();
         assert_eq!(
             buf,
             r#""\u0000\u0001\u0002\u0003\u0004\u0005\u0006\u0007\u000b\u000e\u000f\u0010\u0011\u0012\u0013\u0014\u0015\u0016\u0017\u0018\u0019\u001a\u001b\u001c\u001d\u001e\u001f""#
         );
+
+        let mut buf = String::new();
+        Formatter::write_basic_string("\u{10000}\x7f\u{1f60e}\x1f\u{10ffff}", false, &mut buf)
+            .unwrap();
+        assert_eq!(buf, "\"\u{10000}\\u007f\u{1f60e}\\u001f\u{10ffff}\"");
+
+        let mut buf = String::new();
+        Formatter::write_basic_string("crème brûlée 😎\x7f", true, &mut buf).unwrap();
+        assert_eq!(buf, r#""cr\u00e8me br\u00fbl\u00e9e \U0001f60e\u007f""#);
+
+        for ascii_only in [false, true] {
+            let value = "a😎b\x00c\u{10ffff}\n\u{1d11e}é";
+            let mut buf = String::from("str = ");
+            Formatter::write_basic_string(value, ascii_only, &mut buf).unwrap();
+            assert_eq!(buf.is_ascii(), ascii_only);
+            let parsed: HashMap<String, String> = crate::from_str(&buf).unwrap();
+            assert_eq!(parsed["str"], value);
+        }
     }
 
     #[test]
     fn formatter_write_multiline_basic_str() {
         let mut buf = String::new();
-        Formatter::write_multiline_basic_string("foo", &mut buf).unwrap();
+        Formatter::write_multiline_basic_string("foo", false, &mut buf).unwrap();
         assert_eq!(
             buf,
             indoc! {r#"
@@ -452,7 +686,7 @@ mod tests {
                 foo""""#}
         );
         let mut buf = String::new();
-        Formatter::write_multiline_basic_string("😎", &mut buf).unwrap();
+        Formatter::write_multiline_basic_string("😎", false, &mut buf).unwrap();
         assert_eq!(
             buf,
             indoc! {r#"
@@ -460,7 +694,7 @@ mod tests {
                 😎""""#}
         );
         let mut buf = String::new();
-        Formatter::write_multiline_basic_string("abc\ndef\n", &mut buf).unwrap();
+        Formatter::write_multiline_basic_string("abc\ndef\n", false, &mut buf).unwrap();
         assert_eq!(
             buf,
             indoc! {r#"
@@ -470,7 +704,8 @@ mod tests {
                 """"#}
         );
         let mut buf = String::new();
-        Formatter::write_multiline_basic_string("\x08\x09\x0A\x0C\x0D\"\\", &mut buf).unwrap();
+        Formatter::write_multiline_basic_string("\x08\x09\x0A\x0C\x0D\"\\", false, &mut buf)
+            .unwrap();
         assert_eq!(
             buf,
             indoc! {"
@@ -479,59 +714,261 @@ mod tests {
                 \\f\\r\\\"\\\\\"\"\""}
         );
         let mut buf = String::new();
-        Formatter::write_multiline_basic_string("\x00\x01\x02\x03\x04\x05\x06\x07\x0B\x0E\x0F\x10\x11\x12\x13\x14\x15\x16\x17\x18\x19\x1A\x1B\x1C\x1D\x1E\x1F", &mut buf).unwrap();
+        Formatter::write_multiline_basic_string("\x00\x01\x02\x03\x04\x05\x06\x07\x0B\x0E\x0F\x10\x11\x12\x13\x14\x15\x16\x17\x18\x19\x1A\x1B\x1C\x1D\x1E\x1F", false, &mut buf).unwrap();
         assert_eq!(
             buf,
             indoc! {r#"
                 """
                 \u0000\u0001\u0002\u0003\u0004\u0005\u0006\u0007\u000b\u000e\u000f\u0010\u0011\u0012\u0013\u0014\u0015\u0016\u0017\u0018\u0019\u001a\u001b\u001c\u001d\u001e\u001f""""#}
         );
+        let mut buf = String::new();
+        Formatter::write_multiline_basic_string(
+            "\u{10000}\x7f\n\u{1f60e}\x1f\u{10ffff}",
+            false,
+            &mut buf,
+        )
+        .unwrap();
+        assert_eq!(
+            buf,
+            indoc! {"
+                \"\"\"
+                \u{10000}\\u007f
+                \u{1f60e}\\u001f\u{10ffff}\"\"\""}
+        );
+
+        let mut buf = String::new();
+        Formatter::write_multiline_basic_string("naïve\n😎", true, &mut buf).unwrap();
+        assert_eq!(
+            buf,
+            indoc! {r#"
+                """
+                na\u00efve
+                \U0001f60e""""#}
+        );
+
+        for ascii_only in [false, true] {
+            let value = "a😎b\x00c\u{10ffff}\n\u{1d11e}é";
+            let mut buf = String::from("str = ");
+            Formatter::write_multiline_basic_string(value, ascii_only, &mut buf).unwrap();
+            assert_eq!(buf.is_ascii(), ascii_only);
+            let parsed: HashMap<String, String> = crate::from_str(&buf).unwrap();
+            assert_eq!(parsed["str"], value);
+        }
     }
 
     #[test]
     fn formatter_write_integer() {
         let mut buf = String::new();
-        Formatter::write_integer(&42, &mut buf).unwrap();
+        Formatter::write_integer(&42, None, &mut buf).unwrap();
         assert_eq!(buf, "42");
 
         let mut buf = String::new();
-        Formatter::write_integer(&-12, &mut buf).unwrap();
+        Formatter::write_integer(&-12, None, &mut buf).unwrap();
         assert_eq!(buf, "-12");
+
+        let mut buf = String::new();
+        Formatter::write_integer(&1_234_567, None, &mut buf).unwrap();
+        assert_eq!(buf, "1234567");
+
+        let mut buf = String::new();
+        Formatter::write_integer(&1_234_567, Some(3), &mut buf).unwrap();
+        assert_eq!(buf, "1_234_567");
+
+        let mut buf = String::new();
+        Formatter::write_integer(&-1_234_567, Some(3), &mut buf).unwrap();
+        assert_eq!(buf, "-1_234_567");
+
+        let mut buf = String::new();
+        Formatter::write_integer(&123_456, Some(3), &mut buf).unwrap();
+        assert_eq!(buf, "123_456");
+
+        let mut buf = String::new();
+        Formatter::write_integer(&-123, Some(3), &mut buf).unwrap();
+        assert_eq!(buf, "-123");
+
+        let mut buf = String::new();
+        Formatter::write_integer(&0, Some(3), &mut buf).unwrap();
+        assert_eq!(buf, "0");
+
+        let mut buf = String::new();
+        Formatter::write_integer(&12_345, Some(2), &mut buf).unwrap();
+        assert_eq!(buf, "1_23_45");
+
+        let mut buf = String::new();
+        Formatter::write_integer(&123, Some(1), &mut buf).unwrap();
+        assert_eq!(buf, "1_2_3");
+
+        let mut buf = String::new();
+        Formatter::write_integer(&1_234_567, Some(0), &mut buf).unwrap();
+        assert_eq!(buf, "1234567");
+
+        let mut buf = String::new();
+        Formatter::write_integer(&u128::MAX, Some(3), &mut buf).unwrap();
+        assert_eq!(buf, "340_282_366_920_938_463_463_374_607_431_768_211_455");
     }
 
     #[test]
     fn formatter_write_float() {
         let mut buf = String::new();
-        Formatter::write_float(&42.0, &mut buf).unwrap();
+        Formatter::write_float(&42.0, None, FloatFormat::Shortest, &mut buf).unwrap();
         assert_eq!(buf, "42.0");
 
         let mut buf = String::new();
-        Formatter::write_float(&-12.0, &mut buf).unwrap();
+        Formatter::write_float(&-12.0, None, FloatFormat::Shortest, &mut buf).unwrap();
         assert_eq!(buf, "-12.0");
 
         let mut buf = String::new();
-        Formatter::write_float(&1e28, &mut buf).unwrap();
+        Formatter::write_float(&1e28, None, FloatFormat::Shortest, &mut buf).unwrap();
         assert_eq!(buf, "1e28");
 
         let mut buf = String::new();
-        Formatter::write_float(&0.5e-9, &mut buf).unwrap();
+        Formatter::write_float(&0.5e-9, None, FloatFormat::Shortest, &mut buf).unwrap();
         assert_eq!(buf, "5e-10");
 
         let mut buf = String::new();
-        Formatter::write_float(&f64::INFINITY, &mut buf).unwrap();
+        Formatter::write_float(&f64::INFINITY, None, FloatFormat::Shortest, &mut buf).unwrap();
         assert_eq!(buf, "inf");
 
         let mut buf = String::new();
-        Formatter::write_float(&f64::NEG_INFINITY, &mut buf).unwrap();
+        Formatter::write_float(&f64::NEG_INFINITY, None, FloatFormat::Shortest, &mut buf).unwrap();
         assert_eq!(buf, "-inf");
 
         let mut buf = String::new();
-        Formatter::write_float(&f64::NAN, &mut buf).unwrap();
+        Formatter::write_float(&f64::NAN, None, FloatFormat::Shortest, &mut buf).unwrap();
         assert_eq!(buf, "nan");
 
         let mut buf = String::new();
-        Formatter::write_float(&-f64::NAN, &mut buf).unwrap();
+        Formatter::write_float(&-f64::NAN, None, FloatFormat::Shortest, &mut buf).unwrap();
         assert_eq!(buf, "-nan");
+
+        let mut buf = String::new();
+        Formatter::write_float(&1_234_567.125, Some(3), FloatFormat::Shortest, &mut buf).unwrap();
+        assert_eq!(buf, "1_234_567.125");
+
+        let mut buf = String::new();
+        Formatter::write_float(&-1_234.5, Some(3), FloatFormat::Shortest, &mut buf).unwrap();
+        assert_eq!(buf, "-1_234.5");
+
+        let mut buf = String::new();
+        Formatter::write_float(&1e28, Some(3), FloatFormat::Shortest, &mut buf).unwrap();
+        assert_eq!(buf, "1e28");
+
+        let mut buf = String::new();
+        Formatter::write_float(&f64::NEG_INFINITY, Some(3), FloatFormat::Shortest, &mut buf)
+            .unwrap();
+        assert_eq!(buf, "-inf");
+
+        let mut buf = String::new();
+        Formatter::write_float(&f64::NAN, Some(3), FloatFormat::Shortest, &mut buf).unwrap();
+        assert_eq!(buf, "nan");
+    }
+
+    #[test]
+    fn formatter_write_float_scientific() {
+        let cases = [
+            (1e6, 6, "1e6"),
+            (1e6, 7, "1000000.0"),
+            (1e-9, 6, "1e-9"),
+            (1e-9, 9, "0.000000001"),
+            (42.5, 6, "42.5"),
+            (42.5, 1, "4.25e1"),
+            (-1.5e-9, 6, "-1.5e-9"),
+            (0.000_125, 3, "1.25e-4"),
+            (0.000_125, 4, "0.000125"),
+            (1e28, 30, "10000000000000000000000000000.0"),
+            (123_456.0, 6, "123456.0"),
+            (0.0, 0, "0.0"),
+            (-0.0, 6, "-0.0"),
+            (f64::INFINITY, 6, "inf"),
+            (f64::NEG_INFINITY, 6, "-inf"),
+            (f64::NAN, 6, "nan"),
+        ];
+        for (value, threshold, expected) in cases {
+            let mut buf = String::new();
+            Formatter::write_float(&value, None, FloatFormat::Scientific(threshold), &mut buf)
+                .unwrap();
+            assert_eq!(buf, expected);
+        }
+
+        let mut buf = String::new();
+        Formatter::write_float(&1_234_567.5, Some(3), FloatFormat::Scientific(9), &mut buf)
+            .unwrap();
+        assert_eq!(buf, "1_234_567.5");
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)] // strict cmp is fine for infinities
+    fn formatter_write_float_special_round_trip() {
+        for value in [f64::INFINITY, f64::NEG_INFINITY, f64::NAN, -f64::NAN] {
+            let mut buf = String::from("float = ");
+            Formatter::write_float(&value, None, FloatFormat::Shortest, &mut buf).unwrap();
+
+            let parsed: HashMap<String, f64> = crate::from_str(&buf).unwrap();
+            let parsed = parsed["float"];
+            if value.is_nan() {
+                assert!(parsed.is_nan());
+            } else {
+                assert_eq!(parsed, value);
+            }
+            assert_eq!(parsed.is_sign_positive(), value.is_sign_positive());
+        }
+    }
+
+    #[test]
+    fn trim_trailing_newline() {
+        let mut buf = String::new();
+        let mut writer = TrimTrailingNewline {
+            writer: &mut buf,
+            pending: false,
+        };
+        writer.write_str("a = 1\n").unwrap();
+        writer.write_str("").unwrap();
+        writeln!(writer).unwrap();
+        writer.write_str("[b]\nc = 2\n").unwrap();
+        assert!(writer.pending);
+        assert_eq!(buf, "a = 1\n\n[b]\nc = 2");
+    }
+
+    #[test]
+    fn formatter_write_document() {
+        let mut buf = String::new();
+        Formatter::write_document(false, true, &mut buf, |f| f.write_str("a = 1\n")).unwrap();
+        assert_eq!(buf, "a = 1\n");
+
+        let mut buf = String::new();
+        Formatter::write_document(false, false, &mut buf, |f| f.write_str("a = 1\n")).unwrap();
+        assert_eq!(buf, "a = 1");
+
+        let mut buf = String::new();
+        Formatter::write_document(false, false, &mut buf, |f| f.write_str("")).unwrap();
+        assert_eq!(buf, "");
+
+        let mut buf = String::new();
+        Formatter::write_document(true, true, &mut buf, |f| f.write_str("a = 1\n")).unwrap();
+        assert_eq!(buf, "\na = 1\n");
+
+        let mut buf = String::new();
+        Formatter::write_document(true, false, &mut buf, |f| f.write_str("a = 1\n")).unwrap();
+        assert_eq!(buf, "\na = 1");
+
+        let mut buf = String::new();
+        Formatter::write_document(true, false, &mut buf, |f| f.write_str("")).unwrap();
+        assert_eq!(buf, "");
+    }
+
+    #[test]
+    fn formatter_write_preamble() {
+        let mut buf = String::new();
+        Formatter::write_preamble("Do not edit", &mut buf).unwrap();
+        assert_eq!(buf, "# Do not edit\n");
+
+        let mut buf = String::new();
+        Formatter::write_preamble("# Generated by build.rs\n\nDo not edit\n", &mut buf).unwrap();
+        assert_eq!(buf, "# Generated by build.rs\n#\n# Do not edit\n");
+
+        let mut buf = String::new();
+        Formatter::write_preamble("#!shebang\r\n#comment", &mut buf).unwrap();
+        assert_eq!(buf, "#!shebang\n#comment\n");
     }
 
     #[test]
@@ -584,6 +1021,7 @@ mod tests {
         assert_eq!(buf, "[[a.😎.b]]\n");
     }
 
+    #[allow(clippy::too_many_lines)]
     #[test]
     fn formatter_write_table() {
         use tree::{Table, Value};
@@ -601,6 +1039,7 @@ mod tests {
                 ),
             ],
             &[&"foo".to_string()],
+            false,
             &mut buf,
         )
         .unwrap();
@@ -625,19 +1064,72 @@ mod tests {
                 )])),
             )],
             &[&"foo".to_string()],
+            false,
+            &mut buf,
+        )
+        .unwrap();
+        assert_eq!(
+            buf,
+            indoc! {r"
+                [foo.bar]
+                baz = qux
+            "}
+        );
+
+        let mut buf = String::new();
+        Formatter::write_table(
+            &[(
+                "bar".to_string(),
+                Value::Table(Table::Table(vec![(
+                    "baz".to_string(),
+                    Value::Inline("qux".to_string()),
+                )])),
+            )],
+            &[&"foo".to_string()],
+            true,
             &mut buf,
         )
         .unwrap();
         assert_eq!(
             buf,
             indoc! {r"
+                [foo]
+
                 [foo.bar]
                 baz = qux
             "}
         );
 
         let mut buf = String::new();
-        Formatter::write_table(&[], &[&"foo".to_string()], &mut buf).unwrap();
+        // Inlines are always written before subtables, regardless of their order in the table
+        Formatter::write_table(
+            &[
+                (
+                    "qux".to_string(),
+                    Value::Table(Table::Table(vec![(
+                        "quux".to_string(),
+                        Value::Inline("corge".to_string()),
+                    )])),
+                ),
+                ("bar".to_string(), Value::Inline("baz".to_string())),
+            ],
+            &[],
+            false,
+            &mut buf,
+        )
+        .unwrap();
+        assert_eq!(
+            buf,
+            indoc! {r"
+                bar = baz
+
+                [qux]
+                quux = corge
+            "}
+        );
+
+        let mut buf = String::new();
+        Formatter::write_table(&[], &[&"foo".to_string()], false, &mut buf).unwrap();
         assert_eq!(
             buf,
             indoc! {r"
@@ -666,6 +1158,7 @@ mod tests {
                 vec![("grault".to_string(), Value::Inline("garply".to_string()))],
             ],
             &[&"foo".to_string()],
+            false,
             &mut buf,
         )
         .unwrap();
@@ -693,6 +1186,7 @@ mod tests {
                 )])),
             )]],
             &[&"foo".to_string()],
+            false,
             &mut buf,
         )
         .unwrap();
@@ -706,7 +1200,8 @@ mod tests {
         );
 
         let mut buf = String::new();
-        Formatter::write_array_of_tables(&[vec![]], &[&"foo".to_string()], &mut buf).unwrap();
+        Formatter::write_array_of_tables(&[vec![]], &[&"foo".to_string()], false, &mut buf)
+            .unwrap();
         assert_eq!(
             buf,
             indoc! {r"
@@ -715,7 +1210,7 @@ mod tests {
         );
 
         let mut buf = String::new();
-        Formatter::write_array_of_tables(&[], &[&"foo".to_string()], &mut buf).unwrap();
+        Formatter::write_array_of_tables(&[], &[&"foo".to_string()], false, &mut buf).unwrap();
         assert_eq!(buf, indoc! {r""});
     }
 
@@ -774,6 +1269,7 @@ mod tests {
                 ),
             ],
             &[&"foo".to_string()],
+            false,
             &mut buf,
         )
         .unwrap();
@@ -789,7 +1285,7 @@ mod tests {
         );
 
         let mut buf = String::new();
-        Formatter::write_subtables(&[], &[&"foo".to_string()], &mut buf).unwrap();
+        Formatter::write_subtables(&[], &[&"foo".to_string()], false, &mut buf).unwrap();
         assert_eq!(buf, "");
     }
 
@@ -802,6 +1298,7 @@ mod tests {
             &"bar".to_string(),
             &Table::Table(vec![("baz".to_string(), Value::Inline("qux".to_string()))]),
             &[&"foo".to_string()],
+            false,
             &mut buf,
         )
         .unwrap();
@@ -821,6 +1318,7 @@ mod tests {
                 Value::Inline("qux".to_string()),
             )]]),
             &[&"foo".to_string()],
+            false,
             &mut buf,
         )
         .unwrap();
@@ -837,6 +1335,7 @@ mod tests {
             &"foo".to_string(),
             &Table::Table(vec![("bar".to_string(), Value::Inline("baz".to_string()))]),
             &[],
+            false,
             &mut buf,
         )
         .unwrap();
@@ -0,0 +1,228 @@
+#[cfg(feature = "datetime")]
+use super::datetime::{Datetime, LocalDate, LocalTime, Offset};
+use super::Value;
+
+impl From<Value> for toml::Value {
+    #[inline]
+    fn from(value: Value) -> Self {
+        match value {
+            Value::String(str) => Self::String(str),
+            Value::Integer(int) => Self::Integer(int),
+            Value::Float(float) => Self::Float(float),
+            Value::Boolean(bool) => Self::Boolean(bool),
+            #[cfg(feature = "datetime")]
+            Value::Datetime(datetime) => Self::Datetime(datetime.into()),
+            Value::Array(array) => Self::Array(array.into_iter().map(Into::into).collect()),
+            Value::Table(table) => Self::Table(
+                table
+                    .into_iter()
+                    .map(|(key, value)| (key, value.into()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl From<toml::Value> for Value {
+    /// Converts a [`toml::Value`] into a [`Value`].
+    ///
+    /// Without the `datetime` feature date-time values are converted to strings.
+    #[inline]
+    fn from(value: toml::Value) -> Self {
+        match value {
+            toml::Value::String(str) => Self::String(str),
+            toml::Value::Integer(int) => Self::Integer(int),
+            toml::Value::Float(float) => Self::Float(float),
+            toml::Value::Boolean(bool) => Self::Boolean(bool),
+            #[cfg(feature = "datetime")]
+            toml::Value::Datetime(datetime) => Self::Datetime(datetime.into()),
+            #[cfg(not(feature = "datetime"))]
+            toml::Value::Datetime(datetime) => Self::String(datetime.to_string()),
+            toml::Value::Array(array) => Self::Array(array.into_iter().map(Into::into).collect()),
+            toml::Value::Table(table) => Self::Table(
+                table
+                    .into_iter()
+                    .map(|(key, value)| (key, value.into()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "datetime")]
+impl From<Datetime> for toml::value::Datetime {
+    #[inline]
+    fn from(value: Datetime) -> Self {
+        Self {
+            date: value.date.map(|date| toml::value::Date {
+                year: date.year,
+                month: date.month,
+                day: date.day,
+            }),
+            time: value.time.map(|time| toml::value::Time {
+                hour: time.hour,
+                minute: time.minute,
+                second: time.second,
+                nanosecond: time.nanosecond,
+            }),
+            offset: value.offset.map(|offset| match offset {
+                Offset::Z => toml::value::Offset::Z,
+                Offset::Custom { minutes } => toml::value::Offset::Custom { minutes },
+            }),
+        }
+    }
+}
+
+#[cfg(feature = "datetime")]
+impl From<toml::value::Datetime> for Datetime {
+    #[inline]
+    fn from(value: toml::value::Datetime) -> Self {
+        Self {
+            date: value.date.map(|date| LocalDate {
+                year: date.year,
+                month: date.month,
+                day: date.day,
+            }),
+            time: value.time.map(|time| LocalTime {
+                hour: time.hour,
+                minute: time.minute,
+                second: time.second,
+                nanosecond: time.nanosecond,
+            }),
+            offset: value.offset.map(|offset| match offset {
+                toml::value::Offset::Z => Offset::Z,
+                toml::value::Offset::Custom { minutes } => Offset::Custom { minutes },
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage, coverage(off))]
+mod tests {
+    use super::*;
+    use crate::Table;
+
+    fn soml_value() -> Value {
+        Value::Table(Table::from([
+            ("string".into(), Value::String("foo".into())),
+            ("integer".into(), Value::Integer(42)),
+            ("float".into(), Value::Float(4.2)),
+            ("boolean".into(), Value::Boolean(true)),
+            #[cfg(feature = "datetime")]
+            (
+                "datetime".into(),
+                Value::Datetime(Datetime::EXAMPLE_OFFSET_DATETIME),
+            ),
+            (
+                "array".into(),
+                Value::Array(vec![Value::Integer(1), Value::Integer(2)]),
+            ),
+            (
+                "table".into(),
+                Value::Table(Table::from([("bar".into(), Value::Integer(3))])),
+            ),
+        ]))
+    }
+
+    fn toml_value() -> toml::Value {
+        toml::Value::Table(toml::Table::from_iter([
+            ("string".into(), toml::Value::String("foo".into())),
+            ("integer".into(), toml::Value::Integer(42)),
+            ("float".into(), toml::Value::Float(4.2)),
+            ("boolean".into(), toml::Value::Boolean(true)),
+            #[cfg(feature = "datetime")]
+            (
+                "datetime".into(),
+                toml::Value::Datetime(toml::value::Datetime {
+                    date: Some(toml::value::Date {
+                        year: 2023,
+                        month: 1,
+                        day: 2,
+                    }),
+                    time: Some(toml::value::Time {
+                        hour: 3,
+                        minute: 4,
+                        second: 5,
+                        nanosecond: 6_000_000,
+                    }),
+                    offset: Some(toml::value::Offset::Custom { minutes: 428 }),
+                }),
+            ),
+            (
+                "array".into(),
+                toml::Value::Array(vec![toml::Value::Integer(1), toml::Value::Integer(2)]),
+            ),
+            (
+                "table".into(),
+                toml::Value::Table(toml::Table::from_iter([(
+                    "bar".into(),
+                    toml::Value::Integer(3),
+                )])),
+            ),
+        ]))
+    }
+
+    #[test]
+    fn toml_value_from_value() {
+        assert_eq!(toml::Value::from(soml_value()), toml_value());
+    }
+
+    #[test]
+    fn value_from_toml_value() {
+        assert_eq!(Value::from(toml_value()), soml_value());
+    }
+
+    #[cfg(not(feature = "datetime"))]
+    #[test]
+    fn value_from_toml_value_datetime() {
+        let datetime = toml::value::Datetime {
+            date: Some(toml::value::Date {
+                year: 2023,
+                month: 1,
+                day: 2,
+            }),
+            time: None,
+            offset: None,
+        };
+        assert_eq!(
+            Value::from(toml::Value::Datetime(datetime)),
+            Value::String("2023-01-02".into())
+        );
+    }
+
+    #[cfg(feature = "datetime")]
+    #[test]
+    fn toml_datetime_from_datetime() {
+        let datetime = toml::value::Datetime::from(Datetime::EXAMPLE_OFFSET_DATETIME);
+        assert_eq!(datetime.to_string(), "2023-01-02T03:04:05.006+07:08");
+
+        let datetime = toml::value::Datetime::from(Datetime::EXAMPLE_LOCAL_DATE);
+        assert_eq!(datetime.to_string(), "2023-01-02");
+
+        let datetime = toml::value::Datetime::from(Datetime {
+            offset: Some(Offset::Z),
+            ..Datetime::EXAMPLE_OFFSET_DATETIME
+        });
+        assert_eq!(datetime.to_string(), "2023-01-02T03:04:05.006Z");
+    }
+
+    #[cfg(feature = "datetime")]
+    #[test]
+    fn datetime_from_toml_datetime() {
+        let datetime: toml::value::Datetime = "2023-01-02T03:04:05.006+07:08".parse().unwrap();
+        assert_eq!(Datetime::from(datetime), Datetime::EXAMPLE_OFFSET_DATETIME);
+
+        let datetime: toml::value::Datetime = "03:04:05.006".parse().unwrap();
+        assert_eq!(Datetime::from(datetime), Datetime::EXAMPLE_LOCAL_TIME);
+
+        let datetime: toml::value::Datetime = "2023-01-02T03:04:05.006Z".parse().unwrap();
+        assert_eq!(
+            Datetime::from(datetime),
+            Datetime {
+                offset: Some(Offset::Z),
+                ..Datetime::EXAMPLE_OFFSET_DATETIME
+            }
+        );
+    }
+}
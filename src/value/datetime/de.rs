@@ -210,7 +210,7 @@ impl<'de> de::Deserialize<'de> for EncodedOffsetDatetime {
             }
         }
 
-        deserializer.deserialize_seq(Visitor)
+        deserializer.deserialize_bytes(Visitor)
     }
 }
 
@@ -373,7 +373,7 @@ impl<'de> de::Deserialize<'de> for EncodedLocalDatetime {
             }
         }
 
-        deserializer.deserialize_seq(Visitor)
+        deserializer.deserialize_bytes(Visitor)
     }
 }
 
@@ -533,7 +533,7 @@ impl<'de> de::Deserialize<'de> for EncodedLocalDate {
             }
         }
 
-        deserializer.deserialize_seq(Visitor)
+        deserializer.deserialize_bytes(Visitor)
     }
 }
 
@@ -691,7 +691,7 @@ impl<'de> de::Deserialize<'de> for EncodedLocalTime {
             }
         }
 
-        deserializer.deserialize_seq(Visitor)
+        deserializer.deserialize_bytes(Visitor)
     }
 }
 
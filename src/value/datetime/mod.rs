@@ -12,6 +12,11 @@ use crate::de::{Error, ErrorKind};
 mod de;
 mod ser;
 
+// Creates an error for an invalid date-time, including the literal in the message
+fn invalid_datetime(bytes: &[u8]) -> ErrorKind {
+    ErrorKind::InvalidDatetime(String::from_utf8_lossy(bytes).into())
+}
+
 /// A generic TOML date-time enum.
 ///
 /// This struct can represent any of the TOML date-time types, depending on the variant.
@@ -57,15 +62,17 @@ impl AnyDatetime {
         if let Some(position) = bytes.iter().position(|b| b"Tt ".contains(b)) {
             let (date, rest) = (&bytes[..position], &bytes[position + 1..]);
 
-            let date = LocalDate::from_slice(date)?;
+            let date = LocalDate::from_slice(date).map_err(|_| invalid_datetime(bytes))?;
 
             if let Some(off_pos) = rest.iter().position(|b| b"Zz+-".contains(b)) {
-                let time = LocalTime::from_slice(&rest[..off_pos])?;
-                let offset = Offset::from_slice(&rest[off_pos..])?;
+                let time =
+                    LocalTime::from_slice(&rest[..off_pos]).map_err(|_| invalid_datetime(bytes))?;
+                let offset =
+                    Offset::from_slice(&rest[off_pos..]).map_err(|_| invalid_datetime(bytes))?;
 
                 Ok(Self::OffsetDatetime(OffsetDatetime { date, time, offset }))
             } else {
-                let time = LocalTime::from_slice(rest)?;
+                let time = LocalTime::from_slice(rest).map_err(|_| invalid_datetime(bytes))?;
 
                 Ok(Self::LocalDatetime(LocalDatetime { date, time }))
             }
@@ -344,18 +351,18 @@ impl OffsetDatetime {
         let position = bytes
             .iter()
             .position(|b| b"Tt ".contains(b))
-            .ok_or(ErrorKind::InvalidDatetime)?;
+            .ok_or_else(|| invalid_datetime(bytes))?;
         let (date, rest) = (&bytes[..position], &bytes[position + 1..]);
 
         let position = rest
             .iter()
             .position(|b| b"Zz+-".contains(b))
-            .ok_or(ErrorKind::InvalidDatetime)?;
+            .ok_or_else(|| invalid_datetime(bytes))?;
         let (time, offset) = (&rest[..position], &rest[position..]);
 
-        let date = LocalDate::from_slice(date)?;
-        let time = LocalTime::from_slice(time)?;
-        let offset = Offset::from_slice(offset)?;
+        let date = LocalDate::from_slice(date).map_err(|_| invalid_datetime(bytes))?;
+        let time = LocalTime::from_slice(time).map_err(|_| invalid_datetime(bytes))?;
+        let offset = Offset::from_slice(offset).map_err(|_| invalid_datetime(bytes))?;
 
         Ok(Self { date, time, offset })
     }
@@ -477,11 +484,11 @@ impl LocalDatetime {
         let position = bytes
             .iter()
             .position(|b| b"Tt ".contains(b))
-            .ok_or(ErrorKind::InvalidDatetime)?;
+            .ok_or_else(|| invalid_datetime(bytes))?;
         let (date, time) = (&bytes[..position], &bytes[position + 1..]);
 
-        let date = LocalDate::from_slice(date)?;
-        let time = LocalTime::from_slice(time)?;
+        let date = LocalDate::from_slice(date).map_err(|_| invalid_datetime(bytes))?;
+        let time = LocalTime::from_slice(time).map_err(|_| invalid_datetime(bytes))?;
 
         Ok(Self { date, time })
     }
@@ -618,47 +625,56 @@ impl LocalDate {
         let position = bytes
             .iter()
             .position(|b| *b == b'-')
-            .ok_or(ErrorKind::InvalidDatetime)?;
+            .ok_or_else(|| invalid_datetime(bytes))?;
         let (year, rest) = (&bytes[..position], &bytes[position + 1..]);
 
         let position = rest
             .iter()
             .position(|b| *b == b'-')
-            .ok_or(ErrorKind::InvalidDatetime)?;
+            .ok_or_else(|| invalid_datetime(bytes))?;
         let (month, day) = (&rest[..position], &rest[position + 1..]);
 
         if year.len() != 4 || month.len() != 2 || day.len() != 2 {
-            return Err(ErrorKind::InvalidDatetime.into());
+            return Err(invalid_datetime(bytes).into());
         }
 
         let year = str::from_utf8(year)
             .unwrap_or_else(|_| unreachable!("we should only have ASCII digits at this point"));
-        let year = u16::from_str(year).map_err(|_| ErrorKind::InvalidDatetime)?;
+        let year = u16::from_str(year).map_err(|_| invalid_datetime(bytes))?;
         let month = str::from_utf8(month)
             .unwrap_or_else(|_| unreachable!("we should only have ASCII digits at this point"));
-        let month = u8::from_str(month).map_err(|_| ErrorKind::InvalidDatetime)?;
+        let month = u8::from_str(month).map_err(|_| invalid_datetime(bytes))?;
         let day = str::from_utf8(day)
             .unwrap_or_else(|_| unreachable!("we should only have ASCII digits at this point"));
-        let day = u8::from_str(day).map_err(|_| ErrorKind::InvalidDatetime)?;
+        let day = u8::from_str(day).map_err(|_| invalid_datetime(bytes))?;
+
+        let date = Self { year, month, day };
 
         #[cfg(feature = "strict")]
-        {
-            let is_valid = match month {
-                1 | 3 | 5 | 7 | 8 | 10 | 12 => (1..=31).contains(&day),
-                4 | 6 | 9 | 11 => (1..=30).contains(&day),
-                // Check for leap year
-                2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => {
-                    (1..=29).contains(&day)
-                }
-                2 => (1..=28).contains(&day),
-                _ => false,
-            };
-            if !is_valid {
-                return Err(ErrorKind::InvalidDatetime.into());
-            }
+        if !date.is_valid() {
+            return Err(invalid_datetime(bytes).into());
         }
 
-        Ok(Self { year, month, day })
+        Ok(date)
+    }
+
+    /// Returns `true` if the date is valid.
+    ///
+    /// A date is valid if the year is between 0--9999, the month is between 1--12, and the day is
+    /// between 1 and the number of days in the given month (accounting for leap years).
+    #[inline]
+    #[must_use]
+    pub const fn is_valid(&self) -> bool {
+        let Self { year, month, day } = *self;
+        let days_in_month = match month {
+            1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+            4 | 6 | 9 | 11 => 30,
+            // Check for leap year
+            2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+            2 => 28,
+            _ => return false,
+        };
+        year <= 9999 && day >= 1 && day <= days_in_month
     }
 
     /// Generates a string representation of the local date as a sequence of bytes.
@@ -786,6 +802,9 @@ impl LocalTime {
 
     /// Parses a [`LocalTime`] from a byte slice.
     ///
+    /// Fractional seconds are kept up to nanosecond precision (9 digits); any further digits are
+    /// truncated.
+    ///
     /// # Errors
     ///
     /// Returns an error if the slice is not a valid TOML time value.
@@ -794,25 +813,25 @@ impl LocalTime {
         let position = bytes
             .iter()
             .position(|b| *b == b':')
-            .ok_or(ErrorKind::InvalidDatetime)?;
+            .ok_or_else(|| invalid_datetime(bytes))?;
         let (hour, rest) = (&bytes[..position], &bytes[position + 1..]);
 
         let position = rest
             .iter()
             .position(|b| *b == b':')
-            .ok_or(ErrorKind::InvalidDatetime)?;
+            .ok_or_else(|| invalid_datetime(bytes))?;
         let (minute, second) = (&rest[..position], &rest[position + 1..]);
 
         if hour.len() != 2 || minute.len() != 2 {
-            return Err(ErrorKind::InvalidDatetime.into());
+            return Err(invalid_datetime(bytes).into());
         }
 
         let hour = str::from_utf8(hour)
             .unwrap_or_else(|_| unreachable!("we should only have ASCII digits at this point"));
-        let hour = u8::from_str(hour).map_err(|_| ErrorKind::InvalidDatetime)?;
+        let hour = u8::from_str(hour).map_err(|_| invalid_datetime(bytes))?;
         let minute = str::from_utf8(minute)
             .unwrap_or_else(|_| unreachable!("we should only have ASCII digits at this point"));
-        let minute = u8::from_str(minute).map_err(|_| ErrorKind::InvalidDatetime)?;
+        let minute = u8::from_str(minute).map_err(|_| invalid_datetime(bytes))?;
 
         let (second, fraction) = second
             .iter()
@@ -822,15 +841,15 @@ impl LocalTime {
             });
 
         if second.len() != 2 {
-            return Err(ErrorKind::InvalidDatetime.into());
+            return Err(invalid_datetime(bytes).into());
         }
         let second = str::from_utf8(second)
             .unwrap_or_else(|_| unreachable!("we should only have ASCII digits at this point"));
-        let second = u8::from_str(second).map_err(|_| ErrorKind::InvalidDatetime)?;
+        let second = u8::from_str(second).map_err(|_| invalid_datetime(bytes))?;
 
         let nanosecond = if let Some(fraction) = fraction {
             if fraction.is_empty() {
-                return Err(ErrorKind::InvalidDatetime.into());
+                return Err(invalid_datetime(bytes).into());
             }
 
             // The TOML spec requires at least milliseconds (6 digits) and truncate additional
@@ -839,7 +858,7 @@ impl LocalTime {
 
             let fraction = str::from_utf8(fraction)
                 .unwrap_or_else(|_| unreachable!("we should only have ASCII digits at this point"));
-            let nanosecond = u32::from_str(fraction).map_err(|_| ErrorKind::InvalidDatetime)?;
+            let nanosecond = u32::from_str(fraction).map_err(|_| invalid_datetime(bytes))?;
 
             // If we parsed <9 digits, we need to multiply by 10 for each digit we're short
             let extra_zeros = 9 - u32::try_from(fraction.len())
@@ -849,18 +868,29 @@ impl LocalTime {
             0
         };
 
-        #[cfg(feature = "strict")]
-        if hour >= 24 || minute >= 60 || second >= 61 {
-            // second == 60 is valid for a leap second
-            return Err(ErrorKind::InvalidDatetime.into());
-        }
-
-        Ok(Self {
+        let time = Self {
             hour,
             minute,
             second,
             nanosecond,
-        })
+        };
+
+        #[cfg(feature = "strict")]
+        if !time.is_valid() {
+            return Err(invalid_datetime(bytes).into());
+        }
+
+        Ok(time)
+    }
+
+    /// Returns `true` if the time is valid.
+    ///
+    /// A time is valid if the hour is between 0--23, the minute is between 0--59, the second is
+    /// between 0--60 (to allow for leap seconds), and the nanosecond is between 0--999 999 999.
+    #[inline]
+    #[must_use]
+    pub const fn is_valid(&self) -> bool {
+        self.hour < 24 && self.minute < 60 && self.second < 61 && self.nanosecond < 1_000_000_000
     }
 
     /// Generates a string representation of the local time as a sequence of bytes.
@@ -975,7 +1005,7 @@ pub enum Offset {
     Custom {
         /// The offset in minutes.
         ///
-        /// This is always between -1440--+1440 (-24:00--+24:00) (inclusive).
+        /// This is always between -1439--+1439 (-23:59--+23:59) (inclusive).
         minutes: i16,
     },
 }
@@ -984,6 +1014,21 @@ impl Offset {
     #[cfg(test)]
     pub(crate) const EXAMPLE: Self = Self::Custom { minutes: 428 };
 
+    /// Creates a numeric [`Offset`] from a number of minutes.
+    ///
+    /// Returns `None` if the offset is not between -23:59--+23:59 (-1439--+1439 minutes)
+    /// (inclusive).
+    #[inline]
+    #[must_use]
+    pub const fn from_minutes(minutes: i16) -> Option<Self> {
+        let offset = Self::Custom { minutes };
+        if offset.is_valid() {
+            Some(offset)
+        } else {
+            None
+        }
+    }
+
     /// Parses an [`Offset`] from a byte slice.
     ///
     /// # Errors
@@ -994,38 +1039,68 @@ impl Offset {
         if bytes == b"Z" || bytes == b"z" {
             Ok(Self::Z)
         } else {
-            let (sign, bytes) = bytes.split_first().ok_or(ErrorKind::InvalidDatetime)?;
+            let (sign, rest) = bytes.split_first().ok_or_else(|| invalid_datetime(bytes))?;
             let sign = match *sign {
                 b'+' => 1,
                 b'-' => -1,
-                _ => return Err(ErrorKind::InvalidDatetime.into()),
+                _ => return Err(invalid_datetime(bytes).into()),
             };
 
-            let position = bytes
+            let position = rest
                 .iter()
                 .position(|b| *b == b':')
-                .ok_or(ErrorKind::InvalidDatetime)?;
-            let (hours, minutes) = (&bytes[..position], &bytes[position + 1..]);
+                .ok_or_else(|| invalid_datetime(bytes))?;
+            let (hours, minutes) = (&rest[..position], &rest[position + 1..]);
+
+            // ISO 8601 allows offsets with seconds (e.g. +07:08:09), but TOML does not
+            if minutes.contains(&b':') {
+                return Err(invalid_datetime(bytes).into());
+            }
 
-            if hours.len() != 2 || minutes.len() != 2 {
-                return Err(ErrorKind::InvalidDatetime.into());
+            if hours.len() != 2
+                || minutes.len() != 2
+                || !hours.iter().chain(minutes).all(u8::is_ascii_digit)
+            {
+                return Err(invalid_datetime(bytes).into());
             }
 
             // TODO use int::from_ascii when it's stable
             let hours = str::from_utf8(hours)
                 .unwrap_or_else(|_| unreachable!("we should only have ASCII digits at this point"));
-            let hours = i16::from_str(hours).map_err(|_| ErrorKind::InvalidDatetime)?;
+            let hours = i16::from_str(hours).map_err(|_| invalid_datetime(bytes))?;
             let minutes = str::from_utf8(minutes)
                 .unwrap_or_else(|_| unreachable!("we should only have ASCII digits at this point"));
-            let minutes = i16::from_str(minutes).map_err(|_| ErrorKind::InvalidDatetime)?;
+            let minutes = i16::from_str(minutes).map_err(|_| invalid_datetime(bytes))?;
 
+            // The minutes field must be checked here since an overflow into the hours cannot be
+            // detected after they are combined
             #[cfg(feature = "strict")]
-            if !(0..=23).contains(&hours) || !(0..=59).contains(&minutes) {
-                return Err(ErrorKind::InvalidDatetime.into());
+            if minutes >= 60 {
+                return Err(invalid_datetime(bytes).into());
             }
 
-            let minutes = sign * (hours * 60 + minutes);
-            Ok(Self::Custom { minutes })
+            let offset = Self::Custom {
+                minutes: sign * (hours * 60 + minutes),
+            };
+
+            #[cfg(feature = "strict")]
+            if !offset.is_valid() {
+                return Err(invalid_datetime(bytes).into());
+            }
+
+            Ok(offset)
+        }
+    }
+
+    /// Returns `true` if the offset is valid.
+    ///
+    /// An offset is valid if it is between -23:59--+23:59 (inclusive).
+    #[inline]
+    #[must_use]
+    pub const fn is_valid(&self) -> bool {
+        match *self {
+            Self::Z => true,
+            Self::Custom { minutes } => minutes >= -(23 * 60 + 59) && minutes <= 23 * 60 + 59,
         }
     }
 
@@ -1045,7 +1120,7 @@ impl fmt::Display for Offset {
             Self::Z => write!(f, "Z"),
             Self::Custom { minutes } => {
                 let sign = if minutes < 0 { "-" } else { "+" };
-                let minutes = minutes.abs();
+                let minutes = minutes.unsigned_abs();
                 let (hours, minutes) = (minutes / 60, minutes % 60);
                 write!(f, "{sign}{hours:02}:{minutes:02}")
             }
@@ -1107,7 +1182,7 @@ mod tests {
 
         assert_matches!(
             AnyDatetime::from_slice(b"invalid string"),
-            Err(Error(ErrorKind::InvalidDatetime))
+            Err(Error(ErrorKind::InvalidDatetime(..)))
         );
     }
 
@@ -1146,7 +1221,7 @@ mod tests {
 
         assert_matches!(
             AnyDatetime::from_str("invalid string"),
-            Err(Error(ErrorKind::InvalidDatetime))
+            Err(Error(ErrorKind::InvalidDatetime(..)))
         );
     }
 
@@ -1201,7 +1276,7 @@ mod tests {
 
         assert_matches!(
             Datetime::from_slice(b"invalid string"),
-            Err(Error(ErrorKind::InvalidDatetime))
+            Err(Error(ErrorKind::InvalidDatetime(..)))
         );
     }
 
@@ -1265,7 +1340,7 @@ mod tests {
 
         assert_matches!(
             Datetime::from_str("invalid string"),
-            Err(Error(ErrorKind::InvalidDatetime))
+            Err(Error(ErrorKind::InvalidDatetime(..)))
         );
     }
 
@@ -1391,9 +1466,14 @@ mod tests {
         let result = OffsetDatetime::from_slice(b"2023-01-02 03:04:05.006+07:08").unwrap();
         assert_eq!(result, OffsetDatetime::EXAMPLE);
 
+        assert_matches!(
+            OffsetDatetime::from_slice(b"2023-01-02T03:04:05.006+07:08:09"),
+            Err(Error(ErrorKind::InvalidDatetime(lit))) if &*lit == "2023-01-02T03:04:05.006+07:08:09"
+        );
+
         assert_matches!(
             OffsetDatetime::from_slice(b"invalid string"),
-            Err(Error(ErrorKind::InvalidDatetime))
+            Err(Error(ErrorKind::InvalidDatetime(lit))) if &*lit == "invalid string"
         );
     }
 
@@ -1404,7 +1484,7 @@ mod tests {
 
         assert_matches!(
             OffsetDatetime::from_str("invalid string"),
-            Err(Error(ErrorKind::InvalidDatetime))
+            Err(Error(ErrorKind::InvalidDatetime(..)))
         );
     }
 
@@ -1529,7 +1609,7 @@ mod tests {
 
         assert_matches!(
             LocalDatetime::from_slice(b"invalid string"),
-            Err(Error(ErrorKind::InvalidDatetime))
+            Err(Error(ErrorKind::InvalidDatetime(..)))
         );
     }
 
@@ -1540,7 +1620,7 @@ mod tests {
 
         assert_matches!(
             LocalDatetime::from_str("invalid string"),
-            Err(Error(ErrorKind::InvalidDatetime))
+            Err(Error(ErrorKind::InvalidDatetime(..)))
         );
     }
 
@@ -1660,66 +1740,66 @@ mod tests {
         // Incorrect lengths
         assert_matches!(
             LocalDate::from_slice(b"123-01-02"),
-            Err(Error(ErrorKind::InvalidDatetime))
+            Err(Error(ErrorKind::InvalidDatetime(..)))
         );
         assert_matches!(
             LocalDate::from_slice(b"2023-123-02"),
-            Err(Error(ErrorKind::InvalidDatetime))
+            Err(Error(ErrorKind::InvalidDatetime(..)))
         );
         assert_matches!(
             LocalDate::from_slice(b"2023-01-123"),
-            Err(Error(ErrorKind::InvalidDatetime))
+            Err(Error(ErrorKind::InvalidDatetime(..)))
         );
 
         // Invalid numbers
         assert_matches!(
             LocalDate::from_slice(b"abcd-01-02"),
-            Err(Error(ErrorKind::InvalidDatetime))
+            Err(Error(ErrorKind::InvalidDatetime(..)))
         );
         assert_matches!(
             LocalDate::from_slice(b"2023-ef-02"),
-            Err(Error(ErrorKind::InvalidDatetime))
+            Err(Error(ErrorKind::InvalidDatetime(..)))
         );
         assert_matches!(
             LocalDate::from_slice(b"2023-01-gh"),
-            Err(Error(ErrorKind::InvalidDatetime))
+            Err(Error(ErrorKind::InvalidDatetime(..)))
         );
 
         // Month in range
         assert_matches!(
             LocalDate::from_slice(b"2023-00-02"),
-            Err(Error(ErrorKind::InvalidDatetime))
+            Err(Error(ErrorKind::InvalidDatetime(..)))
         );
         assert_matches!(
             LocalDate::from_slice(b"2023-13-02"),
-            Err(Error(ErrorKind::InvalidDatetime))
+            Err(Error(ErrorKind::InvalidDatetime(..)))
         );
 
         // Day in range
         assert_matches!(LocalDate::from_slice(b"2023-01-31"), Ok(LocalDate { .. }));
         assert_matches!(
             LocalDate::from_slice(b"2023-01-32"),
-            Err(Error(ErrorKind::InvalidDatetime))
+            Err(Error(ErrorKind::InvalidDatetime(..)))
         );
         assert_matches!(LocalDate::from_slice(b"2023-04-30"), Ok(LocalDate { .. }));
         assert_matches!(
             LocalDate::from_slice(b"2023-04-31"),
-            Err(Error(ErrorKind::InvalidDatetime))
+            Err(Error(ErrorKind::InvalidDatetime(..)))
         );
         assert_matches!(LocalDate::from_slice(b"2023-02-28"), Ok(LocalDate { .. }));
         assert_matches!(
             LocalDate::from_slice(b"2023-02-29"),
-            Err(Error(ErrorKind::InvalidDatetime))
+            Err(Error(ErrorKind::InvalidDatetime(..)))
         );
         assert_matches!(LocalDate::from_slice(b"2024-02-29"), Ok(LocalDate { .. }));
         assert_matches!(
             LocalDate::from_slice(b"2024-02-30"),
-            Err(Error(ErrorKind::InvalidDatetime))
+            Err(Error(ErrorKind::InvalidDatetime(..)))
         );
 
         assert_matches!(
             LocalDate::from_slice(b"invalid string"),
-            Err(Error(ErrorKind::InvalidDatetime))
+            Err(Error(ErrorKind::InvalidDatetime(..)))
         );
     }
 
@@ -1730,10 +1810,76 @@ mod tests {
 
         assert_matches!(
             LocalDate::from_str("invalid string"),
-            Err(Error(ErrorKind::InvalidDatetime))
+            Err(Error(ErrorKind::InvalidDatetime(..)))
         );
     }
 
+    #[test]
+    fn local_date_is_valid() {
+        assert!(LocalDate::EXAMPLE.is_valid());
+        assert!(LocalDate {
+            year: 9999,
+            month: 12,
+            day: 31
+        }
+        .is_valid());
+        assert!(LocalDate {
+            year: 2024,
+            month: 2,
+            day: 29
+        }
+        .is_valid());
+        assert!(LocalDate {
+            year: 2000,
+            month: 2,
+            day: 29
+        }
+        .is_valid());
+
+        assert!(!LocalDate {
+            year: 10000,
+            month: 1,
+            day: 1
+        }
+        .is_valid());
+        assert!(!LocalDate {
+            year: 2023,
+            month: 0,
+            day: 1
+        }
+        .is_valid());
+        assert!(!LocalDate {
+            year: 2023,
+            month: 13,
+            day: 1
+        }
+        .is_valid());
+        assert!(!LocalDate {
+            year: 2023,
+            month: 1,
+            day: 0
+        }
+        .is_valid());
+        assert!(!LocalDate {
+            year: 2023,
+            month: 4,
+            day: 31
+        }
+        .is_valid());
+        assert!(!LocalDate {
+            year: 2023,
+            month: 2,
+            day: 29
+        }
+        .is_valid());
+        assert!(!LocalDate {
+            year: 1900,
+            month: 2,
+            day: 29
+        }
+        .is_valid());
+    }
+
     #[test]
     fn local_date_display() {
         assert_eq!(LocalDate::EXAMPLE.to_string(), LocalDate::EXAMPLE_STR);
@@ -1852,62 +1998,66 @@ mod tests {
         let result = LocalTime::from_slice(b"03:04:05.006000000999999").unwrap();
         assert_eq!(result, LocalTime::EXAMPLE);
 
+        // Digits beyond nanoseconds are truncated, not rounded
+        let result = LocalTime::from_slice(b"03:04:05.123456789999").unwrap();
+        assert_eq!(result.nanosecond, 123_456_789);
+
         // Incorrect lengths
         assert_matches!(
             LocalTime::from_slice(b"123:04:05"),
-            Err(Error(ErrorKind::InvalidDatetime))
+            Err(Error(ErrorKind::InvalidDatetime(..)))
         );
         assert_matches!(
             LocalTime::from_slice(b"03:123:05"),
-            Err(Error(ErrorKind::InvalidDatetime))
+            Err(Error(ErrorKind::InvalidDatetime(..)))
         );
         assert_matches!(
             LocalTime::from_slice(b"03:04:123"),
-            Err(Error(ErrorKind::InvalidDatetime))
+            Err(Error(ErrorKind::InvalidDatetime(..)))
         );
         assert_matches!(
             LocalTime::from_slice(b"03:04:05."),
-            Err(Error(ErrorKind::InvalidDatetime))
+            Err(Error(ErrorKind::InvalidDatetime(..)))
         );
 
         // Invalid numbers
         assert_matches!(
             LocalTime::from_slice(b"ab:04:05"),
-            Err(Error(ErrorKind::InvalidDatetime))
+            Err(Error(ErrorKind::InvalidDatetime(..)))
         );
         assert_matches!(
             LocalTime::from_slice(b"03:cd:05"),
-            Err(Error(ErrorKind::InvalidDatetime))
+            Err(Error(ErrorKind::InvalidDatetime(..)))
         );
         assert_matches!(
             LocalTime::from_slice(b"03:04:ef"),
-            Err(Error(ErrorKind::InvalidDatetime))
+            Err(Error(ErrorKind::InvalidDatetime(..)))
         );
         assert_matches!(
             LocalTime::from_slice(b"03:04:05.gh"),
-            Err(Error(ErrorKind::InvalidDatetime))
+            Err(Error(ErrorKind::InvalidDatetime(..)))
         );
 
         // Time in range
         assert_matches!(LocalTime::from_slice(b"23:04:05"), Ok(LocalTime { .. }));
         assert_matches!(
             LocalTime::from_slice(b"24:04:05"),
-            Err(Error(ErrorKind::InvalidDatetime))
+            Err(Error(ErrorKind::InvalidDatetime(..)))
         );
         assert_matches!(LocalTime::from_slice(b"03:59:05"), Ok(LocalTime { .. }));
         assert_matches!(
             LocalTime::from_slice(b"03:60:05"),
-            Err(Error(ErrorKind::InvalidDatetime))
+            Err(Error(ErrorKind::InvalidDatetime(..)))
         );
         assert_matches!(LocalTime::from_slice(b"03:04:60"), Ok(LocalTime { .. })); // Allows for leap second
         assert_matches!(
             LocalTime::from_slice(b"03:04:61"),
-            Err(Error(ErrorKind::InvalidDatetime))
+            Err(Error(ErrorKind::InvalidDatetime(..)))
         );
 
         assert_matches!(
             LocalTime::from_slice(b"invalid string"),
-            Err(Error(ErrorKind::InvalidDatetime))
+            Err(Error(ErrorKind::InvalidDatetime(..)))
         );
     }
 
@@ -1918,10 +2068,43 @@ mod tests {
 
         assert_matches!(
             Time::from_str("invalid string"),
-            Err(Error(ErrorKind::InvalidDatetime))
+            Err(Error(ErrorKind::InvalidDatetime(..)))
         );
     }
 
+    #[test]
+    fn local_time_is_valid() {
+        assert!(LocalTime::EXAMPLE.is_valid());
+        assert!(LocalTime {
+            hour: 23,
+            minute: 59,
+            second: 60,
+            nanosecond: 999_999_999
+        }
+        .is_valid());
+
+        assert!(!LocalTime {
+            hour: 24,
+            ..LocalTime::EXAMPLE
+        }
+        .is_valid());
+        assert!(!LocalTime {
+            minute: 60,
+            ..LocalTime::EXAMPLE
+        }
+        .is_valid());
+        assert!(!LocalTime {
+            second: 61,
+            ..LocalTime::EXAMPLE
+        }
+        .is_valid());
+        assert!(!LocalTime {
+            nanosecond: 1_000_000_000,
+            ..LocalTime::EXAMPLE
+        }
+        .is_valid());
+    }
+
     #[test]
     fn local_time_display() {
         assert_eq!(LocalTime::EXAMPLE.to_string(), LocalTime::EXAMPLE_STR);
@@ -2105,54 +2288,72 @@ mod tests {
 
         assert_matches!(
             Offset::from_slice(b"07:08"),
-            Err(Error(ErrorKind::InvalidDatetime))
+            Err(Error(ErrorKind::InvalidDatetime(..)))
         );
 
         // Incorrect lengths
         assert_matches!(
             Offset::from_slice(b"+123:08"),
-            Err(Error(ErrorKind::InvalidDatetime))
+            Err(Error(ErrorKind::InvalidDatetime(..)))
         );
         assert_matches!(
             Offset::from_slice(b"+07:123"),
-            Err(Error(ErrorKind::InvalidDatetime))
+            Err(Error(ErrorKind::InvalidDatetime(..)))
+        );
+
+        // Offset with seconds
+        assert_matches!(
+            Offset::from_slice(b"+07:08:09"),
+            Err(Error(ErrorKind::InvalidDatetime(..)))
+        );
+        assert_matches!(
+            Offset::from_slice(b"-07:08:09"),
+            Err(Error(ErrorKind::InvalidDatetime(..)))
         );
 
         // Invalid numbers
         assert_matches!(
             Offset::from_slice(b"+ab:08"),
-            Err(Error(ErrorKind::InvalidDatetime))
+            Err(Error(ErrorKind::InvalidDatetime(..)))
         );
         assert_matches!(
             Offset::from_slice(b"+07:cd"),
-            Err(Error(ErrorKind::InvalidDatetime))
+            Err(Error(ErrorKind::InvalidDatetime(..)))
+        );
+        assert_matches!(
+            Offset::from_slice(b"+-1:08"),
+            Err(Error(ErrorKind::InvalidDatetime(..)))
+        );
+        assert_matches!(
+            Offset::from_slice(b"+\xff\xff:08"),
+            Err(Error(ErrorKind::InvalidDatetime(..)))
         );
 
         // Offset in range
         assert_matches!(Offset::from_slice(b"+23:08"), Ok(Offset::Custom { .. }));
         assert_matches!(
             Offset::from_slice(b"+24:08"),
-            Err(Error(ErrorKind::InvalidDatetime))
+            Err(Error(ErrorKind::InvalidDatetime(..)))
         );
         assert_matches!(Offset::from_slice(b"-23:08"), Ok(Offset::Custom { .. }));
         assert_matches!(
             Offset::from_slice(b"-24:08"),
-            Err(Error(ErrorKind::InvalidDatetime))
+            Err(Error(ErrorKind::InvalidDatetime(..)))
         );
         assert_matches!(Offset::from_slice(b"+07:59"), Ok(Offset::Custom { .. }));
         assert_matches!(
             Offset::from_slice(b"+07:60"),
-            Err(Error(ErrorKind::InvalidDatetime))
+            Err(Error(ErrorKind::InvalidDatetime(..)))
         );
         assert_matches!(Offset::from_slice(b"-07:59"), Ok(Offset::Custom { .. }));
         assert_matches!(
             Offset::from_slice(b"-07:60"),
-            Err(Error(ErrorKind::InvalidDatetime))
+            Err(Error(ErrorKind::InvalidDatetime(..)))
         );
 
         assert_matches!(
             Offset::from_slice(b"invalid string"),
-            Err(Error(ErrorKind::InvalidDatetime))
+            Err(Error(ErrorKind::InvalidDatetime(..)))
         );
     }
 
@@ -2164,6 +2365,10 @@ mod tests {
         assert_eq!(offset.to_string(), "-07:08");
 
         assert_eq!(Offset::Z.to_string(), "Z");
+
+        // Out of range offsets shouldn't overflow
+        let offset = Offset::Custom { minutes: i16::MIN };
+        assert_eq!(offset.to_string(), "-546:08");
     }
 
     #[test]
@@ -2186,7 +2391,32 @@ mod tests {
 
         assert_matches!(
             Offset::from_str("invalid string"),
-            Err(Error(ErrorKind::InvalidDatetime))
+            Err(Error(ErrorKind::InvalidDatetime(..)))
+        );
+    }
+
+    #[test]
+    fn offset_from_minutes() {
+        assert_eq!(Offset::from_minutes(428), Some(Offset::EXAMPLE));
+        assert_eq!(Offset::from_minutes(0), Some(Offset::Custom { minutes: 0 }));
+        assert_eq!(
+            Offset::from_minutes(-1439),
+            Some(Offset::Custom { minutes: -1439 })
         );
+
+        assert_eq!(Offset::from_minutes(1440), None);
+        assert_eq!(Offset::from_minutes(-1440), None);
+        assert_eq!(Offset::from_minutes(i16::MIN), None);
+    }
+
+    #[test]
+    fn offset_is_valid() {
+        assert!(Offset::Z.is_valid());
+        assert!(Offset::EXAMPLE.is_valid());
+        assert!(Offset::Custom { minutes: 1439 }.is_valid());
+        assert!(Offset::Custom { minutes: -1439 }.is_valid());
+
+        assert!(!Offset::Custom { minutes: 1440 }.is_valid());
+        assert!(!Offset::Custom { minutes: -1440 }.is_valid());
     }
 }
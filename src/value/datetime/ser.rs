@@ -12,6 +12,12 @@ impl ser::Serialize for AnyDatetime {
     {
         use ser::SerializeStruct as _;
 
+        if let Self::OffsetDatetime(ref datetime) = *self {
+            if !datetime.offset.is_valid() {
+                return Err(S::Error::custom("offset out of range"));
+            }
+        }
+
         let mut s = serializer.serialize_struct(Self::WRAPPER_TYPE, 1)?;
         match *self {
             Self::OffsetDatetime(ref datetime) => s.serialize_field(
@@ -53,6 +59,11 @@ impl ser::Serialize for OffsetDatetime {
     {
         use ser::SerializeStruct as _;
 
+        // An out of range offset would be written as an invalid TOML date-time
+        if !self.offset.is_valid() {
+            return Err(S::Error::custom("offset out of range"));
+        }
+
         let mut s = serializer.serialize_struct(Self::WRAPPER_TYPE, 1)?;
         s.serialize_field(Self::WRAPPER_FIELD, &ByteBuf::from(self.to_bytes()))?;
         s.end()
@@ -107,6 +118,7 @@ mod tests {
     use serde_test::{assert_ser_tokens, assert_ser_tokens_error, Token};
 
     use super::*;
+    use crate::value::Offset;
 
     #[test]
     fn serialize_any_datetime() {
@@ -242,6 +254,17 @@ mod tests {
             Token::StructEnd,
         ];
         assert_ser_tokens(&OffsetDatetime::EXAMPLE, tokens);
+
+        let datetime = OffsetDatetime {
+            offset: Offset::Custom { minutes: 1440 },
+            ..OffsetDatetime::EXAMPLE
+        };
+        assert_ser_tokens_error(&datetime, &[], "offset out of range");
+        assert_ser_tokens_error(
+            &AnyDatetime::OffsetDatetime(datetime),
+            &[],
+            "offset out of range",
+        );
     }
 
     #[test]
@@ -1165,7 +1165,7 @@ mod tests {
     use std::marker::PhantomData;
 
     use assert_matches::assert_matches;
-    use maplit::{btreemap, hashmap};
+    use maplit::hashmap;
     use serde::de::{EnumAccess as _, MapAccess as _, SeqAccess as _, VariantAccess as _};
     use serde::Deserialize;
 
@@ -1337,7 +1337,7 @@ mod tests {
         .unwrap();
         assert_eq!(
             value,
-            Value::Table(btreemap! {
+            Value::Table(table! {
                 "one".to_string() => Value::Integer(1),
                 "two".to_string() => Value::Integer(2),
                 "three".to_string() => Value::Integer(3),
@@ -1357,7 +1357,7 @@ mod tests {
         .unwrap();
         assert_eq!(
             value,
-            Value::Table(btreemap! {
+            Value::Table(table! {
                 "one".to_string() => Value::Integer(1),
                 "two".to_string() => Value::Integer(2),
                 "three".to_string() => Value::Integer(3),
@@ -1368,7 +1368,7 @@ mod tests {
             iter::empty::<(de::value::StrDeserializer<_>, de::value::I64Deserializer<_>)>(),
         ))
         .unwrap();
-        assert_eq!(value, Value::Table(btreemap! {}));
+        assert_eq!(value, Value::Table(table! {}));
 
         let result = Value::deserialize(de::value::MapDeserializer::<_, Error>::new(iter::once((
             de::value::I64Deserializer::new(123),
@@ -1520,7 +1520,7 @@ mod tests {
         );
 
         assert_matches!(
-            HashMap::<String, i32>::deserialize(Value::Table(btreemap! {
+            HashMap::<String, i32>::deserialize(Value::Table(table! {
                 "abc".into() => Value::Integer(123),
                 "def".into() => Value::Integer(456),
                 "ghi".into() => Value::Integer(789),
@@ -1554,7 +1554,7 @@ mod tests {
         let result = Enum::deserialize(Value::String("A".to_string())).unwrap();
         assert_eq!(result, Enum::A);
 
-        let result = Enum::deserialize(Value::Table(btreemap! {
+        let result = Enum::deserialize(Value::Table(table! {
             "B".into() => Value::Integer(123),
         }))
         .unwrap();
@@ -1642,7 +1642,13 @@ mod tests {
 
     #[test]
     fn map_access() {
-        let mut map_access = MapAccess::new(btreemap! {
+        // BTreeMap will alphabetise the keys
+        #[cfg(not(feature = "preserve_order"))]
+        let expected = [("one", 1), ("three", 3), ("two", 2)];
+        #[cfg(feature = "preserve_order")]
+        let expected = [("one", 1), ("two", 2), ("three", 3)];
+
+        let mut map_access = MapAccess::new(table! {
             "one".to_string() => Value::Integer(1),
             "two".to_string() => Value::Integer(2),
             "three".to_string() => Value::Integer(3),
@@ -1650,12 +1656,10 @@ mod tests {
 
         assert_eq!(map_access.size_hint(), Some(3));
 
-        assert_matches!(map_access.next_key::<String>(), Ok(Some(k)) if k == "one");
-        assert_matches!(map_access.next_value::<i32>(), Ok(1));
-        assert_matches!(map_access.next_key::<String>(), Ok(Some(k)) if k == "three");
-        assert_matches!(map_access.next_value::<i32>(), Ok(3));
-        assert_matches!(map_access.next_key::<String>(), Ok(Some(k)) if k == "two");
-        assert_matches!(map_access.next_value::<i32>(), Ok(2));
+        for (key, value) in expected {
+            assert_matches!(map_access.next_key::<String>(), Ok(Some(k)) if k == key);
+            assert_matches!(map_access.next_value::<i32>(), Ok(v) if v == value);
+        }
 
         assert_eq!(map_access.size_hint(), Some(0));
 
@@ -1663,7 +1667,7 @@ mod tests {
 
         assert_eq!(map_access.size_hint(), Some(0));
 
-        let mut map_access = MapAccess::new(btreemap! {
+        let mut map_access = MapAccess::new(table! {
             "one".to_string() => Value::Integer(1),
             "two".to_string() => Value::Integer(2),
             "three".to_string() => Value::Integer(3),
@@ -1671,18 +1675,12 @@ mod tests {
 
         assert_eq!(map_access.size_hint(), Some(3));
 
-        assert_matches!(
-            map_access.next_entry::<String, i32>(),
-            Ok(Some((k, v))) if k == "one" && v == 1
-        );
-        assert_matches!(
-            map_access.next_entry::<String, i32>(),
-            Ok(Some((k, v))) if k == "three" && v == 3
-        );
-        assert_matches!(
-            map_access.next_entry::<String, i32>(),
-            Ok(Some((k, v))) if k == "two" && v == 2
-        );
+        for (key, value) in expected {
+            assert_matches!(
+                map_access.next_entry::<String, i32>(),
+                Ok(Some((k, v))) if k == key && v == value
+            );
+        }
 
         assert_eq!(map_access.size_hint(), Some(0));
 
@@ -1694,7 +1692,7 @@ mod tests {
     #[test]
     #[should_panic = "MapAccess::next_value called without calling MapAccess::next_key first"]
     fn map_access_empty() {
-        let mut map_access = MapAccess::new(btreemap! {});
+        let mut map_access = MapAccess::new(table! {});
 
         let _result = map_access.next_value::<i32>();
     }
@@ -1856,8 +1854,8 @@ mod tests {
 
     #[test]
     fn enum_access_unit() {
-        let enum_access = EnumAccess::new(btreemap! {
-            "variant".to_string() => Value::Table(btreemap! {}),
+        let enum_access = EnumAccess::new(table! {
+            "variant".to_string() => Value::Table(table! {}),
         })
         .unwrap();
 
@@ -1865,7 +1863,7 @@ mod tests {
         assert_eq!(variant, "variant".to_string());
         assert!(value.unit_variant().is_ok());
 
-        let enum_access = EnumAccess::new(btreemap! {
+        let enum_access = EnumAccess::new(table! {
             "variant".to_string() => Value::Integer(42),
         })
         .unwrap();
@@ -1874,8 +1872,8 @@ mod tests {
         assert_eq!(variant, "variant".to_string());
         assert_matches!(value.unit_variant(), Err(Error(ErrorKind::InvalidType(..))));
 
-        let enum_access = EnumAccess::new(btreemap! {
-            "variant".to_string() => Value::Table(btreemap! {
+        let enum_access = EnumAccess::new(table! {
+            "variant".to_string() => Value::Table(table! {
                 "foo".to_string() => Value::Integer(42),
             }),
         })
@@ -1891,7 +1889,7 @@ mod tests {
 
     #[test]
     fn enum_access_newtype() {
-        let enum_access = EnumAccess::new(btreemap! {
+        let enum_access = EnumAccess::new(table! {
             "variant".to_string() => Value::Integer(42),
         })
         .unwrap();
@@ -1924,7 +1922,7 @@ mod tests {
             }
         }
 
-        let enum_access = EnumAccess::new(btreemap! {
+        let enum_access = EnumAccess::new(table! {
             "variant".to_string() => Value::Array(vec![
                 Value::Integer(1),
                 Value::Integer(2),
@@ -1961,8 +1959,8 @@ mod tests {
             }
         }
 
-        let enum_access = EnumAccess::new(btreemap! {
-            "variant".to_string() => Value::Table(btreemap! {
+        let enum_access = EnumAccess::new(table! {
+            "variant".to_string() => Value::Table(table! {
                 "one".to_string() => Value::Integer(1),
                 "two".to_string() => Value::Integer(2),
             }),
@@ -1982,10 +1980,10 @@ mod tests {
 
     #[test]
     fn enum_access_error() {
-        let enum_access = EnumAccess::new(btreemap! {});
+        let enum_access = EnumAccess::new(table! {});
         assert_matches!(enum_access, Err(Error(ErrorKind::InvalidValue(..))));
 
-        let enum_access = EnumAccess::new(btreemap! {
+        let enum_access = EnumAccess::new(table! {
             "variant".to_string() => Value::Integer(1),
             "variant2".to_string() => Value::Integer(2),
         });
@@ -2013,7 +2011,7 @@ mod tests {
         );
 
         assert_matches!(
-            HashMap::<String, i32>::deserialize(&Value::Table(btreemap! {
+            HashMap::<String, i32>::deserialize(&Value::Table(table! {
                 "abc".into() => Value::Integer(123),
                 "def".into() => Value::Integer(456),
                 "ghi".into() => Value::Integer(789),
@@ -2047,7 +2045,7 @@ mod tests {
         let result = Enum::deserialize(&Value::String("A".to_string())).unwrap();
         assert_eq!(result, Enum::A);
 
-        let result = Enum::deserialize(&Value::Table(btreemap! {
+        let result = Enum::deserialize(&Value::Table(table! {
             "B".into() => Value::Integer(123),
         }))
         .unwrap();
@@ -2134,7 +2132,13 @@ mod tests {
 
     #[test]
     fn map_ref_access() {
-        let table = btreemap! {
+        // BTreeMap will alphabetise the keys
+        #[cfg(not(feature = "preserve_order"))]
+        let expected = [("one", 1), ("three", 3), ("two", 2)];
+        #[cfg(feature = "preserve_order")]
+        let expected = [("one", 1), ("two", 2), ("three", 3)];
+
+        let table = table! {
             "one".to_string() => Value::Integer(1),
             "two".to_string() => Value::Integer(2),
             "three".to_string() => Value::Integer(3),
@@ -2143,12 +2147,10 @@ mod tests {
 
         assert_eq!(map_access.size_hint(), Some(3));
 
-        assert_matches!(map_access.next_key::<String>(), Ok(Some(k)) if k == "one");
-        assert_matches!(map_access.next_value::<i32>(), Ok(1));
-        assert_matches!(map_access.next_key::<String>(), Ok(Some(k)) if k == "three");
-        assert_matches!(map_access.next_value::<i32>(), Ok(3));
-        assert_matches!(map_access.next_key::<String>(), Ok(Some(k)) if k == "two");
-        assert_matches!(map_access.next_value::<i32>(), Ok(2));
+        for (key, value) in expected {
+            assert_matches!(map_access.next_key::<String>(), Ok(Some(k)) if k == key);
+            assert_matches!(map_access.next_value::<i32>(), Ok(v) if v == value);
+        }
 
         assert_eq!(map_access.size_hint(), Some(0));
 
@@ -2156,27 +2158,16 @@ mod tests {
 
         assert_eq!(map_access.size_hint(), Some(0));
 
-        let table = btreemap! {
-            "one".to_string() => Value::Integer(1),
-            "two".to_string() => Value::Integer(2),
-            "three".to_string() => Value::Integer(3),
-        };
         let mut map_access = MapRefAccess::new(&table);
 
         assert_eq!(map_access.size_hint(), Some(3));
 
-        assert_matches!(
-            map_access.next_entry::<String, i32>(),
-            Ok(Some((k, v))) if k == "one" && v == 1
-        );
-        assert_matches!(
-            map_access.next_entry::<String, i32>(),
-            Ok(Some((k, v))) if k == "three" && v == 3
-        );
-        assert_matches!(
-            map_access.next_entry::<String, i32>(),
-            Ok(Some((k, v))) if k == "two" && v == 2
-        );
+        for (key, value) in expected {
+            assert_matches!(
+                map_access.next_entry::<String, i32>(),
+                Ok(Some((k, v))) if k == key && v == value
+            );
+        }
 
         assert_eq!(map_access.size_hint(), Some(0));
 
@@ -2188,7 +2179,7 @@ mod tests {
     #[test]
     #[should_panic = "MapRefAccess::next_value called without calling MapRefAccess::next_key first"]
     fn map_ref_access_empty() {
-        let table = btreemap! {};
+        let table = table! {};
         let mut map_access = MapRefAccess::new(&table);
 
         let _result = map_access.next_value::<i32>();
@@ -2294,8 +2285,8 @@ mod tests {
 
     #[test]
     fn enum_ref_access_unit() {
-        let table = btreemap! {
-            "variant".to_string() => Value::Table(btreemap! {}),
+        let table = table! {
+            "variant".to_string() => Value::Table(table! {}),
         };
         let enum_access = EnumRefAccess::new(&table).unwrap();
 
@@ -2303,7 +2294,7 @@ mod tests {
         assert_eq!(variant, "variant".to_string());
         assert!(value.unit_variant().is_ok());
 
-        let table = btreemap! {
+        let table = table! {
             "variant".to_string() => Value::Integer(42),
         };
         let enum_access = EnumRefAccess::new(&table).unwrap();
@@ -2312,8 +2303,8 @@ mod tests {
         assert_eq!(variant, "variant".to_string());
         assert_matches!(value.unit_variant(), Err(Error(ErrorKind::InvalidType(..))));
 
-        let table = btreemap! {
-            "variant".to_string() => Value::Table(btreemap! {
+        let table = table! {
+            "variant".to_string() => Value::Table(table! {
                 "foo".to_string() => Value::Integer(42),
             }),
         };
@@ -2329,7 +2320,7 @@ mod tests {
 
     #[test]
     fn enum_ref_access_newtype() {
-        let table = btreemap! {
+        let table = table! {
             "variant".to_string() => Value::Integer(42),
         };
         let enum_access = EnumRefAccess::new(&table).unwrap();
@@ -2362,7 +2353,7 @@ mod tests {
             }
         }
 
-        let table = btreemap! {
+        let table = table! {
             "variant".to_string() => Value::Array(vec![
                 Value::Integer(1),
                 Value::Integer(2),
@@ -2399,8 +2390,8 @@ mod tests {
             }
         }
 
-        let table = btreemap! {
-            "variant".to_string() => Value::Table(btreemap! {
+        let table = table! {
+            "variant".to_string() => Value::Table(table! {
                 "one".to_string() => Value::Integer(1),
                 "two".to_string() => Value::Integer(2),
             }),
@@ -2420,11 +2411,11 @@ mod tests {
 
     #[test]
     fn enum_ref_access_error() {
-        let table = btreemap! {};
+        let table = table! {};
         let enum_access = EnumRefAccess::new(&table);
         assert_matches!(enum_access, Err(Error(ErrorKind::InvalidValue(..))));
 
-        let table = btreemap! {
+        let table = table! {
             "variant".to_string() => Value::Integer(1),
             "variant2".to_string() => Value::Integer(2),
         };
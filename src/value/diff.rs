@@ -0,0 +1,356 @@
+use std::{fmt, slice, vec};
+
+use super::Value;
+use crate::ser::Formatter;
+
+/// The differences between two [`Value`]s, returned by [`Value::diff`].
+///
+/// Each change is given with a dotted path from the root of the value, for example
+/// `server.port`. Keys are written as they would be in a TOML document, so any key which is not a
+/// valid bare key is quoted, for example `servers."eu.west".port`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Diff(pub(super) Vec<Change>);
+
+impl Diff {
+    /// Returns `true` if there are no differences.
+    #[must_use]
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns the number of changes.
+    #[must_use]
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns an iterator over the changes.
+    #[inline]
+    pub fn iter(&self) -> slice::Iter<'_, Change> {
+        self.0.iter()
+    }
+}
+
+impl IntoIterator for Diff {
+    type Item = Change;
+    type IntoIter = vec::IntoIter<Change>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Diff {
+    type Item = &'a Change;
+    type IntoIter = slice::Iter<'a, Change>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+/// A single change in a [`Diff`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    /// A key which is only present in the new value.
+    Added {
+        /// The dotted path to the key.
+        path: String,
+        /// The added value.
+        value: Value,
+    },
+    /// A key which is only present in the old value.
+    Removed {
+        /// The dotted path to the key.
+        path: String,
+        /// The removed value.
+        value: Value,
+    },
+    /// A value which is different in the old and new values, including a change of type.
+    Changed {
+        /// The dotted path to the value. This is empty for the root.
+        path: String,
+        /// The old value.
+        old: Value,
+        /// The new value.
+        new: Value,
+    },
+}
+
+impl Change {
+    /// The dotted path to the changed value.
+    #[must_use]
+    #[inline]
+    pub fn path(&self) -> &str {
+        match *self {
+            Self::Added { ref path, .. }
+            | Self::Removed { ref path, .. }
+            | Self::Changed { ref path, .. } => path,
+        }
+    }
+}
+
+impl fmt::Display for Change {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::Added {
+                ref path,
+                ref value,
+            } => write!(f, "added {path}: {value}"),
+            Self::Removed {
+                ref path,
+                ref value,
+            } => write!(f, "removed {path}: {value}"),
+            Self::Changed {
+                ref path,
+                ref old,
+                ref new,
+            } => {
+                if path.is_empty() {
+                    write!(f, "changed {old} to {new}")
+                } else {
+                    write!(f, "changed {path}: {old} to {new}")
+                }
+            }
+        }
+    }
+}
+
+pub(super) fn diff(old: &Value, new: &Value, path: &str, changes: &mut Vec<Change>) {
+    let child_path = |key: &str| {
+        let mut child = path.to_owned();
+        Formatter::push_key(&mut child, key);
+        child
+    };
+
+    match (old.as_table(), new.as_table()) {
+        (Some(old), Some(new)) => {
+            for (key, old) in old {
+                match new.get(key) {
+                    Some(new) => diff(old, new, &child_path(key), changes),
+                    None => changes.push(Change::Removed {
+                        path: child_path(key),
+                        value: old.clone(),
+                    }),
+                }
+            }
+            changes.extend(new.iter().filter(|&(key, _)| !old.contains_key(key)).map(
+                |(key, new)| Change::Added {
+                    path: child_path(key),
+                    value: new.clone(),
+                },
+            ));
+        }
+        // Arrays are compared as a whole, like they are by `Value::merge`
+        _ if !equal(old, new) => changes.push(Change::Changed {
+            path: path.to_owned(),
+            old: old.clone(),
+            new: new.clone(),
+        }),
+        _ => {}
+    }
+}
+
+// Like `==`, but treats NaN floats as equal so that a value always has no differences from itself
+#[allow(clippy::float_cmp)] // strict cmp is intended, the same as `==`
+fn equal(old: &Value, new: &Value) -> bool {
+    match *old {
+        Value::Float(old) => matches!(
+            *new,
+            Value::Float(new) if old == new || (old.is_nan() && new.is_nan())
+        ),
+        Value::Array(ref old) => matches!(
+            *new,
+            Value::Array(ref new)
+                if old.len() == new.len() && old.iter().zip(new).all(|(old, new)| equal(old, new))
+        ),
+        Value::Table(ref old) => matches!(
+            *new,
+            Value::Table(ref new) if old.len() == new.len()
+                && old
+                    .iter()
+                    .all(|(key, old)| new.get(key).map_or(false, |new| equal(old, new)))
+        ),
+        _ => old == new,
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage, coverage(off))]
+mod tests {
+    use assert_matches::assert_matches;
+
+    use super::*;
+
+    #[test]
+    fn diff_iter() {
+        let diff = Diff(vec![Change::Added {
+            path: "a".into(),
+            value: Value::Integer(1),
+        }]);
+        assert!(!diff.is_empty());
+        assert_eq!(diff.len(), 1);
+        assert_eq!(diff.iter().map(Change::path).collect::<Vec<_>>(), ["a"]);
+        assert_eq!((&diff).into_iter().count(), 1);
+        assert_eq!(diff.into_iter().count(), 1);
+
+        assert!(Diff::default().is_empty());
+    }
+
+    #[test]
+    fn diff_values() {
+        let old = Value::Table(table! {
+            "debug".into() => Value::Boolean(false),
+            "name".into() => Value::String("example".into()),
+            "port".into() => Value::Integer(8080),
+            "server".into() => Value::Table(table! {
+                "host".into() => Value::String("localhost".into()),
+                "tags".into() => Value::Array(vec![Value::Integer(1)]),
+                "tls".into() => Value::Table(table! {
+                    "enabled".into() => Value::Boolean(false),
+                }),
+            }),
+        });
+        let new = Value::Table(table! {
+            "name".into() => Value::String("example".into()),
+            "port".into() => Value::String("8080".into()),
+            "server".into() => Value::Table(table! {
+                "host".into() => Value::String("0.0.0.0".into()),
+                "tags".into() => Value::Array(vec![Value::Integer(1), Value::Integer(2)]),
+                "tls".into() => Value::Table(table! {
+                    "enabled".into() => Value::Boolean(false),
+                    "cert".into() => Value::String("cert.pem".into()),
+                }),
+            }),
+            "timeout".into() => Value::Integer(30),
+        });
+
+        let mut changes = Vec::new();
+        diff(&old, &new, "", &mut changes);
+        let changes: Vec<_> = changes.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            changes,
+            [
+                "removed debug: false",
+                r#"changed port: 8080 to "8080""#,
+                r#"changed server.host: "localhost" to "0.0.0.0""#,
+                "changed server.tags: [1] to [1, 2]",
+                r#"added server.tls.cert: "cert.pem""#,
+                "added timeout: 30",
+            ]
+        );
+
+        let mut changes = Vec::new();
+        diff(&old, &old, "", &mut changes);
+        assert!(changes.is_empty());
+
+        let mut changes = Vec::new();
+        diff(&old, &Value::Integer(1), "", &mut changes);
+        assert_matches!(
+            &*changes,
+            [Change::Changed { path, old: Value::Table(_), new: Value::Integer(1) }] if path.is_empty()
+        );
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)] // strict cmp is fine for literals
+    fn diff_nan() {
+        let value = Value::Table(table! {
+            "float".into() => Value::Float(f64::NAN),
+            "array".into() => Value::Array(vec![
+                Value::Float(f64::NAN),
+                Value::Table(table! { "float".into() => Value::Float(f64::NAN) }),
+            ]),
+        });
+
+        let mut changes = Vec::new();
+        diff(&value, &value, "", &mut changes);
+        assert!(changes.is_empty());
+
+        let mut changes = Vec::new();
+        diff(
+            &Value::Float(f64::NAN),
+            &Value::Float(1.0),
+            "",
+            &mut changes,
+        );
+        assert_matches!(
+            &*changes,
+            [Change::Changed { old: Value::Float(old), new: Value::Float(new), .. }]
+                if old.is_nan() && *new == 1.0
+        );
+
+        assert!(equal(&Value::Float(0.0), &Value::Float(-0.0)));
+        assert!(!equal(
+            &Value::Array(vec![Value::Float(f64::NAN)]),
+            &Value::Array(vec![])
+        ));
+        assert!(!equal(
+            &Value::Table(table! { "a".into() => Value::Float(f64::NAN) }),
+            &Value::Table(table! { "b".into() => Value::Float(f64::NAN) }),
+        ));
+    }
+
+    #[test]
+    fn diff_quoted_keys() {
+        let old = Value::Table(table! {
+            "a b".into() => Value::Boolean(true),
+            "servers".into() => Value::Table(table! {
+                "eu.west".into() => Value::Table(table! {
+                    "port".into() => Value::Integer(80),
+                }),
+            }),
+        });
+        let new = Value::Table(table! {
+            "servers".into() => Value::Table(table! {
+                "eu.west".into() => Value::Table(table! {
+                    "port".into() => Value::Integer(8080),
+                }),
+            }),
+            String::new() => Value::Boolean(false),
+        });
+
+        let mut changes = Vec::new();
+        diff(&old, &new, "", &mut changes);
+        let paths: Vec<_> = changes.iter().map(Change::path).collect();
+        assert_eq!(paths, [r#""a b""#, r#"servers."eu.west".port"#, "\"\""]);
+    }
+
+    #[test]
+    fn change_display() {
+        let change = Change::Added {
+            path: "a.b".into(),
+            value: Value::Integer(1),
+        };
+        assert_eq!(change.path(), "a.b");
+        assert_eq!(change.to_string(), "added a.b: 1");
+
+        let change = Change::Removed {
+            path: "a".into(),
+            value: Value::String("x".into()),
+        };
+        assert_eq!(change.path(), "a");
+        assert_eq!(change.to_string(), r#"removed a: "x""#);
+
+        let change = Change::Changed {
+            path: "a".into(),
+            old: Value::Integer(1),
+            new: Value::Boolean(true),
+        };
+        assert_eq!(change.path(), "a");
+        assert_eq!(change.to_string(), "changed a: 1 to true");
+
+        let change = Change::Changed {
+            path: String::new(),
+            old: Value::Integer(1),
+            new: Value::Integer(2),
+        };
+        assert_eq!(change.path(), "");
+        assert_eq!(change.to_string(), "changed 1 to 2");
+    }
+}
@@ -10,7 +10,11 @@ use std::{fmt, ops};
 pub use self::datetime::{
     AnyDatetime, Date, Datetime, LocalDate, LocalDatetime, LocalTime, Offset, OffsetDatetime, Time,
 };
+pub use self::diff::{Change, Diff};
+pub use self::schema::{Fields, Schema, Violation};
 use self::ser::ToValueSerializer;
+use crate::map;
+use crate::ser::Formatter;
 
 /// A TOML table type.
 pub type Table = crate::Table;
@@ -18,9 +22,13 @@ pub type Table = crate::Table;
 /// A TOML array type.
 pub type Array = Vec<Value>;
 
+#[cfg(feature = "toml-compat")]
+mod compat;
 #[cfg(feature = "datetime")]
 pub(crate) mod datetime;
 mod de;
+mod diff;
+mod schema;
 mod ser;
 
 mod private {
@@ -62,6 +70,43 @@ impl fmt::Display for Type {
     }
 }
 
+/// The error returned by the typed getters such as [`Value::get_str`], and by the `TryFrom<&Value>`
+/// conversions, when a [`Value`] is not of the requested type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TypeError {
+    expected: Type,
+    found: Type,
+}
+
+impl TypeError {
+    /// The type which was requested, for example `"string"`.
+    #[must_use]
+    #[inline]
+    pub fn expected(&self) -> &'static str {
+        self.expected.to_str()
+    }
+
+    /// The actual type of the value, for example `"integer"`.
+    #[must_use]
+    #[inline]
+    pub fn found(&self) -> &'static str {
+        self.found.to_str()
+    }
+}
+
+impl fmt::Display for TypeError {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid type: expected {}, found {}",
+            self.expected, self.found
+        )
+    }
+}
+
+impl std::error::Error for TypeError {}
+
 /// A generic TOML value type.
 #[derive(Debug, Clone, PartialEq)]
 pub enum Value {
@@ -96,6 +141,69 @@ impl Value {
         value.serialize(ToValueSerializer)
     }
 
+    /// Creates a local date [`Value`] from its components.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the date is not valid, for example if the day is past the end of the
+    /// month. See [`LocalDate::is_valid`].
+    #[cfg(feature = "datetime")]
+    #[inline]
+    pub fn local_date(year: u16, month: u8, day: u8) -> Result<Self, crate::de::Error> {
+        let date = LocalDate { year, month, day };
+        checked_datetime(date.is_valid(), date)
+    }
+
+    /// Creates a local time [`Value`] from its components.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the time is not valid. See [`LocalTime::is_valid`].
+    #[cfg(feature = "datetime")]
+    #[inline]
+    pub fn local_time(
+        hour: u8,
+        minute: u8,
+        second: u8,
+        nanosecond: u32,
+    ) -> Result<Self, crate::de::Error> {
+        let time = LocalTime {
+            hour,
+            minute,
+            second,
+            nanosecond,
+        };
+        checked_datetime(time.is_valid(), time)
+    }
+
+    /// Creates a local date-time [`Value`] from a date and a time.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if either the date or the time is not valid.
+    #[cfg(feature = "datetime")]
+    #[inline]
+    pub fn local_datetime(date: LocalDate, time: LocalTime) -> Result<Self, crate::de::Error> {
+        let valid = date.is_valid() && time.is_valid();
+        checked_datetime(valid, LocalDatetime { date, time })
+    }
+
+    /// Creates an offset date-time [`Value`] from a date, a time, and a UTC offset.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the date, the time, or the offset is not valid.
+    #[cfg(feature = "datetime")]
+    #[inline]
+    pub fn offset_datetime(
+        date: LocalDate,
+        time: LocalTime,
+        offset: Offset,
+    ) -> Result<Self, crate::de::Error> {
+        let valid = date.is_valid() && time.is_valid() && offset.is_valid();
+        checked_datetime(valid, OffsetDatetime { date, time, offset })
+    }
+
     /// Return an element of a TOML array or table, depending on the type of the index.
     ///
     /// Returns `None` if the index is a [`usize`] and `self` is not an array, or if the index is
@@ -233,6 +341,17 @@ impl Value {
         }
     }
 
+    /// If `self` is a date-time, returns it as a mutable reference to a [`Datetime`].
+    #[cfg(feature = "datetime")]
+    #[must_use]
+    #[inline]
+    pub fn as_datetime_mut(&mut self) -> Option<&mut Datetime> {
+        match *self {
+            Self::Datetime(ref mut datetime) => Some(datetime),
+            _ => None,
+        }
+    }
+
     /// If `self` is an array, returns it as a [`Vec<Value>`].
     #[must_use]
     #[inline]
@@ -273,6 +392,125 @@ impl Value {
         }
     }
 
+    /// If `self` is a string, returns the owned [`String`] without cloning it.
+    ///
+    /// # Errors
+    ///
+    /// Returns `self` unchanged if it is not a string.
+    #[inline]
+    pub fn into_string(self) -> Result<String, Self> {
+        match self {
+            Self::String(str) => Ok(str),
+            _ => Err(self),
+        }
+    }
+
+    /// If `self` is an array, returns the owned [`Vec<Value>`] without cloning it.
+    ///
+    /// # Errors
+    ///
+    /// Returns `self` unchanged if it is not an array.
+    #[inline]
+    pub fn into_array(self) -> Result<Vec<Self>, Self> {
+        match self {
+            Self::Array(array) => Ok(array),
+            _ => Err(self),
+        }
+    }
+
+    /// If `self` is a table, returns the owned [`Table`] without cloning it.
+    ///
+    /// # Errors
+    ///
+    /// Returns `self` unchanged if it is not a table.
+    #[inline]
+    pub fn into_table(self) -> Result<Table, Self> {
+        match self {
+            Self::Table(table) => Ok(table),
+            _ => Err(self),
+        }
+    }
+
+    /// Returns `self` as a `&str`, or an error if it is not a string.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`TypeError`] containing the actual type if `self` is not a string.
+    #[inline]
+    pub fn get_str(&self) -> Result<&str, TypeError> {
+        self.as_str().ok_or_else(|| self.type_error(Type::String))
+    }
+
+    /// Returns `self` as an `i64`, or an error if it is not an integer.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`TypeError`] containing the actual type if `self` is not an integer.
+    #[inline]
+    pub fn get_integer(&self) -> Result<i64, TypeError> {
+        self.as_integer()
+            .ok_or_else(|| self.type_error(Type::Integer))
+    }
+
+    /// Returns `self` as an `f64`, or an error if it is not a float.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`TypeError`] containing the actual type if `self` is not a float.
+    #[inline]
+    pub fn get_float(&self) -> Result<f64, TypeError> {
+        self.as_float().ok_or_else(|| self.type_error(Type::Float))
+    }
+
+    /// Returns `self` as a `bool`, or an error if it is not a boolean.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`TypeError`] containing the actual type if `self` is not a boolean.
+    #[inline]
+    pub fn get_bool(&self) -> Result<bool, TypeError> {
+        self.as_bool().ok_or_else(|| self.type_error(Type::Boolean))
+    }
+
+    /// Returns `self` as a [`Datetime`], or an error if it is not a date-time.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`TypeError`] containing the actual type if `self` is not a date-time.
+    #[cfg(feature = "datetime")]
+    #[inline]
+    pub fn get_datetime(&self) -> Result<&Datetime, TypeError> {
+        self.as_datetime()
+            .ok_or_else(|| self.type_error(Type::Datetime))
+    }
+
+    /// Returns `self` as a [`Vec<Value>`], or an error if it is not an array.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`TypeError`] containing the actual type if `self` is not an array.
+    #[inline]
+    pub fn get_array(&self) -> Result<&Vec<Self>, TypeError> {
+        self.as_array().ok_or_else(|| self.type_error(Type::Array))
+    }
+
+    /// Returns `self` as a [`Table`], or an error if it is not a table.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`TypeError`] containing the actual type if `self` is not a table.
+    #[inline]
+    pub fn get_table(&self) -> Result<&Table, TypeError> {
+        self.as_table().ok_or_else(|| self.type_error(Type::Table))
+    }
+
+    const fn type_error(&self, expected: Type) -> TypeError {
+        TypeError {
+            expected,
+            found: self.typ(),
+        }
+    }
+
     /// Returns `true` if two values have the same type.
     #[must_use]
     #[inline]
@@ -301,17 +539,348 @@ impl Value {
     pub fn type_str(&self) -> &'static str {
         self.typ().to_str()
     }
+
+    /// Returns a rank for the type of `self`, for use as a sort key when ordering values of
+    /// different types.
+    ///
+    /// The types are ranked in the order boolean (`0`), integer (`1`), float (`2`), string (`3`),
+    /// date-time (`4`), array (`5`), and table (`6`). The rank of each type is fixed regardless
+    /// of which features are enabled. Values of the same type have the same rank, so a stable
+    /// sort by rank groups values by type while preserving their order within each group.
+    #[must_use]
+    #[inline]
+    pub const fn type_rank(&self) -> u8 {
+        match *self {
+            Self::Boolean(_) => 0,
+            Self::Integer(_) => 1,
+            Self::Float(_) => 2,
+            Self::String(_) => 3,
+            #[cfg(feature = "datetime")]
+            Self::Datetime(_) => 4,
+            Self::Array(_) => 5,
+            Self::Table(_) => 6,
+        }
+    }
+
+    /// Checks `self` against the shape declared by `schema`.
+    ///
+    /// # Errors
+    ///
+    /// Returns every mismatch found if `self` does not match `schema`.
+    #[inline]
+    pub fn validate(&self, schema: &Schema) -> Result<(), Vec<Violation>> {
+        let mut violations = Vec::new();
+        schema::validate(self, schema, "", &mut violations);
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(violations)
+        }
+    }
+
+    /// Returns an approximate number of bytes `self` will take up when serialized.
+    ///
+    /// A table is estimated as a document written by [`to_string`](crate::to_string), including
+    /// the headers of any nested tables, and any other value as an inline value. This is a rough
+    /// heuristic which does not allocate, and is intended for pre-sizing an output buffer as
+    /// [`Self::to_toml_string`] does. The actual output may be longer or shorter, for example if
+    /// strings contain characters which need escaping.
+    #[must_use]
+    #[inline]
+    pub fn estimated_serialized_len(&self) -> usize {
+        match *self {
+            Self::Table(ref table) => Self::estimated_document_len(table, 0),
+            _ => self.estimated_inline_len(),
+        }
+    }
+
+    /// Serializes `self` to a TOML document, pre-sizing the output using
+    /// [`Self::estimated_serialized_len`].
+    ///
+    /// This is equivalent to [`to_string`](crate::to_string), but avoids reallocating the output
+    /// as it grows.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `self` is not a table, since the root of a TOML document is always a
+    /// table.
+    #[inline]
+    pub fn to_toml_string(&self) -> Result<String, crate::ser::Error> {
+        let mut dst = String::with_capacity(self.estimated_serialized_len());
+        crate::to_string_in(self, &mut dst)?;
+        Ok(dst)
+    }
+
+    /// Returns the differences between `self` and `other`, treating `self` as the old value and
+    /// `other` as the new value.
+    ///
+    /// Tables are compared key by key, recursing into nested tables, so each added, removed, or
+    /// changed key is reported with its own path. Arrays and other values are compared as a
+    /// whole, and are reported as changed if they are not equal. Unlike `==`, a NaN float is
+    /// considered equal to another NaN, so a value containing NaN has no differences from itself.
+    #[must_use]
+    #[inline]
+    pub fn diff(&self, other: &Self) -> Diff {
+        let mut changes = Vec::new();
+        diff::diff(self, other, "", &mut changes);
+        Diff(changes)
+    }
+
+    /// Deep merges `other` into `self`.
+    ///
+    /// If both `self` and `other` are tables, each key in `other` is merged into the value with
+    /// the same key in `self`, or inserted if there is no such key. Otherwise `self` is replaced
+    /// by `other`. Arrays are not merged element-wise, so an array in `other` replaces any
+    /// value in `self`.
+    #[inline]
+    pub fn merge(&mut self, other: Self) {
+        match (self.as_table_mut(), other) {
+            (Some(table), Self::Table(other)) => {
+                for (key, value) in other {
+                    match table.entry(key) {
+                        map::Entry::Occupied(entry) => entry.into_mut().merge(value),
+                        map::Entry::Vacant(entry) => {
+                            entry.insert(value);
+                        }
+                    }
+                }
+            }
+            (_, other) => *self = other,
+        }
+    }
+
+    /// Parses `toml` as a TOML document and deep merges it into `self`.
+    ///
+    /// This is useful for applying an override file on top of a base configuration. See
+    /// [`Self::merge`] for how the values are merged.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `toml` is not a valid TOML document, in which case `self` is left
+    /// unchanged.
+    #[inline]
+    pub fn merge_from_str(&mut self, toml: &str) -> Result<(), crate::de::Error> {
+        self.merge(crate::from_str(toml)?);
+        Ok(())
+    }
+
+    /// Traverse `self` depth-first, calling the methods of `visitor` for each value.
+    ///
+    /// Each method is passed the path from `self` to the value being visited, so `self` is
+    /// visited with an empty path.
+    #[inline]
+    pub fn walk(&self, visitor: &mut impl ValueVisitor) {
+        self.walk_inner(&mut Vec::new(), visitor);
+    }
+
+    fn walk_inner<'a>(&'a self, path: &mut Vec<PathSegment<'a>>, visitor: &mut impl ValueVisitor) {
+        match *self {
+            Self::Table(ref table) => {
+                visitor.enter_table(path, table);
+                for (key, value) in table {
+                    path.push(PathSegment::Key(key));
+                    value.walk_inner(path, visitor);
+                    path.pop();
+                }
+                visitor.leave_table(path, table);
+            }
+            Self::Array(ref array) => {
+                visitor.enter_array(path, array);
+                for (index, value) in array.iter().enumerate() {
+                    path.push(PathSegment::Index(index));
+                    value.walk_inner(path, visitor);
+                    path.pop();
+                }
+                visitor.leave_array(path, array);
+            }
+            _ => visitor.visit_scalar(path, self),
+        }
+    }
+}
+
+impl Value {
+    // The estimated length of `table` written as a document, where `path_len` is the length of
+    // the dotted path in its header. Nested tables are written under their own headers after a
+    // blank line, and the header of a table with only nested tables is omitted
+    fn estimated_document_len(table: &Table, path_len: usize) -> usize {
+        let is_array_of_tables =
+            |array: &Array| !array.is_empty() && array.iter().all(Self::is_table);
+
+        table
+            .iter()
+            .map(|(key, value)| {
+                let key_len = formatted_len(|f| Formatter::write_key(key, false, f));
+                let path_len = if path_len == 0 {
+                    key_len
+                } else {
+                    path_len + 1 + key_len
+                };
+                match *value {
+                    // "\n[path]\n"
+                    Self::Table(ref table) => {
+                        let has_header = table.is_empty()
+                            || table.values().any(|value| match *value {
+                                Self::Table(_) => false,
+                                Self::Array(ref array) => !is_array_of_tables(array),
+                                _ => true,
+                            });
+                        usize::from(has_header) * (path_len + 4)
+                            + Self::estimated_document_len(table, path_len)
+                    }
+                    // "\n[[path]]\n" for each table
+                    Self::Array(ref array) if is_array_of_tables(array) => array
+                        .iter()
+                        .filter_map(Self::as_table)
+                        .map(|table| path_len + 6 + Self::estimated_document_len(table, path_len))
+                        .sum(),
+                    // "key = value\n"
+                    _ => key_len + 3 + value.estimated_inline_len() + 1,
+                }
+            })
+            .sum()
+    }
+
+    // The estimated length of the value written inline
+    fn estimated_inline_len(&self) -> usize {
+        // The total length of the items with a ", " between each
+        fn separated_len(lens: impl ExactSizeIterator<Item = usize>) -> usize {
+            2 * lens.len().saturating_sub(1) + lens.sum::<usize>()
+        }
+
+        match *self {
+            // Allow for the surrounding quotes, but not for any escape sequences
+            Self::String(ref str) => str.len() + 2,
+            Self::Integer(int) => formatted_len(|f| write!(f, "{int}")),
+            Self::Float(float) => formatted_len(|f| {
+                Formatter::write_float(&float, None, crate::ser::FloatFormat::Shortest, f)
+            }),
+            Self::Boolean(bool) => formatted_len(|f| write!(f, "{bool}")),
+            #[cfg(feature = "datetime")]
+            Self::Datetime(ref datetime) => formatted_len(|f| write!(f, "{datetime}")),
+            // "[a, b]"
+            Self::Array(ref array) => {
+                2 + separated_len(array.iter().map(Self::estimated_inline_len))
+            }
+            // "{ a = 1, b = 2 }", or "{}" if empty
+            Self::Table(ref table) if table.is_empty() => 2,
+            Self::Table(ref table) => {
+                4 + separated_len(table.iter().map(|(key, value)| {
+                    formatted_len(|f| Formatter::write_key(key, false, f))
+                        + 3
+                        + value.estimated_inline_len()
+                }))
+            }
+        }
+    }
+
+    // Writes the value with non-empty arrays split over multiple lines, with each element indented
+    // by `depth` levels. Inline tables must be on a single line in TOML, so they are written in the
+    // compact form. Used for the alternate (`{:#}`) Display format
+    fn fmt_pretty(&self, depth: usize, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use serde::Serialize as _;
+
+        const INDENT: &str = "    ";
+
+        match *self {
+            Self::Array(ref array) if !array.is_empty() => {
+                f.write_str("[\n")?;
+                for value in array {
+                    f.write_str(&INDENT.repeat(depth + 1))?;
+                    value.fmt_pretty(depth + 1, f)?;
+                    f.write_str(",\n")?;
+                }
+                f.write_str(&INDENT.repeat(depth))?;
+                f.write_str("]")
+            }
+            _ => self
+                .serialize(crate::ser::ValueSerializer::new(f))
+                .map_err(|_| fmt::Error),
+        }
+    }
 }
 
 impl fmt::Display for Value {
+    /// Formats the value as an inline TOML value.
+    ///
+    /// The alternate flag (`{:#}`) writes a table as a TOML document, with nested tables under
+    /// their own headers, and writes non-empty arrays over multiple lines with indentation. This
+    /// can be easier to read for nested values.
     #[allow(clippy::panic)]
     #[inline]
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         use serde::Serialize as _;
 
-        self.serialize(crate::ser::ValueSerializer::new(f))
-            .map_err(|_| fmt::Error)
+        match *self {
+            Self::Table(ref table) if f.alternate() => crate::to_fmt(f, table),
+            _ if f.alternate() => self.fmt_pretty(0, f),
+            _ => self
+                .serialize(crate::ser::ValueSerializer::new(f))
+                .map_err(|_| fmt::Error),
+        }
+    }
+}
+
+// Returns the length of the output of `write` without allocating
+fn formatted_len(write: impl FnOnce(&mut dyn fmt::Write) -> fmt::Result) -> usize {
+    struct Counter(usize);
+
+    impl fmt::Write for Counter {
+        fn write_str(&mut self, s: &str) -> fmt::Result {
+            self.0 += s.len();
+            Ok(())
+        }
     }
+
+    let mut counter = Counter(0);
+    // Counting the output can't fail
+    _ = write(&mut counter);
+    counter.0
+}
+
+/// A segment of the path to a value visited by [`Value::walk`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PathSegment<'a> {
+    /// A key in a table.
+    Key(&'a str),
+    /// An index in an array.
+    Index(usize),
+}
+
+impl fmt::Display for PathSegment<'_> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::Key(key) => f.write_str(key),
+            Self::Index(index) => write!(f, "{index}"),
+        }
+    }
+}
+
+/// A visitor for traversing a [`Value`] using [`Value::walk`].
+///
+/// All methods have a default implementation which does nothing, so implementations only need to
+/// override the methods they are interested in.
+#[allow(clippy::module_name_repetitions)] // avoids confusion with serde::de::Visitor
+pub trait ValueVisitor {
+    /// Called for each value which is not a table or array.
+    #[inline]
+    fn visit_scalar(&mut self, _path: &[PathSegment<'_>], _value: &Value) {}
+
+    /// Called for each table before any of its values are visited.
+    #[inline]
+    fn enter_table(&mut self, _path: &[PathSegment<'_>], _table: &Table) {}
+
+    /// Called for each table after all of its values are visited.
+    #[inline]
+    fn leave_table(&mut self, _path: &[PathSegment<'_>], _table: &Table) {}
+
+    /// Called for each array before any of its elements are visited.
+    #[inline]
+    fn enter_array(&mut self, _path: &[PathSegment<'_>], _array: &Array) {}
+
+    /// Called for each array after all of its elements are visited.
+    #[inline]
+    fn leave_array(&mut self, _path: &[PathSegment<'_>], _array: &Array) {}
 }
 
 /// A trait for indexing into TOML values.
@@ -604,6 +1173,19 @@ impl From<bool> for Value {
     }
 }
 
+// Wraps a date-time built from its components in a `Value`, or reports it as invalid
+#[cfg(feature = "datetime")]
+fn checked_datetime<T>(valid: bool, datetime: T) -> Result<Value, crate::de::Error>
+where
+    T: Into<Value> + fmt::Display,
+{
+    if valid {
+        Ok(datetime.into())
+    } else {
+        Err(crate::de::ErrorKind::InvalidDatetime(datetime.to_string().into()).into())
+    }
+}
+
 #[cfg(feature = "datetime")]
 impl From<Datetime> for Value {
     #[inline]
@@ -714,6 +1296,51 @@ where
     }
 }
 
+impl TryFrom<&Value> for i64 {
+    type Error = TypeError;
+
+    #[inline]
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        value.get_integer()
+    }
+}
+
+impl TryFrom<&Value> for f64 {
+    type Error = TypeError;
+
+    #[inline]
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        value.get_float()
+    }
+}
+
+impl TryFrom<&Value> for bool {
+    type Error = TypeError;
+
+    #[inline]
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        value.get_bool()
+    }
+}
+
+impl TryFrom<&Value> for String {
+    type Error = TypeError;
+
+    #[inline]
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        <&str>::try_from(value).map(ToOwned::to_owned)
+    }
+}
+
+impl<'a> TryFrom<&'a Value> for &'a str {
+    type Error = TypeError;
+
+    #[inline]
+    fn try_from(value: &'a Value) -> Result<Self, Self::Error> {
+        value.get_str()
+    }
+}
+
 impl FromStr for Value {
     type Err = crate::de::Error;
 
@@ -964,7 +1591,7 @@ mod tests {
         .unwrap();
         assert_eq!(
             value,
-            Value::Table(btreemap! {
+            Value::Table(table! {
                 "a".to_string() => Value::Integer(1),
                 "b".to_string() => Value::Integer(2),
                 "c".to_string() => Value::Integer(3),
@@ -972,9 +1599,88 @@ mod tests {
         );
     }
 
+    #[test]
+    #[cfg(feature = "datetime")]
+    fn value_datetime_constructors() {
+        use crate::de::{Error, ErrorKind};
+
+        assert_eq!(
+            Value::local_date(2023, 1, 2).unwrap(),
+            Value::from(LocalDate::EXAMPLE)
+        );
+        assert_eq!(
+            Value::local_date(2024, 2, 29).unwrap().to_string(),
+            "2024-02-29"
+        );
+        assert_matches!(
+            Value::local_date(2023, 2, 29),
+            Err(Error(ErrorKind::InvalidDatetime(lit))) if &*lit == "2023-02-29"
+        );
+        assert_matches!(
+            Value::local_date(2023, 13, 1),
+            Err(Error(ErrorKind::InvalidDatetime(..)))
+        );
+
+        assert_eq!(
+            Value::local_time(3, 4, 5, 6_000_000).unwrap(),
+            Value::from(LocalTime::EXAMPLE)
+        );
+        assert_matches!(
+            Value::local_time(24, 0, 0, 0),
+            Err(Error(ErrorKind::InvalidDatetime(lit))) if &*lit == "24:00:00"
+        );
+        assert_matches!(
+            Value::local_time(0, 0, 0, 1_000_000_000),
+            Err(Error(ErrorKind::InvalidDatetime(..)))
+        );
+
+        assert_eq!(
+            Value::local_datetime(LocalDate::EXAMPLE, LocalTime::EXAMPLE).unwrap(),
+            Value::from(LocalDatetime::EXAMPLE)
+        );
+        let date = LocalDate {
+            day: 32,
+            ..LocalDate::EXAMPLE
+        };
+        assert_matches!(
+            Value::local_datetime(date.clone(), LocalTime::EXAMPLE),
+            Err(Error(ErrorKind::InvalidDatetime(..)))
+        );
+        let time = LocalTime {
+            minute: 60,
+            ..LocalTime::EXAMPLE
+        };
+        assert_matches!(
+            Value::local_datetime(LocalDate::EXAMPLE, time.clone()),
+            Err(Error(ErrorKind::InvalidDatetime(..)))
+        );
+
+        assert_eq!(
+            Value::offset_datetime(LocalDate::EXAMPLE, LocalTime::EXAMPLE, Offset::EXAMPLE)
+                .unwrap(),
+            Value::from(OffsetDatetime::EXAMPLE)
+        );
+        assert_matches!(
+            Value::offset_datetime(date, LocalTime::EXAMPLE, Offset::Z),
+            Err(Error(ErrorKind::InvalidDatetime(..)))
+        );
+        assert_matches!(
+            Value::offset_datetime(LocalDate::EXAMPLE, time, Offset::Z),
+            Err(Error(ErrorKind::InvalidDatetime(..)))
+        );
+        assert_matches!(
+            Value::offset_datetime(
+                LocalDate::EXAMPLE,
+                LocalTime::EXAMPLE,
+                Offset::Custom { minutes: 1440 }
+            ),
+            Err(Error(ErrorKind::InvalidDatetime(..)))
+        );
+    }
+
     #[test]
     fn value_get() {
-        let value = Value::Table(btreemap! {
+        let value = Value::Table(table! {
             "a".to_string() => Value::Integer(1),
             "b".to_string() => Value::String("Hello!".to_string()),
         });
@@ -986,7 +1692,7 @@ mod tests {
 
     #[test]
     fn value_get_mut() {
-        let mut value = Value::Table(btreemap! {
+        let mut value = Value::Table(table! {
             "a".to_string() => Value::Integer(1),
             "b".to_string() => Value::String("Hello!".to_string()),
         });
@@ -1080,7 +1786,7 @@ mod tests {
         assert!(value.is_array());
         assert!(!value.is_table());
 
-        let value = Value::Table(btreemap! {
+        let value = Value::Table(table! {
             "one".to_string() => Value::Integer(1),
             "two".to_string() => Value::Integer(2),
             "three".to_string() => Value::Integer(3),
@@ -1098,7 +1804,7 @@ mod tests {
     }
 
     #[test]
-    #[allow(clippy::cognitive_complexity)]
+    #[allow(clippy::too_many_lines, clippy::cognitive_complexity)]
     fn value_as() {
         let mut value = Value::String("Hello!".to_string());
         assert_matches!(value.as_str(), Some("Hello!"));
@@ -1107,6 +1813,8 @@ mod tests {
         assert!(value.as_bool().is_none());
         #[cfg(feature = "datetime")]
         assert!(value.as_datetime().is_none());
+        #[cfg(feature = "datetime")]
+        assert!(value.as_datetime_mut().is_none());
         assert!(value.as_array().is_none());
         assert!(value.as_array_mut().is_none());
         assert!(value.as_table().is_none());
@@ -1119,6 +1827,8 @@ mod tests {
         assert!(value.as_bool().is_none());
         #[cfg(feature = "datetime")]
         assert!(value.as_datetime().is_none());
+        #[cfg(feature = "datetime")]
+        assert!(value.as_datetime_mut().is_none());
         assert!(value.as_array().is_none());
         assert!(value.as_array_mut().is_none());
         assert!(value.as_table().is_none());
@@ -1131,6 +1841,8 @@ mod tests {
         assert!(value.as_bool().is_none());
         #[cfg(feature = "datetime")]
         assert!(value.as_datetime().is_none());
+        #[cfg(feature = "datetime")]
+        assert!(value.as_datetime_mut().is_none());
         assert!(value.as_array().is_none());
         assert!(value.as_array_mut().is_none());
         assert!(value.as_table().is_none());
@@ -1143,6 +1855,8 @@ mod tests {
         assert_matches!(value.as_bool(), Some(true));
         #[cfg(feature = "datetime")]
         assert!(value.as_datetime().is_none());
+        #[cfg(feature = "datetime")]
+        assert!(value.as_datetime_mut().is_none());
         assert!(value.as_array().is_none());
         assert!(value.as_array_mut().is_none());
         assert!(value.as_table().is_none());
@@ -1157,10 +1871,17 @@ mod tests {
             assert!(value.as_float().is_none());
             assert!(value.as_bool().is_none());
             assert_matches!(value.as_datetime(), Some(d) if d == &datetime);
+            assert_matches!(value.as_datetime_mut(), Some(d) if d == &datetime);
             assert!(value.as_array().is_none());
             assert!(value.as_array_mut().is_none());
             assert!(value.as_table().is_none());
             assert!(value.as_table_mut().is_none());
+
+            // Normalize the offset in place
+            if let Some(datetime) = value.as_datetime_mut() {
+                datetime.offset = Some(Offset::Z);
+            }
+            assert_matches!(value.as_datetime(), Some(d) if d.offset == Some(Offset::Z));
         };
 
         let array = vec![Value::Integer(1), Value::Integer(2), Value::Integer(3)];
@@ -1171,12 +1892,14 @@ mod tests {
         assert!(value.as_bool().is_none());
         #[cfg(feature = "datetime")]
         assert!(value.as_datetime().is_none());
+        #[cfg(feature = "datetime")]
+        assert!(value.as_datetime_mut().is_none());
         assert_matches!(value.as_array(), Some(a) if a == &array);
         assert_matches!(value.as_array_mut(), Some(a) if a == &array);
         assert!(value.as_table().is_none());
         assert!(value.as_table_mut().is_none());
 
-        let table = btreemap! {
+        let table = table! {
             "one".to_string() => Value::Integer(1),
             "two".to_string() => Value::Integer(2),
             "three".to_string() => Value::Integer(3),
@@ -1188,12 +1911,101 @@ mod tests {
         assert!(value.as_bool().is_none());
         #[cfg(feature = "datetime")]
         assert!(value.as_datetime().is_none());
+        #[cfg(feature = "datetime")]
+        assert!(value.as_datetime_mut().is_none());
         assert!(value.as_array().is_none());
         assert!(value.as_array_mut().is_none());
         assert_matches!(value.as_table(), Some(t) if t == &table);
         assert_matches!(value.as_table_mut(), Some(t) if t == &table);
     }
 
+    #[test]
+    fn value_into() {
+        let value = Value::String("Hello!".to_string());
+        assert_eq!(value.clone().into_string(), Ok("Hello!".to_string()));
+        assert_eq!(value.clone().into_array(), Err(value.clone()));
+        assert_eq!(value.clone().into_table(), Err(value));
+
+        let array = vec![Value::Integer(1), Value::Integer(2)];
+        let value = Value::Array(array.clone());
+        assert_eq!(value.clone().into_string(), Err(value.clone()));
+        assert_eq!(value.clone().into_array(), Ok(array));
+        assert_eq!(value.clone().into_table(), Err(value));
+
+        let table = table! {
+            "a".to_string() => Value::Integer(1),
+            "b".to_string() => Value::Boolean(true),
+        };
+        let value = Value::Table(table.clone());
+        assert_eq!(value.clone().into_string(), Err(value.clone()));
+        assert_eq!(value.clone().into_array(), Err(value.clone()));
+        assert_eq!(value.into_table(), Ok(table));
+
+        let value = Value::Integer(42);
+        assert_eq!(value.clone().into_string(), Err(value.clone()));
+        assert_eq!(value.clone().into_array(), Err(value.clone()));
+        assert_eq!(value.clone().into_table(), Err(value));
+    }
+
+    #[test]
+    fn value_get_typed() {
+        let value = Value::String("Hello!".to_string());
+        assert_eq!(value.get_str(), Ok("Hello!"));
+        let error = value.get_integer().unwrap_err();
+        assert_eq!(error.expected(), "integer");
+        assert_eq!(error.found(), "string");
+
+        let value = Value::Integer(42);
+        assert_eq!(value.get_integer(), Ok(42));
+        assert_matches!(value.get_float(), Err(e) if e.expected() == "float");
+
+        let value = Value::Float(42.0);
+        assert_eq!(value.get_float(), Ok(42.0));
+        assert_matches!(value.get_bool(), Err(e) if e.found() == "float");
+
+        let value = Value::Boolean(true);
+        assert_eq!(value.get_bool(), Ok(true));
+        assert_matches!(value.get_str(), Err(e) if e.expected() == "string");
+
+        #[cfg(feature = "datetime")]
+        {
+            let datetime = Datetime::EXAMPLE_OFFSET_DATETIME;
+            let value = Value::Datetime(datetime.clone());
+            assert_eq!(value.get_datetime(), Ok(&datetime));
+            assert_matches!(value.get_array(), Err(e) if e.found() == "datetime");
+
+            let value = Value::Integer(42);
+            assert_matches!(value.get_datetime(), Err(e) if e.expected() == "datetime");
+        };
+
+        let array = vec![Value::Integer(1), Value::Integer(2), Value::Integer(3)];
+        let value = Value::Array(array.clone());
+        assert_eq!(value.get_array(), Ok(&array));
+        assert_matches!(value.get_table(), Err(e) if e.found() == "array");
+
+        let table = table! {
+            "one".to_string() => Value::Integer(1),
+        };
+        let value = Value::Table(table.clone());
+        assert_eq!(value.get_table(), Ok(&table));
+        assert_matches!(value.get_array(), Err(e) if e.found() == "table");
+
+        // Works with ? in functions returning a boxed error
+        let port = || -> Result<i64, Box<dyn std::error::Error>> {
+            Ok(value.get("one").ok_or("missing")?.get_integer()?)
+        };
+        assert_eq!(port().unwrap(), 1);
+    }
+
+    #[test]
+    fn type_error_display() {
+        let error = Value::Integer(42).get_str().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid type: expected string, found integer"
+        );
+    }
+
     #[test]
     fn value_same_type() {
         let values1 = [
@@ -1208,7 +2020,7 @@ mod tests {
                 Value::Integer(2),
                 Value::Integer(3),
             ]),
-            Value::Table(btreemap! {
+            Value::Table(table! {
                 "one".to_string() => Value::Integer(1),
                 "two".to_string() => Value::Integer(2),
                 "three".to_string() => Value::Integer(3),
@@ -1227,7 +2039,7 @@ mod tests {
                 Value::Integer(5),
                 Value::Integer(6),
             ]),
-            Value::Table(btreemap! {
+            Value::Table(table! {
                 "four".to_string() => Value::Integer(4),
                 "five".to_string() => Value::Integer(5),
                 "six".to_string() => Value::Integer(6),
@@ -1272,7 +2084,7 @@ mod tests {
         ]);
         assert_eq!(value.typ(), Type::Array);
 
-        let value = Value::Table(btreemap! {
+        let value = Value::Table(table! {
             "one".to_string() => Value::Integer(1),
             "two".to_string() => Value::Integer(2),
             "three".to_string() => Value::Integer(3),
@@ -1280,6 +2092,38 @@ mod tests {
         assert_eq!(value.typ(), Type::Table);
     }
 
+    #[test]
+    fn value_type_rank() {
+        let mut values = vec![
+            Value::Table(Table::new()),
+            Value::String("b".into()),
+            Value::Array(vec![]),
+            Value::Float(1.5),
+            Value::String("a".into()),
+            Value::Integer(2),
+            Value::Boolean(true),
+            Value::Integer(1),
+        ];
+        #[cfg(feature = "datetime")]
+        values.push(Value::Datetime(Datetime::EXAMPLE_OFFSET_DATETIME));
+
+        values.sort_by_key(Value::type_rank);
+        let ranks: Vec<_> = values.iter().map(Value::type_rank).collect();
+        let expected = match () {
+            #[cfg(feature = "datetime")]
+            () => vec![0, 1, 1, 2, 3, 3, 4, 5, 6],
+            #[cfg(not(feature = "datetime"))]
+            () => vec![0, 1, 1, 2, 3, 3, 5, 6],
+        };
+        assert_eq!(ranks, expected);
+
+        // The sort is stable, so values of the same type keep their order
+        assert_eq!(values[1], Value::Integer(2));
+        assert_eq!(values[2], Value::Integer(1));
+        assert_eq!(values[4], Value::String("b".into()));
+        assert_eq!(values[5], Value::String("a".into()));
+    }
+
     #[test]
     fn value_typ_str() {
         let value = Value::String("Hello!".to_string());
@@ -1307,7 +2151,7 @@ mod tests {
         ]);
         assert_eq!(value.type_str(), "array");
 
-        let value = Value::Table(btreemap! {
+        let value = Value::Table(table! {
             "one".to_string() => Value::Integer(1),
             "two".to_string() => Value::Integer(2),
             "three".to_string() => Value::Integer(3),
@@ -1315,6 +2159,299 @@ mod tests {
         assert_eq!(value.type_str(), "table");
     }
 
+    #[test]
+    fn value_estimated_serialized_len() {
+        assert_eq!(Value::String("abc".into()).estimated_serialized_len(), 5);
+        assert_eq!(Value::Integer(0).estimated_serialized_len(), 1);
+        assert_eq!(Value::Integer(-123).estimated_serialized_len(), 4);
+        assert_eq!(Value::Integer(i64::MIN).estimated_serialized_len(), 20);
+        assert_eq!(Value::Float(0.5).estimated_serialized_len(), 3);
+        assert_eq!(Value::Float(-1e100).estimated_serialized_len(), 6);
+        assert_eq!(Value::Float(f64::NAN).estimated_serialized_len(), 3);
+        assert_eq!(Value::Boolean(true).estimated_serialized_len(), 4);
+        assert_eq!(Value::Boolean(false).estimated_serialized_len(), 5);
+        assert_eq!(
+            Value::Array(vec![Value::Integer(1), Value::Integer(2)]).estimated_serialized_len(),
+            6
+        );
+        assert_eq!(Value::Array(vec![]).estimated_serialized_len(), 2);
+
+        let value = Value::Array(vec![Value::Table(table! {
+            "a b".into() => Value::Integer(1),
+            "c".into() => Value::Table(table! {}),
+        })]);
+        assert_eq!(value.to_string(), r#"[{ "a b" = 1, c = {} }]"#);
+        assert_eq!(value.estimated_serialized_len(), value.to_string().len());
+
+        let value = Value::Table(table! {
+            "a".into() => Value::Integer(1),
+            "b".into() => Value::String("c".into()),
+        });
+        let result = crate::to_string(&value).unwrap();
+        assert_eq!(result, "a = 1\nb = \"c\"\n");
+        assert_eq!(value.estimated_serialized_len(), result.len());
+
+        // Table headers are counted too
+        let value = Value::Table(table! {
+            "name".into() => Value::String("example".into()),
+            "a".into() => Value::Table(table! {
+                "b".into() => Value::Table(table! {
+                    "c".into() => Value::Float(1.5),
+                }),
+            }),
+            "servers".into() => Value::Array(vec![
+                Value::Table(table! {
+                    "ip".into() => Value::String("10.0.0.1".into()),
+                    "weight".into() => Value::Float(0.5),
+                }),
+                Value::Table(table! {
+                    "ip".into() => Value::String("10.0.0.2".into()),
+                    "weight".into() => Value::Float(1e-10),
+                }),
+            ]),
+        });
+        let len = crate::to_string(&value).unwrap().len();
+        assert_eq!(value.estimated_serialized_len(), len);
+
+        // Escapes aren't counted, but the estimate is still within a reasonable factor
+        let value = Value::Table(table! {
+            "escaped".into() => Value::String("\t\"\\\n".repeat(10)),
+            "unicode".into() => Value::String("\u{7f}".repeat(10)),
+        });
+        let len = crate::to_string(&value).unwrap().len();
+        let estimate = value.estimated_serialized_len();
+        assert!(estimate < len && estimate * 3 > len);
+    }
+
+    #[cfg(feature = "datetime")]
+    #[test]
+    fn value_estimated_serialized_len_datetime() {
+        let value = Value::Table(table! {
+            "a".into() => Value::Datetime("1979-05-27T07:32:00.999999-07:00".parse().unwrap()),
+            "b".into() => Value::Datetime("07:32:00".parse().unwrap()),
+        });
+        let len = crate::to_string(&value).unwrap().len();
+        assert_eq!(value.estimated_serialized_len(), len);
+    }
+
+    #[test]
+    fn value_to_toml_string() {
+        let value = Value::Table(table! {
+            "a".into() => Value::Integer(1),
+            "b".into() => Value::Table(table! {
+                "c".into() => Value::String("d".into()),
+            }),
+        });
+        let result = value.to_toml_string().unwrap();
+        assert_eq!(result, crate::to_string(&value).unwrap());
+        assert!(result.capacity() >= value.estimated_serialized_len());
+
+        assert_matches!(
+            Value::Integer(1).to_toml_string(),
+            Err(crate::ser::Error(crate::ser::ErrorKind::UnsupportedRoot(
+                ..
+            )))
+        );
+    }
+
+    #[test]
+    fn value_diff() {
+        let old = Value::Table(table! {
+            "a".into() => Value::Integer(1),
+            "b".into() => Value::Table(table! {
+                "c".into() => Value::Integer(2),
+            }),
+        });
+        let new = Value::Table(table! {
+            "b".into() => Value::Table(table! {
+                "c".into() => Value::Float(2.0),
+            }),
+            "d".into() => Value::Boolean(true),
+        });
+
+        let diff = old.diff(&new);
+        assert_eq!(
+            diff.into_iter().collect::<Vec<_>>(),
+            [
+                Change::Removed {
+                    path: "a".into(),
+                    value: Value::Integer(1),
+                },
+                Change::Changed {
+                    path: "b.c".into(),
+                    old: Value::Integer(2),
+                    new: Value::Float(2.0),
+                },
+                Change::Added {
+                    path: "d".into(),
+                    value: Value::Boolean(true),
+                },
+            ]
+        );
+
+        assert!(old.diff(&old).is_empty());
+    }
+
+    #[test]
+    fn value_merge() {
+        let mut value = Value::Table(table! {
+            "a".into() => Value::Integer(1),
+            "b".into() => Value::Table(table! {
+                "c".into() => Value::Boolean(true),
+                "d".into() => Value::Array(vec![Value::Integer(1), Value::Integer(2)]),
+            }),
+            "e".into() => Value::Integer(3),
+        });
+        value.merge(Value::Table(table! {
+            "a".into() => Value::String("one".into()),
+            "b".into() => Value::Table(table! {
+                "d".into() => Value::Array(vec![Value::Integer(3)]),
+                "f".into() => Value::Float(1.5),
+            }),
+            "e".into() => Value::Table(table! {
+                "g".into() => Value::Integer(4),
+            }),
+            "h".into() => Value::Boolean(false),
+        }));
+        assert_eq!(
+            value,
+            Value::Table(table! {
+                "a".into() => Value::String("one".into()),
+                "b".into() => Value::Table(table! {
+                    "c".into() => Value::Boolean(true),
+                    "d".into() => Value::Array(vec![Value::Integer(3)]),
+                    "f".into() => Value::Float(1.5),
+                }),
+                "e".into() => Value::Table(table! {
+                    "g".into() => Value::Integer(4),
+                }),
+                "h".into() => Value::Boolean(false),
+            })
+        );
+
+        value.merge(Value::Integer(42));
+        assert_eq!(value, Value::Integer(42));
+
+        let mut value = Value::Array(vec![Value::Integer(1)]);
+        value.merge(Value::Array(vec![Value::Integer(2)]));
+        assert_eq!(value, Value::Array(vec![Value::Integer(2)]));
+    }
+
+    #[test]
+    fn value_merge_from_str() {
+        let mut value = Value::Table(table! {
+            "name".into() => Value::String("example".into()),
+            "server".into() => Value::Table(table! {
+                "host".into() => Value::String("localhost".into()),
+                "port".into() => Value::Integer(8080),
+            }),
+        });
+        value
+            .merge_from_str(indoc! {"
+                [server]
+                port = 80
+                tls = true
+            "})
+            .unwrap();
+        assert_eq!(
+            value,
+            Value::Table(table! {
+                "name".into() => Value::String("example".into()),
+                "server".into() => Value::Table(table! {
+                    "host".into() => Value::String("localhost".into()),
+                    "port".into() => Value::Integer(80),
+                    "tls".into() => Value::Boolean(true),
+                }),
+            })
+        );
+
+        let expected = value.clone();
+        assert!(value.merge_from_str("name = ").is_err());
+        assert_eq!(value, expected);
+    }
+
+    #[test]
+    fn value_walk() {
+        struct Noop;
+
+        impl ValueVisitor for Noop {}
+
+        #[derive(Default)]
+        struct StringPaths {
+            paths: Vec<String>,
+            events: Vec<String>,
+        }
+
+        impl ValueVisitor for StringPaths {
+            fn visit_scalar(&mut self, path: &[PathSegment<'_>], value: &Value) {
+                if value.is_str() {
+                    let path: Vec<_> = path.iter().map(ToString::to_string).collect();
+                    self.paths.push(path.join("."));
+                }
+            }
+
+            fn enter_table(&mut self, path: &[PathSegment<'_>], table: &Table) {
+                self.events
+                    .push(format!("enter table {} ({})", path.len(), table.len()));
+            }
+
+            fn leave_table(&mut self, path: &[PathSegment<'_>], table: &Table) {
+                self.events
+                    .push(format!("leave table {} ({})", path.len(), table.len()));
+            }
+
+            fn enter_array(&mut self, path: &[PathSegment<'_>], array: &Array) {
+                self.events
+                    .push(format!("enter array {} ({})", path.len(), array.len()));
+            }
+
+            fn leave_array(&mut self, path: &[PathSegment<'_>], array: &Array) {
+                self.events
+                    .push(format!("leave array {} ({})", path.len(), array.len()));
+            }
+        }
+
+        let value = Value::Table(table! {
+            "a".to_string() => Value::String("foo".to_string()),
+            "b".to_string() => Value::Integer(1),
+            "c".to_string() => Value::Array(vec![
+                Value::String("bar".to_string()),
+                Value::Table(table! {
+                    "d".to_string() => Value::String("baz".to_string()),
+                }),
+            ]),
+        });
+
+        let mut visitor = StringPaths::default();
+        value.walk(&mut visitor);
+        assert_eq!(visitor.paths, ["a", "c.0", "c.1.d"]);
+        assert_eq!(
+            visitor.events,
+            [
+                "enter table 0 (3)",
+                "enter array 1 (2)",
+                "enter table 2 (1)",
+                "leave table 2 (1)",
+                "leave array 1 (2)",
+                "leave table 0 (3)",
+            ]
+        );
+
+        // The default methods do nothing
+        value.walk(&mut Noop);
+
+        let mut visitor = StringPaths::default();
+        Value::String("foo".to_string()).walk(&mut visitor);
+        assert_eq!(visitor.paths, [""]);
+        assert!(visitor.events.is_empty());
+    }
+
+    #[test]
+    fn path_segment_display() {
+        assert_eq!(PathSegment::Key("foo").to_string(), "foo");
+        assert_eq!(PathSegment::Index(42).to_string(), "42");
+    }
+
     #[test]
     fn value_display() {
         let value = Value::String("Hello!".to_string());
@@ -1342,12 +2479,67 @@ mod tests {
         ]);
         assert_eq!(value.to_string(), "[1, 2, 3]");
 
-        let value = Value::Table(btreemap! {
+        let value = Value::Table(table! {
             "one".to_string() => Value::Integer(1),
             "two".to_string() => Value::Integer(2),
             "three".to_string() => Value::Integer(3),
         });
+        #[cfg(not(feature = "preserve_order"))]
         assert_eq!(value.to_string(), "{ one = 1, three = 3, two = 2 }");
+        #[cfg(feature = "preserve_order")]
+        assert_eq!(value.to_string(), "{ one = 1, two = 2, three = 3 }");
+
+        let value = Value::Table(table! {
+            "array".to_string() => Value::Array(vec![
+                Value::Integer(1),
+                Value::Table(table! { "a b".to_string() => Value::Boolean(true) }),
+            ]),
+            "empty".to_string() => Value::Array(vec![]),
+            "table".to_string() => Value::Table(table! {
+                "string".to_string() => Value::String("Hello!".to_string()),
+            }),
+        });
+        assert_eq!(
+            value.to_string(),
+            r#"{ array = [1, { "a b" = true }], empty = [], table = { string = "Hello!" } }"#
+        );
+        assert_eq!(
+            format!("{value:#}"),
+            indoc! {r#"
+                array = [1, { "a b" = true }]
+                empty = []
+
+                [table]
+                string = "Hello!"
+            "#}
+        );
+        assert_eq!(
+            crate::from_str::<Value>(&format!("{value:#}")).unwrap(),
+            value
+        );
+
+        let value = Value::Array(vec![
+            Value::Array(vec![Value::Integer(1), Value::Integer(2)]),
+            Value::Table(table! { "a b".to_string() => Value::Boolean(true) }),
+            Value::Array(vec![]),
+        ]);
+        assert_eq!(
+            format!("{value:#}"),
+            indoc! {r#"
+                [
+                    [
+                        1,
+                        2,
+                    ],
+                    { "a b" = true },
+                    [],
+                ]"#}
+        );
+        let parsed: Table = crate::from_str(&format!("value = {value:#}")).unwrap();
+        assert_eq!(parsed["value"], value);
+
+        let value = Value::Integer(42);
+        assert_eq!(format!("{value:#}"), "42");
     }
 
     #[test]
@@ -1375,7 +2567,7 @@ mod tests {
         assert_eq!(1.index_mut(&mut value), &Value::Integer(2));
         assert_eq!(2.index_mut(&mut value), &Value::Integer(3));
 
-        let mut value = Value::Table(btreemap! {
+        let mut value = Value::Table(table! {
             "one".to_string() => Value::Integer(1),
             "two".to_string() => Value::Integer(2),
             "three".to_string() => Value::Integer(3),
@@ -1414,7 +2606,7 @@ mod tests {
     #[test]
     #[should_panic = "cannot index TOML table with `usize`"]
     fn usize_index_type_error() {
-        let value = Value::Table(btreemap! {
+        let value = Value::Table(table! {
             "one".to_string() => Value::Integer(1),
             "two".to_string() => Value::Integer(2),
             "three".to_string() => Value::Integer(3),
@@ -1425,7 +2617,7 @@ mod tests {
     #[test]
     #[should_panic = "cannot index TOML table with `usize`"]
     fn usize_index_mut_type_error() {
-        let mut value = Value::Table(btreemap! {
+        let mut value = Value::Table(table! {
             "one".to_string() => Value::Integer(1),
             "two".to_string() => Value::Integer(2),
             "three".to_string() => Value::Integer(3),
@@ -1435,7 +2627,7 @@ mod tests {
 
     #[test]
     fn str_index() {
-        let mut value = Value::Table(btreemap! {
+        let mut value = Value::Table(table! {
             "one".to_string() => Value::Integer(1),
             "two".to_string() => Value::Integer(2),
             "three".to_string() => Value::Integer(3),
@@ -1484,7 +2676,7 @@ mod tests {
     #[test]
     #[should_panic = r#"key "four" is not present in TOML table"#]
     fn str_index_missing_error() {
-        let value = Value::Table(btreemap! {
+        let value = Value::Table(table! {
             "one".to_string() => Value::Integer(1),
             "two".to_string() => Value::Integer(2),
             "three".to_string() => Value::Integer(3),
@@ -1495,7 +2687,7 @@ mod tests {
     #[test]
     #[should_panic = r#"key "four" is not present in TOML table"#]
     fn str_index_mut_missing_error() {
-        let mut value = Value::Table(btreemap! {
+        let mut value = Value::Table(table! {
             "one".to_string() => Value::Integer(1),
             "two".to_string() => Value::Integer(2),
             "three".to_string() => Value::Integer(3),
@@ -1527,7 +2719,7 @@ mod tests {
 
     #[test]
     fn string_index() {
-        let mut value = Value::Table(btreemap! {
+        let mut value = Value::Table(table! {
             "one".to_string() => Value::Integer(1),
             "two".to_string() => Value::Integer(2),
             "three".to_string() => Value::Integer(3),
@@ -1597,7 +2789,7 @@ mod tests {
     #[test]
     #[should_panic = r#"key "four" is not present in TOML table"#]
     fn string_index_missing_error() {
-        let value = Value::Table(btreemap! {
+        let value = Value::Table(table! {
             "one".to_string() => Value::Integer(1),
             "two".to_string() => Value::Integer(2),
             "three".to_string() => Value::Integer(3),
@@ -1608,7 +2800,7 @@ mod tests {
     #[test]
     #[should_panic = r#"key "four" is not present in TOML table"#]
     fn string_index_mut_missing_error() {
-        let mut value = Value::Table(btreemap! {
+        let mut value = Value::Table(table! {
             "one".to_string() => Value::Integer(1),
             "two".to_string() => Value::Integer(2),
             "three".to_string() => Value::Integer(3),
@@ -1640,7 +2832,7 @@ mod tests {
 
     #[test]
     fn str_ref_index() {
-        let mut value = Value::Table(btreemap! {
+        let mut value = Value::Table(table! {
             "one".to_string() => Value::Integer(1),
             "two".to_string() => Value::Integer(2),
             "three".to_string() => Value::Integer(3),
@@ -1689,7 +2881,7 @@ mod tests {
     #[test]
     #[should_panic = r#"key "four" is not present in TOML table"#]
     fn str_ref_index_missing_error() {
-        let value = Value::Table(btreemap! {
+        let value = Value::Table(table! {
             "one".to_string() => Value::Integer(1),
             "two".to_string() => Value::Integer(2),
             "three".to_string() => Value::Integer(3),
@@ -1700,7 +2892,7 @@ mod tests {
     #[test]
     #[should_panic = r#"key "four" is not present in TOML table"#]
     fn str_ref_index_mut_missing_error() {
-        let mut value = Value::Table(btreemap! {
+        let mut value = Value::Table(table! {
             "one".to_string() => Value::Integer(1),
             "two".to_string() => Value::Integer(2),
             "three".to_string() => Value::Integer(3),
@@ -1741,7 +2933,7 @@ mod tests {
         assert_eq!(value.index(&1), &Value::Integer(2));
         assert_eq!(value.index(&2), &Value::Integer(3));
 
-        let value = Value::Table(btreemap! {
+        let value = Value::Table(table! {
             "one".to_string() => Value::Integer(1),
             "two".to_string() => Value::Integer(2),
             "three".to_string() => Value::Integer(3),
@@ -1762,7 +2954,7 @@ mod tests {
         assert_eq!(value.index_mut(&1), &Value::Integer(2));
         assert_eq!(value.index_mut(&2), &Value::Integer(3));
 
-        let mut value = Value::Table(btreemap! {
+        let mut value = Value::Table(table! {
             "one".to_string() => Value::Integer(1),
             "two".to_string() => Value::Integer(2),
             "three".to_string() => Value::Integer(3),
@@ -1828,7 +3020,7 @@ mod tests {
                 "b" => 2,
                 "c" => 3,
             }),
-            Value::Table(btreemap! {
+            Value::Table(table! {
                 "a".to_string() => Value::Integer(1),
                 "b".to_string() => Value::Integer(2),
                 "c".to_string() => Value::Integer(3),
@@ -1840,7 +3032,7 @@ mod tests {
                 "b" => 2,
                 "c" => 3,
             }),
-            Value::Table(btreemap! {
+            Value::Table(table! {
                 "a".to_string() => Value::Integer(1),
                 "b".to_string() => Value::Integer(2),
                 "c".to_string() => Value::Integer(3),
@@ -1878,6 +3070,40 @@ mod tests {
         );
     }
 
+    #[test]
+    fn scalar_try_from_value() {
+        assert_matches!(i64::try_from(&Value::Integer(42)), Ok(42));
+        assert_matches!(
+            i64::try_from(&Value::Float(42.0)),
+            Err(e) if e.expected() == "integer" && e.found() == "float"
+        );
+
+        assert_matches!(f64::try_from(&Value::Float(42.0)), Ok(42.0));
+        assert_matches!(
+            f64::try_from(&Value::Integer(42)),
+            Err(e) if e.expected() == "float" && e.found() == "integer"
+        );
+
+        assert_matches!(bool::try_from(&Value::Boolean(true)), Ok(true));
+        assert_matches!(
+            bool::try_from(&Value::String("true".into())),
+            Err(e) if e.expected() == "boolean" && e.found() == "string"
+        );
+
+        assert_matches!(String::try_from(&Value::String("foo".into())), Ok(s) if s == "foo");
+        assert_matches!(
+            String::try_from(&Value::Array(vec![])),
+            Err(e) if e.expected() == "string" && e.found() == "array"
+        );
+
+        let value = Value::String("foo".into());
+        assert_matches!(<&str>::try_from(&value), Ok("foo"));
+        assert_matches!(
+            <&str>::try_from(&Value::Table(Table::new())),
+            Err(e) if e.expected() == "string" && e.found() == "table"
+        );
+    }
+
     #[test]
     fn value_from_str() {
         let result = Value::from_str(
@@ -1929,11 +3155,11 @@ mod tests {
 
         assert_eq!(
             result,
-            Value::Table(btreemap! {
+            Value::Table(table! {
                 "title".to_string() => Value::String("TOML Example".to_string()),
                 "owner".to_string() => Value::Table(match () {
                     #[cfg(feature = "datetime")]
-                    () => btreemap! {
+                    () => table! {
                         "name".to_string() => Value::String("Tom Preston-Werner".to_string()),
                         "dob".to_string() => Value::Datetime(Datetime {
                             date: Some(LocalDate {
@@ -1951,29 +3177,29 @@ mod tests {
                         }),
                     },
                     #[cfg(not(feature = "datetime"))]
-                    () => btreemap! {
+                    () => table! {
                         "name".to_string() => Value::String("Tom Preston-Werner".to_string()),
                     },
                 }),
-                "database".to_string() => Value::Table(btreemap! {
+                "database".to_string() => Value::Table(table! {
                     "server".to_string() => Value::String("192.168.1.1".to_string()),
                     "ports".to_string() => Value::Array(vec![Value::Integer(8000), Value::Integer(8001), Value::Integer(8002)]),
                     "connection_max".to_string() => Value::Integer(5000),
                     "enabled".to_string() => Value::Boolean(true),
                 }),
-                "servers".to_string() => Value::Table(btreemap! {
-                    "alpha".to_string() => Value::Table(btreemap! {
+                "servers".to_string() => Value::Table(table! {
+                    "alpha".to_string() => Value::Table(table! {
                         "ip".to_string() => Value::String("10.0.0.1".to_string()),
                         "dc".to_string() => Value::String("eqdc10".to_string()),
                     }),
-                    "beta".to_string() => Value::Table(btreemap! {
+                    "beta".to_string() => Value::Table(table! {
                         "ip".to_string() => Value::String("10.0.0.2".to_string()),
                         "dc".to_string() => Value::String("eqdc10".to_string()),
                     }),
                 }),
-                "clients".to_string() => Value::Table(btreemap! {
+                "clients".to_string() => Value::Table(table! {
                     "hosts".to_string() => Value::Array(vec![Value::String("alpha".to_string()), Value::String("omega".to_string())]),
-                    "data".to_string() => Value::Table(btreemap! {
+                    "data".to_string() => Value::Table(table! {
                         "gamma".to_string() => Value::Integer(1),
                         "delta".to_string() => Value::Integer(2),
                     }),
@@ -1997,12 +3223,26 @@ mod tests {
         let result = Value::from_iter([("one", 1), ("two", 2), ("three", 3)]);
         assert_eq!(
             result,
-            Value::Table(btreemap! {
+            Value::Table(table! {
                 "one".to_string() => Value::Integer(1),
                 "two".to_string() => Value::Integer(2),
                 "three".to_string() => Value::Integer(3),
             })
         );
+
+        // Keys are sorted unless the preserve_order feature is enabled
+        let keys: Vec<_> = result.as_table().unwrap().keys().collect();
+        let toml = crate::to_string(&result).unwrap();
+        #[cfg(not(feature = "preserve_order"))]
+        {
+            assert_eq!(keys, ["one", "three", "two"]);
+            assert_eq!(toml, "one = 1\nthree = 3\ntwo = 2\n");
+        }
+        #[cfg(feature = "preserve_order")]
+        {
+            assert_eq!(keys, ["one", "two", "three"]);
+            assert_eq!(toml, "one = 1\ntwo = 2\nthree = 3\n");
+        }
     }
 
     #[test]
@@ -0,0 +1,315 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use super::{Type, Value};
+use crate::ser::Formatter;
+
+/// A lightweight description of the expected shape of a [`Value`].
+///
+/// A schema can be checked against a value with [`Value::validate`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Schema {
+    /// Any value.
+    Any,
+    /// A string.
+    String,
+    /// An integer.
+    Integer,
+    /// A float. Integers are not accepted.
+    Float,
+    /// A boolean.
+    Boolean,
+    /// A date-time of any kind.
+    #[cfg(feature = "datetime")]
+    Datetime,
+    /// An array where every element matches the given schema.
+    Array(Box<Self>),
+    /// A table with the given keys.
+    Table(Fields),
+}
+
+impl Schema {
+    /// Creates a schema for an array where every element matches the given schema.
+    #[must_use]
+    #[inline]
+    pub fn array_of(schema: Self) -> Self {
+        Self::Array(Box::new(schema))
+    }
+
+    fn typ(&self) -> Option<Type> {
+        match *self {
+            Self::Any => None,
+            Self::String => Some(Type::String),
+            Self::Integer => Some(Type::Integer),
+            Self::Float => Some(Type::Float),
+            Self::Boolean => Some(Type::Boolean),
+            #[cfg(feature = "datetime")]
+            Self::Datetime => Some(Type::Datetime),
+            Self::Array(_) => Some(Type::Array),
+            Self::Table(_) => Some(Type::Table),
+        }
+    }
+}
+
+/// The expected keys of a table in a [`Schema`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Fields {
+    fields: BTreeMap<String, (Schema, bool)>,
+    deny_unknown: bool,
+}
+
+impl Fields {
+    /// Creates a new empty set of fields.
+    #[must_use]
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a key which must be present in the table.
+    #[must_use]
+    #[inline]
+    pub fn required(mut self, key: impl Into<String>, schema: Schema) -> Self {
+        self.fields.insert(key.into(), (schema, true));
+        self
+    }
+
+    /// Adds a key which may be omitted from the table.
+    #[must_use]
+    #[inline]
+    pub fn optional(mut self, key: impl Into<String>, schema: Schema) -> Self {
+        self.fields.insert(key.into(), (schema, false));
+        self
+    }
+
+    /// Sets whether keys which are not declared are reported as violations.
+    ///
+    /// By default unknown keys are allowed.
+    #[must_use]
+    #[inline]
+    pub fn deny_unknown(mut self, value: bool) -> Self {
+        self.deny_unknown = value;
+        self
+    }
+}
+
+/// A mismatch between a [`Value`] and a [`Schema`] found by [`Value::validate`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    path: String,
+    message: String,
+}
+
+impl Violation {
+    /// The dotted path to the value, for example `servers.0.name`. Keys which are not valid bare
+    /// keys are quoted, for example `servers."eu.west".name`. This is empty for the root.
+    #[must_use]
+    #[inline]
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// A description of the problem.
+    #[must_use]
+    #[inline]
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for Violation {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.path.is_empty() {
+            f.write_str(&self.message)
+        } else {
+            write!(f, "{}: {}", self.path, self.message)
+        }
+    }
+}
+
+pub(super) fn validate(
+    value: &Value,
+    schema: &Schema,
+    path: &str,
+    violations: &mut Vec<Violation>,
+) {
+    let child_path = |key: &str| {
+        let mut child = path.to_owned();
+        Formatter::push_key(&mut child, key);
+        child
+    };
+
+    if let Some(typ) = schema.typ() {
+        if value.typ() != typ {
+            violations.push(Violation {
+                path: path.to_owned(),
+                message: format!("expected {typ}, found {}", value.typ()),
+            });
+            return;
+        }
+    }
+
+    // The type check above guarantees the value is the same kind of container as the schema
+    match *schema {
+        Schema::Array(ref schema) => {
+            let Some(array) = value.as_array() else {
+                return;
+            };
+            for (index, value) in array.iter().enumerate() {
+                validate(value, schema, &child_path(&index.to_string()), violations);
+            }
+        }
+        Schema::Table(ref fields) => {
+            let Some(table) = value.as_table() else {
+                return;
+            };
+            for (key, &(ref schema, required)) in &fields.fields {
+                match table.get(key) {
+                    Some(value) => validate(value, schema, &child_path(key), violations),
+                    None if required => violations.push(Violation {
+                        path: child_path(key),
+                        message: "missing required key".to_owned(),
+                    }),
+                    None => {}
+                }
+            }
+            if fields.deny_unknown {
+                violations.extend(
+                    table
+                        .keys()
+                        .filter(|&key| !fields.fields.contains_key(key))
+                        .map(|key| Violation {
+                            path: child_path(key),
+                            message: "unknown key".to_owned(),
+                        }),
+                );
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage, coverage(off))]
+mod tests {
+    use maplit::btreemap;
+
+    use super::*;
+
+    fn schema() -> Schema {
+        Schema::Table(
+            Fields::new()
+                .required("name", Schema::String)
+                .optional("port", Schema::Integer)
+                .required(
+                    "servers",
+                    Schema::array_of(Schema::Table(
+                        Fields::new()
+                            .required("ip", Schema::String)
+                            .optional("weight", Schema::Float)
+                            .deny_unknown(true),
+                    )),
+                )
+                .optional("extra", Schema::Any),
+        )
+    }
+
+    #[test]
+    fn schema_array_of() {
+        assert_eq!(
+            Schema::array_of(Schema::Integer),
+            Schema::Array(Box::new(Schema::Integer))
+        );
+    }
+
+    #[test]
+    fn fields_builder() {
+        let fields = Fields::new()
+            .required("a", Schema::String)
+            .optional("b", Schema::Any)
+            .deny_unknown(true);
+        assert_eq!(
+            fields.fields,
+            btreemap! {
+                "a".to_owned() => (Schema::String, true),
+                "b".to_owned() => (Schema::Any, false),
+            }
+        );
+        assert!(fields.deny_unknown);
+    }
+
+    #[test]
+    fn violation_display() {
+        let violation = Violation {
+            path: "a.0.b".into(),
+            message: "unknown key".into(),
+        };
+        assert_eq!(violation.path(), "a.0.b");
+        assert_eq!(violation.message(), "unknown key");
+        assert_eq!(violation.to_string(), "a.0.b: unknown key");
+
+        let violation = Violation {
+            path: String::new(),
+            message: "expected table, found integer".into(),
+        };
+        assert_eq!(violation.to_string(), "expected table, found integer");
+    }
+
+    #[test]
+    fn validate_value() {
+        let value = Value::Table(table! {
+            "name".into() => Value::String("example".into()),
+            "servers".into() => Value::Array(vec![
+                Value::Table(table! {
+                    "ip".into() => Value::String("10.0.0.1".into()),
+                    "weight".into() => Value::Float(0.5),
+                }),
+            ]),
+            "extra".into() => Value::Boolean(true),
+            "other".into() => Value::Integer(1),
+        });
+        assert_eq!(value.validate(&schema()), Ok(()));
+
+        let value = Value::Table(table! {
+            "port".into() => Value::String("8080".into()),
+            "servers".into() => Value::Array(vec![
+                Value::Table(table! {
+                    "ip".into() => Value::String("10.0.0.1".into()),
+                    "dc".into() => Value::String("eqdc10".into()),
+                    "eu.west".into() => Value::Boolean(true),
+                }),
+                Value::Table(table! {
+                    "ip".into() => Value::Integer(10),
+                    "weight".into() => Value::Integer(1),
+                }),
+                Value::Integer(3),
+            ]),
+        });
+        let violations = value.validate(&schema()).unwrap_err();
+        let violations: Vec<_> = violations.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            violations,
+            [
+                "name: missing required key",
+                "port: expected integer, found string",
+                "servers.0.dc: unknown key",
+                r#"servers.0."eu.west": unknown key"#,
+                "servers.1.ip: expected string, found integer",
+                "servers.1.weight: expected float, found integer",
+                "servers.2: expected table, found integer",
+            ]
+        );
+
+        let value = Value::Integer(1);
+        assert_eq!(value.validate(&Schema::Any), Ok(()));
+        assert_eq!(value.validate(&Schema::Integer), Ok(()));
+        assert_eq!(
+            value.validate(&schema()),
+            Err(vec![Violation {
+                path: String::new(),
+                message: "expected table, found integer".into(),
+            }])
+        );
+    }
+}
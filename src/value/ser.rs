@@ -4,7 +4,7 @@ use super::Value;
 #[cfg(feature = "datetime")]
 use super::{AnyDatetime, LocalDate, LocalDatetime, LocalTime, OffsetDatetime};
 use crate::ser::{Error, ErrorKind};
-use crate::{Table, __serialize_unsupported};
+use crate::{__serialize_unsupported, Table};
 
 impl ser::Serialize for Value {
     #[inline]
@@ -69,11 +69,7 @@ impl ser::Serializer for ToValueSerializer {
 
     #[inline]
     fn serialize_i128(self, value: i128) -> Result<Self::Ok, Self::Error> {
-        self.serialize_i64(
-            value
-                .try_into()
-                .map_err(|_| ErrorKind::UnsupportedValue("integer out of range of i64"))?,
-        )
+        self.serialize_i64(value.try_into().map_err(|_| ErrorKind::IntegerOutOfRange)?)
     }
 
     #[inline]
@@ -93,20 +89,12 @@ impl ser::Serializer for ToValueSerializer {
 
     #[inline]
     fn serialize_u64(self, value: u64) -> Result<Self::Ok, Self::Error> {
-        self.serialize_i64(
-            value
-                .try_into()
-                .map_err(|_| ErrorKind::UnsupportedValue("integer out of range of i64"))?,
-        )
+        self.serialize_i64(value.try_into().map_err(|_| ErrorKind::IntegerOutOfRange)?)
     }
 
     #[inline]
     fn serialize_u128(self, value: u128) -> Result<Self::Ok, Self::Error> {
-        self.serialize_i64(
-            value
-                .try_into()
-                .map_err(|_| ErrorKind::UnsupportedValue("integer out of range of i64"))?,
-        )
+        self.serialize_i64(value.try_into().map_err(|_| ErrorKind::IntegerOutOfRange)?)
     }
 
     #[inline]
@@ -380,8 +368,8 @@ pub struct ToValueTableSerializer {
 impl ToValueTableSerializer {
     #[allow(clippy::unnecessary_wraps)]
     #[inline]
-    fn start(_len: Option<usize>) -> Result<Self, Error> {
-        let table = Table::new(); // BTreeMap has no with_capacity
+    fn start(len: Option<usize>) -> Result<Self, Error> {
+        let table = Table::with_capacity(len.unwrap_or(0));
         Ok(Self { key: None, table })
     }
 }
@@ -638,7 +626,6 @@ impl ser::Serializer for RawBytesSerializer {
 #[cfg_attr(coverage, coverage(off))]
 mod tests {
     use assert_matches::assert_matches;
-    use maplit::btreemap;
     use serde::Serializer as _;
     #[cfg(feature = "datetime")]
     use serde_bytes::Bytes;
@@ -695,11 +682,12 @@ mod tests {
         ];
         assert_ser_tokens(&value, &tokens);
 
-        let value = Value::Table(btreemap! {
+        let value = Value::Table(table! {
             "one".to_string() => Value::Integer(1),
             "two".to_string() => Value::Integer(2),
             "three".to_string() => Value::Integer(3),
         });
+        #[cfg(not(feature = "preserve_order"))]
         let tokens = [
             Token::Map { len: Some(3) },
             Token::Str("one"),
@@ -710,6 +698,17 @@ mod tests {
             Token::I64(2),
             Token::MapEnd,
         ];
+        #[cfg(feature = "preserve_order")]
+        let tokens = [
+            Token::Map { len: Some(3) },
+            Token::Str("one"),
+            Token::I64(1),
+            Token::Str("two"),
+            Token::I64(2),
+            Token::Str("three"),
+            Token::I64(3),
+            Token::MapEnd,
+        ];
         assert_ser_tokens(&value, &tokens);
     }
 
@@ -734,7 +733,7 @@ mod tests {
         let result = ToValueSerializer.serialize_i128(42).unwrap();
         assert_eq!(result, Value::Integer(42));
         let result = ToValueSerializer.serialize_i128(i128::MIN);
-        assert_matches!(result, Err(Error(ErrorKind::UnsupportedValue(..))));
+        assert_matches!(result, Err(Error(ErrorKind::IntegerOutOfRange)));
 
         let result = ToValueSerializer.serialize_u8(42).unwrap();
         assert_eq!(result, Value::Integer(42));
@@ -748,12 +747,12 @@ mod tests {
         let result = ToValueSerializer.serialize_u64(42).unwrap();
         assert_eq!(result, Value::Integer(42));
         let result = ToValueSerializer.serialize_u64(u64::MAX);
-        assert_matches!(result, Err(Error(ErrorKind::UnsupportedValue(..))));
+        assert_matches!(result, Err(Error(ErrorKind::IntegerOutOfRange)));
 
         let result = ToValueSerializer.serialize_u128(42).unwrap();
         assert_eq!(result, Value::Integer(42));
         let result = ToValueSerializer.serialize_u128(u128::MAX);
-        assert_matches!(result, Err(Error(ErrorKind::UnsupportedValue(..))));
+        assert_matches!(result, Err(Error(ErrorKind::IntegerOutOfRange)));
 
         let result = ToValueSerializer.serialize_f32(42.0).unwrap();
         assert_eq!(result, Value::Float(42.0));
@@ -804,7 +803,7 @@ mod tests {
             .unwrap();
         assert_eq!(
             result,
-            Value::Table(btreemap! { "NewtypeVariant".to_string() => Value::Integer(42) })
+            Value::Table(table! { "NewtypeVariant".to_string() => Value::Integer(42) })
         );
 
         // These create a type-specific serializer which is tested below, so just unwrap to test for panics
@@ -913,7 +912,7 @@ mod tests {
         assert_eq!(
             result,
             Value::Table(
-                btreemap! { "TupleVariant".to_string() => Value::Array(vec![Value::Integer(1), Value::Integer(2), Value::Integer(3)]) }
+                table! { "TupleVariant".to_string() => Value::Array(vec![Value::Integer(1), Value::Integer(2), Value::Integer(3)]) }
             )
         );
     }
@@ -933,7 +932,7 @@ mod tests {
         assert_eq!(
             result,
             Value::Table(
-                btreemap! { "one".to_string() => Value::Integer(1), "two".to_string() => Value::Integer(2), "three".to_string() => Value::Integer(3) }
+                table! { "one".to_string() => Value::Integer(1), "two".to_string() => Value::Integer(2), "three".to_string() => Value::Integer(3) }
             )
         );
     }
@@ -959,7 +958,7 @@ mod tests {
         assert_eq!(
             result,
             Value::Table(
-                btreemap! { "one".to_string() => Value::Integer(1), "two".to_string() => Value::Integer(2), "three".to_string() => Value::Integer(3) }
+                table! { "one".to_string() => Value::Integer(1), "two".to_string() => Value::Integer(2), "three".to_string() => Value::Integer(3) }
             )
         );
     }
@@ -977,7 +976,7 @@ mod tests {
         assert_eq!(
             result,
             Value::Table(
-                btreemap! { "one".to_string() => Value::Integer(1), "two".to_string() => Value::Integer(2), "three".to_string() => Value::Integer(3) }
+                table! { "one".to_string() => Value::Integer(1), "two".to_string() => Value::Integer(2), "three".to_string() => Value::Integer(3) }
             )
         );
 
@@ -1163,7 +1162,7 @@ mod tests {
         assert_eq!(
             result,
             Value::Table(
-                btreemap! { "StructVariant".to_string() => Value::Table(btreemap! { "one".to_string() => Value::Integer(1), "two".to_string() => Value::Integer(2), "three".to_string() => Value::Integer(3) }) }
+                table! { "StructVariant".to_string() => Value::Table(table! { "one".to_string() => Value::Integer(1), "two".to_string() => Value::Integer(2), "three".to_string() => Value::Integer(3) }) }
             )
         );
     }
@@ -0,0 +1,169 @@
+#![allow(missing_docs, clippy::panic, clippy::unwrap_used)]
+
+// Checks that any float the deserializer can produce is serialized in a form which parses back to
+// a bit-identical `f64`, including the sign of zeros and NaNs
+
+use serde::Deserialize as _;
+use soml::ser::FloatFormat;
+use soml::{Deserializer, Serializer, Table, Value};
+
+// Edge cases for the float formatting, in addition to the random values below
+const EDGE_CASES: &[f64] = &[
+    0.0,
+    -0.0,
+    1.0,
+    -1.0,
+    0.1,
+    0.2,
+    0.3,
+    1.0 / 3.0,
+    42.5,
+    1e6,
+    1e-9,
+    1.5e300,
+    1e-300,
+    123_456_789.123_456_79,
+    9_007_199_254_740_993.0,
+    f64::MAX,
+    f64::MIN,
+    f64::MIN_POSITIVE,
+    -f64::MIN_POSITIVE,
+    f64::MIN_POSITIVE / 2.0,
+    f64::EPSILON,
+    5e-324,
+    -5e-324,
+    2.225_073_858_507_201e-308,
+    f64::INFINITY,
+    f64::NEG_INFINITY,
+    f64::NAN,
+    -f64::NAN,
+];
+
+// Literals as they may appear in a TOML document
+const LITERALS: &[&str] = &[
+    "0.0",
+    "-0.0",
+    "+0.0",
+    "0e0",
+    "-0e0",
+    "3.141_592_653_589_793",
+    "6.022_140_76e23",
+    "1E-7",
+    "-1.7976931348623157e308",
+    "4.9e-324",
+    "1e400",
+    "123456789012345678901234567890.0",
+    "0.000_000_000_000_000_000_1",
+    "inf",
+    "+inf",
+    "-inf",
+    "nan",
+    "+nan",
+    "-nan",
+];
+
+// A simple deterministic PRNG (xorshift64*) so the test is reproducible without dependencies
+fn random_floats(count: usize) -> impl Iterator<Item = f64> {
+    let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+    (0..count).map(move |_| {
+        state ^= state >> 12;
+        state ^= state << 25;
+        state ^= state >> 27;
+        let float = f64::from_bits(state.wrapping_mul(0x2545_f491_4f6c_dd1d));
+        // The deserializer only ever produces the canonical NaNs
+        match float {
+            float if float.is_nan() && float.is_sign_negative() => -f64::NAN,
+            float if float.is_nan() => f64::NAN,
+            float => float,
+        }
+    })
+}
+
+type Configure = fn(Serializer<&mut String>) -> Serializer<&mut String>;
+
+// The serializer options which affect how floats are written
+const SERIALIZERS: &[(&str, Configure)] = &[
+    ("default", |ser| ser),
+    ("scientific(0)", |ser| {
+        ser.float_format(FloatFormat::Scientific(0))
+    }),
+    ("scientific(3)", |ser| {
+        ser.float_format(FloatFormat::Scientific(3))
+    }),
+    ("scientific(6)", |ser| {
+        ser.float_format(FloatFormat::Scientific(6))
+    }),
+    ("scientific(16)", |ser| {
+        ser.float_format(FloatFormat::Scientific(16))
+    }),
+    ("scientific(255)", |ser| {
+        ser.float_format(FloatFormat::Scientific(255))
+    }),
+    ("grouped", |ser| ser.integer_grouping(Some(3))),
+];
+
+fn as_float(value: &Value) -> f64 {
+    match *value {
+        Value::Float(float) => float,
+        ref other => panic!("expected a float, got {other:?}"),
+    }
+}
+
+fn assert_round_trip(float: f64) {
+    let value = Value::Table(Table::from_iter([("a".into(), Value::Float(float))]));
+
+    for &(name, configure) in SERIALIZERS {
+        let mut buf = String::new();
+        serde::Serialize::serialize(&value, configure(Serializer::new(&mut buf))).unwrap();
+        let result: Value = soml::from_str(&buf)
+            .unwrap_or_else(|err| panic!("{name}: {float:?} serialized as {buf:?}: {err}"));
+        let result = as_float(&result["a"]);
+        assert_eq!(
+            result.to_bits(),
+            float.to_bits(),
+            "{name}: {float:?} serialized as {buf:?} parsed as {result:?}"
+        );
+    }
+
+    // Value's Display impl uses a different serializer
+    let string = Value::Float(float).to_string();
+    let result = Value::deserialize(Deserializer::value_from_str(&string)).unwrap();
+    let result = as_float(&result);
+    assert_eq!(
+        result.to_bits(),
+        float.to_bits(),
+        "display: {float:?} written as {string:?} parsed as {result:?}"
+    );
+}
+
+#[test]
+fn float_round_trip_edge_cases() {
+    for &float in EDGE_CASES {
+        assert_round_trip(float);
+    }
+}
+
+#[test]
+fn float_round_trip_literals() {
+    for literal in LITERALS {
+        let value: Value = soml::from_str(&format!("a = {literal}")).unwrap();
+        assert_round_trip(as_float(&value["a"]));
+    }
+}
+
+#[test]
+fn float_round_trip_random() {
+    for float in random_floats(10_000) {
+        assert_round_trip(float);
+    }
+}
+
+#[test]
+fn float_round_trip_f32() {
+    // Floats with short mantissas, such as values which were originally f32
+    for float in random_floats(1_000) {
+        #[allow(clippy::cast_possible_truncation)] // truncation is intended
+        let float = f64::from(float as f32);
+        assert_round_trip(float);
+    }
+}